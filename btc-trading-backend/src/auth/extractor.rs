@@ -0,0 +1,70 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+};
+
+use super::Claims;
+
+/// The device behind an authenticated request
+///
+/// Reads the `Claims` that `auth_middleware` stored in the request extensions.
+pub struct AuthedDevice(pub Claims);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthedDevice
+where
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match parts.extensions.get::<Claims>() {
+            Some(claims) => Ok(AuthedDevice(claims.clone())),
+            None => {
+                // Only reachable if a route is missing auth_middleware
+                tracing::warn!("Authenticated route reached without claims");
+                Err(StatusCode::UNAUTHORIZED)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+
+    fn claims() -> Claims {
+        Claims {
+            sub: "device_123".to_string(),
+            exp: 0,
+            iat: 0,
+            iss: "btc-trading-backend".to_string(),
+            aud: "btc-widget".to_string(),
+            device_name: "iPhone 15 Pro".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extracts_claims_from_extensions() {
+        let mut request = Request::new(Body::empty());
+        request.extensions_mut().insert(claims());
+        let (mut parts, _) = request.into_parts();
+
+        let AuthedDevice(extracted) = AuthedDevice::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(extracted.sub, "device_123");
+        assert_eq!(extracted.device_name, "iPhone 15 Pro");
+    }
+
+    #[tokio::test]
+    async fn test_missing_claims_is_unauthorized() {
+        let (mut parts, _) = Request::new(Body::empty()).into_parts();
+
+        let result = AuthedDevice::from_request_parts(&mut parts, &()).await;
+        assert!(matches!(result, Err(StatusCode::UNAUTHORIZED)));
+    }
+}
@@ -7,6 +7,8 @@ pub struct Claims {
     pub sub: String,        // Device ID
     pub exp: i64,           // Expiration time
     pub iat: i64,           // Issued at
+    pub iss: String,        // Issuer (this backend)
+    pub aud: String,        // Audience (the app the token is for)
     pub device_name: String, // Device name for identification
 }
 
@@ -16,6 +18,8 @@ pub fn create_token(
     device_name: &str,
     secret: &str,
     expiry_minutes: i64,
+    issuer: &str,
+    audience: &str,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     let now = Utc::now();
     let expiry = now + Duration::minutes(expiry_minutes);
@@ -24,6 +28,8 @@ pub fn create_token(
         sub: device_id.to_string(),
         exp: expiry.timestamp(),
         iat: now.timestamp(),
+        iss: issuer.to_string(),
+        aud: audience.to_string(),
         device_name: device_name.to_string(),
     };
 
@@ -35,11 +41,24 @@ pub fn create_token(
 }
 
 /// Validate a JWT token and return claims
-pub fn validate_token(token: &str, secret: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+///
+/// The signature alone isn't enough: tokens must also name this backend as
+/// issuer and the app as audience, so other HS256 tokens sharing the secret
+/// are rejected.
+pub fn validate_token(
+    token: &str,
+    secret: &str,
+    issuer: &str,
+    audience: &str,
+) -> Result<Claims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[audience]);
+
     let token_data = decode::<Claims>(
         token,
         &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
+        &validation,
     )?;
 
     Ok(token_data.claims)
@@ -48,6 +67,10 @@ pub fn validate_token(token: &str, secret: &str) -> Result<Claims, jsonwebtoken:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use jsonwebtoken::errors::ErrorKind;
+
+    const ISSUER: &str = "btc-trading-backend";
+    const AUDIENCE: &str = "btc-widget";
 
     #[test]
     fn test_create_and_validate_token() {
@@ -55,12 +78,14 @@ mod tests {
         let device_id = "device_123";
         let device_name = "iPhone 15 Pro";
 
-        let token = create_token(device_id, device_name, secret, 15).unwrap();
+        let token = create_token(device_id, device_name, secret, 15, ISSUER, AUDIENCE).unwrap();
 
-        let claims = validate_token(&token, secret).unwrap();
+        let claims = validate_token(&token, secret, ISSUER, AUDIENCE).unwrap();
 
         assert_eq!(claims.sub, device_id);
         assert_eq!(claims.device_name, device_name);
+        assert_eq!(claims.iss, ISSUER);
+        assert_eq!(claims.aud, AUDIENCE);
     }
 
     #[test]
@@ -68,9 +93,59 @@ mod tests {
         let secret = "test_secret_key_12345";
         let wrong_secret = "wrong_secret";
 
-        let token = create_token("device", "iPhone", secret, 15).unwrap();
+        let token = create_token("device", "iPhone", secret, 15, ISSUER, AUDIENCE).unwrap();
 
-        let result = validate_token(&token, wrong_secret);
+        let result = validate_token(&token, wrong_secret, ISSUER, AUDIENCE);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_wrong_audience_is_rejected() {
+        let secret = "test_secret_key_12345";
+
+        // Correctly signed, but issued for another app
+        let token = create_token("device", "iPhone", secret, 15, ISSUER, "other-app").unwrap();
+
+        let error = validate_token(&token, secret, ISSUER, AUDIENCE).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidAudience), "{:?}", error);
+    }
+
+    #[test]
+    fn test_wrong_issuer_is_rejected() {
+        let secret = "test_secret_key_12345";
+
+        let token = create_token("device", "iPhone", secret, 15, "other-issuer", AUDIENCE).unwrap();
+
+        let error = validate_token(&token, secret, ISSUER, AUDIENCE).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidIssuer), "{:?}", error);
+    }
+
+    #[test]
+    fn test_token_without_audience_is_rejected() {
+        let secret = "test_secret_key_12345";
+
+        // Shaped like tokens issued before iss/aud were added
+        #[derive(Serialize)]
+        struct LegacyClaims {
+            sub: String,
+            exp: i64,
+            iat: i64,
+            device_name: String,
+        }
+        let now = Utc::now();
+        let legacy = LegacyClaims {
+            sub: "device".to_string(),
+            exp: (now + Duration::minutes(15)).timestamp(),
+            iat: now.timestamp(),
+            device_name: "iPhone".to_string(),
+        };
+        let token = encode(
+            &Header::default(),
+            &legacy,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap();
+
+        assert!(validate_token(&token, secret, ISSUER, AUDIENCE).is_err());
+    }
 }
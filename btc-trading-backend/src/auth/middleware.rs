@@ -4,13 +4,19 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use std::sync::Arc;
 
-use super::jwt::{validate_token, Claims};
+use super::jwt::validate_token;
+use super::signature::verify_request_signature;
 use crate::config::Config;
+use crate::redact::token_prefix;
 
 /// Authentication middleware that validates JWT tokens
+///
+/// With `REQUIRE_REQUEST_SIGNATURES` set, the request's `X-Signature` is
+/// verified too (see `verify_request_signature`).
 pub async fn auth_middleware(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
@@ -29,20 +35,23 @@ pub async fn auth_middleware(
     };
 
     // Validate token
-    match validate_token(token, &config.jwt_secret) {
+    match validate_token(
+        token,
+        &config.jwt_secret,
+        &config.jwt_issuer,
+        &config.jwt_audience,
+    ) {
         Ok(claims) => {
             // Store claims in request extensions for use in handlers
             request.extensions_mut().insert(claims);
+            if config.require_request_signatures {
+                request = verify_request_signature(&config, request).await?;
+            }
             Ok(next.run(request).await)
         }
         Err(e) => {
-            tracing::warn!("Token validation failed: {:?}", e);
+            tracing::warn!("Token {} failed validation: {:?}", token_prefix(token), e);
             Err(StatusCode::UNAUTHORIZED)
         }
     }
 }
-
-/// Extract claims from request (use in handlers)
-pub fn get_claims(request: &Request) -> Option<&Claims> {
-    request.extensions().get::<Claims>()
-}
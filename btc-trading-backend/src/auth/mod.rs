@@ -1,5 +1,8 @@
+mod extractor;
 mod jwt;
 mod middleware;
+mod signature;
 
+pub use extractor::AuthedDevice;
 pub use jwt::{create_token, validate_token, Claims};
 pub use middleware::auth_middleware;
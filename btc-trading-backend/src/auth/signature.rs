@@ -0,0 +1,272 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{OriginalUri, Request},
+    http::StatusCode,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use thiserror::Error;
+
+use crate::config::Config;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hex HMAC-SHA256 of the request, keyed with `APP_SECRET`
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+/// Milliseconds since the epoch when the app signed the request
+pub const TIMESTAMP_HEADER: &str = "X-Timestamp";
+
+/// Largest body read for verification; the API's JSON bodies are far smaller
+const MAX_SIGNED_BODY_BYTES: usize = 1024 * 1024;
+
+/// Signatures accepted within the window, so a captured request can't be resent
+static SEEN_SIGNATURES: ReplayGuard = ReplayGuard::new();
+
+/// Why a signed request was rejected
+#[derive(Debug, PartialEq, Error)]
+pub enum SignatureError {
+    #[error("missing or malformed X-Signature / X-Timestamp header")]
+    Missing,
+    #[error("timestamp outside the allowed window")]
+    Expired,
+    #[error("signature does not match the request")]
+    Mismatch,
+    #[error("signature was already used")]
+    Replayed,
+}
+
+/// MAC over the timestamp, method, path with query, and body, newline-separated
+fn request_mac(secret: &str, timestamp: &str, method: &str, path: &str, body: &[u8]) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    for part in [timestamp.as_bytes(), method.as_bytes(), path.as_bytes()] {
+        mac.update(part);
+        mac.update(b"\n");
+    }
+    mac.update(body);
+    mac
+}
+
+/// The parts of a request the app signs, with the signature it sent
+struct SignedRequest<'a> {
+    /// Hex HMAC, any case
+    signature: &'a str,
+    timestamp: &'a str,
+    method: &'a str,
+    /// Full path including the query string
+    path: &'a str,
+    body: &'a [u8],
+}
+
+impl SignedRequest<'_> {
+    /// Check the signature and that the timestamp is within `window_ms` of `now_ms`
+    fn verify(&self, secret: &str, now_ms: i64, window_ms: i64) -> Result<(), SignatureError> {
+        let signed_at: i64 = self
+            .timestamp
+            .parse()
+            .map_err(|_| SignatureError::Missing)?;
+        if (now_ms - signed_at).abs() > window_ms {
+            return Err(SignatureError::Expired);
+        }
+
+        let signature = hex::decode(self.signature).map_err(|_| SignatureError::Mismatch)?;
+        request_mac(secret, self.timestamp, self.method, self.path, self.body)
+            .verify_slice(&signature)
+            .map_err(|_| SignatureError::Mismatch)
+    }
+}
+
+/// Remembers recently accepted signatures until they age out of the window
+struct ReplayGuard {
+    seen: Mutex<BTreeMap<String, i64>>,
+}
+
+impl ReplayGuard {
+    const fn new() -> Self {
+        Self {
+            seen: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record `signature`, returning false if it was already seen
+    fn first_use(&self, signature: &str, now_ms: i64, window_ms: i64) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        // Anything older would fail the timestamp check anyway
+        seen.retain(|_, accepted_at| now_ms - *accepted_at <= 2 * window_ms);
+        seen.insert(signature.to_ascii_lowercase(), now_ms)
+            .is_none()
+    }
+}
+
+/// Verify a request's `X-Signature`, returning it with its body restored
+///
+/// Rejects with 401 when the signature is missing, stale, wrong, or replayed.
+pub async fn verify_request_signature(
+    config: &Config,
+    request: Request,
+) -> Result<Request, StatusCode> {
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_SIGNED_BODY_BYTES)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+
+    // Nested routers see a path with their prefix stripped; the app signs the full one
+    let uri = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map_or(&parts.uri, |original| &original.0);
+    let path = uri.path_and_query().map_or(uri.path(), |p| p.as_str());
+    let header = |name: &str| parts.headers.get(name).and_then(|v| v.to_str().ok());
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let window_ms = config.request_signature_window_secs as i64 * 1000;
+    let result = match (header(SIGNATURE_HEADER), header(TIMESTAMP_HEADER)) {
+        (Some(signature), Some(timestamp)) => SignedRequest {
+            signature,
+            timestamp,
+            method: parts.method.as_str(),
+            path,
+            body: &body,
+        }
+        .verify(&config.app_secret, now_ms, window_ms)
+        .and_then(|()| {
+            SEEN_SIGNATURES
+                .first_use(signature, now_ms, window_ms)
+                .then_some(())
+                .ok_or(SignatureError::Replayed)
+        }),
+        _ => Err(SignatureError::Missing),
+    };
+
+    match result {
+        Ok(()) => Ok(Request::from_parts(parts, Body::from(body))),
+        Err(e) => {
+            tracing::warn!("Rejected signed request {} {}: {}", parts.method, path, e);
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Config::for_tests().app_secret`
+    const SECRET: &str = "test_app_secret";
+    const WINDOW_MS: i64 = 300_000;
+    const NOW_MS: i64 = 1_700_000_000_000;
+
+    fn sign(timestamp: &str, method: &str, path: &str, body: &[u8]) -> String {
+        let mac = request_mac(SECRET, timestamp, method, path, body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn signed_request(path: &str, body: &str, timestamp: i64) -> Request {
+        let timestamp = timestamp.to_string();
+        Request::builder()
+            .method("POST")
+            .uri(path)
+            .header(
+                SIGNATURE_HEADER,
+                sign(&timestamp, "POST", path, body.as_bytes()),
+            )
+            .header(TIMESTAMP_HEADER, timestamp)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_signature_checks() {
+        let body = br#"{"side":"BUY","price":50000.0,"quantity":0.001}"#;
+        let ts = NOW_MS.to_string();
+        let signature = sign(&ts, "POST", "/order/limit", body);
+        let verify = |signature: &str, timestamp: &str, path: &str, body: &[u8]| {
+            SignedRequest {
+                signature,
+                timestamp,
+                method: "POST",
+                path,
+                body,
+            }
+            .verify(SECRET, NOW_MS, WINDOW_MS)
+        };
+
+        assert_eq!(verify(&signature, &ts, "/order/limit", body), Ok(()));
+        assert_eq!(
+            verify(&signature.to_uppercase(), &ts, "/order/limit", body),
+            Ok(())
+        );
+
+        let tampered = br#"{"side":"BUY","price":50000.0,"quantity":0.1}"#;
+        assert_eq!(
+            verify(&signature, &ts, "/order/limit", tampered),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify(&signature, &ts, "/order/market", body),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify("not-hex", &ts, "/order/limit", body),
+            Err(SignatureError::Mismatch)
+        );
+
+        // The timestamp is signed too, so it can't be refreshed on an old request
+        let later = (NOW_MS + 1).to_string();
+        assert_eq!(
+            verify(&signature, &later, "/order/limit", body),
+            Err(SignatureError::Mismatch)
+        );
+
+        let stale = (NOW_MS - WINDOW_MS - 1).to_string();
+        let signature = sign(&stale, "POST", "/order/limit", body);
+        assert_eq!(
+            verify(&signature, &stale, "/order/limit", body),
+            Err(SignatureError::Expired)
+        );
+        assert_eq!(
+            verify(&signature, "yesterday", "/order/limit", body),
+            Err(SignatureError::Missing)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_request_passes_with_body_intact() {
+        let config = Config::for_tests();
+        let now = chrono::Utc::now().timestamp_millis();
+        let body = r#"{"order_id":12345,"new_price":95500.0}"#;
+
+        let request = signed_request("/grid/modify?symbol=BTCUSDT", body, now);
+        let request = verify_request_signature(&config, request).await.unwrap();
+        let received = to_bytes(request.into_body(), MAX_SIGNED_BODY_BYTES)
+            .await
+            .unwrap();
+        assert_eq!(received, body.as_bytes());
+
+        // The same request sent again is a replay
+        let replayed = signed_request("/grid/modify?symbol=BTCUSDT", body, now);
+        let result = verify_request_signature(&config, replayed).await;
+        assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_or_unsigned_request_is_rejected() {
+        let config = Config::for_tests();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let mut request = signed_request("/order/limit", r#"{"quantity":0.001}"#, now);
+        *request.body_mut() = Body::from(r#"{"quantity":1.0}"#);
+        let result = verify_request_signature(&config, request).await;
+        assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
+
+        let unsigned = Request::builder()
+            .method("POST")
+            .uri("/order/limit")
+            .body(Body::from(r#"{"quantity":0.001}"#))
+            .unwrap();
+        let result = verify_request_signature(&config, unsigned).await;
+        assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,333 @@
+use futures::StreamExt;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tokio_util::sync::CancellationToken;
+
+use super::client::{BinanceApiError, BinanceClient};
+use super::models::BookTicker;
+use crate::notifications::BOOK_STREAM;
+
+/// Delay before the first reconnect; doubles on each failure up to the max
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Oldest streamed book served when the REST fallback fails; past this the
+/// REST error is returned instead
+const MAX_FALLBACK_AGE: Duration = Duration::from_secs(60);
+
+/// `<symbol>@bookTicker` stream message
+#[derive(Debug, Deserialize)]
+struct StreamBookTicker {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "b")]
+    bid_price: String,
+    #[serde(rename = "B")]
+    bid_qty: String,
+    #[serde(rename = "a")]
+    ask_price: String,
+    #[serde(rename = "A")]
+    ask_qty: String,
+}
+
+impl From<StreamBookTicker> for BookTicker {
+    fn from(ticker: StreamBookTicker) -> Self {
+        Self {
+            symbol: ticker.symbol,
+            bid_price: ticker.bid_price,
+            bid_qty: ticker.bid_qty,
+            ask_price: ticker.ask_price,
+            ask_qty: ticker.ask_qty,
+        }
+    }
+}
+
+/// Where a book ticker was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSource {
+    Stream,
+    Rest,
+}
+
+impl BookSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BookSource::Stream => "stream",
+            BookSource::Rest => "rest",
+        }
+    }
+}
+
+/// A book ticker with where it came from and how old it is
+#[derive(Debug, Clone)]
+pub struct BookQuote {
+    pub ticker: BookTicker,
+    pub source: BookSource,
+    /// Time since the quote was received; zero for a REST fetch
+    pub age: Duration,
+}
+
+#[derive(Debug)]
+struct CachedBook {
+    ticker: BookTicker,
+    received_at: Instant,
+}
+
+/// Latest best bid/ask from one symbol's bookTicker stream
+///
+/// Reads use the streamed book while it's fresh. If no update has arrived
+/// within `stale_after`, or the request is for another symbol or environment,
+/// they fall back to a REST `bookTicker` call. If that call fails too, the
+/// last streamed book is returned with its age so callers can decide whether
+/// it's still usable, unless it's older than `MAX_FALLBACK_AGE`.
+pub struct BookCache {
+    /// Stream this cache mirrors, which also identifies its symbol and environment
+    stream_url: String,
+    stale_after: Duration,
+    latest: watch::Sender<Option<CachedBook>>,
+}
+
+impl BookCache {
+    pub fn new(client: &BinanceClient, symbol: &str, stale_after: Duration) -> Self {
+        Self {
+            stream_url: client.book_ticker_stream_url(symbol),
+            stale_after,
+            latest: watch::channel(None).0,
+        }
+    }
+
+    fn update(&self, ticker: BookTicker) {
+        self.update_received_at(ticker, Instant::now());
+    }
+
+    fn update_received_at(&self, ticker: BookTicker, received_at: Instant) {
+        self.latest.send_replace(Some(CachedBook {
+            ticker,
+            received_at,
+        }));
+    }
+
+    /// Cache `ticker` as if it had been streamed `age` ago
+    #[cfg(test)]
+    pub(crate) fn update_aged(&self, ticker: BookTicker, age: Duration) {
+        self.update_received_at(ticker, Instant::now() - age);
+    }
+
+    /// Last streamed book for `symbol` on `client`'s environment, however old
+    fn cached(&self, client: &BinanceClient, symbol: &str) -> Option<BookQuote> {
+        if client.book_ticker_stream_url(symbol) != self.stream_url {
+            return None;
+        }
+
+        self.latest.borrow().as_ref().map(|cached| BookQuote {
+            ticker: cached.ticker.clone(),
+            source: BookSource::Stream,
+            age: cached.received_at.elapsed(),
+        })
+    }
+
+    /// Best bid/ask for `symbol`, streamed when fresh and fetched over REST otherwise
+    pub async fn book_ticker(
+        &self,
+        client: &BinanceClient,
+        symbol: &str,
+    ) -> Result<BookQuote, BinanceApiError> {
+        let cached = self.cached(client, symbol);
+        if let Some(quote) = cached.as_ref().filter(|quote| quote.age <= self.stale_after) {
+            return Ok(quote.clone());
+        }
+
+        match client.get_book_ticker(symbol).await {
+            Ok(ticker) => Ok(BookQuote {
+                ticker,
+                source: BookSource::Rest,
+                age: Duration::ZERO,
+            }),
+            Err(e) => match cached {
+                Some(quote) if quote.age <= MAX_FALLBACK_AGE => {
+                    tracing::warn!(
+                        "bookTicker fetch failed ({}), using the {:?} old streamed book",
+                        e,
+                        quote.age
+                    );
+                    Ok(quote)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Keep the cache fed until `shutdown` is cancelled, reconnecting with backoff
+    pub async fn run(self: Arc<Self>, shutdown: CancellationToken) {
+        let mut delay = MIN_RECONNECT_DELAY;
+
+        loop {
+            match self.stream(&shutdown, &mut delay).await {
+                Ok(()) => return,
+                Err(e) => tracing::warn!(
+                    "bookTicker stream failed ({}), reconnecting in {:?}",
+                    e,
+                    delay
+                ),
+            }
+
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = tokio::time::sleep(delay) => {}
+            }
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    /// Read the stream into the cache; `Ok` only once `shutdown` is cancelled
+    async fn stream(
+        &self,
+        shutdown: &CancellationToken,
+        delay: &mut Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (socket, _) = connect_async(self.stream_url.as_str()).await?;
+        tracing::info!("📈 Connected to {}", self.stream_url);
+        *delay = MIN_RECONNECT_DELAY;
+
+        BOOK_STREAM.record_connected();
+        let result = self.read(socket, shutdown).await;
+        BOOK_STREAM.record_disconnected();
+        result
+    }
+
+    /// Feed messages from a connected socket into the cache
+    async fn read(
+        &self,
+        mut socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+        shutdown: &CancellationToken,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return Ok(()),
+                message = socket.next() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<StreamBookTicker>(&text) {
+                            Ok(ticker) => {
+                                BOOK_STREAM.record_message();
+                                self.update(ticker.into());
+                            }
+                            Err(e) => tracing::debug!("Ignoring bookTicker message: {}", e),
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        return Err("bookTicker stream closed".into());
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.into()),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_client;
+    use axum::{routing::get, Json, Router};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Mock Binance whose REST book is 47000 / 47010, counting requests
+    async fn mock_binance() -> (BinanceClient, Arc<AtomicU32>) {
+        let requests = Arc::new(AtomicU32::new(0));
+        let counter = requests.clone();
+        let mock = Router::new().route(
+            "/api/v3/ticker/bookTicker",
+            get(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Json(serde_json::json!({
+                    "symbol": "BTCUSDT",
+                    "bidPrice": "47000.00",
+                    "bidQty": "0.5",
+                    "askPrice": "47010.00",
+                    "askQty": "0.5"
+                }))
+            }),
+        );
+        let client = mock_client(mock).await;
+
+        (client, requests)
+    }
+
+    fn streamed(text: &str) -> BookTicker {
+        serde_json::from_str::<StreamBookTicker>(text)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_stream_message_is_parsed() {
+        let ticker = streamed(
+            r#"{"u":400900217,"s":"BTCUSDT","b":"50000.00","B":"1.2","a":"50001.00","A":"0.8"}"#,
+        );
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert_eq!(ticker.bid_price_f64(), 50000.0);
+        assert_eq!(ticker.ask_price_f64(), 50001.0);
+    }
+
+    #[tokio::test]
+    async fn test_stale_book_falls_back_to_rest() {
+        let (client, requests) = mock_binance().await;
+        let cache = BookCache::new(&client, "BTCUSDT", Duration::from_millis(50));
+
+        // Nothing streamed yet
+        let quote = cache.book_ticker(&client, "BTCUSDT").await.unwrap();
+        assert_eq!(quote.source, BookSource::Rest);
+        assert_eq!(quote.age, Duration::ZERO);
+        assert_eq!(quote.ticker.bid_price_f64(), 47000.0);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        cache.update(streamed(
+            r#"{"u":1,"s":"BTCUSDT","b":"50000.00","B":"1","a":"50001.00","A":"1"}"#,
+        ));
+        let quote = cache.book_ticker(&client, "BTCUSDT").await.unwrap();
+        assert_eq!(quote.source, BookSource::Stream);
+        assert_eq!(quote.ticker.bid_price_f64(), 50000.0);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Other symbols aren't streamed
+        let quote = cache.book_ticker(&client, "ETHUSDT").await.unwrap();
+        assert_eq!(quote.source, BookSource::Rest);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // No update within the threshold: the streamed book is stale
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let quote = cache.book_ticker(&client, "BTCUSDT").await.unwrap();
+        assert_eq!(quote.source, BookSource::Rest);
+        assert_eq!(quote.ticker.bid_price_f64(), 47000.0);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stale_book_is_served_with_its_age_when_rest_fails() {
+        // No bookTicker route: the REST fallback gets a 404
+        let client = mock_client(Router::new()).await;
+        let cache = BookCache::new(&client, "BTCUSDT", Duration::from_millis(50));
+        assert!(cache.book_ticker(&client, "BTCUSDT").await.is_err());
+
+        let ticker = streamed(
+            r#"{"u":1,"s":"BTCUSDT","b":"50000.00","B":"1","a":"50001.00","A":"1"}"#,
+        );
+        cache.update_aged(ticker, Duration::from_secs(30));
+        let quote = cache.book_ticker(&client, "BTCUSDT").await.unwrap();
+        assert_eq!(quote.source, BookSource::Stream);
+        assert!(quote.age >= Duration::from_secs(30));
+        assert_eq!(quote.ticker.bid_price_f64(), 50000.0);
+
+        // Too old to serve at all
+        let ticker = streamed(
+            r#"{"u":2,"s":"BTCUSDT","b":"50000.00","B":"1","a":"50001.00","A":"1"}"#,
+        );
+        cache.update_aged(ticker, MAX_FALLBACK_AGE + Duration::from_secs(1));
+        assert!(cache.book_ticker(&client, "BTCUSDT").await.is_err());
+    }
+}
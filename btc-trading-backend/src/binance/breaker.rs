@@ -0,0 +1,272 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Consecutive systemic failures that open the breaker
+const FAILURE_THRESHOLD: u32 = 5;
+/// Failures further apart than this don't count as consecutive
+const FAILURE_WINDOW: Duration = Duration::from_secs(60);
+/// How long the breaker stays open before letting a probe through
+const OPEN_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Process-wide breakers guarding outbound Binance requests, one per environment
+pub static BREAKER: EnvironmentBreakers = EnvironmentBreakers {
+    testnet: CircuitBreaker::new(FAILURE_THRESHOLD, FAILURE_WINDOW, OPEN_COOLDOWN),
+    production: CircuitBreaker::new(FAILURE_THRESHOLD, FAILURE_WINDOW, OPEN_COOLDOWN),
+};
+
+/// A breaker each for testnet and production, so an outage on one doesn't
+/// pause trading on the other
+pub struct EnvironmentBreakers {
+    testnet: CircuitBreaker,
+    production: CircuitBreaker,
+}
+
+impl EnvironmentBreakers {
+    /// The breaker for requests to the given environment
+    pub fn get(&self, use_production: bool) -> &CircuitBreaker {
+        if use_production {
+            &self.production
+        } else {
+            &self.testnet
+        }
+    }
+
+    pub fn snapshot(&self) -> EnvironmentBreakerSnapshot {
+        EnvironmentBreakerSnapshot {
+            testnet: self.testnet.snapshot(),
+            production: self.production.snapshot(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Requests flow normally
+    Closed,
+    /// Binance is failing; trading is paused until the cooldown elapses
+    Open,
+    /// Cooldown elapsed; the next result decides whether to close or re-open
+    HalfOpen,
+}
+
+/// Point-in-time view of the breaker for `/debug/health`
+#[derive(Debug, Serialize)]
+pub struct BreakerSnapshot {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    /// Seconds until an open breaker lets a probe through
+    pub retry_after_secs: Option<u64>,
+}
+
+/// Both environments' breakers for `/debug/health`
+#[derive(Debug, Serialize)]
+pub struct EnvironmentBreakerSnapshot {
+    pub testnet: BreakerSnapshot,
+    pub production: BreakerSnapshot,
+}
+
+struct BreakerInner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    first_failure_at: Option<Instant>,
+    opened_at: Option<Instant>,
+}
+
+/// Opens after repeated Binance failures so callers stop hammering the API
+pub struct CircuitBreaker {
+    threshold: u32,
+    window: Duration,
+    cooldown: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub const fn new(threshold: u32, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            cooldown,
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                first_failure_at: None,
+                opened_at: None,
+            }),
+        }
+    }
+
+    /// Whether a request may proceed, half-opening once the cooldown elapses
+    pub fn allow_request(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Closed | BreakerState::HalfOpen => true,
+            BreakerState::Open => {
+                let cooled_down = inner
+                    .opened_at
+                    .is_none_or(|opened| opened.elapsed() >= self.cooldown);
+                if cooled_down {
+                    tracing::info!("Circuit breaker half-open, probing Binance");
+                    inner.state = BreakerState::HalfOpen;
+                }
+                cooled_down
+            }
+        }
+    }
+
+    /// Binance answered; close the breaker
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != BreakerState::Closed {
+            tracing::info!("Circuit breaker closed, Binance recovered");
+        }
+        inner.state = BreakerState::Closed;
+        inner.consecutive_failures = 0;
+        inner.first_failure_at = None;
+        inner.opened_at = None;
+    }
+
+    /// Binance failed in a way that suggests it's unusable right now
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+
+        match inner.state {
+            BreakerState::HalfOpen => {
+                tracing::warn!("Circuit breaker probe failed, re-opening");
+                inner.state = BreakerState::Open;
+                inner.opened_at = Some(now);
+            }
+            BreakerState::Open => {}
+            BreakerState::Closed => {
+                let in_window = inner
+                    .first_failure_at
+                    .is_some_and(|first| now.duration_since(first) <= self.window);
+                if !in_window {
+                    inner.consecutive_failures = 0;
+                    inner.first_failure_at = Some(now);
+                }
+
+                inner.consecutive_failures += 1;
+                if inner.consecutive_failures >= self.threshold {
+                    tracing::error!(
+                        "Circuit breaker opened after {} consecutive Binance failures",
+                        inner.consecutive_failures
+                    );
+                    inner.state = BreakerState::Open;
+                    inner.opened_at = Some(now);
+                }
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> BreakerSnapshot {
+        let inner = self.inner.lock().unwrap();
+        let retry_after_secs = match (inner.state, inner.opened_at) {
+            (BreakerState::Open, Some(opened)) => {
+                Some(self.cooldown.saturating_sub(opened.elapsed()).as_secs())
+            }
+            _ => None,
+        };
+
+        BreakerSnapshot {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            retry_after_secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(3, Duration::from_secs(60), cooldown)
+    }
+
+    #[test]
+    fn test_opens_after_threshold_failures() {
+        let breaker = breaker(Duration::from_secs(30));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.snapshot().state, BreakerState::Closed);
+        assert!(breaker.allow_request());
+
+        breaker.record_failure();
+        assert_eq!(breaker.snapshot().state, BreakerState::Open);
+        assert!(!breaker.allow_request());
+        assert!(breaker.snapshot().retry_after_secs.is_some());
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let breaker = breaker(Duration::from_secs(30));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+
+        assert_eq!(breaker.snapshot().state, BreakerState::Closed);
+        assert_eq!(breaker.snapshot().consecutive_failures, 1);
+    }
+
+    #[test]
+    fn test_failures_outside_window_do_not_open() {
+        let breaker = CircuitBreaker::new(2, Duration::ZERO, Duration::from_secs(30));
+
+        breaker.record_failure();
+        std::thread::sleep(Duration::from_millis(5));
+        breaker.record_failure();
+
+        assert_eq!(breaker.snapshot().state, BreakerState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_probe_success_closes() {
+        let breaker = breaker(Duration::ZERO);
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        assert_eq!(breaker.snapshot().state, BreakerState::Open);
+
+        assert!(breaker.allow_request());
+        assert_eq!(breaker.snapshot().state, BreakerState::HalfOpen);
+
+        breaker.record_success();
+        assert_eq!(breaker.snapshot().state, BreakerState::Closed);
+        assert_eq!(breaker.snapshot().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_half_open_probe_failure_reopens() {
+        let breaker = breaker(Duration::ZERO);
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+
+        assert!(breaker.allow_request());
+        breaker.record_failure();
+        assert_eq!(breaker.snapshot().state, BreakerState::Open);
+    }
+
+    #[test]
+    fn test_environments_trip_independently() {
+        let breakers = EnvironmentBreakers {
+            testnet: breaker(Duration::from_secs(30)),
+            production: breaker(Duration::from_secs(30)),
+        };
+        for _ in 0..3 {
+            breakers.get(false).record_failure();
+        }
+
+        assert!(!breakers.get(false).allow_request());
+        assert!(breakers.get(true).allow_request());
+        let snapshot = breakers.snapshot();
+        assert_eq!(snapshot.testnet.state, BreakerState::Open);
+        assert_eq!(snapshot.production.state, BreakerState::Closed);
+    }
+}
@@ -1,13 +1,57 @@
+use super::breaker::{CircuitBreaker, BREAKER};
+use super::client_tag::{decode_client_tag, encode_client_order_id};
+use super::clock::SERVER_CLOCK;
 use super::models::*;
 use super::signing::build_signed_query;
+use super::single_flight::ACCOUNT_FLIGHTS;
+use super::stats::{weight, STATS};
+use super::symbol_table::SYMBOL_TABLE;
+use super::throttle::throttle;
 use crate::config::{BinanceCredentials, Config};
-use reqwest::Client;
+use crate::metrics::METRICS;
+use crate::redact::redact;
+use reqwest::{Client, StatusCode};
+use std::collections::HashMap;
+use std::time::Duration;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
-pub enum BinanceError {
+/// Longest startTime..endTime span `myTrades` accepts (24 hours)
+pub const MAX_TRADE_WINDOW_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Most trades `myTrades` returns per request
+const TRADE_PAGE_LIMIT: u32 = 1000;
+
+/// Day-long windows searched before the cursor when paging back through trades
+const CURSOR_LOOKBACK_WINDOWS: i64 = 7;
+
+/// Binance error codes meaning the API is unusable for every request right now
+/// (-1000 unknown, -1001 disconnected, -1003 rate limited, -1021 clock skew,
+/// -1022 bad signature, -2014/-2015 bad API key, IP, or permissions)
+const SYSTEMIC_CODES: [i32; 7] = [-1000, -1001, -1003, -1021, -1022, -2014, -2015];
+
+/// Binance's "timestamp outside of the recvWindow" code: our clock has drifted
+const TIMESTAMP_OUTSIDE_RECV_WINDOW: i32 = -1021;
+
+/// Binance error codes meaning the order is no longer on the book
+/// (-2011 cancel rejected: unknown order, -2013 order does not exist)
+const UNKNOWN_ORDER_CODES: [i32; 2] = [-2011, -2013];
+
+/// Code given to a 404 without a Binance error body: the endpoint itself is
+/// missing, e.g. cancelReplace on a server that predates it
+const ENDPOINT_NOT_FOUND: i32 = -404;
+
+#[derive(Error, Debug, Clone)]
+pub enum BinanceApiError {
+    /// Transport failure; the message is redacted (see `BinanceClient::transport_error`)
     #[error("Network error: {0}")]
-    Network(#[from] reqwest::Error),
+    Network(String),
+
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    /// The order was filled, cancelled, or never existed
+    #[error("API error: {code} - {message}")]
+    UnknownOrder { code: i32, message: String },
 
     #[error("API error: {code} - {message}")]
     Api { code: i32, message: String },
@@ -17,31 +61,138 @@ pub enum BinanceError {
 
     #[error("Production keys not configured")]
     ProductionNotConfigured,
+
+    /// Trading is paused after repeated Binance failures
+    #[error("Trading paused: Binance is failing repeatedly, retrying shortly")]
+    CircuitOpen,
+
+    /// Too many requests to this endpoint were already in flight
+    #[error("Too many concurrent {0} requests, try again shortly")]
+    Throttled(String),
+
+    /// The order was cancelled but its replacement wasn't placed (-2021), so
+    /// nothing is on the book in its place
+    #[error("Order {} cancelled but its replacement failed: {code} - {message}", cancelled.order_id)]
+    ReplacementFailed {
+        cancelled: Box<CancelOrderResponse>,
+        code: i32,
+        message: String,
+    },
+}
+
+impl BinanceApiError {
+    /// Build an error from a non-success response body
+    pub fn from_body(body: &str) -> Self {
+        match serde_json::from_str::<super::models::BinanceError>(body) {
+            Ok(error) => error.into(),
+            Err(_) => BinanceApiError::Api {
+                code: -1,
+                message: body.to_string(),
+            },
+        }
+    }
+
+    /// Whether the error means Binance is unusable rather than the request bad
+    pub fn is_systemic(&self) -> bool {
+        match self {
+            BinanceApiError::Network(_) | BinanceApiError::Timeout(_) => true,
+            BinanceApiError::Api { code, .. } => SYSTEMIC_CODES.contains(code),
+            _ => false,
+        }
+    }
+
+    /// Binance error code, if the error came from the API
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            BinanceApiError::UnknownOrder { code, .. }
+            | BinanceApiError::Api { code, .. }
+            | BinanceApiError::ReplacementFailed { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
 }
 
+impl From<super::models::BinanceError> for BinanceApiError {
+    fn from(error: super::models::BinanceError) -> Self {
+        // A failed cancelReplace reports each step's result under `data`
+        if let Some(data) = error.data {
+            match data.cancel_response {
+                Some(CancelStep::Failed(StepResult {
+                    code: Some(code),
+                    msg,
+                })) if UNKNOWN_ORDER_CODES.contains(&code) => {
+                    return BinanceApiError::UnknownOrder {
+                        code,
+                        message: msg.unwrap_or(error.msg),
+                    };
+                }
+                Some(CancelStep::Cancelled(cancelled)) => {
+                    let new_order = data.new_order_response.unwrap_or_default();
+                    return BinanceApiError::ReplacementFailed {
+                        cancelled: Box::new(cancelled),
+                        code: new_order.code.unwrap_or(error.code),
+                        message: new_order.msg.unwrap_or(error.msg),
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        if UNKNOWN_ORDER_CODES.contains(&error.code) {
+            BinanceApiError::UnknownOrder {
+                code: error.code,
+                message: error.msg,
+            }
+        } else {
+            BinanceApiError::Api {
+                code: error.code,
+                message: error.msg,
+            }
+        }
+    }
+}
+
+/// HTTP client that gives up on slow connects and hung responses
+///
+/// A request exceeding either limit fails with `BinanceApiError::Timeout`.
+pub fn http_client(connect_timeout_ms: u64, request_timeout_ms: u64) -> Client {
+    Client::builder()
+        .connect_timeout(Duration::from_millis(connect_timeout_ms))
+        .timeout(Duration::from_millis(request_timeout_ms))
+        .build()
+        .expect("failed to build HTTP client")
+}
+
+#[derive(Clone)]
 pub struct BinanceClient {
     client: Client,
     base_url: String,
     api_key: String,
     secret_key: String,
+    stream_url: String,
+    recv_window_ms: u64,
+    breaker: &'static CircuitBreaker,
 }
 
 impl BinanceClient {
     /// Create a client from credentials
     pub fn from_credentials(credentials: &BinanceCredentials) -> Self {
         Self {
-            client: Client::new(),
-            base_url: credentials.base_url.to_string(),
+            client: http_client(credentials.connect_timeout_ms, credentials.request_timeout_ms),
+            base_url: credentials.base_url.clone(),
             api_key: credentials.api_key.clone(),
             secret_key: credentials.secret_key.clone(),
+            stream_url: credentials.stream_url.to_string(),
+            recv_window_ms: credentials.recv_window_ms,
+            breaker: BREAKER.get(credentials.use_production),
         }
     }
 
     /// Create a client for the specified environment
-    pub fn for_environment(config: &Config, use_production: bool) -> Result<Self, BinanceError> {
+    pub fn for_environment(config: &Config, use_production: bool) -> Result<Self, BinanceApiError> {
         let credentials = config
             .get_credentials(use_production)
-            .ok_or(BinanceError::ProductionNotConfigured)?;
+            .ok_or(BinanceApiError::ProductionNotConfigured)?;
         Ok(Self::from_credentials(&credentials))
     }
 
@@ -55,22 +206,138 @@ impl BinanceClient {
     // Public Endpoints (no signature required)
     // ========================================================================
 
-    /// Get current price for BTCUSDT
-    pub async fn get_price(&self) -> Result<f64, BinanceError> {
-        let url = format!("{}/api/v3/ticker/price?symbol=BTCUSDT", self.base_url);
+    /// Get current price for a symbol
+    pub async fn get_price(&self, symbol: &str) -> Result<f64, BinanceApiError> {
+        let url = format!("{}/api/v3/ticker/price?symbol={}", self.base_url, symbol);
+        let request = self.client.get(&url);
 
-        let response = self.client.get(&url).send().await?;
+        let ticker: TickerPrice = self
+            .execute("GET /api/v3/ticker/price", weight::TICKER_PRICE, request)
+            .await?;
+        ticker
+            .try_price_f64()
+            .map_err(|e| BinanceApiError::Parse(format!("{} price: {}", symbol, e)))
+    }
 
-        if !response.status().is_success() {
-            let error: super::models::BinanceError = response.json().await?;
-            return Err(BinanceError::Api {
-                code: error.code,
-                message: error.msg,
-            });
+    /// Get the best bid and ask for a symbol
+    pub async fn get_book_ticker(&self, symbol: &str) -> Result<BookTicker, BinanceApiError> {
+        let url = format!("{}/api/v3/ticker/bookTicker?symbol={}", self.base_url, symbol);
+        let request = self.client.get(&url);
+
+        self.execute("GET /api/v3/ticker/bookTicker", weight::BOOK_TICKER, request).await
+    }
+
+    /// Get Binance server time (milliseconds) - cheap connectivity probe
+    pub async fn get_server_time(&self) -> Result<i64, BinanceApiError> {
+        let url = format!("{}/api/v3/time", self.base_url);
+        let request = self.client.get(&url);
+
+        let time: ServerTime = self
+            .execute("GET /api/v3/time", weight::SERVER_TIME, request)
+            .await?;
+        Ok(time.server_time)
+    }
+
+    /// Get a symbol's trading rules, from the symbol table or else exchangeInfo
+    async fn get_symbol_info(&self, symbol: &str) -> Result<SymbolInfo, BinanceApiError> {
+        if let Some(info) = SYMBOL_TABLE.get(&self.base_url, symbol) {
+            return Ok(info);
+        }
+
+        let url = format!("{}/api/v3/exchangeInfo?symbol={}", self.base_url, symbol);
+        let request = self.client.get(&url);
+
+        let info: ExchangeInfo = self
+            .execute("GET /api/v3/exchangeInfo", weight::EXCHANGE_INFO, request)
+            .await?;
+        let info = info
+            .symbols
+            .into_iter()
+            .find(|s| s.symbol == symbol)
+            .ok_or_else(|| BinanceApiError::Parse(format!("{} not in exchangeInfo", symbol)))?;
+        SYMBOL_TABLE.insert(&self.base_url, info.clone());
+        Ok(info)
+    }
+
+    /// Fetch `symbols`' trading rules in one exchangeInfo call into the symbol
+    /// table, returning how many were found
+    pub async fn load_symbol_table(&self, symbols: &[String]) -> Result<usize, BinanceApiError> {
+        let list = serde_json::to_string(symbols)
+            .map_err(|e| BinanceApiError::Parse(e.to_string()))?;
+        let url = format!("{}/api/v3/exchangeInfo", self.base_url);
+        let request = self.client.get(&url).query(&[("symbols", list)]);
+
+        let info: ExchangeInfo = self
+            .execute("GET /api/v3/exchangeInfo", weight::EXCHANGE_INFO, request)
+            .await?;
+        let mut loaded = 0;
+        for symbol in info.symbols.into_iter().filter(|s| symbols.contains(&s.symbol)) {
+            if symbol.precision().is_none() {
+                tracing::warn!("exchangeInfo for {} lacks PRICE_FILTER or LOT_SIZE", symbol.symbol);
+            }
+            SYMBOL_TABLE.insert(&self.base_url, symbol);
+            loaded += 1;
         }
+        Ok(loaded)
+    }
+
+    /// Get a symbol's quantity step size from exchangeInfo
+    pub async fn get_step_size(&self, symbol: &str) -> Result<f64, BinanceApiError> {
+        self.get_symbol_info(symbol)
+            .await?
+            .step_size()
+            .ok_or_else(|| BinanceApiError::Parse("LOT_SIZE filter not found".to_string()))
+    }
+
+    /// Get a symbol's price tick and quantity step from exchangeInfo
+    pub async fn get_precision(&self, symbol: &str) -> Result<SymbolPrecision, BinanceApiError> {
+        self.get_symbol_info(symbol).await?.precision().ok_or_else(|| {
+            BinanceApiError::Parse("PRICE_FILTER or LOT_SIZE filter not found".to_string())
+        })
+    }
+
+    /// A symbol's price tick and quantity step from the symbol table alone,
+    /// without falling back to exchangeInfo
+    pub fn cached_precision(&self, symbol: &str) -> Option<SymbolPrecision> {
+        SYMBOL_TABLE.get(&self.base_url, symbol)?.precision()
+    }
+
+    /// `price` and `quantity` as request parameters, to the symbol's tick and
+    /// step decimals when the symbol table has them
+    fn order_params(&self, symbol: &str, price: f64, quantity: f64) -> (String, String) {
+        match self.cached_precision(symbol) {
+            Some(precision) => (precision.format_price(price), precision.format_quantity(quantity)),
+            None => (format_decimal(price), format_decimal(quantity)),
+        }
+    }
 
-        let ticker: TickerPrice = response.json().await?;
-        Ok(ticker.price_f64())
+    /// Get a symbol's minimum order value (in the quote asset) from exchangeInfo
+    pub async fn get_min_notional(&self, symbol: &str) -> Result<f64, BinanceApiError> {
+        self.get_symbol_info(symbol)
+            .await?
+            .min_notional()
+            .ok_or_else(|| BinanceApiError::Parse("NOTIONAL filter not found".to_string()))
+    }
+
+    /// Whether `quantity` at `price` is below the symbol's LOT_SIZE minQty or
+    /// minimum order value, so Binance would reject it as an order
+    pub async fn is_dust(
+        &self,
+        symbol: &str,
+        quantity: f64,
+        price: f64,
+    ) -> Result<bool, BinanceApiError> {
+        let info = self.get_symbol_info(symbol).await?;
+        Ok(quantity < info.min_qty().unwrap_or(0.0)
+            || quantity * price < info.min_notional().unwrap_or(0.0))
+    }
+
+    /// Get current prices for every symbol on the exchange
+    pub async fn get_all_prices(&self) -> Result<Vec<TickerPrice>, BinanceApiError> {
+        let url = format!("{}/api/v3/ticker/price", self.base_url);
+        let request = self.client.get(&url);
+
+        self.execute("GET /api/v3/ticker/price (all)", weight::TICKER_PRICE_ALL, request).await
     }
 
     // ========================================================================
@@ -78,172 +345,727 @@ impl BinanceClient {
     // ========================================================================
 
     /// Get account balance
-    pub async fn get_account(&self) -> Result<AccountInfo, BinanceError> {
-        let query = build_signed_query(&[], &self.secret_key);
-        let url = format!("{}/api/v3/account?{}", self.base_url, query);
+    ///
+    /// Concurrent calls for the same account share one request, so endpoints
+    /// fired together by the app cost a single signed call.
+    pub async fn get_account(&self) -> Result<AccountInfo, BinanceApiError> {
+        let key = format!("{} {}", self.base_url, self.api_key);
+        let client = self.clone();
+        ACCOUNT_FLIGHTS
+            .run(key, async move { client.fetch_account().await })
+            .await
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .send()
-            .await?;
+    /// Get account balance with a request of its own
+    ///
+    /// For reads right after this client cancelled or placed something: an
+    /// in-flight [`get_account`](Self::get_account) may predate the change.
+    pub async fn get_fresh_account(&self) -> Result<AccountInfo, BinanceApiError> {
+        self.fetch_account().await
+    }
 
-        self.handle_response(response).await
+    async fn fetch_account(&self) -> Result<AccountInfo, BinanceApiError> {
+        self.execute_signed("GET /api/v3/account", weight::ACCOUNT, &[], |query| {
+            let url = format!("{}/api/v3/account?{}", self.base_url, query);
+            self.client.get(&url).header("X-MBX-APIKEY", &self.api_key)
+        })
+        .await
     }
 
-    /// Get open orders for BTCUSDT
-    pub async fn get_open_orders(&self) -> Result<Vec<Order>, BinanceError> {
-        let query = build_signed_query(&[("symbol", "BTCUSDT")], &self.secret_key);
-        let url = format!("{}/api/v3/openOrders?{}", self.base_url, query);
+    /// Get open orders for a symbol
+    pub async fn get_open_orders(&self, symbol: &str) -> Result<Vec<Order>, BinanceApiError> {
+        let params = [("symbol", symbol)];
+        let orders = self
+            .execute_signed("GET /api/v3/openOrders", weight::OPEN_ORDERS, &params, |query| {
+                let url = format!("{}/api/v3/openOrders?{}", self.base_url, query);
+                self.client.get(&url).header("X-MBX-APIKEY", &self.api_key)
+            })
+            .await?;
+        Ok(with_client_tags(orders))
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .send()
+    /// Get orders (open or not) with ids from `from_order_id` onward
+    pub async fn get_all_orders(
+        &self,
+        symbol: &str,
+        from_order_id: i64,
+        limit: u32,
+    ) -> Result<Vec<Order>, BinanceApiError> {
+        let order_id_str = from_order_id.to_string();
+        let limit_str = limit.to_string();
+        let params = [
+            ("symbol", symbol),
+            ("orderId", order_id_str.as_str()),
+            ("limit", limit_str.as_str()),
+        ];
+        let orders = self
+            .execute_signed("GET /api/v3/allOrders", weight::ALL_ORDERS, &params, |query| {
+                let url = format!("{}/api/v3/allOrders?{}", self.base_url, query);
+                self.client.get(&url).header("X-MBX-APIKEY", &self.api_key)
+            })
             .await?;
+        Ok(with_client_tags(orders))
+    }
 
-        self.handle_response(response).await
+    /// Get the current state of a single order, including how much has filled
+    pub async fn get_order(&self, symbol: &str, order_id: i64) -> Result<Order, BinanceApiError> {
+        let order_id_str = order_id.to_string();
+        let params = [("symbol", symbol), ("orderId", order_id_str.as_str())];
+        let mut order: Order = self
+            .execute_signed("GET /api/v3/order", weight::QUERY_ORDER, &params, |query| {
+                let url = format!("{}/api/v3/order?{}", self.base_url, query);
+                self.client.get(&url).header("X-MBX-APIKEY", &self.api_key)
+            })
+            .await?;
+        order.client_tag = decode_client_tag(&order.client_order_id);
+        Ok(order)
+    }
+
+    /// Copy each order's client tag onto the trades that filled it
+    ///
+    /// `myTrades` only reports the order id, so the orders are looked up with
+    /// one `allOrders` call starting at the oldest order traded.
+    pub async fn tag_trades(
+        &self,
+        symbol: &str,
+        trades: &mut [Trade],
+    ) -> Result<(), BinanceApiError> {
+        let Some(oldest) = trades.iter().map(|t| t.order_id).min() else {
+            return Ok(());
+        };
+
+        let orders = self.get_all_orders(symbol, oldest, 1000).await?;
+        let tags: HashMap<i64, String> = orders
+            .into_iter()
+            .filter_map(|o| o.client_tag.map(|tag| (o.order_id, tag)))
+            .collect();
+
+        for trade in trades {
+            trade.client_tag = tags.get(&trade.order_id).cloned();
+        }
+        Ok(())
     }
 
     /// Get trade history
-    pub async fn get_trades(&self, limit: u32) -> Result<Vec<Trade>, BinanceError> {
+    pub async fn get_trades(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<Vec<Trade>, BinanceApiError> {
+        self.get_trades_in_range(symbol, limit, None, None).await
+    }
+
+    /// Get trade history within an optional time window (milliseconds)
+    pub async fn get_trades_in_range(
+        &self,
+        symbol: &str,
+        limit: u32,
+        start_time: Option<i64>,
+        end_time: Option<i64>,
+    ) -> Result<Vec<Trade>, BinanceApiError> {
         let limit_str = limit.to_string();
-        let query = build_signed_query(
-            &[("symbol", "BTCUSDT"), ("limit", &limit_str)],
-            &self.secret_key,
-        );
-        let url = format!("{}/api/v3/myTrades?{}", self.base_url, query);
+        let start_str = start_time.map(|t| t.to_string());
+        let end_str = end_time.map(|t| t.to_string());
 
-        let response = self
-            .client
-            .get(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .send()
-            .await?;
+        let mut params = vec![("symbol", symbol), ("limit", limit_str.as_str())];
+        if let Some(ref start) = start_str {
+            params.push(("startTime", start));
+        }
+        if let Some(ref end) = end_str {
+            params.push(("endTime", end));
+        }
 
-        self.handle_response(response).await
+        self.execute_signed("GET /api/v3/myTrades", weight::MY_TRADES, &params, |query| {
+            let url = format!("{}/api/v3/myTrades?{}", self.base_url, query);
+            self.client.get(&url).header("X-MBX-APIKEY", &self.api_key)
+        })
+        .await
+    }
+
+    /// Get up to `limit` trades starting at trade id `from_id`, oldest first
+    async fn get_trades_from(
+        &self,
+        symbol: &str,
+        from_id: i64,
+        limit: u32,
+    ) -> Result<Vec<Trade>, BinanceApiError> {
+        let from_id_str = from_id.to_string();
+        let limit_str = limit.to_string();
+        let params = [
+            ("symbol", symbol),
+            ("fromId", from_id_str.as_str()),
+            ("limit", limit_str.as_str()),
+        ];
+
+        self.execute_signed("GET /api/v3/myTrades", weight::MY_TRADES, &params, |query| {
+            let url = format!("{}/api/v3/myTrades?{}", self.base_url, query);
+            self.client.get(&url).header("X-MBX-APIKEY", &self.api_key)
+        })
+        .await
+    }
+
+    /// Get the `limit` most recent trades older than trade `before_id`, oldest first
+    ///
+    /// `myTrades` only pages forward by id, so this reads day-long windows back from
+    /// the cursor trade's time (at most a week of them) until the page is full.
+    pub async fn get_trades_before(
+        &self,
+        symbol: &str,
+        before_id: i64,
+        limit: u32,
+    ) -> Result<Vec<Trade>, BinanceApiError> {
+        let Some(cursor) = self.get_trades_from(symbol, before_id, 1).await?.pop() else {
+            return Ok(Vec::new());
+        };
+
+        let mut trades: Vec<Trade> = Vec::new();
+        let mut end_time = cursor.time;
+        for _ in 0..CURSOR_LOOKBACK_WINDOWS {
+            let start_time = end_time - MAX_TRADE_WINDOW_MS + 1;
+            let mut window = self
+                .get_trades_in_range(symbol, TRADE_PAGE_LIMIT, Some(start_time), Some(end_time))
+                .await?;
+
+            // A full window is only its oldest trades; read on by id up to the cursor
+            let mut page_len = window.len();
+            while page_len == TRADE_PAGE_LIMIT as usize {
+                let next_id = window.last().map_or(before_id, |t| t.id + 1);
+                if next_id >= before_id {
+                    break;
+                }
+                let page = self.get_trades_from(symbol, next_id, TRADE_PAGE_LIMIT).await?;
+                page_len = page.len();
+                window.extend(page);
+            }
+
+            window.retain(|t| t.id < before_id && t.time <= end_time);
+            window.append(&mut trades);
+            trades = window;
+            if trades.len() >= limit as usize {
+                break;
+            }
+            end_time = start_time - 1;
+        }
+
+        let skip = trades.len().saturating_sub(limit as usize);
+        Ok(trades.split_off(skip))
+    }
+
+    /// Get every trade since `start_time` (milliseconds), or all of them, oldest first
+    ///
+    /// `myTrades` windows are at most a day long, so this reads day-long windows
+    /// from `start_time` until one has a trade, then pages forward by id from there.
+    pub async fn get_trades_since(
+        &self,
+        symbol: &str,
+        start_time: Option<i64>,
+    ) -> Result<Vec<Trade>, BinanceApiError> {
+        let mut trades: Vec<Trade> = Vec::new();
+        if let Some(mut window_start) = start_time {
+            let now = chrono::Utc::now().timestamp_millis();
+            while trades.is_empty() {
+                if window_start > now {
+                    return Ok(trades);
+                }
+                let window_end = window_start + MAX_TRADE_WINDOW_MS - 1;
+                trades = self
+                    .get_trades_in_range(
+                        symbol,
+                        TRADE_PAGE_LIMIT,
+                        Some(window_start),
+                        Some(window_end),
+                    )
+                    .await?;
+                window_start = window_end + 1;
+            }
+        }
+
+        loop {
+            let next_id = trades.last().map_or(0, |t| t.id + 1);
+            let page = self.get_trades_from(symbol, next_id, TRADE_PAGE_LIMIT).await?;
+            let full = page.len() == TRADE_PAGE_LIMIT as usize;
+            trades.extend(page);
+            if !full {
+                return Ok(trades);
+            }
+        }
+    }
+
+    /// Get the fills of one order, oldest first; empty if nothing has filled
+    pub async fn get_order_trades(
+        &self,
+        symbol: &str,
+        order_id: i64,
+    ) -> Result<Vec<Trade>, BinanceApiError> {
+        let order_id_str = order_id.to_string();
+        let params = [("symbol", symbol), ("orderId", order_id_str.as_str())];
+
+        self.execute_signed("GET /api/v3/myTrades", weight::MY_TRADES_BY_ORDER, &params, |query| {
+            let url = format!("{}/api/v3/myTrades?{}", self.base_url, query);
+            self.client.get(&url).header("X-MBX-APIKEY", &self.api_key)
+        })
+        .await
     }
 
     /// Create a limit order
     pub async fn create_limit_order(
         &self,
+        symbol: &str,
         side: &str,
         price: f64,
         quantity: f64,
-    ) -> Result<NewOrderResponse, BinanceError> {
-        let price_str = format!("{:.2}", price);
-        let qty_str = format!("{:.5}", quantity);
+        time_in_force: TimeInForce,
+        client_tag: Option<&str>,
+    ) -> Result<NewOrderResponse, BinanceApiError> {
+        if !self.breaker.allow_request() {
+            return Err(BinanceApiError::CircuitOpen);
+        }
 
-        let params = [
-            ("symbol", "BTCUSDT"),
+        let (price_str, qty_str) = self.order_params(symbol, price, quantity);
+        let client_order_id = client_tag.and_then(encode_client_order_id);
+
+        let mut params = vec![("symbol", symbol), ("side", side)];
+        match time_in_force.as_param() {
+            Some(tif) => {
+                params.push(("type", "LIMIT"));
+                params.push(("timeInForce", tif));
+            }
+            None => params.push(("type", "LIMIT_MAKER")),
+        }
+        params.push(("price", &price_str));
+        params.push(("quantity", &qty_str));
+        if let Some(ref id) = client_order_id {
+            params.push(("newClientOrderId", id));
+        }
+
+        let order: NewOrderResponse = self
+            .execute_signed("POST /api/v3/order", weight::ORDER, &params, |query| {
+                self.client
+                    .post(format!("{}/api/v3/order", self.base_url))
+                    .header("X-MBX-APIKEY", &self.api_key)
+                    .header("Content-Type", "application/x-www-form-urlencoded")
+                    .body(query)
+            })
+            .await?;
+        METRICS.record_order(side);
+        Ok(order)
+    }
+
+    /// Create a GTC stop-limit order that triggers at `price` and fills no worse
+    /// than it, e.g. a BUY waiting above the market for a breakout
+    pub async fn create_stop_limit_order(
+        &self,
+        symbol: &str,
+        side: &str,
+        price: f64,
+        quantity: f64,
+        client_tag: Option<&str>,
+    ) -> Result<NewOrderResponse, BinanceApiError> {
+        if !self.breaker.allow_request() {
+            return Err(BinanceApiError::CircuitOpen);
+        }
+
+        let (price_str, qty_str) = self.order_params(symbol, price, quantity);
+        let client_order_id = client_tag.and_then(encode_client_order_id);
+
+        let mut params = vec![
+            ("symbol", symbol),
             ("side", side),
-            ("type", "LIMIT"),
+            ("type", "STOP_LOSS_LIMIT"),
             ("timeInForce", "GTC"),
             ("price", &price_str),
+            ("stopPrice", &price_str),
             ("quantity", &qty_str),
         ];
+        if let Some(ref id) = client_order_id {
+            params.push(("newClientOrderId", id));
+        }
 
-        let query = build_signed_query(&params, &self.secret_key);
-        let url = format!("{}/api/v3/order", self.base_url);
-
-        let response = self
-            .client
-            .post(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .body(query)
-            .send()
+        let order: NewOrderResponse = self
+            .execute_signed("POST /api/v3/order", weight::ORDER, &params, |query| {
+                self.client
+                    .post(format!("{}/api/v3/order", self.base_url))
+                    .header("X-MBX-APIKEY", &self.api_key)
+                    .header("Content-Type", "application/x-www-form-urlencoded")
+                    .body(query)
+            })
             .await?;
-
-        self.handle_response(response).await
+        METRICS.record_order(side);
+        Ok(order)
     }
 
     /// Create a market order (immediate execution at current price)
     pub async fn create_market_order(
         &self,
+        symbol: &str,
         side: &str,
-        quantity: f64,
-    ) -> Result<NewOrderResponse, BinanceError> {
-        let qty_str = format!("{:.5}", quantity);
+        quantity: MarketQuantity,
+        client_tag: Option<&str>,
+    ) -> Result<NewOrderResponse, BinanceApiError> {
+        if !self.breaker.allow_request() {
+            return Err(BinanceApiError::CircuitOpen);
+        }
 
-        let params = [
-            ("symbol", "BTCUSDT"),
+        let (qty_param, qty_str) = quantity.as_param(self.cached_precision(symbol).as_ref());
+        let client_order_id = client_tag.and_then(encode_client_order_id);
+
+        let mut params = vec![
+            ("symbol", symbol),
             ("side", side),
             ("type", "MARKET"),
-            ("quantity", &qty_str),
+            (qty_param, &qty_str),
         ];
+        if let Some(ref id) = client_order_id {
+            params.push(("newClientOrderId", id));
+        }
 
-        let query = build_signed_query(&params, &self.secret_key);
-        let url = format!("{}/api/v3/order", self.base_url);
-
-        let response = self
-            .client
-            .post(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .body(query)
-            .send()
+        let order: NewOrderResponse = self
+            .execute_signed("POST /api/v3/order", weight::ORDER, &params, |query| {
+                self.client
+                    .post(format!("{}/api/v3/order", self.base_url))
+                    .header("X-MBX-APIKEY", &self.api_key)
+                    .header("Content-Type", "application/x-www-form-urlencoded")
+                    .body(query)
+            })
             .await?;
+        METRICS.record_order(side);
+        Ok(order)
+    }
 
-        self.handle_response(response).await
+    /// Market BUY spending `quote_qty` of the quote asset (e.g. $100 of BTC)
+    ///
+    /// Sent as `quoteOrderQty`, so Binance sizes the order; the fills report
+    /// how much was bought.
+    pub async fn create_market_buy_quote(
+        &self,
+        symbol: &str,
+        quote_qty: f64,
+        client_tag: Option<&str>,
+    ) -> Result<NewOrderResponse, BinanceApiError> {
+        self.create_market_order(symbol, "BUY", MarketQuantity::Quote(quote_qty), client_tag)
+            .await
     }
 
     /// Cancel an order
-    pub async fn cancel_order(&self, order_id: i64) -> Result<CancelOrderResponse, BinanceError> {
+    pub async fn cancel_order(
+        &self,
+        symbol: &str,
+        order_id: i64,
+    ) -> Result<CancelOrderResponse, BinanceApiError> {
         let order_id_str = order_id.to_string();
-        let query = build_signed_query(
-            &[("symbol", "BTCUSDT"), ("orderId", &order_id_str)],
-            &self.secret_key,
-        );
-        let url = format!("{}/api/v3/order?{}", self.base_url, query);
+        let params = [("symbol", symbol), ("orderId", order_id_str.as_str())];
+        self.execute_signed("DELETE /api/v3/order", weight::ORDER, &params, |query| {
+            let url = format!("{}/api/v3/order?{}", self.base_url, query);
+            self.client.delete(&url).header("X-MBX-APIKEY", &self.api_key)
+        })
+        .await
+    }
 
-        let response = self
+    /// Atomically cancel `order_id` and place a GTC order resting at `price` in its place
+    ///
+    /// Sent with `STOP_ON_FAILURE`, so no new order is placed if the cancel fails.
+    pub async fn cancel_replace_order(
+        &self,
+        symbol: &str,
+        order_id: i64,
+        side: &str,
+        price: RestingPrice,
+        quantity: f64,
+        client_tag: Option<&str>,
+    ) -> Result<CancelReplaceResponse, BinanceApiError> {
+        if !self.breaker.allow_request() {
+            return Err(BinanceApiError::CircuitOpen);
+        }
+
+        let order_id_str = order_id.to_string();
+        let (price_str, qty_str) = self.order_params(symbol, price.price(), quantity);
+        let client_order_id = client_tag.and_then(encode_client_order_id);
+
+        let mut params = vec![
+            ("symbol", symbol),
+            ("side", side),
+            ("type", price.order_type()),
+            ("timeInForce", "GTC"),
+            ("cancelReplaceMode", "STOP_ON_FAILURE"),
+            ("cancelOrderId", order_id_str.as_str()),
+            ("price", &price_str),
+        ];
+        if let RestingPrice::StopLimit(_) = price {
+            params.push(("stopPrice", &price_str));
+        }
+        params.push(("quantity", &qty_str));
+        if let Some(ref id) = client_order_id {
+            params.push(("newClientOrderId", id));
+        }
+
+        let replaced: CancelReplaceResponse = self
+            .execute_signed(
+                "POST /api/v3/order/cancelReplace",
+                weight::CANCEL_REPLACE,
+                &params,
+                |query| {
+                    self.client
+                        .post(format!("{}/api/v3/order/cancelReplace", self.base_url))
+                        .header("X-MBX-APIKEY", &self.api_key)
+                        .header("Content-Type", "application/x-www-form-urlencoded")
+                        .body(query)
+                },
+            )
+            .await?;
+        METRICS.record_order(side);
+        Ok(replaced)
+    }
+
+    // ========================================================================
+    // User Data Stream (API key only, no signature)
+    // ========================================================================
+
+    /// Open a user data stream and return its listenKey
+    pub async fn start_user_data_stream(&self) -> Result<String, BinanceApiError> {
+        let url = format!("{}/api/v3/userDataStream", self.base_url);
+        let request = self
             .client
-            .delete(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .send()
+            .post(&url)
+            .header("X-MBX-APIKEY", &self.api_key);
+
+        let key: ListenKey = self
+            .execute("POST /api/v3/userDataStream", weight::USER_DATA_STREAM, request)
             .await?;
+        Ok(key.listen_key)
+    }
 
-        self.handle_response(response).await
+    /// Extend a listenKey's validity by 60 minutes
+    pub async fn keepalive_user_data_stream(
+        &self,
+        listen_key: &str,
+    ) -> Result<(), BinanceApiError> {
+        let url = format!(
+            "{}/api/v3/userDataStream?listenKey={}",
+            self.base_url, listen_key
+        );
+        let request = self
+            .client
+            .put(&url)
+            .header("X-MBX-APIKEY", &self.api_key);
+
+        let _: serde_json::Value = self
+            .execute("PUT /api/v3/userDataStream", weight::USER_DATA_STREAM, request)
+            .await?;
+        Ok(())
+    }
+
+    /// WebSocket URL for a listenKey
+    pub fn user_stream_url(&self, listen_key: &str) -> String {
+        format!("{}/{}", self.stream_url, listen_key)
+    }
+
+    /// WebSocket URL for a symbol's best bid/ask stream
+    pub fn book_ticker_stream_url(&self, symbol: &str) -> String {
+        format!("{}/{}@bookTicker", self.stream_url, symbol.to_lowercase())
     }
 
     // ========================================================================
     // Helper Methods
     // ========================================================================
 
+    /// Send a request, recording its weight and latency
+    async fn execute<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        weight: u64,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, BinanceApiError> {
+        let _permit = throttle().acquire(endpoint).await?;
+        self.send(endpoint, weight, request).await
+    }
+
+    /// Send a request whose throttle permit the caller already holds
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        weight: u64,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, BinanceApiError> {
+        STATS.record(endpoint, weight);
+        let started = std::time::Instant::now();
+        let response = request.send().await;
+        METRICS.observe_binance_latency(endpoint, started.elapsed());
+        self.handle_response(response).await
+    }
+
+    /// Sign `params` and send the request `build` makes from the signed query
+    ///
+    /// A -1021 rejection means our clock has drifted from Binance's, e.g. after
+    /// a suspend or a long pause. The offset is resynced and the request is
+    /// re-signed and sent once more. Binance rejects these before acting, so
+    /// retrying an order can't place it twice.
+    async fn execute_signed<T, F>(
+        &self,
+        endpoint: &'static str,
+        weight: u64,
+        params: &[(&str, &str)],
+        build: F,
+    ) -> Result<T, BinanceApiError>
+    where
+        T: serde::de::DeserializeOwned,
+        F: Fn(String) -> reqwest::RequestBuilder,
+    {
+        let result = self.execute_signed_once(endpoint, weight, params, &build).await;
+        let clock_drifted = matches!(
+            &result,
+            Err(e) if e.code() == Some(TIMESTAMP_OUTSIDE_RECV_WINDOW)
+        );
+        if !clock_drifted {
+            return result;
+        }
+
+        match self.sync_server_time().await {
+            Ok(offset) => {
+                tracing::warn!(
+                    "{} rejected our timestamp; clock offset resynced to {}ms, retrying",
+                    endpoint,
+                    offset
+                );
+                self.execute_signed_once(endpoint, weight, params, &build).await
+            }
+            Err(e) => {
+                tracing::warn!("Server time resync after -1021 failed: {}", e);
+                result
+            }
+        }
+    }
+
+    /// Sign `params` and send the request once, signing only after the throttle
+    /// lets it through so its timestamp can't age in the queue past recvWindow
+    async fn execute_signed_once<T, F>(
+        &self,
+        endpoint: &'static str,
+        weight: u64,
+        params: &[(&str, &str)],
+        build: &F,
+    ) -> Result<T, BinanceApiError>
+    where
+        T: serde::de::DeserializeOwned,
+        F: Fn(String) -> reqwest::RequestBuilder,
+    {
+        let _permit = throttle().acquire(endpoint).await?;
+        self.send(endpoint, weight, build(self.signed_query(params))).await
+    }
+
+    /// The signed query a request with `params` would send now, for
+    /// `/debug/sign-preview`; callers must not expose it whole
+    pub fn preview_signed_query(&self, params: &[(&str, &str)]) -> String {
+        self.signed_query(params)
+    }
+
+    /// Query string signed with Binance's current time
+    fn signed_query(&self, params: &[(&str, &str)]) -> String {
+        let timestamp = SERVER_CLOCK.now_ms(&self.base_url);
+        build_signed_query(params, &self.secret_key, self.recv_window_ms, timestamp)
+    }
+
+    /// Measure how far our clock is from Binance's and use that for signing
+    async fn sync_server_time(&self) -> Result<i64, BinanceApiError> {
+        let sent_at = chrono::Utc::now().timestamp_millis();
+        let server_time = self.get_server_time().await?;
+        let received_at = chrono::Utc::now().timestamp_millis();
+        Ok(SERVER_CLOCK.record(&self.base_url, server_time, sent_at, received_at))
+    }
+
+    /// Text safe to log: reqwest errors embed the signed URL
+    fn redact(&self, text: &str) -> String {
+        redact(text, &[&self.api_key, &self.secret_key])
+    }
+
+    /// Map a failed send, keeping the signature, timestamp, and keys out of the message
+    fn transport_error(&self, error: reqwest::Error) -> BinanceApiError {
+        let message = self.redact(&error.to_string());
+        if error.is_timeout() {
+            BinanceApiError::Timeout(message)
+        } else {
+            BinanceApiError::Network(message)
+        }
+    }
+
     async fn handle_response<T: serde::de::DeserializeOwned>(
         &self,
-        response: reqwest::Response,
-    ) -> Result<T, BinanceError> {
-        if !response.status().is_success() {
+        response: Result<reqwest::Response, reqwest::Error>,
+    ) -> Result<T, BinanceApiError> {
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                self.breaker.record_failure();
+                return Err(self.transport_error(e));
+            }
+        };
+
+        // Binance reports the weight consumed in the current minute
+        if let Some(used) = response
+            .headers()
+            .get("X-MBX-USED-WEIGHT-1M")
+            .and_then(|v| v.to_str().ok())
+        {
+            tracing::debug!("Binance used weight (1m): {}", used);
+        }
+
+        let status = response.status();
+        if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            if let Ok(error) = serde_json::from_str::<super::models::BinanceError>(&error_text) {
-                return Err(BinanceError::Api {
-                    code: error.code,
-                    message: error.msg,
-                });
+            let error = match BinanceApiError::from_body(&self.redact(&error_text)) {
+                BinanceApiError::Api { code: -1, message } if status == StatusCode::NOT_FOUND => {
+                    BinanceApiError::Api {
+                        code: ENDPOINT_NOT_FOUND,
+                        message,
+                    }
+                }
+                error => error,
+            };
+
+            // 5xx, 418/429 bans, and auth failures say nothing about this request.
+            // Any other rejection is about the request itself: Binance answered,
+            // but that proves nothing about its health, so the breaker is left as is
+            let banned = status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 418;
+            if status.is_server_error() || banned || error.is_systemic() {
+                self.breaker.record_failure();
             }
-            return Err(BinanceError::Api {
-                code: -1,
-                message: error_text,
-            });
+            return Err(error);
         }
+        self.breaker.record_success();
 
         response
             .json()
             .await
-            .map_err(|e| BinanceError::Parse(e.to_string()))
+            .map_err(|e| BinanceApiError::Parse(self.redact(&e.to_string())))
     }
 
-    /// Calculate BTC quantity from USD amount
-    pub fn calculate_quantity(usd_amount: f64, price: f64) -> f64 {
-        if price <= 0.0 {
+    /// Calculate BTC quantity from USD amount, floored to `step_size`
+    ///
+    /// The result is always a whole number of steps, so Binance accepts it, and
+    /// never costs more than `usd_amount` at `price`.
+    pub fn calculate_quantity(usd_amount: f64, price: f64, step_size: f64) -> f64 {
+        if price <= 0.0 || usd_amount <= 0.0 || step_size <= 0.0 {
             return 0.0;
         }
-        usd_amount / price
+
+        // Nudge up before flooring so 199.99999999 steps (float noise) counts as 200
+        let mut steps = (usd_amount / price / step_size + 1e-9).floor();
+        if steps * step_size * price > usd_amount * (1.0 + 1e-12) {
+            steps -= 1.0;
+        }
+
+        // Trim float noise to the step's decimal places
+        let decimals = (-step_size.log10()).ceil().max(0.0) as i32;
+        let scale = 10f64.powi(decimals);
+        (steps * step_size * scale).round() / scale
+    }
+}
+
+/// Decode each order's client tag from its client order id
+fn with_client_tags(mut orders: Vec<Order>) -> Vec<Order> {
+    for order in &mut orders {
+        order.client_tag = decode_client_tag(&order.client_order_id);
     }
+    orders
 }
 
 // ============================================================================
@@ -251,36 +1073,636 @@ impl BinanceClient {
 // ============================================================================
 
 impl BinanceClient {
-    /// Create a grid pair (BUY + SELL orders)
+    /// Create a grid pair (BUY + SELL orders) for an already step-rounded quantity
     pub async fn create_grid_pair(
         &self,
+        symbol: &str,
         buy_price: f64,
         sell_price: f64,
-        amount_usd: f64,
-    ) -> Result<(NewOrderResponse, NewOrderResponse), BinanceError> {
-        let quantity = Self::calculate_quantity(amount_usd, buy_price);
+        quantity: f64,
+        client_tag: Option<&str>,
+    ) -> Result<(NewOrderResponse, NewOrderResponse), BinanceApiError> {
+        let gtc = TimeInForce::Gtc;
 
         // Create both orders concurrently
         let (buy_result, sell_result) = tokio::join!(
-            self.create_limit_order("BUY", buy_price, quantity),
-            self.create_limit_order("SELL", sell_price, quantity)
+            self.create_limit_order(symbol, "BUY", buy_price, quantity, gtc, client_tag),
+            self.create_limit_order(symbol, "SELL", sell_price, quantity, gtc, client_tag)
         );
 
         Ok((buy_result?, sell_result?))
     }
 
     /// Modify an order (cancel and recreate at new price)
+    ///
+    /// Uses cancelReplace so the order is never off the book between the two
+    /// steps. Where that endpoint doesn't exist it falls back to a separate
+    /// cancel and create. A plain price places a limit order.
     pub async fn modify_order(
         &self,
+        symbol: &str,
         order_id: i64,
         side: &str,
-        new_price: f64,
+        new_price: impl Into<RestingPrice>,
         quantity: f64,
-    ) -> Result<NewOrderResponse, BinanceError> {
+        client_tag: Option<&str>,
+    ) -> Result<ReplacedOrder, BinanceApiError> {
+        let new_price = new_price.into();
+        match self
+            .cancel_replace_order(symbol, order_id, side, new_price, quantity, client_tag)
+            .await
+        {
+            Ok(replaced) => {
+                return Ok(ReplacedOrder {
+                    cancelled: replaced.cancel_response,
+                    new_order: replaced.new_order_response,
+                })
+            }
+            Err(e) if cancel_replace_unsupported(&e) => tracing::warn!(
+                "cancelReplace unavailable ({}), cancelling and recreating order {}",
+                e,
+                order_id
+            ),
+            Err(e) => return Err(e),
+        }
+
         // First cancel the existing order
-        self.cancel_order(order_id).await?;
+        let cancelled = self.cancel_order(symbol, order_id).await?;
+
+        // Create new order at the new price, keeping its tag
+        let created = match new_price {
+            RestingPrice::Limit(price) => {
+                let gtc = TimeInForce::Gtc;
+                self.create_limit_order(symbol, side, price, quantity, gtc, client_tag)
+                    .await
+            }
+            RestingPrice::StopLimit(price) => {
+                self.create_stop_limit_order(symbol, side, price, quantity, client_tag)
+                    .await
+            }
+        };
+        let new_order = created.map_err(|e| {
+            let (code, message) = match e {
+                BinanceApiError::Api { code, message } => (code, message),
+                other => (other.code().unwrap_or(-1), other.to_string()),
+            };
+            BinanceApiError::ReplacementFailed {
+                cancelled: Box::new(cancelled.clone()),
+                code,
+                message,
+            }
+        })?;
+
+        Ok(ReplacedOrder {
+            cancelled,
+            new_order,
+        })
+    }
+}
+
+/// Whether a cancelReplace failed because the endpoint isn't there rather
+/// than on the order itself
+fn cancel_replace_unsupported(error: &BinanceApiError) -> bool {
+    error.code() == Some(ENDPOINT_NOT_FOUND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::{credentials, mock_client, serve};
+
+    #[test]
+    fn test_quantity_is_floored_to_step() {
+        let cases = [
+            (100.0, 50000.0),
+            (100.0, 43210.55),
+            (37.5, 61234.0),
+            (1.0, 97000.0),
+            (1000.0, 33333.33),
+        ];
+
+        // BTCUSDT's LOT_SIZE step
+        let step_size = 0.00001;
+        for (amount, price) in cases {
+            let quantity = BinanceClient::calculate_quantity(amount, price, step_size);
+            let steps = quantity / step_size;
+
+            assert!((steps - steps.round()).abs() < 1e-6, "{} not a step multiple", quantity);
+            assert!(quantity * price <= amount + 1e-9, "{} over budget at {}", quantity, price);
+            assert!((quantity + step_size) * price > amount, "{} not maximal", quantity);
+        }
+    }
+
+    #[test]
+    fn test_exact_multiple_is_kept() {
+        assert_eq!(BinanceClient::calculate_quantity(100.0, 50000.0, 0.00001), 0.002);
+    }
+
+    #[test]
+    fn test_amount_below_one_step_is_zero() {
+        assert_eq!(BinanceClient::calculate_quantity(0.1, 50000.0, 0.00001), 0.0);
+        assert_eq!(BinanceClient::calculate_quantity(100.0, 0.0, 0.00001), 0.0);
+    }
+
+    #[test]
+    fn test_unknown_order_body_maps_to_variant() {
+        let body = r#"{"code":-2011,"msg":"Unknown order sent."}"#;
+
+        match BinanceApiError::from_body(body) {
+            BinanceApiError::UnknownOrder { code, message } => {
+                assert_eq!(code, -2011);
+                assert_eq!(message, "Unknown order sent.");
+            }
+            other => panic!("expected UnknownOrder, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_other_api_codes_stay_generic() {
+        let body = r#"{"code":-1013,"msg":"Filter failure: LOT_SIZE"}"#;
+
+        let error = BinanceApiError::from_body(body);
+        assert!(matches!(error, BinanceApiError::Api { code: -1013, .. }));
+        assert_eq!(error.code(), Some(-1013));
+    }
+
+    #[test]
+    fn test_unparseable_body_is_kept_as_message() {
+        let error = BinanceApiError::from_body("<html>502</html>");
+        assert!(matches!(error, BinanceApiError::Api { code: -1, .. }));
+    }
+
+    #[test]
+    fn test_cancel_replace_with_gone_order_maps_to_unknown_order() {
+        let body = r#"{"code":-2022,"msg":"Order cancel-replace failed.","data":{
+            "cancelResult":"FAILURE","newOrderResult":"NOT_ATTEMPTED",
+            "cancelResponse":{"code":-2011,"msg":"Unknown order sent."},
+            "newOrderResponse":null}}"#;
+
+        let error = BinanceApiError::from_body(body);
+        assert!(matches!(error, BinanceApiError::UnknownOrder { code: -2011, .. }), "{:?}", error);
+
+        // The cancel succeeded but the new order didn't: not an unknown order
+        let body = r#"{"code":-2021,"msg":"Order cancel-replace partially failed.","data":{
+            "cancelResult":"SUCCESS","newOrderResult":"FAILURE",
+            "cancelResponse":{"symbol":"BTCUSDT","orderId":42,"status":"CANCELED"},
+            "newOrderResponse":{"code":-2010,"msg":"Account has insufficient balance."}}}"#;
+        let BinanceApiError::ReplacementFailed { cancelled, code, message } =
+            BinanceApiError::from_body(body)
+        else {
+            panic!("cancelled without a replacement");
+        };
+        assert_eq!(cancelled.order_id, 42);
+        assert_eq!(cancelled.status, "CANCELED");
+        assert_eq!(code, -2010);
+        assert_eq!(message, "Account has insufficient balance.");
+    }
+
+    fn replacement_order() -> serde_json::Value {
+        serde_json::json!({
+            "symbol": "BTCUSDT",
+            "orderId": 43,
+            "clientOrderId": "def",
+            "transactTime": 1700000000000i64,
+            "price": "51000.00",
+            "origQty": "0.00100",
+            "executedQty": "0",
+            "status": "NEW",
+            "type": "LIMIT",
+            "side": "BUY"
+        })
+    }
+
+    /// Mock Binance for `modify_order`, with or without cancelReplace,
+    /// recording the endpoints hit in order
+    async fn mock_modify_client(
+        cancel_replace: bool,
+    ) -> (BinanceClient, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use axum::{routing::post, Json, Router};
+        use std::sync::{Arc, Mutex};
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (on_replace, on_cancel, on_create) = (calls.clone(), calls.clone(), calls.clone());
+        let mut mock = Router::new().route(
+            "/api/v3/order",
+            post(move || async move {
+                on_create.lock().unwrap().push("create".to_string());
+                Json(replacement_order())
+            })
+            .delete(move || async move {
+                on_cancel.lock().unwrap().push("cancel".to_string());
+                Json(serde_json::json!({
+                    "symbol": "BTCUSDT",
+                    "orderId": 42,
+                    "status": "CANCELED"
+                }))
+            }),
+        );
+        if cancel_replace {
+            mock = mock.route(
+                "/api/v3/order/cancelReplace",
+                post(move |body: String| async move {
+                    on_replace.lock().unwrap().push(body);
+                    Json(serde_json::json!({
+                        "cancelResult": "SUCCESS",
+                        "newOrderResult": "SUCCESS",
+                        "cancelResponse": {
+                            "symbol": "BTCUSDT",
+                            "origClientOrderId": "abc",
+                            "orderId": 42,
+                            "status": "CANCELED"
+                        },
+                        "newOrderResponse": replacement_order()
+                    }))
+                }),
+            );
+        }
+        let client = mock_client(mock).await;
+        (client, calls)
+    }
+
+    #[tokio::test]
+    async fn test_modify_order_uses_cancel_replace() {
+        let (client, calls) = mock_modify_client(true).await;
+        let info: SymbolInfo = serde_json::from_value(serde_json::json!({
+            "symbol": "BTCUSDT",
+            "filters": [
+                { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                { "filterType": "LOT_SIZE", "stepSize": "0.00001000" }
+            ]
+        }))
+        .unwrap();
+        SYMBOL_TABLE.insert(&client.base_url, info);
+
+        let replaced = client
+            .modify_order("BTCUSDT", 42, "BUY", 51000.0, 0.001, Some("grid-A"))
+            .await
+            .unwrap();
+        assert_eq!(replaced.cancelled.order_id, 42);
+        assert_eq!(replaced.new_order.order_id, 43);
+        assert_eq!(replaced.new_order.price, "51000.00");
+
+        // One atomic request, no separate cancel or create
+        let calls = calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 1, "{:?}", calls);
+        let form = &calls[0];
+        assert!(form.contains("cancelReplaceMode=STOP_ON_FAILURE"), "{}", form);
+        assert!(form.contains("cancelOrderId=42"), "{}", form);
+        assert!(form.contains("price=51000.00&quantity=0.00100"), "{}", form);
+        assert!(form.contains("newClientOrderId="), "{}", form);
+    }
+
+    #[tokio::test]
+    async fn test_stop_limit_replacement_carries_its_stop_price() {
+        let (client, calls) = mock_modify_client(true).await;
+        client
+            .modify_order("BTCUSDT", 42, "BUY", RestingPrice::StopLimit(51000.0), 0.001, None)
+            .await
+            .unwrap();
+
+        let form = calls.lock().unwrap()[0].clone();
+        assert!(form.contains("type=STOP_LOSS_LIMIT&timeInForce=GTC"), "{}", form);
+        assert!(form.contains("price=51000&stopPrice=51000&"), "{}", form);
+    }
+
+    #[tokio::test]
+    async fn test_modify_order_falls_back_without_cancel_replace() {
+        let (client, calls) = mock_modify_client(false).await;
+        let replaced = client
+            .modify_order("BTCUSDT", 42, "BUY", 51000.0, 0.001, None)
+            .await
+            .unwrap();
+        assert_eq!(replaced.cancelled.order_id, 42);
+        assert_eq!(replaced.new_order.order_id, 43);
+        assert_eq!(*calls.lock().unwrap(), ["cancel", "create"]);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_create_failure_reports_the_cancelled_order() {
+        use axum::{http::StatusCode, routing::delete, Json, Router};
+
+        let mock = Router::new().route(
+            "/api/v3/order",
+            delete(|| async {
+                Json(serde_json::json!({ "symbol": "BTCUSDT", "orderId": 42, "status": "CANCELED" }))
+            })
+            .post(|| async {
+                (
+                    StatusCode::BAD_REQUEST,
+                    r#"{"code":-2010,"msg":"Account has insufficient balance."}"#,
+                )
+            }),
+        );
+        let client = mock_client(mock).await;
+
+        let error = client
+            .modify_order("BTCUSDT", 42, "BUY", 51000.0, 0.001, None)
+            .await
+            .unwrap_err();
+        let BinanceApiError::ReplacementFailed { cancelled, code, .. } = error else {
+            panic!("expected ReplacementFailed, got {:?}", error);
+        };
+        assert_eq!(cancelled.order_id, 42);
+        assert_eq!(code, -2010);
+    }
+
+    /// Mock Binance accepting any order, returning the client and the last form received
+    async fn mock_order_client() -> (BinanceClient, std::sync::Arc<std::sync::Mutex<String>>) {
+        use axum::{routing::post, Json, Router};
+        use std::sync::{Arc, Mutex};
+
+        let received = Arc::new(Mutex::new(String::new()));
+        let recorded = received.clone();
+        let mock = Router::new().route(
+            "/api/v3/order",
+            post(move |body: String| async move {
+                *recorded.lock().unwrap() = body;
+                Json(serde_json::json!({
+                    "symbol": "BTCUSDT",
+                    "orderId": 1,
+                    "clientOrderId": "abc",
+                    "transactTime": 1700000000000i64,
+                    "price": "50000.00",
+                    "origQty": "0.00100",
+                    "executedQty": "0",
+                    "status": "NEW",
+                    "type": "LIMIT",
+                    "side": "BUY"
+                }))
+            }),
+        );
+        let client = mock_client(mock).await;
+        (client, received)
+    }
+
+    /// Place a limit order against a mock Binance and return the form it received
+    async fn limit_order_form(time_in_force: TimeInForce, client_tag: Option<&str>) -> String {
+        let (client, received) = mock_order_client().await;
+        client
+            .create_limit_order("BTCUSDT", "BUY", 50000.0, 0.001, time_in_force, client_tag)
+            .await
+            .unwrap();
+
+        let form = received.lock().unwrap();
+        form.clone()
+    }
+
+    #[tokio::test]
+    async fn test_market_buy_quote_sends_quote_order_qty() {
+        let (client, received) = mock_order_client().await;
+        client.create_market_buy_quote("BTCUSDT", 100.0, None).await.unwrap();
+
+        let form = received.lock().unwrap().clone();
+        assert!(form.contains("side=BUY&type=MARKET&quoteOrderQty=100&"), "{}", form);
+        assert!(!form.contains("&quantity="), "{}", form);
+    }
+
+    #[tokio::test]
+    async fn test_limit_order_sends_time_in_force() {
+        for (time_in_force, param) in [
+            (TimeInForce::Gtc, "GTC"),
+            (TimeInForce::Ioc, "IOC"),
+            (TimeInForce::Fok, "FOK"),
+        ] {
+            let form = limit_order_form(time_in_force, None).await;
+            assert!(form.contains("type=LIMIT&"), "{}", form);
+            assert!(form.contains(&format!("timeInForce={}", param)), "{}", form);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_only_is_sent_as_limit_maker() {
+        let form = limit_order_form(TimeInForce::PostOnly, None).await;
+        assert!(form.contains("type=LIMIT_MAKER"), "{}", form);
+        assert!(!form.contains("timeInForce"), "{}", form);
+    }
+
+    #[tokio::test]
+    async fn test_client_tag_is_sent_as_client_order_id() {
+        let form = limit_order_form(TimeInForce::Gtc, Some("grid-A")).await;
+        let id = form
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("newClientOrderId="))
+            .expect("newClientOrderId not sent");
+        assert_eq!(decode_client_tag(id).as_deref(), Some("grid-A"));
+
+        let form = limit_order_form(TimeInForce::Gtc, None).await;
+        assert!(!form.contains("newClientOrderId"), "{}", form);
+    }
+
+    #[test]
+    fn test_auth_failures_are_systemic() {
+        let body = r#"{"code":-2015,"msg":"Invalid API-key, IP, or permissions for action."}"#;
+        assert!(BinanceApiError::from_body(body).is_systemic());
+
+        let body = r#"{"code":-2010,"msg":"Account has insufficient balance."}"#;
+        assert!(!BinanceApiError::from_body(body).is_systemic());
+    }
+
+    #[tokio::test]
+    async fn test_trades_before_cursor_walk_back_by_day() {
+        use axum::{extract::Query, routing::get, Json, Router};
+
+        const HOUR_MS: i64 = 60 * 60 * 1000;
+        let base = 1_700_000_000_000i64;
+        // Two trades the day before the cursor's window, two inside it, then the cursor
+        let times = [(1, 0), (2, 1), (3, 30), (4, 31), (5, 32), (6, 33)];
+        let mock = Router::new().route(
+            "/api/v3/myTrades",
+            get(move |Query(query): Query<HashMap<String, String>>| async move {
+                let param = |name: &str| query.get(name).map(|v| v.parse::<i64>().unwrap());
+                let limit = param("limit").unwrap() as usize;
+                let trades: Vec<_> = times
+                    .iter()
+                    .map(|&(id, hours)| (id, base + hours * HOUR_MS))
+                    .filter(|&(id, time)| match param("fromId") {
+                        Some(from_id) => id >= from_id,
+                        None => (param("startTime").unwrap()..=param("endTime").unwrap())
+                            .contains(&time),
+                    })
+                    .take(limit)
+                    .map(|(id, time)| {
+                        serde_json::json!({
+                            "id": id, "orderId": id, "symbol": "BTCUSDT",
+                            "price": "50000.00", "qty": "0.00100", "quoteQty": "50",
+                            "commission": "0", "commissionAsset": "USDT",
+                            "time": time, "isBuyer": true, "isMaker": true
+                        })
+                    })
+                    .collect();
+                Json(trades)
+            }),
+        );
+        let client = mock_client(mock).await;
+
+        let ids = |trades: Vec<Trade>| trades.iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(client.get_trades_before("BTCUSDT", 5, 2).await.unwrap()), [3, 4]);
+        assert_eq!(ids(client.get_trades_before("BTCUSDT", 5, 3).await.unwrap()), [2, 3, 4]);
+        assert_eq!(ids(client.get_trades_before("BTCUSDT", 3, 10).await.unwrap()), [1, 2]);
+        assert!(client.get_trades_before("BTCUSDT", 1, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trades_since_spans_more_than_a_day() {
+        use axum::{extract::Query, routing::get, Json, Router};
+
+        const HOUR_MS: i64 = 60 * 60 * 1000;
+        let base = 1_700_000_000_000i64;
+        // A quiet first day, then trades spread over the next three
+        let times = [(1, 30), (2, 31), (3, 60), (4, 90)];
+        let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = requests.clone();
+        let mock = Router::new().route(
+            "/api/v3/myTrades",
+            get(move |Query(query): Query<HashMap<String, String>>| async move {
+                counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let param = |name: &str| query.get(name).map(|v| v.parse::<i64>().unwrap());
+                let trades: Vec<_> = times
+                    .iter()
+                    .map(|&(id, hours)| (id, base + hours * HOUR_MS))
+                    .filter(|&(id, time)| match param("fromId") {
+                        Some(from_id) => id >= from_id,
+                        None => (param("startTime").unwrap()..=param("endTime").unwrap())
+                            .contains(&time),
+                    })
+                    .map(|(id, time)| {
+                        serde_json::json!({
+                            "id": id, "orderId": id, "symbol": "BTCUSDT",
+                            "price": "50000.00", "qty": "0.00100", "quoteQty": "50",
+                            "commission": "0", "commissionAsset": "USDT",
+                            "time": time, "isBuyer": true, "isMaker": true
+                        })
+                    })
+                    .collect();
+                Json(trades)
+            }),
+        );
+        let client = mock_client(mock).await;
+
+        let ids = |trades: Vec<Trade>| trades.iter().map(|t| t.id).collect::<Vec<_>>();
+        let since = client.get_trades_since("BTCUSDT", Some(base)).await.unwrap();
+        assert_eq!(ids(since), [1, 2, 3, 4]);
+        // Two day windows to find the first trade, then one page by id
+        assert_eq!(requests.swap(0, std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(ids(client.get_trades_since("BTCUSDT", None).await.unwrap()), [1, 2, 3, 4]);
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_slow_response_maps_to_timeout() {
+        use axum::{routing::get, Router};
+
+        let mock = Router::new().route(
+            "/api/v3/ticker/price",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                r#"{"symbol":"BTCUSDT","price":"50000.00"}"#
+            }),
+        );
+        let client = BinanceClient::from_credentials(&BinanceCredentials {
+            request_timeout_ms: 100,
+            ..credentials(serve(mock).await)
+        });
+
+        match client.get_price("BTCUSDT").await {
+            Err(BinanceApiError::Timeout(_)) => {}
+            other => panic!("expected Timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signed_request_error_is_redacted() {
+        use axum::{routing::get, Router};
+
+        let mock = Router::new().route(
+            "/api/v3/openOrders",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                "[]"
+            }),
+        );
+        let client = BinanceClient::from_credentials(&BinanceCredentials {
+            request_timeout_ms: 100,
+            ..credentials(serve(mock).await)
+        });
+
+        // reqwest puts the full signed URL in its error message
+        let message = client.get_open_orders("BTCUSDT").await.unwrap_err().to_string();
+        assert!(message.contains("symbol=BTCUSDT"), "{}", message);
+        assert!(message.contains("timestamp=[REDACTED]"), "{}", message);
+        assert!(message.contains("signature=[REDACTED]"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_get_account_sends_one_request() {
+        use axum::{routing::get, Router};
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        let requests = Arc::new(AtomicU32::new(0));
+        let counter = requests.clone();
+        let mock = Router::new().route(
+            "/api/v3/account",
+            get(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                r#"{"balances":[{"asset":"BTC","free":"0.5","locked":"0"}],
+                    "canTrade":true,"canWithdraw":true,"canDeposit":true}"#
+            }),
+        );
+        let client = mock_client(mock).await;
+
+        let results = futures::future::join_all((0..8).map(|_| client.get_account())).await;
+        for result in results {
+            assert_eq!(result.unwrap().balances[0].free, "0.5");
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // A fresh read doesn't join the one already running
+        let (shared, fresh) = tokio::join!(client.get_account(), client.get_fresh_account());
+        assert!(shared.is_ok() && fresh.is_ok());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_error_resyncs_clock_and_retries_once() {
+        use axum::{extract::Query, http::StatusCode, routing::get, Router};
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        // Binance's clock runs a minute ahead; timestamps from ours are too old
+        const AHEAD_MS: i64 = 60_000;
+        const TIMESTAMP_ERROR: &str =
+            r#"{"code":-1021,"msg":"Timestamp for this request is outside of the recvWindow."}"#;
+        let now = || chrono::Utc::now().timestamp_millis();
+
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let mock = Router::new()
+            .route(
+                "/api/v3/time",
+                get(move || async move { format!(r#"{{"serverTime":{}}}"#, now() + AHEAD_MS) }),
+            )
+            .route(
+                "/api/v3/openOrders",
+                get(move |Query(query): Query<HashMap<String, String>>| async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let timestamp: i64 = query["timestamp"].parse().unwrap();
+                    if timestamp < now() + AHEAD_MS - 5000 {
+                        return (StatusCode::BAD_REQUEST, TIMESTAMP_ERROR);
+                    }
+                    (StatusCode::OK, "[]")
+                }),
+            );
+        let base_url = serve(mock).await;
+        let client = BinanceClient::from_credentials(&credentials(base_url));
+
+        assert!(client.get_open_orders("BTCUSDT").await.unwrap().is_empty());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert!((SERVER_CLOCK.offset_ms(base_url) - AHEAD_MS).abs() < 5000);
 
-        // Create new order at the new price
-        self.create_limit_order(side, new_price, quantity).await
+        // Later requests are signed with the synced offset from the start
+        assert!(client.get_open_orders("BTCUSDT").await.unwrap().is_empty());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
 }
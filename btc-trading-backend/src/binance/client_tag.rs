@@ -0,0 +1,83 @@
+use uuid::Uuid;
+
+/// Binance rejects `newClientOrderId`s longer than this
+const MAX_CLIENT_ORDER_ID_LEN: usize = 36;
+/// Marks client order ids that carry a tag, so Binance's own ids are ignored
+const TAG_PREFIX: &str = "tag.";
+/// Random suffix keeping ids unique among open orders with the same tag
+const SUFFIX_LEN: usize = 8;
+/// Longest tag that still fits: `tag.<tag>.<suffix>`
+pub const MAX_CLIENT_TAG_LEN: usize = MAX_CLIENT_ORDER_ID_LEN - TAG_PREFIX.len() - 1 - SUFFIX_LEN;
+
+/// Clean a user-supplied tag for use in a client order id
+///
+/// Characters Binance doesn't accept (and `.`, the separator) become `_`, and
+/// the result is truncated to `MAX_CLIENT_TAG_LEN`. Blank tags are None.
+pub fn normalize_client_tag(tag: &str) -> Option<String> {
+    let tag: String = tag
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_CLIENT_TAG_LEN)
+        .collect();
+
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// `newClientOrderId` carrying `tag`, or None when the tag is blank
+pub fn encode_client_order_id(tag: &str) -> Option<String> {
+    let tag = normalize_client_tag(tag)?;
+    let suffix = Uuid::new_v4().simple().to_string();
+    Some(format!("{}{}.{}", TAG_PREFIX, tag, &suffix[..SUFFIX_LEN]))
+}
+
+/// Tag encoded in a client order id, None for untagged or foreign ids
+pub fn decode_client_tag(client_order_id: &str) -> Option<String> {
+    let (tag, _suffix) = client_order_id.strip_prefix(TAG_PREFIX)?.rsplit_once('.')?;
+    (!tag.is_empty()).then(|| tag.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_round_trip() {
+        let id = encode_client_order_id("grid-A").unwrap();
+        assert!(id.len() <= MAX_CLIENT_ORDER_ID_LEN);
+        assert_eq!(decode_client_tag(&id).as_deref(), Some("grid-A"));
+    }
+
+    #[test]
+    fn test_ids_are_unique_per_order() {
+        assert_ne!(encode_client_order_id("manual"), encode_client_order_id("manual"));
+    }
+
+    #[test]
+    fn test_long_tag_is_truncated_to_fit() {
+        let tag = "a-very-long-strategy-name-that-binance-would-reject";
+        let id = encode_client_order_id(tag).unwrap();
+        assert_eq!(id.len(), MAX_CLIENT_ORDER_ID_LEN);
+        assert_eq!(decode_client_tag(&id).unwrap(), &tag[..MAX_CLIENT_TAG_LEN]);
+    }
+
+    #[test]
+    fn test_invalid_characters_are_replaced() {
+        let id = encode_client_order_id(" grid A.1 ").unwrap();
+        assert_eq!(decode_client_tag(&id).as_deref(), Some("grid_A_1"));
+        assert_eq!(encode_client_order_id("   "), None);
+    }
+
+    #[test]
+    fn test_untagged_ids_decode_to_none() {
+        assert_eq!(decode_client_tag("6gCrw2kRUAF9CvJDGP16IP"), None);
+        assert_eq!(decode_client_tag("web_5f0c1a"), None);
+        assert_eq!(decode_client_tag("tag.nosuffix"), None);
+    }
+}
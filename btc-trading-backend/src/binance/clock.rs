@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// How far Binance's clock is ahead of ours, per API host
+pub static SERVER_CLOCK: ServerClock = ServerClock::new();
+
+/// Offsets applied to signed request timestamps
+///
+/// Clients are created per request, so the offset lives here rather than on
+/// the client. A host that was never synced has an offset of 0.
+pub struct ServerClock {
+    offsets_ms: Mutex<BTreeMap<String, i64>>,
+}
+
+impl ServerClock {
+    pub const fn new() -> Self {
+        Self {
+            offsets_ms: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Current time on `base_url`'s clock, in milliseconds
+    pub fn now_ms(&self, base_url: &str) -> i64 {
+        chrono::Utc::now().timestamp_millis() + self.offset_ms(base_url)
+    }
+
+    pub fn offset_ms(&self, base_url: &str) -> i64 {
+        self.offsets_ms
+            .lock()
+            .unwrap()
+            .get(base_url)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Store the offset from a server time read between `sent_at` and
+    /// `received_at` (local milliseconds), and return it
+    ///
+    /// The server is assumed to have answered halfway through the round trip.
+    pub fn record(&self, base_url: &str, server_time: i64, sent_at: i64, received_at: i64) -> i64 {
+        let offset = server_time - (sent_at + received_at) / 2;
+        self.offsets_ms
+            .lock()
+            .unwrap()
+            .insert(base_url.to_string(), offset);
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_is_measured_from_round_trip_midpoint() {
+        let clock = ServerClock::new();
+        assert_eq!(clock.offset_ms("http://binance"), 0);
+
+        // Sent at 1000, answered at 1200: the server read 61100 around 1100
+        assert_eq!(clock.record("http://binance", 61_100, 1_000, 1_200), 60_000);
+        assert_eq!(clock.offset_ms("http://binance"), 60_000);
+
+        // Hosts keep their own offset
+        assert_eq!(clock.offset_ms("http://testnet"), 0);
+    }
+}
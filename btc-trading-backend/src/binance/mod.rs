@@ -1,6 +1,22 @@
+mod book_stream;
+mod breaker;
 mod client;
+mod client_tag;
+mod clock;
 mod models;
 mod signing;
+mod single_flight;
+mod stats;
+mod symbol_table;
+#[cfg(test)]
+pub mod test_support;
+mod throttle;
 
-pub use client::BinanceClient;
+pub use book_stream::BookCache;
+pub use breaker::{EnvironmentBreakerSnapshot, BREAKER};
+pub use client::{http_client, BinanceApiError, BinanceClient, MAX_TRADE_WINDOW_MS};
+pub use client_tag::{decode_client_tag, normalize_client_tag};
 pub use models::*;
+pub use stats::{StatsSnapshot, STATS};
+pub use symbol_table::{load_symbol_table, refresh_symbol_table};
+pub use throttle::{configure_throttle, RequestThrottle};
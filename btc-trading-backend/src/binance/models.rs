@@ -1,18 +1,58 @@
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// A decimal field from Binance that isn't a number
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("malformed decimal {0:?}")]
+pub struct ParseError(pub String);
+
+/// Parse one of Binance's decimal strings (prices, quantities, balances)
+pub fn parse_decimal(value: &str) -> Result<f64, ParseError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| ParseError(value.to_string()))
+}
+
+/// Lossy parse for the `_f64` accessors: 0 for malformed input, but logged
+/// so bad data doesn't pass for a real zero unnoticed
+fn decimal_or_zero(field: &str, value: &str) -> f64 {
+    parse_decimal(value).unwrap_or_else(|e| {
+        tracing::warn!("Treating Binance field {} as 0: {}", field, e);
+        0.0
+    })
+}
 
 // ============================================================================
 // Account Models
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct AccountInfo {
     pub balances: Vec<Balance>,
-    #[serde(rename = "canTrade")]
-    pub can_trade: bool,
-    #[serde(rename = "canWithdraw")]
-    pub can_withdraw: bool,
-    #[serde(rename = "canDeposit")]
-    pub can_deposit: bool,
+    /// The account's own fee rates (absent on older API responses)
+    #[serde(rename = "commissionRates", default)]
+    pub commission_rates: Option<CommissionRates>,
+}
+
+/// Fee rates as fractions of notional, e.g. "0.00100000"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommissionRates {
+    pub maker: String,
+    pub taker: String,
+}
+
+impl CommissionRates {
+    pub fn maker_f64(&self) -> Option<f64> {
+        self.maker.parse().ok()
+    }
+
+    pub fn taker_f64(&self) -> Option<f64> {
+        self.taker.parse().ok()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,11 +64,15 @@ pub struct Balance {
 
 impl Balance {
     pub fn free_f64(&self) -> f64 {
-        self.free.parse().unwrap_or(0.0)
+        decimal_or_zero("free", &self.free)
+    }
+
+    pub fn try_free_f64(&self) -> Result<f64, ParseError> {
+        parse_decimal(&self.free)
     }
 
     pub fn locked_f64(&self) -> f64 {
-        self.locked.parse().unwrap_or(0.0)
+        decimal_or_zero("locked", &self.locked)
     }
 
     pub fn total(&self) -> f64 {
@@ -55,27 +99,45 @@ pub struct Order {
     pub executed_qty: String,
     pub status: String,
     pub time: i64,
+    #[serde(rename = "clientOrderId", default)]
+    pub client_order_id: String,
+    /// Strategy tag decoded from `client_order_id`, filled in by the client
+    #[serde(default, skip_deserializing)]
+    pub client_tag: Option<String>,
 }
 
 impl Order {
     pub fn price_f64(&self) -> f64 {
-        self.price.parse().unwrap_or(0.0)
+        decimal_or_zero("price", &self.price)
     }
 
     pub fn quantity_f64(&self) -> f64 {
-        self.orig_qty.parse().unwrap_or(0.0)
+        decimal_or_zero("orig_qty", &self.orig_qty)
+    }
+
+    pub fn executed_qty_f64(&self) -> f64 {
+        decimal_or_zero("executed_qty", &self.executed_qty)
+    }
+
+    pub fn try_executed_qty_f64(&self) -> Result<f64, ParseError> {
+        parse_decimal(&self.executed_qty)
     }
 
     pub fn is_buy(&self) -> bool {
         self.side == "BUY"
     }
 
+    /// Still resting on the book (possibly partly filled)
+    pub fn is_open(&self) -> bool {
+        matches!(self.status.as_str(), "NEW" | "PARTIALLY_FILLED")
+    }
+
     pub fn usd_value(&self) -> f64 {
         self.price_f64() * self.quantity_f64()
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct NewOrderResponse {
     pub symbol: String,
     #[serde(rename = "orderId")]
@@ -93,21 +155,200 @@ pub struct NewOrderResponse {
     #[serde(rename = "type")]
     pub order_type: String,
     pub side: String,
+    /// Executions so far; a MARKET order's real price is only found here
+    #[serde(default)]
+    pub fills: Vec<Fill>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl NewOrderResponse {
+    /// Quantity-weighted average price across fills, None when nothing filled
+    pub fn avg_fill_price(&self) -> Option<f64> {
+        let (cost, quantity) = self.fills.iter().fold((0.0, 0.0), |(cost, quantity), fill| {
+            let qty = fill.quantity_f64();
+            (cost + fill.price_f64() * qty, quantity + qty)
+        });
+
+        (quantity > 0.0).then_some(cost / quantity)
+    }
+}
+
+/// One execution of a new order
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Fill {
+    pub price: String,
+    pub qty: String,
+    pub commission: String,
+    #[serde(rename = "commissionAsset")]
+    pub commission_asset: String,
+}
+
+impl Fill {
+    pub fn price_f64(&self) -> f64 {
+        decimal_or_zero("price", &self.price)
+    }
+
+    pub fn quantity_f64(&self) -> f64 {
+        decimal_or_zero("qty", &self.qty)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CancelOrderResponse {
     pub symbol: String,
     #[serde(rename = "orderId")]
     pub order_id: i64,
     pub status: String,
+    /// Quantity filled by the time the order came off the book
+    #[serde(rename = "executedQty", default)]
+    pub executed_qty: String,
+}
+
+impl CancelOrderResponse {
+    pub fn try_executed_qty_f64(&self) -> Result<f64, ParseError> {
+        parse_decimal(&self.executed_qty)
+    }
+}
+
+/// `POST /api/v3/order/cancelReplace` response when both steps succeed
+#[derive(Debug, Deserialize)]
+pub struct CancelReplaceResponse {
+    #[serde(rename = "cancelResponse")]
+    pub cancel_response: CancelOrderResponse,
+    #[serde(rename = "newOrderResponse")]
+    pub new_order_response: NewOrderResponse,
+}
+
+/// An order moved to a new price: the order that was cancelled and its replacement
+#[derive(Debug)]
+pub struct ReplacedOrder {
+    pub cancelled: CancelOrderResponse,
+    pub new_order: NewOrderResponse,
+}
+
+/// Price of a GTC order left resting on the book
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestingPrice {
+    /// Plain limit order at the price
+    Limit(f64),
+    /// Stop-limit (`STOP_LOSS_LIMIT`) triggering at the price and limited to it,
+    /// so a BUY can wait above the market for a breakout
+    StopLimit(f64),
+}
+
+impl RestingPrice {
+    pub fn price(&self) -> f64 {
+        match *self {
+            RestingPrice::Limit(price) | RestingPrice::StopLimit(price) => price,
+        }
+    }
+
+    /// Value for Binance's `type`
+    pub fn order_type(&self) -> &'static str {
+        match self {
+            RestingPrice::Limit(_) => "LIMIT",
+            RestingPrice::StopLimit(_) => "STOP_LOSS_LIMIT",
+        }
+    }
+}
+
+impl From<f64> for RestingPrice {
+    fn from(price: f64) -> Self {
+        RestingPrice::Limit(price)
+    }
+}
+
+/// How long a limit order stays on the book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeInForce {
+    /// Good till cancelled
+    #[default]
+    Gtc,
+    /// Immediate or cancel: fill what's possible, cancel the rest
+    Ioc,
+    /// Fill or kill: fill entirely or not at all
+    Fok,
+    /// Post-only (GTX): rejected if it would take liquidity
+    PostOnly,
+}
+
+impl TimeInForce {
+    /// Value for Binance's `timeInForce`; post-only orders are sent as
+    /// `LIMIT_MAKER`, which takes no `timeInForce`
+    pub fn as_param(&self) -> Option<&'static str> {
+        match self {
+            TimeInForce::Gtc => Some("GTC"),
+            TimeInForce::Ioc => Some("IOC"),
+            TimeInForce::Fok => Some("FOK"),
+            TimeInForce::PostOnly => None,
+        }
+    }
+}
+
+impl std::str::FromStr for TimeInForce {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_uppercase().as_str() {
+            "GTC" => Ok(TimeInForce::Gtc),
+            "IOC" => Ok(TimeInForce::Ioc),
+            "FOK" => Ok(TimeInForce::Fok),
+            "GTX" => Ok(TimeInForce::PostOnly),
+            _ => Err(format!(
+                "time_in_force must be one of GTC, IOC, FOK, GTX (got {})",
+                value
+            )),
+        }
+    }
+}
+
+/// How a market order is sized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarketQuantity {
+    /// Amount of the base asset (`quantity`)
+    Base(f64),
+    /// Amount of the quote asset to spend or receive (`quoteOrderQty`)
+    Quote(f64),
+}
+
+impl MarketQuantity {
+    /// Binance parameter name and value, to the symbol's step (base) or tick
+    /// (quote) decimals when `precision` is known
+    pub fn as_param(&self, precision: Option<&SymbolPrecision>) -> (&'static str, String) {
+        match *self {
+            MarketQuantity::Base(quantity) => (
+                "quantity",
+                precision.map_or_else(|| format_decimal(quantity), |p| p.format_quantity(quantity)),
+            ),
+            MarketQuantity::Quote(amount) => (
+                "quoteOrderQty",
+                precision.map_or_else(|| format_decimal(amount), |p| p.format_price(amount)),
+            ),
+        }
+    }
+
+    /// Quote value of the order at `price`
+    pub fn notional(&self, price: f64) -> f64 {
+        match *self {
+            MarketQuantity::Base(quantity) => quantity * price,
+            MarketQuantity::Quote(amount) => amount,
+        }
+    }
+
+    /// Base quantity of the order at `price`
+    pub fn base_quantity(&self, price: f64) -> f64 {
+        match *self {
+            MarketQuantity::Base(quantity) => quantity,
+            MarketQuantity::Quote(amount) if price > 0.0 => amount / price,
+            MarketQuantity::Quote(_) => 0.0,
+        }
+    }
 }
 
 // ============================================================================
 // Trade History Models
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Trade {
     pub id: i64,
     #[serde(rename = "orderId")]
@@ -125,15 +366,30 @@ pub struct Trade {
     pub is_buyer: bool,
     #[serde(rename = "isMaker")]
     pub is_maker: bool,
+    /// Tag of the order this trade filled (myTrades doesn't report it)
+    #[serde(default, skip_deserializing)]
+    pub client_tag: Option<String>,
 }
 
 impl Trade {
     pub fn price_f64(&self) -> f64 {
-        self.price.parse().unwrap_or(0.0)
+        decimal_or_zero("price", &self.price)
+    }
+
+    pub fn try_price_f64(&self) -> Result<f64, ParseError> {
+        parse_decimal(&self.price)
     }
 
     pub fn quantity_f64(&self) -> f64 {
-        self.qty.parse().unwrap_or(0.0)
+        decimal_or_zero("qty", &self.qty)
+    }
+
+    pub fn try_quantity_f64(&self) -> Result<f64, ParseError> {
+        parse_decimal(&self.qty)
+    }
+
+    pub fn commission_f64(&self) -> f64 {
+        decimal_or_zero("commission", &self.commission)
     }
 }
 
@@ -149,7 +405,223 @@ pub struct TickerPrice {
 
 impl TickerPrice {
     pub fn price_f64(&self) -> f64 {
-        self.price.parse().unwrap_or(0.0)
+        decimal_or_zero("price", &self.price)
+    }
+
+    pub fn try_price_f64(&self) -> Result<f64, ParseError> {
+        parse_decimal(&self.price)
+    }
+}
+
+/// Best bid and ask currently on the book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookTicker {
+    pub symbol: String,
+    #[serde(rename = "bidPrice")]
+    pub bid_price: String,
+    #[serde(rename = "bidQty")]
+    pub bid_qty: String,
+    #[serde(rename = "askPrice")]
+    pub ask_price: String,
+    #[serde(rename = "askQty")]
+    pub ask_qty: String,
+}
+
+impl BookTicker {
+    pub fn bid_price_f64(&self) -> f64 {
+        decimal_or_zero("bid_price", &self.bid_price)
+    }
+
+    pub fn ask_price_f64(&self) -> f64 {
+        decimal_or_zero("ask_price", &self.ask_price)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ServerTime {
+    #[serde(rename = "serverTime")]
+    pub server_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListenKey {
+    #[serde(rename = "listenKey")]
+    pub listen_key: String,
+}
+
+// ============================================================================
+// Exchange Info Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ExchangeInfo {
+    pub symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SymbolInfo {
+    pub symbol: String,
+    /// Filters vary in shape by `filterType`, so keep them untyped
+    pub filters: Vec<serde_json::Value>,
+}
+
+impl SymbolInfo {
+    /// Price increment from the PRICE_FILTER filter
+    pub fn tick_size(&self) -> Option<f64> {
+        self.filters
+            .iter()
+            .find(|f| f.get("filterType").and_then(|t| t.as_str()) == Some("PRICE_FILTER"))
+            .and_then(|f| f.get("tickSize"))
+            .and_then(|s| s.as_str())
+            .and_then(|s| s.parse().ok())
+    }
+
+    /// Quantity increment from the LOT_SIZE filter
+    pub fn step_size(&self) -> Option<f64> {
+        self.filters
+            .iter()
+            .find(|f| f.get("filterType").and_then(|t| t.as_str()) == Some("LOT_SIZE"))
+            .and_then(|f| f.get("stepSize"))
+            .and_then(|s| s.as_str())
+            .and_then(|s| s.parse().ok())
+    }
+
+    /// Smallest order quantity from the LOT_SIZE filter
+    pub fn min_qty(&self) -> Option<f64> {
+        self.filters
+            .iter()
+            .find(|f| f.get("filterType").and_then(|t| t.as_str()) == Some("LOT_SIZE"))
+            .and_then(|f| f.get("minQty"))
+            .and_then(|s| s.as_str())
+            .and_then(|s| s.parse().ok())
+    }
+
+    /// Smallest order value in the quote asset, from the NOTIONAL filter or the
+    /// older MIN_NOTIONAL one
+    pub fn min_notional(&self) -> Option<f64> {
+        self.filters
+            .iter()
+            .find(|f| {
+                matches!(
+                    f.get("filterType").and_then(|t| t.as_str()),
+                    Some("NOTIONAL") | Some("MIN_NOTIONAL")
+                )
+            })
+            .and_then(|f| f.get("minNotional"))
+            .and_then(|s| s.as_str())
+            .and_then(|s| s.parse().ok())
+    }
+
+    /// Price tick and quantity step, if exchangeInfo lists both
+    pub fn precision(&self) -> Option<SymbolPrecision> {
+        Some(SymbolPrecision {
+            tick_size: self.tick_size()?,
+            step_size: self.step_size()?,
+        })
+    }
+}
+
+/// The increments a symbol's prices and quantities must be multiples of
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolPrecision {
+    pub tick_size: f64,
+    pub step_size: f64,
+}
+
+impl SymbolPrecision {
+    /// Round `price` to the nearest tick
+    pub fn round_price(&self, price: f64) -> f64 {
+        to_increment((price / self.tick_size).round(), self.tick_size)
+    }
+
+    /// Floor `quantity` to a whole number of steps, so it never grows
+    pub fn floor_quantity(&self, quantity: f64) -> f64 {
+        // Nudge up before flooring so 122.99999999 steps (float noise) counts as 123
+        to_increment((quantity / self.step_size + 1e-9).floor(), self.step_size)
+    }
+
+    /// `price` as a request parameter, with the tick size's decimals
+    pub fn format_price(&self, price: f64) -> String {
+        format!("{:.*}", increment_decimals(self.tick_size), price)
+    }
+
+    /// `quantity` as a request parameter, with the step size's decimals
+    pub fn format_quantity(&self, quantity: f64) -> String {
+        format!("{:.*}", increment_decimals(self.step_size), quantity)
+    }
+}
+
+/// Decimal places in `increment`, e.g. 2 for 0.01 and 5 for 0.00025
+fn increment_decimals(increment: f64) -> usize {
+    let digits = format!("{:.8}", increment);
+    digits
+        .trim_end_matches('0')
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len())
+}
+
+/// `value` as a request parameter for a symbol whose precision isn't known:
+/// at most the 8 decimals Binance allows, without trailing zeros
+pub fn format_decimal(value: f64) -> String {
+    let digits = format!("{:.8}", value);
+    digits.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// `steps` whole increments, with float noise trimmed to the increment's decimals
+fn to_increment(steps: f64, increment: f64) -> f64 {
+    let decimals = (-increment.log10()).ceil().max(0.0) as i32;
+    let scale = 10f64.powi(decimals);
+    (steps * increment * scale).round() / scale
+}
+
+// ============================================================================
+// User Data Stream Models
+// ============================================================================
+
+/// `executionReport` event from the user data stream
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExecutionReport {
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "S")]
+    pub side: String,
+    #[serde(rename = "i")]
+    pub order_id: i64,
+    #[serde(rename = "c", default)]
+    pub client_order_id: String,
+    /// Execution type: NEW, TRADE, CANCELED, EXPIRED, ...
+    #[serde(rename = "x")]
+    pub execution_type: String,
+    /// Order status: NEW, PARTIALLY_FILLED, FILLED, ...
+    #[serde(rename = "X")]
+    pub order_status: String,
+    #[serde(rename = "p")]
+    pub price: String,
+    #[serde(rename = "z")]
+    pub cumulative_filled_qty: String,
+    #[serde(rename = "Z")]
+    pub cumulative_quote_qty: String,
+    #[serde(rename = "t")]
+    pub trade_id: i64,
+}
+
+impl ExecutionReport {
+    pub fn is_buy(&self) -> bool {
+        self.side == "BUY"
+    }
+
+    pub fn filled_qty_f64(&self) -> f64 {
+        decimal_or_zero("cumulative_filled_qty", &self.cumulative_filled_qty)
+    }
+
+    /// Average fill price, falling back to the order price before any fill
+    pub fn average_price_f64(&self) -> f64 {
+        let filled = self.filled_qty_f64();
+        if filled > 0.0 {
+            decimal_or_zero("cumulative_quote_qty", &self.cumulative_quote_qty) / filled
+        } else {
+            decimal_or_zero("price", &self.price)
+        }
     }
 }
 
@@ -161,4 +633,292 @@ impl TickerPrice {
 pub struct BinanceError {
     pub code: i32,
     pub msg: String,
+    /// Per-step results sent with a failed cancelReplace (-2021, -2022)
+    #[serde(default)]
+    pub data: Option<CancelReplaceFailure>,
+}
+
+/// The steps of a failed cancelReplace
+#[derive(Debug, Deserialize)]
+pub struct CancelReplaceFailure {
+    #[serde(rename = "cancelResponse")]
+    pub cancel_response: Option<CancelStep>,
+    #[serde(rename = "newOrderResponse")]
+    pub new_order_response: Option<StepResult>,
+}
+
+/// A failed cancelReplace's cancel step: the cancelled order when only the
+/// new order failed (-2021), otherwise why the cancel failed
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum CancelStep {
+    Cancelled(CancelOrderResponse),
+    Failed(StepResult),
+}
+
+/// One cancelReplace step; `code` and `msg` are only set when it failed
+#[derive(Debug, Default, Deserialize)]
+pub struct StepResult {
+    pub code: Option<i32>,
+    pub msg: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse_decimal("0.00150000"), Ok(0.0015));
+        assert_eq!(parse_decimal(" 42000.10 "), Ok(42000.10));
+        assert_eq!(parse_decimal(""), Err(ParseError(String::new())));
+        assert!(parse_decimal("1,5").is_err());
+        assert!(parse_decimal("NaN").is_err());
+        assert!(parse_decimal("inf").is_err());
+    }
+
+    #[test]
+    fn test_malformed_quantity_is_surfaced() {
+        let order: Order = serde_json::from_str(
+            r#"{
+                "symbol": "BTCUSDT",
+                "orderId": 42,
+                "clientOrderId": "abc",
+                "price": "50000.00",
+                "origQty": "0.00200",
+                "executedQty": "0.0O1",
+                "status": "PARTIALLY_FILLED",
+                "type": "LIMIT",
+                "side": "SELL",
+                "time": 1700000000000
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            order.try_executed_qty_f64(),
+            Err(ParseError("0.0O1".to_string()))
+        );
+        // The lossy accessor still zeroes it, for display paths
+        assert_eq!(order.executed_qty_f64(), 0.0);
+    }
+
+    #[test]
+    fn test_market_order_fills_give_average_price() {
+        let order: NewOrderResponse = serde_json::from_str(
+            r#"{
+                "symbol": "BTCUSDT",
+                "orderId": 28,
+                "clientOrderId": "6gCrw2kRUAF9CvJDGP16IP",
+                "transactTime": 1507725176595,
+                "price": "0.00000000",
+                "origQty": "0.00300000",
+                "executedQty": "0.00300000",
+                "status": "FILLED",
+                "type": "MARKET",
+                "side": "BUY",
+                "fills": [
+                    {
+                        "price": "50000.00000000",
+                        "qty": "0.00100000",
+                        "commission": "0.00000100",
+                        "commissionAsset": "BTC",
+                        "tradeId": 56
+                    },
+                    {
+                        "price": "50300.00000000",
+                        "qty": "0.00200000",
+                        "commission": "0.00000200",
+                        "commissionAsset": "BTC",
+                        "tradeId": 57
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(order.fills.len(), 2);
+        assert_eq!(order.fills[0].commission_asset, "BTC");
+        assert!((order.avg_fill_price().unwrap() - 50200.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_response_without_fills_has_no_average() {
+        let order: NewOrderResponse = serde_json::from_str(
+            r#"{
+                "symbol": "BTCUSDT",
+                "orderId": 29,
+                "clientOrderId": "resting",
+                "transactTime": 1507725176595,
+                "price": "50000.00000000",
+                "origQty": "0.00100000",
+                "executedQty": "0.00000000",
+                "status": "NEW",
+                "type": "LIMIT",
+                "side": "BUY"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(order.fills.is_empty());
+        assert_eq!(order.avg_fill_price(), None);
+    }
+
+    #[test]
+    fn test_order_trades_deserialize() {
+        let trades: Vec<Trade> = serde_json::from_str(
+            r#"[
+                {
+                    "symbol": "BTCUSDT",
+                    "id": 28457,
+                    "orderId": 100234,
+                    "orderListId": -1,
+                    "price": "50000.00000000",
+                    "qty": "0.00120000",
+                    "quoteQty": "60.00000000",
+                    "commission": "0.00000120",
+                    "commissionAsset": "BTC",
+                    "time": 1700000000000,
+                    "isBuyer": true,
+                    "isMaker": false,
+                    "isBestMatch": true
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!(trade.order_id, 100234);
+        assert_eq!(trade.price_f64(), 50000.0);
+        assert_eq!(trade.quantity_f64(), 0.0012);
+        assert_eq!(trade.commission_asset, "BTC");
+        assert!(trade.is_buyer && !trade.is_maker);
+        assert_eq!(trade.client_tag, None);
+
+        let none: Vec<Trade> = serde_json::from_str("[]").unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_book_ticker_deserializes() {
+        let ticker: BookTicker = serde_json::from_str(
+            r#"{
+                "symbol": "BTCUSDT",
+                "bidPrice": "50000.01000000",
+                "bidQty": "0.52000000",
+                "askPrice": "50000.02000000",
+                "askQty": "1.10000000"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert_eq!(ticker.bid_price_f64(), 50000.01);
+        assert_eq!(ticker.ask_price_f64(), 50000.02);
+        assert_eq!(ticker.ask_qty, "1.10000000");
+    }
+
+    #[test]
+    fn test_min_notional_from_either_filter() {
+        let info: SymbolInfo = serde_json::from_str(
+            r#"{
+                "symbol": "BTCUSDT",
+                "filters": [
+                    { "filterType": "LOT_SIZE", "stepSize": "0.00001000" },
+                    { "filterType": "NOTIONAL", "minNotional": "5.00000000",
+                      "applyMinToMarket": true, "maxNotional": "9000000.00000000" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(info.min_notional(), Some(5.0));
+
+        let legacy: SymbolInfo = serde_json::from_str(
+            r#"{
+                "symbol": "BTCUSDT",
+                "filters": [{ "filterType": "MIN_NOTIONAL", "minNotional": "10.00000000" }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.min_notional(), Some(10.0));
+    }
+
+    #[test]
+    fn test_precision_rounds_price_and_floors_quantity() {
+        let info: SymbolInfo = serde_json::from_str(
+            r#"{
+                "symbol": "BTCUSDT",
+                "filters": [
+                    { "filterType": "PRICE_FILTER", "minPrice": "0.01000000",
+                      "maxPrice": "1000000.00000000", "tickSize": "0.01000000" },
+                    { "filterType": "LOT_SIZE", "stepSize": "0.00001000" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let precision = info.precision().unwrap();
+        assert_eq!(precision.tick_size, 0.01);
+
+        assert_eq!(precision.round_price(42000.123456789), 42000.12);
+        assert_eq!(precision.round_price(42000.125001), 42000.13);
+        assert_eq!(precision.round_price(42000.12), 42000.12);
+        // Quantities never round up past what the client asked for
+        assert_eq!(precision.floor_quantity(0.0012399), 0.00123);
+        assert_eq!(precision.floor_quantity(0.00123), 0.00123);
+        assert_eq!(precision.floor_quantity(0.000009), 0.0);
+
+        let coarse = SymbolPrecision {
+            tick_size: 0.1,
+            step_size: 1.0,
+        };
+        assert_eq!(coarse.round_price(42000.16), 42000.2);
+        assert_eq!(coarse.floor_quantity(2.9), 2.0);
+    }
+
+    #[test]
+    fn test_params_use_the_symbols_decimals() {
+        let btc = SymbolPrecision {
+            tick_size: 0.01,
+            step_size: 0.00001,
+        };
+        assert_eq!(btc.format_price(42000.1), "42000.10");
+        assert_eq!(btc.format_quantity(0.00123), "0.00123");
+
+        let shib = SymbolPrecision {
+            tick_size: 0.00000001,
+            step_size: 1.0,
+        };
+        assert_eq!(shib.format_price(0.00001234), "0.00001234");
+        assert_eq!(shib.format_quantity(1500000.0), "1500000");
+
+        let quarter_steps = SymbolPrecision {
+            tick_size: 0.05,
+            step_size: 0.00025,
+        };
+        assert_eq!(quarter_steps.format_price(3000.05), "3000.05");
+        assert_eq!(quarter_steps.format_quantity(0.01025), "0.01025");
+
+        assert_eq!(format_decimal(0.00001234), "0.00001234");
+        assert_eq!(format_decimal(42000.0), "42000");
+        assert_eq!(format_decimal(0.5), "0.5");
+    }
+
+    #[test]
+    fn test_market_quantity_params() {
+        let precision = SymbolPrecision {
+            tick_size: 0.01,
+            step_size: 0.00001,
+        };
+
+        let base = MarketQuantity::Base(0.5);
+        assert_eq!(base.as_param(Some(&precision)), ("quantity", "0.50000".to_string()));
+        assert_eq!(base.as_param(None), ("quantity", "0.5".to_string()));
+        assert_eq!(base.notional(50000.0), 25000.0);
+
+        let quote = MarketQuantity::Quote(25.0);
+        assert_eq!(quote.as_param(Some(&precision)), ("quoteOrderQty", "25.00".to_string()));
+        assert_eq!(quote.notional(50000.0), 25.0);
+        assert_eq!(quote.base_quantity(50000.0), 0.0005);
+    }
 }
@@ -12,10 +12,15 @@ pub fn sign_query(query: &str, secret_key: &str) -> String {
     hex::encode(result.into_bytes())
 }
 
-/// Build query string from parameters and add timestamp
-pub fn build_signed_query(params: &[(&str, &str)], secret_key: &str) -> String {
-    let timestamp = chrono::Utc::now().timestamp_millis().to_string();
-    let recv_window = "60000";
+/// Build query string from parameters and add timestamp and recvWindow
+///
+/// `timestamp_ms` should be Binance's time, i.e. local time plus the server offset.
+pub fn build_signed_query(
+    params: &[(&str, &str)],
+    secret_key: &str,
+    recv_window_ms: u64,
+    timestamp_ms: i64,
+) -> String {
 
     // Build query with params
     let mut query_parts: Vec<String> = params
@@ -24,8 +29,8 @@ pub fn build_signed_query(params: &[(&str, &str)], secret_key: &str) -> String {
         .collect();
 
     // Add timestamp and recvWindow
-    query_parts.push(format!("timestamp={}", timestamp));
-    query_parts.push(format!("recvWindow={}", recv_window));
+    query_parts.push(format!("timestamp={}", timestamp_ms));
+    query_parts.push(format!("recvWindow={}", recv_window_ms));
 
     let query = query_parts.join("&");
     let signature = sign_query(&query, secret_key);
@@ -48,4 +53,12 @@ mod tests {
         // Verify signature matches expected format (64 hex chars)
         assert_eq!(signature.len(), 64);
     }
+
+    #[test]
+    fn test_signed_query_uses_recv_window() {
+        let query = build_signed_query(&[("symbol", "BTCUSDT")], "secret", 5000, 1700000000000);
+
+        assert!(query.starts_with("symbol=BTCUSDT&timestamp=1700000000000&"));
+        assert!(query.contains("&recvWindow=5000&signature="));
+    }
 }
@@ -0,0 +1,84 @@
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use super::client::BinanceApiError;
+use super::models::AccountInfo;
+
+/// In-flight `GET /api/v3/account` requests, keyed by account
+pub static ACCOUNT_FLIGHTS: SingleFlight<Result<AccountInfo, BinanceApiError>> =
+    SingleFlight::new();
+
+/// Coalesces concurrent identical requests into one
+///
+/// Callers that arrive while a request for the same key is running wait for
+/// it and get a clone of its result instead of sending their own. Nothing is
+/// kept once the request finishes, so the next caller starts a fresh one.
+pub struct SingleFlight<T> {
+    in_flight: Mutex<BTreeMap<String, Shared<BoxFuture<'static, T>>>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> SingleFlight<T> {
+    pub const fn new() -> Self {
+        Self {
+            in_flight: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Result of the running request for `key`, or of `fetch` if none is running
+    pub async fn run<F>(&self, key: String, fetch: F) -> T
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        let flight = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            in_flight
+                .entry(key.clone())
+                .or_insert_with(|| fetch.boxed().shared())
+                .clone()
+        };
+
+        let result = flight.clone().await;
+
+        // Whichever waiter finishes first clears the entry. The leader may have
+        // been dropped mid-request, so this can't be left to it alone.
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight.get(&key).is_some_and(|f| f.ptr_eq(&flight)) {
+            in_flight.remove(&key);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_one_fetch() {
+        let flights: SingleFlight<u32> = SingleFlight::new();
+        let fetches = Arc::new(AtomicU32::new(0));
+
+        let fetch = || {
+            let fetches = fetches.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                fetches.fetch_add(1, Ordering::SeqCst) + 1
+            }
+        };
+
+        let results =
+            futures::future::join_all((0..5).map(|_| flights.run("account".to_string(), fetch())))
+                .await;
+        assert_eq!(results, vec![1; 5]);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // Finished flights aren't cached
+        assert_eq!(flights.run("account".to_string(), fetch()).await, 2);
+    }
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Request weights per endpoint (from Binance spot API docs)
+pub mod weight {
+    pub const TICKER_PRICE: u64 = 2;
+    pub const TICKER_PRICE_ALL: u64 = 4;
+    pub const BOOK_TICKER: u64 = 2;
+    pub const ACCOUNT: u64 = 20;
+    pub const OPEN_ORDERS: u64 = 6;
+    pub const MY_TRADES: u64 = 20;
+    /// myTrades filtered to one order is cheaper
+    pub const MY_TRADES_BY_ORDER: u64 = 5;
+    pub const ALL_ORDERS: u64 = 20;
+    pub const ORDER: u64 = 1;
+    pub const CANCEL_REPLACE: u64 = 1;
+    pub const QUERY_ORDER: u64 = 4;
+    pub const SERVER_TIME: u64 = 1;
+    pub const USER_DATA_STREAM: u64 = 2;
+    pub const EXCHANGE_INFO: u64 = 20;
+}
+
+/// Process-wide counters for outbound Binance requests
+pub static STATS: RequestStats = RequestStats::new();
+
+/// Counts Binance requests and their estimated weight
+pub struct RequestStats {
+    total_requests: AtomicU64,
+    total_weight: AtomicU64,
+    /// Weight used since the last per-minute reset
+    minute_weight: AtomicU64,
+    per_endpoint: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+/// Point-in-time view of the counters
+#[derive(Debug, Serialize)]
+pub struct StatsSnapshot {
+    pub total_requests: u64,
+    pub total_weight: u64,
+    pub weight_last_minute: u64,
+    pub per_endpoint: BTreeMap<&'static str, u64>,
+}
+
+impl RequestStats {
+    pub const fn new() -> Self {
+        Self {
+            total_requests: AtomicU64::new(0),
+            total_weight: AtomicU64::new(0),
+            minute_weight: AtomicU64::new(0),
+            per_endpoint: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record one request to `endpoint` costing `weight`
+    pub fn record(&self, endpoint: &'static str, weight: u64) {
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
+        self.total_weight.fetch_add(weight, Ordering::Relaxed);
+        self.minute_weight.fetch_add(weight, Ordering::Relaxed);
+
+        let mut per_endpoint = self.per_endpoint.lock().unwrap();
+        *per_endpoint.entry(endpoint).or_insert(0) += 1;
+    }
+
+    /// Start a new per-minute weight bucket
+    pub fn reset_minute(&self) {
+        self.minute_weight.store(0, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            total_requests: self.total_requests.load(Ordering::Relaxed),
+            total_weight: self.total_weight.load(Ordering::Relaxed),
+            weight_last_minute: self.minute_weight.load(Ordering::Relaxed),
+            per_endpoint: self.per_endpoint.lock().unwrap().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_increments_counters() {
+        let stats = RequestStats::new();
+
+        stats.record("GET /api/v3/account", weight::ACCOUNT);
+        stats.record("GET /api/v3/ticker/price", weight::TICKER_PRICE);
+        stats.record("GET /api/v3/ticker/price", weight::TICKER_PRICE);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_requests, 3);
+        assert_eq!(snapshot.total_weight, 24);
+        assert_eq!(snapshot.weight_last_minute, 24);
+        assert_eq!(snapshot.per_endpoint["GET /api/v3/account"], 1);
+        assert_eq!(snapshot.per_endpoint["GET /api/v3/ticker/price"], 2);
+    }
+
+    #[test]
+    fn test_reset_minute_keeps_totals() {
+        let stats = RequestStats::new();
+
+        stats.record("POST /api/v3/order", weight::ORDER);
+        stats.reset_minute();
+        stats.record("POST /api/v3/order", weight::ORDER);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_requests, 2);
+        assert_eq!(snapshot.total_weight, 2);
+        assert_eq!(snapshot.weight_last_minute, 1);
+    }
+}
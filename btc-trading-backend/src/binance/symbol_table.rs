@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use super::client::BinanceClient;
+use super::models::SymbolInfo;
+
+/// exchangeInfo trading rules per API host and symbol
+pub static SYMBOL_TABLE: SymbolTable = SymbolTable::new();
+
+/// Symbol filters (tick size, step size, min notional) shared by every client
+///
+/// Filled for the configured symbols at startup and on a timer, and by any
+/// lookup that had to fall back to exchangeInfo. Keyed by host as well as
+/// symbol because testnet and production rules can differ.
+pub struct SymbolTable {
+    symbols: RwLock<BTreeMap<(String, String), SymbolInfo>>,
+}
+
+impl SymbolTable {
+    pub const fn new() -> Self {
+        Self {
+            symbols: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn get(&self, base_url: &str, symbol: &str) -> Option<SymbolInfo> {
+        self.symbols
+            .read()
+            .unwrap()
+            .get(&(base_url.to_string(), symbol.to_string()))
+            .cloned()
+    }
+
+    pub fn insert(&self, base_url: &str, info: SymbolInfo) {
+        self.symbols
+            .write()
+            .unwrap()
+            .insert((base_url.to_string(), info.symbol.clone()), info);
+    }
+}
+
+/// Load `symbols` for every client's host, returning false if any load failed
+/// or came back incomplete
+pub async fn load_symbol_table(clients: &[BinanceClient], symbols: &[String]) -> bool {
+    let mut complete = true;
+    for client in clients {
+        match client.load_symbol_table(symbols).await {
+            Ok(loaded) if loaded == symbols.len() => {}
+            Ok(loaded) => {
+                tracing::warn!("exchangeInfo listed {} of {} symbols", loaded, symbols.len());
+                complete = false;
+            }
+            Err(e) => {
+                tracing::warn!("Loading exchangeInfo failed: {}", e);
+                complete = false;
+            }
+        }
+    }
+    complete
+}
+
+/// Reload the symbol table every `interval` until `shutdown` is cancelled
+pub async fn refresh_symbol_table(
+    clients: Vec<BinanceClient>,
+    symbols: Vec<String>,
+    interval: Duration,
+    shutdown: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(interval);
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = interval.tick() => {
+                load_symbol_table(&clients, &symbols).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::models::ExchangeInfo;
+    use crate::binance::test_support::mock_client;
+    use axum::{extract::RawQuery, routing::get, Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_table_holds_btcusdt_precision_from_exchange_info() {
+        let info: ExchangeInfo = serde_json::from_str(
+            r#"{
+                "timezone": "UTC",
+                "symbols": [
+                    {
+                        "symbol": "BTCUSDT",
+                        "status": "TRADING",
+                        "filters": [
+                            { "filterType": "PRICE_FILTER", "minPrice": "0.01000000",
+                              "maxPrice": "1000000.00000000", "tickSize": "0.01000000" },
+                            { "filterType": "LOT_SIZE", "minQty": "0.00001000",
+                              "maxQty": "9000.00000000", "stepSize": "0.00001000" },
+                            { "filterType": "NOTIONAL", "minNotional": "5.00000000",
+                              "applyMinToMarket": true }
+                        ]
+                    },
+                    {
+                        "symbol": "ETHUSDT",
+                        "status": "TRADING",
+                        "filters": [
+                            { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                            { "filterType": "LOT_SIZE", "stepSize": "0.00010000" }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let table = SymbolTable::new();
+        for symbol in info.symbols {
+            table.insert("https://api.binance.com", symbol);
+        }
+
+        let btc = table.get("https://api.binance.com", "BTCUSDT").unwrap();
+        let precision = btc.precision().unwrap();
+        assert_eq!(precision.tick_size, 0.01);
+        assert_eq!(precision.step_size, 0.00001);
+        assert_eq!(btc.min_notional(), Some(5.0));
+        assert_eq!(
+            table.get("https://api.binance.com", "ETHUSDT").unwrap().step_size(),
+            Some(0.0001)
+        );
+        // Another host's rules are loaded separately
+        assert!(table.get("https://testnet.binance.vision", "BTCUSDT").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_load_fills_the_table_in_one_request() {
+        let queries = Arc::new(Mutex::new(Vec::new()));
+        let recorded = queries.clone();
+        let mock = Router::new().route(
+            "/api/v3/exchangeInfo",
+            get(move |RawQuery(query): RawQuery| async move {
+                recorded.lock().unwrap().push(query.unwrap_or_default());
+                let symbol = |name: &str, step: &str| {
+                    serde_json::json!({
+                        "symbol": name,
+                        "filters": [
+                            { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                            { "filterType": "LOT_SIZE", "stepSize": step }
+                        ]
+                    })
+                };
+                // ETHUSDT is missing; XRPUSDT wasn't asked for
+                Json(serde_json::json!({
+                    "symbols": [symbol("BTCUSDT", "0.00001000"), symbol("XRPUSDT", "0.10000000")]
+                }))
+            }),
+        );
+        let client = mock_client(mock).await;
+        let symbols = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+
+        assert_eq!(client.load_symbol_table(&symbols).await.unwrap(), 1);
+        assert!(!load_symbol_table(std::slice::from_ref(&client), &symbols).await);
+
+        let sent = queries.lock().unwrap().clone();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0], "symbols=%5B%22BTCUSDT%22%2C%22ETHUSDT%22%5D");
+
+        // Lookups are served from the table, without another exchangeInfo call
+        assert_eq!(client.get_step_size("BTCUSDT").await.unwrap(), 0.00001);
+        assert_eq!(client.cached_precision("BTCUSDT").unwrap().tick_size, 0.01);
+        assert!(client.cached_precision("ETHUSDT").is_none());
+        assert!(client.cached_precision("XRPUSDT").is_none());
+        assert_eq!(queries.lock().unwrap().len(), 2);
+    }
+}
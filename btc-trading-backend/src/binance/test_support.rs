@@ -0,0 +1,43 @@
+//! Mock Binance servers for tests
+
+use axum::Router;
+
+use super::BinanceClient;
+use crate::config::{BinanceCredentials, Config};
+
+/// Serve `router` on an ephemeral local port and return its base URL
+pub async fn serve(router: Router) -> &'static str {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    Box::leak(format!("http://{}", addr).into_boxed_str())
+}
+
+/// Credentials for a mock Binance at `base_url`
+pub fn credentials(base_url: &'static str) -> BinanceCredentials {
+    BinanceCredentials {
+        api_key: "key".to_string(),
+        secret_key: "secret".to_string(),
+        base_url: base_url.to_string(),
+        use_production: false,
+        stream_url: "ws://127.0.0.1:0/ws",
+        recv_window_ms: 5000,
+        connect_timeout_ms: 5000,
+        request_timeout_ms: 10000,
+    }
+}
+
+/// Client talking to a mock Binance that serves `router`
+pub async fn mock_client(router: Router) -> BinanceClient {
+    BinanceClient::from_credentials(&credentials(serve(router).await))
+}
+
+/// Test config whose testnet requests go to a mock Binance that serves `router`
+pub async fn mock_config(router: Router) -> Config {
+    Config {
+        binance_testnet_base_url: serve(router).await.to_string(),
+        ..Config::for_tests()
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use super::client::BinanceApiError;
+
+/// Order placements, cancels and other writes in flight at once, unless configured
+pub const DEFAULT_ORDER_CONCURRENCY: usize = 5;
+/// Reads in flight at once, unless configured
+pub const DEFAULT_READ_CONCURRENCY: usize = 20;
+/// How long a request waits for a free slot before giving up, unless configured
+pub const DEFAULT_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+static THROTTLE: OnceLock<RequestThrottle> = OnceLock::new();
+
+/// Caps concurrent Binance requests so a burst of grid or batch orders can't
+/// trip the order-rate limits, which count orders rather than request weight
+///
+/// Writes and reads have separate pools so a backlog of orders never starves
+/// price and balance lookups. A request past the cap queues for a slot and
+/// fails with `BinanceApiError::Throttled` if none frees up in time.
+pub struct RequestThrottle {
+    orders: Semaphore,
+    reads: Semaphore,
+    queue_timeout: Duration,
+}
+
+impl RequestThrottle {
+    pub fn new(order_permits: usize, read_permits: usize, queue_timeout: Duration) -> Self {
+        Self {
+            orders: Semaphore::new(order_permits.max(1)),
+            reads: Semaphore::new(read_permits.max(1)),
+            queue_timeout,
+        }
+    }
+
+    /// Wait for a slot for `endpoint` ("METHOD /path"), held until the permit drops
+    pub async fn acquire(
+        &self,
+        endpoint: &'static str,
+    ) -> Result<SemaphorePermit<'_>, BinanceApiError> {
+        let pool = if endpoint.starts_with("GET ") {
+            &self.reads
+        } else {
+            &self.orders
+        };
+        match tokio::time::timeout(self.queue_timeout, pool.acquire()).await {
+            Ok(permit) => Ok(permit.expect("throttle semaphores are never closed")),
+            Err(_) => {
+                tracing::warn!("{} waited {:?} for a request slot", endpoint, self.queue_timeout);
+                Err(BinanceApiError::Throttled(endpoint.to_string()))
+            }
+        }
+    }
+}
+
+impl Default for RequestThrottle {
+    fn default() -> Self {
+        Self::new(DEFAULT_ORDER_CONCURRENCY, DEFAULT_READ_CONCURRENCY, DEFAULT_QUEUE_TIMEOUT)
+    }
+}
+
+/// Set the process-wide limits; only the first call before any request counts
+pub fn configure_throttle(throttle: RequestThrottle) {
+    if THROTTLE.set(throttle).is_err() {
+        tracing::warn!("Binance request limits already set, ignoring new ones");
+    }
+}
+
+/// The process-wide limits, the defaults if none were configured
+pub fn throttle() -> &'static RequestThrottle {
+    THROTTLE.get_or_init(RequestThrottle::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_concurrent_orders_never_exceed_permits() {
+        let throttle = Arc::new(RequestThrottle::new(3, 10, Duration::from_secs(5)));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let placements: Vec<_> = (0..12)
+            .map(|_| {
+                let (throttle, in_flight, peak) =
+                    (throttle.clone(), in_flight.clone(), peak.clone());
+                tokio::spawn(async move {
+                    let _permit = throttle.acquire("POST /api/v3/order").await.unwrap();
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for placement in placements {
+            placement.await.unwrap();
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_times_out_without_blocking_reads() {
+        let throttle = RequestThrottle::new(1, 1, Duration::from_millis(20));
+        let _held = throttle.acquire("DELETE /api/v3/order").await.unwrap();
+
+        let error = throttle.acquire("POST /api/v3/order").await.unwrap_err();
+        assert!(matches!(error, BinanceApiError::Throttled(_)));
+        assert!(throttle.acquire("GET /api/v3/openOrders").await.is_ok());
+    }
+}
@@ -1,16 +1,37 @@
 use std::env;
 
+use crate::pricing::PriceSource;
+use crate::trading::NoTradeSchedule;
+
+/// Largest recvWindow Binance accepts
+pub const MAX_RECV_WINDOW_MS: u64 = 60_000;
+/// Default recvWindow - short enough to keep the replay window tight
+pub const DEFAULT_RECV_WINDOW_MS: u64 = 5_000;
+/// REST base used for testnet requests unless BINANCE_TESTNET_BASE_URL overrides it
+pub const DEFAULT_TESTNET_BASE_URL: &str = "https://testnet.binance.vision";
+/// REST base used for production requests unless BINANCE_PROD_BASE_URL overrides it
+pub const DEFAULT_PROD_BASE_URL: &str = "https://api.binance.com";
+
 #[derive(Clone)]
 pub struct BinanceCredentials {
     pub api_key: String,
     pub secret_key: String,
-    pub base_url: &'static str,
+    pub base_url: String,
+    /// Whether these are production keys; picks the environment's circuit breaker
+    pub use_production: bool,
+    /// WebSocket base for user data streams
+    pub stream_url: &'static str,
+    pub recv_window_ms: u64,
+    pub connect_timeout_ms: u64,
+    pub request_timeout_ms: u64,
 }
 
 #[derive(Clone)]
 pub struct Config {
     // Server
     pub port: u16,
+    /// Origins allowed by CORS; empty means any origin (dev only)
+    pub allowed_origins: Vec<String>,
 
     // Binance API - Testnet
     pub binance_testnet_api_key: String,
@@ -20,19 +41,112 @@ pub struct Config {
     pub binance_prod_api_key: Option<String>,
     pub binance_prod_secret_key: Option<String>,
 
+    /// REST base for testnet requests, e.g. a proxy or Binance-compatible venue
+    pub binance_testnet_base_url: String,
+    /// REST base for production requests
+    pub binance_prod_base_url: String,
+
+    /// How long (ms) a signed request stays valid after its timestamp
+    pub recv_window_ms: u64,
+    /// How long (ms) to wait for a TCP/TLS connection to Binance
+    pub http_connect_timeout_ms: u64,
+    /// Upper bound (ms) on a whole Binance request, including the response body
+    pub http_request_timeout_ms: u64,
+    /// Order placements, cancels and other writes allowed in flight at once
+    pub binance_order_concurrency: usize,
+    /// Reads allowed in flight at once
+    pub binance_read_concurrency: usize,
+    /// How long (ms) a request over either limit waits for a slot before failing
+    pub binance_queue_timeout_ms: u64,
+
     // JWT
     pub jwt_secret: String,
     pub jwt_expiry_minutes: i64,
+    /// `iss` claim set on issued tokens and required on incoming ones
+    pub jwt_issuer: String,
+    /// `aud` claim set on issued tokens and required on incoming ones
+    pub jwt_audience: String,
 
     // Security
     pub app_secret: String, // Shared secret with iOS app for request signing
+    /// Reject authenticated requests without a valid `X-Signature` HMAC
+    pub require_request_signatures: bool,
+    /// How far (seconds) a signed request's `X-Timestamp` may be from now
+    pub request_signature_window_secs: u64,
 
     // Apple Push Notifications
     pub apns_key_path: Option<String>,
     pub apns_key_content: Option<String>,
     pub apns_key_id: String,
     pub apns_team_id: String,
+    /// Environment for push tokens registered without one
     pub apns_production: bool,
+    /// Resend through the other APNs endpoint when one rejects a token as BadDeviceToken
+    pub apns_retry_other_environment: bool,
+    /// Rebuild the APNs clients from the key and resend once when APNs rejects the provider token
+    pub apns_refresh_on_auth_error: bool,
+    /// Refuse to start without an APNs key instead of running with notifications off
+    pub apns_required: bool,
+    /// JSON file holding the order monitor's last seen trade and known orders;
+    /// the trailing history is kept next to it. Empty turns persistence off
+    pub monitor_state_path: String,
+    /// JSON file overriding fill notification copy; None uses the built-in text
+    pub notification_templates_path: Option<String>,
+    /// Seconds after a fill during which further fills are pushed together as
+    /// one summary; 0 pushes each fill on its own
+    pub notification_coalesce_secs: u64,
+
+    // Trading
+    /// Symbols the backend may trade; the first is the default
+    pub symbols: Vec<String>,
+    /// Relative quantity difference under which a buy and sell are treated as
+    /// the same size when pairing (0.01 = 1%)
+    pub quantity_match_tolerance: f64,
+    /// Oldest buy (hours before the sell) a fill notification may attribute
+    /// profit to; 0 disables the limit. `/history` matching is never limited.
+    pub profit_match_window_hours: u64,
+    /// Fee for orders resting on the book, as a fraction of notional (0.001 = 0.1%)
+    pub maker_fee: f64,
+    /// Fee for orders that take liquidity (market and crossing limit orders)
+    pub taker_fee: f64,
+    /// Smallest grid spread accepted, relative to the buy price (0.2 = 0.2%)
+    pub grid_min_spread_percent: f64,
+    /// Place a take-profit SELL automatically when a BUY fills
+    pub auto_sell_enabled: bool,
+    /// Take-profit distance above the buy fill price (1.0 = 1%)
+    pub auto_sell_target_percent: f64,
+    /// Expose testing-only routes such as `/debug/simulate-fill`
+    pub debug_endpoints: bool,
+    /// Trailing orders one device may have active at once
+    pub max_trailing_orders_per_device: usize,
+    /// Daily UTC windows in which trailing moves and auto-sells are held back
+    pub no_trade_schedule: NoTradeSchedule,
+    /// Seconds after a fill during which a symbol's trailing moves and auto-sells
+    /// wait; 0 disables
+    pub fill_cooldown_secs: u64,
+    /// Seconds between checks that trailing orders still exist on Binance; 0 disables
+    pub trailing_reconcile_interval_secs: u64,
+    /// Largest gap between the best bid/ask and the last price a market order
+    /// may fill at (1.0 = 1%); 0 disables the check
+    pub max_slippage_percent: f64,
+    /// Largest order value in USD the order and grid routes accept; 0 disables the cap
+    pub max_order_usd: f64,
+    /// Open orders allowed per symbol, counting a new grid's two; 0 disables the check.
+    /// Binance's MAX_NUM_ORDERS filter is 200 for most symbols
+    pub max_open_orders: usize,
+    /// Realized loss in USD for one UTC day after which order routes refuse new
+    /// orders until midnight; 0 disables the kill switch
+    pub daily_loss_limit_usd: f64,
+    /// Age after which the streamed best bid/ask is stale and REST is used instead
+    pub book_stale_after_ms: u64,
+    /// Seconds between exchangeInfo reloads of the symbol table; 0 loads it only at startup
+    pub symbol_table_refresh_secs: u64,
+    /// Oldest best bid/ask a market order's slippage check will trust; 0 disables
+    pub max_price_age_ms: u64,
+    /// Where `/price/current` looks for a price, tried in order until one answers
+    pub price_sources: Vec<PriceSource>,
+    /// Serve the OpenAPI document at `/openapi.json`
+    pub expose_openapi: bool,
 }
 
 impl Config {
@@ -50,6 +164,7 @@ impl Config {
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
                 .unwrap_or(3000),
+            allowed_origins: parse_origins(&env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default()),
 
             binance_testnet_api_key: testnet_api_key,
             binance_testnet_secret_key: testnet_secret_key,
@@ -57,15 +172,61 @@ impl Config {
             binance_prod_api_key: env::var("BINANCE_PROD_API_KEY").ok(),
             binance_prod_secret_key: env::var("BINANCE_PROD_SECRET_KEY").ok(),
 
+            binance_testnet_base_url: parse_base_url(
+                env::var("BINANCE_TESTNET_BASE_URL").ok(),
+                DEFAULT_TESTNET_BASE_URL,
+            )
+            .unwrap_or_else(|e| panic!("Invalid BINANCE_TESTNET_BASE_URL: {}", e)),
+            binance_prod_base_url: parse_base_url(
+                env::var("BINANCE_PROD_BASE_URL").ok(),
+                DEFAULT_PROD_BASE_URL,
+            )
+            .unwrap_or_else(|e| panic!("Invalid BINANCE_PROD_BASE_URL: {}", e)),
+
+            recv_window_ms: parse_recv_window(env::var("BINANCE_RECV_WINDOW_MS").ok())
+                .unwrap_or_else(|e| panic!("Invalid BINANCE_RECV_WINDOW_MS: {}", e)),
+            http_connect_timeout_ms: env::var("HTTP_CONNECT_TIMEOUT_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            http_request_timeout_ms: env::var("HTTP_REQUEST_TIMEOUT_MS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+            binance_order_concurrency: env::var("BINANCE_ORDER_CONCURRENCY")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            binance_read_concurrency: env::var("BINANCE_READ_CONCURRENCY")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
+            binance_queue_timeout_ms: env::var("BINANCE_QUEUE_TIMEOUT_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+
             jwt_secret: env::var("JWT_SECRET")
                 .expect("JWT_SECRET must be set"),
             jwt_expiry_minutes: env::var("JWT_EXPIRY_MINUTES")
                 .unwrap_or_else(|_| "15".to_string())
                 .parse()
                 .unwrap_or(15),
+            jwt_issuer: env::var("JWT_ISSUER")
+                .unwrap_or_else(|_| "btc-trading-backend".to_string()),
+            jwt_audience: env::var("JWT_AUDIENCE")
+                .unwrap_or_else(|_| "btc-widget".to_string()),
 
             app_secret: env::var("APP_SECRET")
                 .expect("APP_SECRET must be set"),
+            require_request_signatures: env::var("REQUIRE_REQUEST_SIGNATURES")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            request_signature_window_secs: env::var("REQUEST_SIGNATURE_WINDOW_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
 
             apns_key_path: env::var("APNS_KEY_PATH").ok(),
             apns_key_content: env::var("APNS_KEY_CONTENT").ok(),
@@ -77,6 +238,113 @@ impl Config {
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
                 .unwrap_or(false),
+            apns_retry_other_environment: env::var("APNS_RETRY_OTHER_ENVIRONMENT")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            apns_refresh_on_auth_error: env::var("APNS_REFRESH_ON_AUTH_ERROR")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()
+                .unwrap_or(true),
+            apns_required: env::var("APNS_REQUIRED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            monitor_state_path: env::var("MONITOR_STATE_PATH")
+                .unwrap_or_else(|_| "./monitor_state.json".to_string()),
+            notification_templates_path: env::var("NOTIFICATION_TEMPLATES_PATH").ok(),
+            notification_coalesce_secs: env::var("NOTIFICATION_COALESCE_SECS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+
+            symbols: parse_symbols(&env::var("SYMBOLS").unwrap_or_default()),
+            quantity_match_tolerance: env::var("QUANTITY_MATCH_TOLERANCE")
+                .unwrap_or_else(|_| "0.01".to_string())
+                .parse()
+                .unwrap_or(0.01),
+            profit_match_window_hours: env::var("PROFIT_MATCH_WINDOW_HOURS")
+                .unwrap_or_else(|_| "168".to_string())
+                .parse()
+                .unwrap_or(168),
+            // FEE_RATE is the older single-rate name
+            maker_fee: env::var("MAKER_FEE")
+                .or_else(|_| env::var("FEE_RATE"))
+                .unwrap_or_else(|_| "0.001".to_string())
+                .parse()
+                .unwrap_or(0.001),
+            taker_fee: env::var("TAKER_FEE")
+                .unwrap_or_else(|_| "0.001".to_string())
+                .parse()
+                .unwrap_or(0.001),
+            grid_min_spread_percent: env::var("GRID_MIN_SPREAD_PERCENT")
+                .unwrap_or_else(|_| "0.2".to_string())
+                .parse()
+                .unwrap_or(0.2),
+            auto_sell_enabled: env::var("AUTO_SELL_ENABLED")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            auto_sell_target_percent: env::var("AUTO_SELL_TARGET_PERCENT")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .unwrap_or(1.0),
+            debug_endpoints: env::var("DEBUG_ENDPOINTS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            max_trailing_orders_per_device: env::var("MAX_TRAILING_ORDERS_PER_DEVICE")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            no_trade_schedule: env::var("NO_TRADE_WINDOWS")
+                .unwrap_or_default()
+                .parse()
+                .unwrap_or_else(|e| panic!("Invalid NO_TRADE_WINDOWS: {}", e)),
+            fill_cooldown_secs: env::var("FILL_COOLDOWN_SECS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0),
+            trailing_reconcile_interval_secs: env::var("TRAILING_RECONCILE_INTERVAL_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            max_slippage_percent: env::var("MAX_SLIPPAGE_PERCENT")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .unwrap_or(1.0),
+            max_order_usd: env::var("MAX_ORDER_USD")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            max_open_orders: env::var("MAX_OPEN_ORDERS")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200),
+            daily_loss_limit_usd: env::var("DAILY_LOSS_LIMIT_USD")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0),
+            book_stale_after_ms: env::var("BOOK_STALE_AFTER_MS")
+                .unwrap_or_else(|_| "5000".to_string())
+                .parse()
+                .unwrap_or(5000),
+            symbol_table_refresh_secs: env::var("SYMBOL_TABLE_REFRESH_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            max_price_age_ms: env::var("MAX_PRICE_AGE_MS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .unwrap_or(10000),
+            price_sources: parse_price_sources(
+                &env::var("PRICE_SOURCES")
+                    .unwrap_or_else(|_| "binance,coinbase,kraken".to_string()),
+            ),
+            expose_openapi: env::var("EXPOSE_OPENAPI")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
         }
     }
 
@@ -88,7 +356,12 @@ impl Config {
                 (Some(api_key), Some(secret_key)) => Some(BinanceCredentials {
                     api_key: api_key.clone(),
                     secret_key: secret_key.clone(),
-                    base_url: "https://api.binance.com",
+                    base_url: self.binance_prod_base_url.clone(),
+                    use_production: true,
+                    stream_url: "wss://stream.binance.com:9443/ws",
+                    recv_window_ms: self.recv_window_ms,
+                    connect_timeout_ms: self.http_connect_timeout_ms,
+                    request_timeout_ms: self.http_request_timeout_ms,
                 }),
                 _ => None, // Production keys not configured
             }
@@ -96,13 +369,293 @@ impl Config {
             Some(BinanceCredentials {
                 api_key: self.binance_testnet_api_key.clone(),
                 secret_key: self.binance_testnet_secret_key.clone(),
-                base_url: "https://testnet.binance.vision",
+                base_url: self.binance_testnet_base_url.clone(),
+                use_production: false,
+                stream_url: "wss://testnet.binance.vision/ws",
+                recv_window_ms: self.recv_window_ms,
+                connect_timeout_ms: self.http_connect_timeout_ms,
+                request_timeout_ms: self.http_request_timeout_ms,
             })
         }
     }
 
+    /// Symbol used when a request doesn't name one
+    pub fn default_symbol(&self) -> &str {
+        &self.symbols[0]
+    }
+
+    /// Resolve a requested symbol against the allow-list, defaulting when absent
+    pub fn resolve_symbol(&self, requested: Option<&str>) -> Result<String, String> {
+        let Some(requested) = requested else {
+            return Ok(self.default_symbol().to_string());
+        };
+
+        let symbol = requested.trim().to_uppercase();
+        if self.symbols.contains(&symbol) {
+            Ok(symbol)
+        } else {
+            Err(format!("Symbol {} is not enabled", symbol))
+        }
+    }
+
     /// Check if production keys are configured
     pub fn has_production_keys(&self) -> bool {
         self.binance_prod_api_key.is_some() && self.binance_prod_secret_key.is_some()
     }
+
+    /// Config with placeholder secrets and defaults, for unit tests
+    #[cfg(test)]
+    pub fn for_tests() -> Self {
+        Self {
+            port: 3000,
+            allowed_origins: Vec::new(),
+            binance_testnet_api_key: "test_api_key".to_string(),
+            binance_testnet_secret_key: "test_secret_key".to_string(),
+            binance_prod_api_key: None,
+            binance_prod_secret_key: None,
+            binance_testnet_base_url: DEFAULT_TESTNET_BASE_URL.to_string(),
+            binance_prod_base_url: DEFAULT_PROD_BASE_URL.to_string(),
+            recv_window_ms: DEFAULT_RECV_WINDOW_MS,
+            http_connect_timeout_ms: 5000,
+            http_request_timeout_ms: 10000,
+            binance_order_concurrency: 5,
+            binance_read_concurrency: 20,
+            binance_queue_timeout_ms: 5000,
+            jwt_secret: "test_jwt_secret".to_string(),
+            jwt_expiry_minutes: 15,
+            jwt_issuer: "btc-trading-backend".to_string(),
+            jwt_audience: "btc-widget".to_string(),
+            app_secret: "test_app_secret".to_string(),
+            require_request_signatures: false,
+            request_signature_window_secs: 300,
+            apns_key_path: None,
+            apns_key_content: None,
+            apns_key_id: "K3ABFWNN73".to_string(),
+            apns_team_id: "93K49S8Q8U".to_string(),
+            apns_production: false,
+            apns_retry_other_environment: true,
+            apns_refresh_on_auth_error: true,
+            apns_required: false,
+            monitor_state_path: String::new(),
+            notification_templates_path: None,
+            notification_coalesce_secs: 0,
+            symbols: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            quantity_match_tolerance: 0.01,
+            profit_match_window_hours: 168,
+            maker_fee: 0.001,
+            taker_fee: 0.001,
+            grid_min_spread_percent: 0.2,
+            auto_sell_enabled: false,
+            auto_sell_target_percent: 1.0,
+            debug_endpoints: false,
+            max_trailing_orders_per_device: 10,
+            no_trade_schedule: NoTradeSchedule::default(),
+            fill_cooldown_secs: 0,
+            trailing_reconcile_interval_secs: 300,
+            max_slippage_percent: 1.0,
+            max_order_usd: 0.0,
+            max_open_orders: 200,
+            daily_loss_limit_usd: 0.0,
+            book_stale_after_ms: 5000,
+            symbol_table_refresh_secs: 3600,
+            max_price_age_ms: 10000,
+            price_sources: vec![PriceSource::Binance],
+            expose_openapi: false,
+        }
+    }
+}
+
+/// Parse a comma-separated origin list, ignoring blanks
+fn parse_origins(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|o| o.trim())
+        .filter(|o| !o.is_empty())
+        .map(|o| o.to_string())
+        .collect()
+}
+
+/// Parse the comma-separated symbol allow-list, defaulting to BTCUSDT
+fn parse_symbols(value: &str) -> Vec<String> {
+    let symbols: Vec<String> = parse_origins(value)
+        .into_iter()
+        .map(|s| s.to_uppercase())
+        .collect();
+
+    if symbols.is_empty() {
+        vec!["BTCUSDT".to_string()]
+    } else {
+        symbols
+    }
+}
+
+/// Parse the ordered price source list, skipping unknown names and
+/// falling back to Binance alone if nothing usable is left
+fn parse_price_sources(value: &str) -> Vec<PriceSource> {
+    let sources: Vec<PriceSource> = parse_origins(value)
+        .iter()
+        .filter_map(|name| match name.parse() {
+            Ok(source) => Some(source),
+            Err(e) => {
+                tracing::warn!("Ignoring PRICE_SOURCES entry: {}", e);
+                None
+            }
+        })
+        .collect();
+
+    if sources.is_empty() {
+        vec![PriceSource::Binance]
+    } else {
+        sources
+    }
+}
+
+/// Parse recvWindow, defaulting when unset and rejecting values Binance won't accept
+fn parse_recv_window(value: Option<String>) -> Result<u64, String> {
+    let Some(value) = value else {
+        return Ok(DEFAULT_RECV_WINDOW_MS);
+    };
+
+    let recv_window: u64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+
+    if recv_window == 0 || recv_window > MAX_RECV_WINDOW_MS {
+        return Err(format!(
+            "{} must be between 1 and {}",
+            recv_window, MAX_RECV_WINDOW_MS
+        ));
+    }
+
+    Ok(recv_window)
+}
+
+/// Parse a REST base URL override, defaulting when unset
+///
+/// Only http(s) URLs with a host and no query are accepted, and a trailing
+/// slash is dropped since request paths are appended directly.
+fn parse_base_url(value: Option<String>, default: &str) -> Result<String, String> {
+    let Some(value) = value.filter(|v| !v.trim().is_empty()) else {
+        return Ok(default.to_string());
+    };
+
+    let trimmed = value.trim().trim_end_matches('/');
+    let url = reqwest::Url::parse(trimmed).map_err(|e| format!("'{}': {}", value, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("'{}' must use http or https", value));
+    }
+    if url.host_str().is_none() {
+        return Err(format!("'{}' has no host", value));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(format!("'{}' must not have a query or fragment", value));
+    }
+
+    Ok(trimmed.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_origins() {
+        let origins = parse_origins("https://a.com,https://b.com");
+        assert_eq!(origins, vec!["https://a.com", "https://b.com"]);
+    }
+
+    #[test]
+    fn test_parse_origins_trims_and_skips_blanks() {
+        assert_eq!(parse_origins(" https://a.com , ,"), vec!["https://a.com"]);
+        assert!(parse_origins("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_symbols_defaults_to_btcusdt() {
+        assert_eq!(parse_symbols(""), vec!["BTCUSDT"]);
+        assert_eq!(parse_symbols("btcusdt, ethusdt"), vec!["BTCUSDT", "ETHUSDT"]);
+    }
+
+    #[test]
+    fn test_parse_price_sources() {
+        assert_eq!(
+            parse_price_sources("kraken, Binance"),
+            vec![PriceSource::Kraken, PriceSource::Binance]
+        );
+        assert_eq!(parse_price_sources("bitstamp,coinbase"), vec![PriceSource::Coinbase]);
+        assert_eq!(parse_price_sources(""), vec![PriceSource::Binance]);
+    }
+
+    #[test]
+    fn test_resolve_symbol() {
+        let config = Config::for_tests();
+
+        assert_eq!(config.resolve_symbol(None), Ok("BTCUSDT".to_string()));
+        assert_eq!(config.resolve_symbol(Some("ethusdt")), Ok("ETHUSDT".to_string()));
+        assert!(config.resolve_symbol(Some("DOGEUSDT")).is_err());
+    }
+
+    #[test]
+    fn test_recv_window_defaults() {
+        assert_eq!(parse_recv_window(None), Ok(DEFAULT_RECV_WINDOW_MS));
+    }
+
+    #[test]
+    fn test_recv_window_accepts_bounds() {
+        assert_eq!(parse_recv_window(Some("1".to_string())), Ok(1));
+        assert_eq!(parse_recv_window(Some("60000".to_string())), Ok(60_000));
+    }
+
+    #[test]
+    fn test_recv_window_rejects_above_max() {
+        assert!(parse_recv_window(Some("60001".to_string())).is_err());
+        assert!(parse_recv_window(Some("0".to_string())).is_err());
+        assert!(parse_recv_window(Some("abc".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_base_url_defaults_when_unset() {
+        assert_eq!(
+            parse_base_url(None, DEFAULT_PROD_BASE_URL),
+            Ok(DEFAULT_PROD_BASE_URL.to_string())
+        );
+        assert_eq!(
+            parse_base_url(Some(" ".to_string()), DEFAULT_TESTNET_BASE_URL),
+            Ok(DEFAULT_TESTNET_BASE_URL.to_string())
+        );
+    }
+
+    #[test]
+    fn test_base_url_validation() {
+        let parse = |value: &str| parse_base_url(Some(value.to_string()), DEFAULT_PROD_BASE_URL);
+
+        assert_eq!(parse("https://proxy.example.com/").unwrap(), "https://proxy.example.com");
+        assert_eq!(
+            parse("http://10.0.0.5:8080/binance").unwrap(),
+            "http://10.0.0.5:8080/binance"
+        );
+        assert!(parse("proxy.example.com").is_err());
+        assert!(parse("ftp://proxy.example.com").is_err());
+        assert!(parse("https://proxy.example.com?key=1").is_err());
+    }
+
+    #[test]
+    fn test_base_url_override_flows_into_credentials() {
+        let mut config = Config::for_tests();
+        config.binance_prod_api_key = Some("prod_key".to_string());
+        config.binance_prod_secret_key = Some("prod_secret".to_string());
+        assert_eq!(config.get_credentials(true).unwrap().base_url, DEFAULT_PROD_BASE_URL);
+
+        config.binance_prod_base_url =
+            parse_base_url(Some("https://exchange.example.com/".to_string()), DEFAULT_PROD_BASE_URL)
+                .unwrap();
+        config.binance_testnet_base_url = "http://127.0.0.1:9000".to_string();
+
+        assert_eq!(
+            config.get_credentials(true).unwrap().base_url,
+            "https://exchange.example.com"
+        );
+        assert_eq!(config.get_credentials(false).unwrap().base_url, "http://127.0.0.1:9000");
+    }
 }
@@ -0,0 +1,26 @@
+//! Rounding for amounts in JSON responses and order prices
+//!
+//! Calculations keep full precision; these serializers only trim float noise
+//! (`123.4500000001`) from what the app is sent.
+
+use serde::Serializer;
+
+/// Decimal places kept on each side: cents for USD, satoshis for crypto
+pub const USD_DECIMALS: i32 = 2;
+pub const ASSET_DECIMALS: i32 = 8;
+
+/// Round `value` to `decimals` places, e.g. a USD price to the cent
+pub fn round_to(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+/// `serialize_with` for USD amounts, rounded to cents
+pub fn usd<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(round_to(*value, USD_DECIMALS))
+}
+
+/// `serialize_with` for asset quantities, rounded to satoshis
+pub fn asset<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(round_to(*value, ASSET_DECIMALS))
+}
@@ -1,14 +1,19 @@
 mod auth;
 mod binance;
 mod config;
+mod display;
+mod metrics;
 mod notifications;
+mod pricing;
+mod redact;
+mod request_id;
 mod routes;
 mod trading;
 mod trailing;
 
 use axum::{
     http::{HeaderValue, Method},
-    Router,
+    middleware, Router,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -16,30 +21,47 @@ use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
 };
+use tokio_util::sync::CancellationToken;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use notifications::{ApnsClient, OrderMonitor};
+use notifications::{ApnsClient, NotificationTemplates, OrderMonitor};
 use trailing::TrailingMonitor;
 
 #[tokio::main]
 async fn main() {
-    // Initialize logging
+    dotenvy::dotenv().ok();
+
+    // Initialize logging - LOG_FORMAT=json for machine-parseable output
+    let json_logs = std::env::var("LOG_FORMAT").is_ok_and(|v| v.eq_ignore_ascii_case("json"));
     tracing_subscriber::registry()
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "btc_trading_backend=debug,tower_http=debug".into()),
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+                "btc_trading_backend=debug,tower_http=debug,access_log=info".into()
+            }),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(json_logs.then(|| tracing_subscriber::fmt::layer().json()))
+        .with((!json_logs).then(tracing_subscriber::fmt::layer))
         .init();
 
-    // Load configuration
-    dotenvy::dotenv().ok();
-    let config = config::Config::from_env();
+    // Load configuration once; every router and the auth middleware share this instance
+    let config = Arc::new(config::Config::from_env());
 
     tracing::info!("Starting BTC Trading Backend");
     tracing::info!("Testnet keys: configured");
     tracing::info!("Production keys: {}", if config.has_production_keys() { "configured" } else { "NOT configured" });
 
+    binance::configure_throttle(binance::RequestThrottle::new(
+        config.binance_order_concurrency,
+        config.binance_read_concurrency,
+        std::time::Duration::from_millis(config.binance_queue_timeout_ms),
+    ));
+
+    let templates = match config.notification_templates_path.as_deref() {
+        Some(path) => NotificationTemplates::from_file(path)
+            .unwrap_or_else(|e| panic!("Invalid NOTIFICATION_TEMPLATES_PATH: {}", e)),
+        None => NotificationTemplates::default(),
+    };
+
     // Initialize APNs client - try APNS_KEY_CONTENT first (for cloud), then APNS_KEY_PATH (for local)
     let apns = if let Some(ref key_content) = config.apns_key_content {
         match ApnsClient::from_key_content(
@@ -52,7 +74,12 @@ async fn main() {
         {
             Ok(client) => {
                 tracing::info!("✅ APNs client initialized from key content");
-                Arc::new(client)
+                Arc::new(
+                    client
+                        .with_templates(templates)
+                        .with_retry_other_environment(config.apns_retry_other_environment)
+                        .with_refresh_on_auth_error(config.apns_refresh_on_auth_error),
+                )
             }
             Err(e) => {
                 panic!("APNs initialization failed: {}. Check APNS_KEY_CONTENT", e);
@@ -69,60 +96,272 @@ async fn main() {
         {
             Ok(client) => {
                 tracing::info!("✅ APNs client initialized from key file");
-                Arc::new(client)
+                Arc::new(
+                    client
+                        .with_templates(templates)
+                        .with_retry_other_environment(config.apns_retry_other_environment)
+                        .with_refresh_on_auth_error(config.apns_refresh_on_auth_error),
+                )
             }
             Err(e) => {
                 panic!("APNs initialization failed: {}. Check APNS_KEY_PATH", e);
             }
         }
-    } else {
+    } else if config.apns_required {
         panic!("APNs required. Set either APNS_KEY_CONTENT or APNS_KEY_PATH");
+    } else {
+        tracing::warn!("APNs not configured (APNS_KEY_CONTENT/APNS_KEY_PATH): notifications disabled");
+        Arc::new(ApnsClient::disabled())
     };
 
+    // Cancelled on SIGTERM/Ctrl-C so background monitors stop cleanly
+    let shutdown = CancellationToken::new();
+
+    // Load every configured symbol's tick/step sizes up front; anything missing is
+    // fetched from exchangeInfo on first use, with the default step as a last resort
+    let mut symbol_clients = vec![binance::BinanceClient::new(&config)];
+    symbol_clients.extend(binance::BinanceClient::for_environment(&config, true).ok());
+    if binance::load_symbol_table(&symbol_clients, &config.symbols).await {
+        tracing::info!("Loaded exchangeInfo for {}", config.symbols.join(", "));
+    } else {
+        tracing::warn!("Symbol table incomplete; precision falls back to per-request lookups");
+    }
+    if config.symbol_table_refresh_secs > 0 {
+        tokio::spawn(binance::refresh_symbol_table(
+            symbol_clients,
+            config.symbols.clone(),
+            std::time::Duration::from_secs(config.symbol_table_refresh_secs),
+            shutdown.clone(),
+        ));
+    }
+
     // Start order monitor in background
-    let monitor_apns = apns.clone();
-    let monitor_config = config.clone();
-    tokio::spawn(async move {
-        let monitor = OrderMonitor::new(monitor_config, monitor_apns);
-        monitor.start().await;
+    let order_monitor = Arc::new(OrderMonitor::new(config::Config::clone(&config), apns.clone()));
+    let monitor = order_monitor.clone();
+    let monitor_shutdown = shutdown.clone();
+    let order_monitor_handle = tokio::spawn(async move {
+        monitor.start(monitor_shutdown).await;
     });
 
     // Initialize trailing order monitor
-    let trailing_monitor = Arc::new(TrailingMonitor::new(config.clone()));
-    let trailing_monitor_task = trailing_monitor.clone();
+    let trailing_monitor = Arc::new(TrailingMonitor::new(config::Config::clone(&config)));
+    let trailing_monitor_handle = tokio::spawn(trailing_monitor.clone().start(shutdown.clone()));
+    tokio::spawn(trailing_monitor.clone().run_reconciliation(shutdown.clone()));
+
+    // Stream the default symbol's best bid/ask for the slippage guard and /price/book
+    let book_symbol = config.resolve_symbol(None).expect("default symbol");
+    let book_cache = Arc::new(binance::BookCache::new(
+        &binance::BinanceClient::new(&config),
+        &book_symbol,
+        std::time::Duration::from_millis(config.book_stale_after_ms),
+    ));
+    tokio::spawn(book_cache.clone().run(shutdown.clone()));
+
+    // Roll over the per-minute Binance weight bucket
+    let stats_shutdown = shutdown.clone();
     tokio::spawn(async move {
-        trailing_monitor_task.start().await;
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = stats_shutdown.cancelled() => break,
+                _ = interval.tick() => binance::STATS.reset_minute(),
+            }
+        }
     });
 
     // Build application with routes
-    let app = create_router(config.clone(), apns, trailing_monitor);
+    let app = create_router(config.clone(), apns, trailing_monitor, order_monitor, book_cache);
 
     // Start server
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
     tracing::info!("Listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let server_shutdown = shutdown.clone();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            server_shutdown.cancel();
+        })
+        .await
+        .unwrap();
+
+    // Let in-flight monitor iterations finish
+    let _ = tokio::join!(order_monitor_handle, trailing_monitor_handle);
+    tracing::info!("Shutdown complete");
 }
 
-fn create_router(config: config::Config, apns: Arc<ApnsClient>, trailing_monitor: Arc<TrailingMonitor>) -> Router {
-    // CORS configuration - restrict in production
+/// Resolve when the process receives Ctrl-C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    tracing::info!("Shutdown signal received, stopping");
+}
+
+fn create_router(
+    config: Arc<config::Config>,
+    apns: Arc<ApnsClient>,
+    trailing_monitor: Arc<TrailingMonitor>,
+    order_monitor: Arc<OrderMonitor>,
+    book_cache: Arc<binance::BookCache>,
+) -> Router {
+    // CORS configuration - explicit origins when configured, any origin otherwise
     let cors = CorsLayer::new()
-        .allow_origin(Any) // In production, restrict to your app's requests
-        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
-        .allow_headers(Any);
+        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE, Method::OPTIONS])
+        .allow_headers(Any)
+        .expose_headers([request_id::REQUEST_ID_HEADER.clone()]);
+
+    let cors = if config.allowed_origins.is_empty() {
+        tracing::warn!("CORS: allowing any origin (set CORS_ALLOWED_ORIGINS to restrict)");
+        cors.allow_origin(Any)
+    } else {
+        let origins: Vec<HeaderValue> = config
+            .allowed_origins
+            .iter()
+            .filter_map(|origin| match HeaderValue::from_str(origin) {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    tracing::warn!("CORS: ignoring invalid origin {:?}", origin);
+                    None
+                }
+            })
+            .collect();
+        tracing::info!("CORS: allowing origins {:?}", config.allowed_origins);
+        cors.allow_origin(origins)
+    };
 
     Router::new()
         .nest("/auth", routes::auth_routes())
-        .nest("/account", routes::account_routes())
-        .nest("/grid", routes::grid_routes())
-        .nest("/order", routes::order_routes(trailing_monitor.clone()))
-        .nest("/trailing", routes::trailing_routes(trailing_monitor))
-        .nest("/history", routes::history_routes())
-        .nest("/price", routes::price_routes())
-        .nest("/notifications", routes::notification_routes(apns))
-        .nest("/debug", routes::debug_routes())
-        .layer(TraceLayer::new_for_http())
+        .nest("/account", routes::account_routes(config.clone(), trailing_monitor.clone()))
+        .nest("/grid", routes::grid_routes(config.clone(), trailing_monitor.clone()))
+        .nest(
+            "/order",
+            routes::order_routes(
+                config.clone(),
+                trailing_monitor.clone(),
+                order_monitor.events(),
+                book_cache.clone(),
+            ),
+        )
+        .nest("/trailing", routes::trailing_routes(config.clone(), trailing_monitor))
+        .nest("/history", routes::history_routes(config.clone()))
+        .nest("/price", routes::price_routes(book_cache))
+        .nest("/notifications", routes::notification_routes(config.clone(), apns))
+        .nest("/debug", routes::debug_routes(config.clone(), order_monitor))
+        .merge(routes::metrics_routes())
+        .merge(routes::openapi_routes())
+        .layer(TraceLayer::new_for_http().make_span_with(request_id::request_span))
         .layer(cors)
+        .layer(middleware::from_fn(request_id::request_id_middleware))
         .with_state(config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    fn request(method: Method, uri: &str, token: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_serves_orders_with_notifications_disabled() {
+        let config = Arc::new(config::Config::for_tests());
+        let apns = Arc::new(ApnsClient::disabled());
+        let order_monitor = Arc::new(OrderMonitor::new(config::Config::clone(&config), apns.clone()));
+        let trailing_monitor = Arc::new(TrailingMonitor::new(config::Config::clone(&config)));
+        let book_cache = Arc::new(binance::BookCache::new(
+            &binance::BinanceClient::new(&config),
+            "BTCUSDT",
+            std::time::Duration::from_secs(5),
+        ));
+        let app = create_router(config.clone(), apns, trailing_monitor, order_monitor, book_cache);
+        let token = auth::create_token(
+            "device-1",
+            "Test iPhone",
+            &config.jwt_secret,
+            config.jwt_expiry_minutes,
+            &config.jwt_issuer,
+            &config.jwt_audience,
+        )
+        .unwrap();
+
+        // Order routes answer (here rejecting a bad side before reaching Binance)
+        let order = serde_json::json!({ "side": "HOLD", "price": 50000.0, "quantity": 0.001 });
+        let response = app
+            .clone()
+            .oneshot(request(Method::POST, "/order/limit", &token, order))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request(Method::GET, "/trailing/orders", &token, serde_json::Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Browsers may preflight trailing updates
+        let preflight = Request::builder()
+            .method(Method::OPTIONS)
+            .uri(format!("/trailing/order/{}", uuid::Uuid::new_v4()))
+            .header("Origin", "https://widget.example")
+            .header("Access-Control-Request-Method", "PATCH")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(preflight).await.unwrap();
+        let allowed = response.headers()["access-control-allow-methods"].to_str().unwrap();
+        assert!(allowed.contains("PATCH"), "{}", allowed);
+
+        // Registering still works; only sending is off
+        let registration = serde_json::json!({ "device_token": "ab".repeat(32), "platform": "ios" });
+        let response = app
+            .clone()
+            .oneshot(request(Method::POST, "/notifications/register", &token, registration))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(request(Method::POST, "/notifications/test", &token, serde_json::Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["error"].as_str().unwrap().starts_with("Notifications disabled"));
+    }
+}
@@ -0,0 +1,337 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the Binance latency histogram buckets
+const LATENCY_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Process-wide metrics scraped from `/metrics`
+pub static METRICS: Metrics = Metrics::new();
+
+/// Push delivery counters, as `/debug/stats` reports them
+#[derive(Debug, Serialize)]
+pub struct PushSnapshot {
+    pub sent: u64,
+    pub failed: u64,
+    /// Extra attempts made after transient APNs failures
+    pub retries: u64,
+}
+
+/// Cumulative latency histogram for one endpoint
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+/// Counters and gauges exported in the Prometheus text format
+pub struct Metrics {
+    buy_orders: AtomicU64,
+    sell_orders: AtomicU64,
+    trailing_adjustments: AtomicU64,
+    notifications_sent: AtomicU64,
+    notifications_failed: AtomicU64,
+    notification_retries: AtomicU64,
+    active_trailing_orders: AtomicI64,
+    binance_latency: Mutex<BTreeMap<&'static str, Histogram>>,
+}
+
+impl Metrics {
+    pub const fn new() -> Self {
+        Self {
+            buy_orders: AtomicU64::new(0),
+            sell_orders: AtomicU64::new(0),
+            trailing_adjustments: AtomicU64::new(0),
+            notifications_sent: AtomicU64::new(0),
+            notifications_failed: AtomicU64::new(0),
+            notification_retries: AtomicU64::new(0),
+            active_trailing_orders: AtomicI64::new(0),
+            binance_latency: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Count an order accepted by Binance
+    pub fn record_order(&self, side: &str) {
+        let counter = if side.eq_ignore_ascii_case("BUY") {
+            &self.buy_orders
+        } else {
+            &self.sell_orders
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_trailing_adjustment(&self) {
+        self.trailing_adjustments.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count one push notification delivery attempt
+    pub fn record_notification(&self, sent: bool) {
+        let counter = if sent {
+            &self.notifications_sent
+        } else {
+            &self.notifications_failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count an extra APNs attempt after a transient failure
+    pub fn record_notification_retry(&self) {
+        self.notification_retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn push_snapshot(&self) -> PushSnapshot {
+        PushSnapshot {
+            sent: self.notifications_sent.load(Ordering::Relaxed),
+            failed: self.notifications_failed.load(Ordering::Relaxed),
+            retries: self.notification_retries.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn set_active_trailing_orders(&self, count: usize) {
+        self.active_trailing_orders.store(count as i64, Ordering::Relaxed);
+    }
+
+    /// Record how long a Binance request to `endpoint` took
+    pub fn observe_binance_latency(&self, endpoint: &'static str, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let mut latency = self.binance_latency.lock().unwrap();
+        let histogram = latency.entry(endpoint).or_default();
+
+        for (bucket, bound) in histogram.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    /// Render every metric in the Prometheus text exposition format (0.0.4)
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        write_family(
+            &mut out,
+            "btc_orders_placed_total",
+            "counter",
+            "Orders accepted by Binance, by side",
+        );
+        let _ = writeln!(
+            out,
+            "btc_orders_placed_total{{side=\"buy\"}} {}",
+            self.buy_orders.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "btc_orders_placed_total{{side=\"sell\"}} {}",
+            self.sell_orders.load(Ordering::Relaxed)
+        );
+
+        write_family(
+            &mut out,
+            "btc_trailing_adjustments_total",
+            "counter",
+            "Trailing orders moved to a new price",
+        );
+        let _ = writeln!(
+            out,
+            "btc_trailing_adjustments_total {}",
+            self.trailing_adjustments.load(Ordering::Relaxed)
+        );
+
+        write_family(
+            &mut out,
+            "btc_notifications_total",
+            "counter",
+            "Push notification deliveries, by result",
+        );
+        let _ = writeln!(
+            out,
+            "btc_notifications_total{{result=\"sent\"}} {}",
+            self.notifications_sent.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "btc_notifications_total{{result=\"failed\"}} {}",
+            self.notifications_failed.load(Ordering::Relaxed)
+        );
+
+        write_family(
+            &mut out,
+            "btc_notification_retries_total",
+            "counter",
+            "Extra push attempts after transient APNs failures",
+        );
+        let _ = writeln!(
+            out,
+            "btc_notification_retries_total {}",
+            self.notification_retries.load(Ordering::Relaxed)
+        );
+
+        write_family(
+            &mut out,
+            "btc_active_trailing_orders",
+            "gauge",
+            "Trailing orders currently monitored",
+        );
+        let _ = writeln!(
+            out,
+            "btc_active_trailing_orders {}",
+            self.active_trailing_orders.load(Ordering::Relaxed)
+        );
+
+        write_family(
+            &mut out,
+            "binance_request_duration_seconds",
+            "histogram",
+            "Latency of outbound Binance requests, by endpoint",
+        );
+        let latency = self.binance_latency.lock().unwrap();
+        for (endpoint, histogram) in latency.iter() {
+            let endpoint = escape_label(endpoint);
+            for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "binance_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"{}\"}} {}",
+                    endpoint, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "binance_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}",
+                endpoint, histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "binance_request_duration_seconds_sum{{endpoint=\"{}\"}} {}",
+                endpoint, histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "binance_request_duration_seconds_count{{endpoint=\"{}\"}} {}",
+                endpoint, histogram.count
+            );
+        }
+
+        out
+    }
+}
+
+fn write_family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value per the exposition format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Minimal exposition-format check: every sample is `name[{labels}] value`
+    /// and belongs to a family declared with `# TYPE`
+    fn parse(text: &str) -> Vec<(String, f64)> {
+        let mut families = HashSet::new();
+        let mut samples = Vec::new();
+
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').expect("TYPE without kind");
+                assert!(["counter", "gauge", "histogram"].contains(&kind), "{}", line);
+                families.insert(name.to_string());
+                continue;
+            }
+            if line.starts_with("# HELP ") {
+                continue;
+            }
+
+            let (series, value) = line.rsplit_once(' ').expect("sample without value");
+            let name = series.split('{').next().unwrap();
+            assert!(
+                name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':'),
+                "bad metric name in {}",
+                line
+            );
+            if let Some(labels) = series.strip_prefix(name) {
+                assert!(
+                    labels.is_empty() || (labels.starts_with('{') && labels.ends_with('}')),
+                    "bad labels in {}",
+                    line
+                );
+            }
+
+            let family = ["_bucket", "_sum", "_count"]
+                .iter()
+                .find_map(|suffix| name.strip_suffix(suffix))
+                .filter(|base| families.contains(*base))
+                .unwrap_or(name);
+            assert!(families.contains(family), "no TYPE for {}", line);
+
+            samples.push((series.to_string(), value.parse().expect("non-numeric value")));
+        }
+
+        samples
+    }
+
+    #[test]
+    fn test_render_is_valid_prometheus_text() {
+        let metrics = Metrics::new();
+        metrics.record_order("BUY");
+        metrics.record_order("SELL");
+        metrics.record_order("SELL");
+        metrics.record_trailing_adjustment();
+        metrics.record_notification(true);
+        metrics.record_notification(false);
+        metrics.record_notification_retry();
+        metrics.set_active_trailing_orders(3);
+        metrics.observe_binance_latency("GET /api/v3/account", Duration::from_millis(30));
+        metrics.observe_binance_latency("GET /api/v3/account", Duration::from_millis(700));
+
+        let samples = parse(&metrics.render());
+        let value = |series: &str| {
+            samples
+                .iter()
+                .find(|(s, _)| s == series)
+                .unwrap_or_else(|| panic!("missing {}", series))
+                .1
+        };
+
+        assert_eq!(value("btc_orders_placed_total{side=\"buy\"}"), 1.0);
+        assert_eq!(value("btc_orders_placed_total{side=\"sell\"}"), 2.0);
+        assert_eq!(value("btc_trailing_adjustments_total"), 1.0);
+        assert_eq!(value("btc_notifications_total{result=\"sent\"}"), 1.0);
+        assert_eq!(value("btc_notifications_total{result=\"failed\"}"), 1.0);
+        assert_eq!(value("btc_notification_retries_total"), 1.0);
+        assert_eq!(value("btc_active_trailing_orders"), 3.0);
+
+        let endpoint = "endpoint=\"GET /api/v3/account\"";
+        assert_eq!(
+            value(&format!("binance_request_duration_seconds_bucket{{{},le=\"0.05\"}}", endpoint)),
+            1.0
+        );
+        assert_eq!(
+            value(&format!("binance_request_duration_seconds_bucket{{{},le=\"+Inf\"}}", endpoint)),
+            2.0
+        );
+        assert_eq!(
+            value(&format!("binance_request_duration_seconds_count{{{}}}", endpoint)),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
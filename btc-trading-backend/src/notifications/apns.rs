@@ -1,37 +1,448 @@
 use a2::{
-    Client, ClientConfig, DefaultNotificationBuilder, Endpoint, NotificationBuilder, NotificationOptions,
+    Client, ClientConfig, CollapseId, DefaultNotificationBuilder, Endpoint, ErrorReason,
+    NotificationBuilder, NotificationOptions, Priority,
 };
-use std::fs::File;
+use a2::request::payload::Payload;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
 use std::io::Cursor;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
+use super::batch::{batch_totals, BatchedFill};
+use super::templates::NotificationTemplates;
+use super::user_stream::FillNotification;
+use crate::metrics::METRICS;
+
+/// Bundle id of the iOS app notifications are addressed to
+const APNS_TOPIC: &str = "com.3dar.BTCWidget";
+
+/// Attempts per device before giving up on a transient failure
+const SEND_ATTEMPTS: u32 = 3;
+/// Delay before the first retry, doubled for each one after
+const RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// APNs endpoint a device token was issued for
+///
+/// Xcode and TestFlight builds get sandbox tokens, App Store builds get
+/// production ones, and each endpoint rejects the other's tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApnsEnvironment {
+    Sandbox,
+    Production,
+}
+
+impl ApnsEnvironment {
+    fn other(self) -> Self {
+        match self {
+            ApnsEnvironment::Sandbox => ApnsEnvironment::Production,
+            ApnsEnvironment::Production => ApnsEnvironment::Sandbox,
+        }
+    }
+}
+
+/// How urgently APNs should deliver a notification
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PushPriority {
+    /// Deliver immediately, waking the device
+    High,
+    /// Deliver when convenient for the device's battery
+    #[default]
+    Normal,
+}
+
+/// Per-notification delivery options
+#[derive(Debug, Clone, Default)]
+pub struct PushOptions {
+    pub priority: PushPriority,
+    /// Notifications sharing an id replace each other on the lock screen
+    /// (e.g. the symbol for price updates, so only the latest is shown)
+    pub collapse_id: Option<String>,
+    /// App icon badge to show; None leaves the current badge unchanged
+    pub badge: Option<u32>,
+}
+
+impl PushOptions {
+    /// Order fills are time-sensitive and each one matters
+    ///
+    /// `open_orders` becomes the badge so the icon shows what's still working.
+    pub fn fill(open_orders: Option<u32>) -> Self {
+        Self {
+            priority: PushPriority::High,
+            collapse_id: None,
+            badge: open_orders,
+        }
+    }
+}
+
+/// Translate our options into APNs request options
+fn apns_options(options: &PushOptions) -> NotificationOptions<'_> {
+    let priority = match options.priority {
+        PushPriority::High => Priority::High,
+        PushPriority::Normal => Priority::Normal,
+    };
+
+    // APNs rejects collapse ids over 64 bytes; send those uncollapsed
+    let collapse_id = options.collapse_id.as_deref().and_then(|id| match CollapseId::new(id) {
+        Ok(collapse_id) => Some(collapse_id),
+        Err(e) => {
+            tracing::warn!("Ignoring collapse id {:?}: {:?}", id, e);
+            None
+        }
+    });
+
+    NotificationOptions {
+        apns_topic: Some(APNS_TOPIC),
+        apns_priority: Some(priority),
+        apns_collapse_id: collapse_id,
+        ..Default::default()
+    }
+}
+
+/// Build the APNs payload for one device
+fn build_payload<'a>(
+    token: &'a str,
+    title: &'a str,
+    body: &'a str,
+    data: Option<&'a serde_json::Value>,
+    options: &'a PushOptions,
+) -> Payload<'a> {
+    let mut builder = DefaultNotificationBuilder::new()
+        .set_title(title)
+        .set_body(body)
+        .set_sound("default");
+
+    if let Some(badge) = options.badge {
+        builder = builder.set_badge(badge);
+    }
+
+    // Custom data goes beside `aps`, and wakes the app to handle it
+    let custom_data = data.and_then(|data| data.as_object());
+    if custom_data.is_some_and(|obj| !obj.is_empty()) {
+        builder = builder.set_content_available();
+    }
+
+    let mut payload = builder.build(token, apns_options(options));
+    for (key, value) in custom_data.into_iter().flatten() {
+        // Serializing a JSON value can't fail
+        let _ = payload.add_custom_data(key, value);
+    }
+    payload
+}
+
+/// Whether APNs might accept the same notification if it's sent again
+///
+/// Dropped connections and 5xx/429 responses (InternalServerError,
+/// ServiceUnavailable, Shutdown, TooManyRequests) are transient. 4xx responses
+/// such as BadDeviceToken or Unregistered fail the same way every time.
+fn is_retryable(error: &a2::Error) -> bool {
+    match error {
+        a2::Error::ResponseError(response) => response.code >= 500 || response.code == 429,
+        a2::Error::ConnectionError(_) => true,
+        _ => false,
+    }
+}
+
+/// Whether APNs rejected the token itself, as it does for the other environment's tokens
+fn is_bad_device_token(error: &a2::Error) -> bool {
+    match error {
+        a2::Error::ResponseError(response) => matches!(
+            response.error.as_ref().map(|body| &body.reason),
+            Some(ErrorReason::BadDeviceToken)
+        ),
+        _ => false,
+    }
+}
+
+/// Send through `environment`'s endpoint, trying the other one if it rejects the token
+///
+/// Returns the environment that accepted the notification. A failure from the
+/// other endpoint too reports the original error.
+async fn send_to_environment<T, E, F, Fut>(
+    environment: ApnsEnvironment,
+    try_other: bool,
+    mut send: F,
+    wrong_environment: fn(&E) -> bool,
+) -> Result<(T, ApnsEnvironment), E>
+where
+    F: FnMut(ApnsEnvironment) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    match send(environment).await {
+        Ok(value) => Ok((value, environment)),
+        Err(e) if try_other && wrong_environment(&e) => {
+            let other = environment.other();
+            match send(other).await {
+                Ok(value) => Ok((value, other)),
+                Err(_) => Err(e),
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Run `send` until it succeeds, fails permanently, or runs out of attempts
+async fn send_with_retry<T, E, F, Fut>(
+    mut send: F,
+    retryable: fn(&E) -> bool,
+    backoff: Duration,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Debug,
+{
+    let mut delay = backoff;
+    let mut attempt = 1;
+
+    loop {
+        match send().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < SEND_ATTEMPTS && retryable(&e) => {
+                tracing::warn!(
+                    "APNs send failed (attempt {}), retrying in {:?}: {:?}",
+                    attempt,
+                    delay,
+                    e
+                );
+                METRICS.record_notification_retry();
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether APNs refused our provider token rather than the notification
+///
+/// a2 signs a JWT with the .p8 key and renews it itself, but a token rejected
+/// as expired or invalid keeps failing every send until the client is rebuilt.
+fn is_auth_error(error: &a2::Error) -> bool {
+    match error {
+        a2::Error::ResponseError(response) => matches!(
+            response.error.as_ref().map(|body| &body.reason),
+            Some(
+                ErrorReason::ExpiredProviderToken
+                    | ErrorReason::InvalidProviderToken
+                    | ErrorReason::MissingProviderToken
+            )
+        ),
+        a2::Error::SignerError(_) => true,
+        _ => false,
+    }
+}
+
+/// Send to every target, and if any fail with an auth error, `refresh` once
+/// and resend to just those
+///
+/// Results are in `targets` order. A refresh that fails, or a second auth
+/// error after it, leaves the original error in place.
+async fn send_all_with_refresh<I, T, E, S, SFut, R, RFut>(
+    targets: &[I],
+    mut send: S,
+    auth_error: fn(&E) -> bool,
+    refresh: R,
+) -> Vec<Result<T, E>>
+where
+    I: Clone,
+    S: FnMut(I) -> SFut,
+    SFut: Future<Output = Result<T, E>>,
+    R: FnOnce() -> RFut,
+    RFut: Future<Output = bool>,
+{
+    let mut results = Vec::with_capacity(targets.len());
+    for target in targets {
+        results.push(send(target.clone()).await);
+    }
+
+    let rejected = |result: &Result<T, E>| matches!(result, Err(e) if auth_error(e));
+    if !results.iter().any(rejected) {
+        return results;
+    }
+
+    tracing::warn!("APNs rejected the provider token, refreshing the client");
+    if !refresh().await {
+        return results;
+    }
+    for (target, result) in targets.iter().zip(results.iter_mut()) {
+        if rejected(result) {
+            *result = send(target.clone()).await;
+        }
+    }
+    results
+}
+
+/// A device's push token and the endpoint it belongs to
+#[derive(Debug, Clone, PartialEq)]
+struct RegisteredToken {
+    token: String,
+    environment: ApnsEnvironment,
+}
+
+/// One push token per device, so a reinstall's new token replaces the old one
+#[derive(Debug, Default)]
+struct DeviceTokens {
+    /// Device id -> its current APNs token
+    by_device: BTreeMap<String, RegisteredToken>,
+}
+
+impl DeviceTokens {
+    /// Store `token` as `device_id`'s only token, returning the token it replaced
+    ///
+    /// The token is also dropped from any other device holding it, so no
+    /// token is ever notified twice.
+    fn register(
+        &mut self,
+        device_id: &str,
+        token: String,
+        environment: ApnsEnvironment,
+    ) -> Option<String> {
+        self.by_device
+            .retain(|device, existing| device == device_id || existing.token != token);
+        self.by_device
+            .insert(
+                device_id.to_string(),
+                RegisteredToken {
+                    token: token.clone(),
+                    environment,
+                },
+            )
+            .map(|previous| previous.token)
+            .filter(|previous| *previous != token)
+    }
+
+    fn unregister(&mut self, token: &str) {
+        self.by_device.retain(|_, existing| existing.token != token);
+    }
+
+    /// Remember that `token` was accepted by `environment` rather than the one registered
+    fn set_environment(&mut self, token: &str, environment: ApnsEnvironment) {
+        for existing in self.by_device.values_mut() {
+            if existing.token == token {
+                existing.environment = environment;
+            }
+        }
+    }
+
+    fn tokens(&self) -> Vec<RegisteredToken> {
+        self.by_device.values().cloned().collect()
+    }
+}
+
+/// Connections to both APNs endpoints
+struct Endpoints {
+    sandbox: Client,
+    production: Client,
+}
+
+impl Endpoints {
+    fn client(&self, environment: ApnsEnvironment) -> &Client {
+        match environment {
+            ApnsEnvironment::Sandbox => &self.sandbox,
+            ApnsEnvironment::Production => &self.production,
+        }
+    }
+}
+
+/// Where the .p8 signing key comes from
+#[derive(Clone)]
+enum KeySource {
+    /// Key text (APNS_KEY_CONTENT)
+    Content(String),
+    /// Key file (APNS_KEY_PATH), read again on every rebuild
+    Path(String),
+}
+
+/// Key material the endpoint clients are built from, kept to rebuild them
+#[derive(Clone)]
+struct ApnsKey {
+    source: KeySource,
+    key_id: String,
+    team_id: String,
+}
+
+impl ApnsKey {
+    /// Build clients for both endpoints from the key
+    fn endpoints(&self) -> Result<Endpoints, Box<dyn std::error::Error + Send + Sync>> {
+        let content = match &self.source {
+            KeySource::Content(content) => content.clone(),
+            KeySource::Path(path) => std::fs::read_to_string(path)?,
+        };
+        let client = |endpoint| {
+            let mut cursor = Cursor::new(content.as_bytes());
+            Client::token(&mut cursor, &self.key_id, &self.team_id, ClientConfig::new(endpoint))
+        };
+
+        Ok(Endpoints {
+            sandbox: client(Endpoint::Sandbox)?,
+            production: client(Endpoint::Production)?,
+        })
+    }
+}
+
+/// The live endpoint clients and the key to rebuild them from
+struct Connection {
+    key: ApnsKey,
+    endpoints: RwLock<Arc<Endpoints>>,
+}
+
+impl Connection {
+    fn new(key: ApnsKey) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let endpoints = key.endpoints()?;
+        Ok(Self {
+            key,
+            endpoints: RwLock::new(Arc::new(endpoints)),
+        })
+    }
+
+    /// Replace both clients with new ones built from the key, returning
+    /// whether that worked
+    async fn refresh(&self) -> bool {
+        match self.key.endpoints() {
+            Ok(endpoints) => {
+                *self.endpoints.write().await = Arc::new(endpoints);
+                tracing::info!("🔑 APNs clients rebuilt from key {}", self.key.key_id);
+                true
+            }
+            Err(e) => {
+                tracing::error!("Failed to rebuild APNs clients: {}", e);
+                false
+            }
+        }
+    }
+}
+
 pub struct ApnsClient {
-    client: Client,
-    device_tokens: Arc<RwLock<Vec<String>>>,
+    /// None when no APNs key is configured; sends are then skipped
+    connection: Option<Connection>,
+    /// Rebuild the clients and resend once when APNs rejects the provider token
+    refresh_on_auth_error: bool,
+    /// Environment for tokens registered without one
+    default_environment: ApnsEnvironment,
+    /// Resend through the other endpoint when one rejects a token as BadDeviceToken
+    retry_other_environment: bool,
+    device_tokens: Arc<RwLock<DeviceTokens>>,
+    templates: NotificationTemplates,
 }
 
 impl ApnsClient {
     /// Create new APNs client from .p8 key file
+    ///
+    /// The file is read again whenever the clients are rebuilt, so a rotated
+    /// key is picked up without a restart.
     pub async fn new(
         key_path: &str,
         key_id: &str,
         team_id: &str,
         is_production: bool,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let mut key_file = File::open(key_path)?;
-        let endpoint = if is_production {
-            Endpoint::Production
-        } else {
-            Endpoint::Sandbox
-        };
-        let config = ClientConfig::new(endpoint);
-        let client = Client::token(&mut key_file, key_id, team_id, config)?;
-
-        Ok(Self {
-            client,
-            device_tokens: Arc::new(RwLock::new(Vec::new())),
-        })
+        let source = KeySource::Path(key_path.to_string());
+        Self::from_key(source, key_id, team_id, is_production)
     }
 
     /// Create new APNs client from key content string (for cloud deployment)
@@ -41,34 +452,98 @@ impl ApnsClient {
         team_id: &str,
         is_production: bool,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let mut cursor = Cursor::new(key_content.as_bytes());
-        let endpoint = if is_production {
-            Endpoint::Production
+        let source = KeySource::Content(key_content.to_string());
+        Self::from_key(source, key_id, team_id, is_production)
+    }
+
+    /// Both endpoints get a client; `is_production` only picks the environment
+    /// for tokens registered without one.
+    fn from_key(
+        source: KeySource,
+        key_id: &str,
+        team_id: &str,
+        is_production: bool,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let key = ApnsKey {
+            source,
+            key_id: key_id.to_string(),
+            team_id: team_id.to_string(),
+        };
+        let default_environment = if is_production {
+            ApnsEnvironment::Production
         } else {
-            Endpoint::Sandbox
+            ApnsEnvironment::Sandbox
         };
-        let config = ClientConfig::new(endpoint);
-        let client = Client::token(&mut cursor, key_id, team_id, config)?;
 
         Ok(Self {
-            client,
-            device_tokens: Arc::new(RwLock::new(Vec::new())),
+            connection: Some(Connection::new(key)?),
+            default_environment,
+            ..Self::disabled()
         })
     }
 
-    /// Register a device token
-    pub async fn register_token(&self, token: String) {
+    /// A client that accepts registrations but never sends, for running
+    /// without an APNs key (e.g. local development)
+    pub fn disabled() -> Self {
+        Self {
+            connection: None,
+            refresh_on_auth_error: true,
+            default_environment: ApnsEnvironment::Sandbox,
+            retry_other_environment: true,
+            device_tokens: Arc::new(RwLock::new(DeviceTokens::default())),
+            templates: NotificationTemplates::default(),
+        }
+    }
+
+    /// Whether notifications actually go out
+    pub fn is_enabled(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    /// Whether a push would reach anyone: sending is enabled and a device is registered
+    pub async fn has_recipients(&self) -> bool {
+        self.is_enabled() && !self.device_tokens.read().await.by_device.is_empty()
+    }
+
+    /// Use custom copy for fill notifications instead of the defaults
+    pub fn with_templates(mut self, templates: NotificationTemplates) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Whether to resend through the other endpoint on BadDeviceToken
+    pub fn with_retry_other_environment(mut self, retry: bool) -> Self {
+        self.retry_other_environment = retry;
+        self
+    }
+
+    /// Whether to rebuild the clients and resend when APNs rejects the provider token
+    pub fn with_refresh_on_auth_error(mut self, refresh: bool) -> Self {
+        self.refresh_on_auth_error = refresh;
+        self
+    }
+
+    /// Register `device_id`'s push token, replacing any token it had before
+    ///
+    /// Tokens registered without an environment use APNS_PRODUCTION's.
+    pub async fn register_token(
+        &self,
+        device_id: &str,
+        token: String,
+        environment: Option<ApnsEnvironment>,
+    ) {
+        let environment = environment.unwrap_or(self.default_environment);
         let mut tokens = self.device_tokens.write().await;
-        if !tokens.contains(&token) {
-            tokens.push(token);
-            tracing::info!("📱 Registered new device token");
+        if tokens.register(device_id, token, environment).is_some() {
+            tracing::info!("📱 Replaced device token for {} ({:?})", device_id, environment);
+        } else {
+            tracing::info!("📱 Registered device token for {} ({:?})", device_id, environment);
         }
     }
 
     /// Remove a device token
     pub async fn unregister_token(&self, token: &str) {
-        let mut tokens = self.device_tokens.write().await;
-        tokens.retain(|t| t != token);
+        self.device_tokens.write().await.unregister(token);
     }
 
     /// Send notification to all registered devices
@@ -77,41 +552,69 @@ impl ApnsClient {
         title: &str,
         body: &str,
         data: Option<serde_json::Value>,
+        options: &PushOptions,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let tokens = self.device_tokens.read().await;
+        let Some(connection) = &self.connection else {
+            tracing::debug!("APNs not configured, skipping notification: {}", title);
+            return Ok(());
+        };
+
+        // Cloned so registrations aren't blocked while sends retry
+        let tokens = self.device_tokens.read().await.tokens();
 
         if tokens.is_empty() {
             tracing::warn!("No device tokens registered, skipping notification");
             return Ok(());
         }
 
-        for token in tokens.iter() {
-            let mut builder = DefaultNotificationBuilder::new()
-                .set_title(title)
-                .set_body(body)
-                .set_sound("default")
-                .set_badge(1);
-
-            // Add custom data if provided
-            if let Some(ref custom_data) = data {
-                if let Some(obj) = custom_data.as_object() {
-                    for (key, value) in obj {
-                        if let Some(s) = value.as_str() {
-                            builder = builder.set_content_available();
-                        }
-                    }
-                }
-            }
-
-            let options = NotificationOptions {
-                apns_topic: Some("com.3dar.BTCWidget"),
-                ..Default::default()
-            };
-
-            let payload = builder.build(token, options);
+        let auth_error: fn(&a2::Error) -> bool = if self.refresh_on_auth_error {
+            is_auth_error
+        } else {
+            |_| false
+        };
+        let data = data.as_ref();
+        let results = send_all_with_refresh(
+            &tokens,
+            |registered| async move {
+                // Read per send so a resend after a refresh uses the new clients
+                let endpoints = connection.endpoints.read().await.clone();
+                let payload = build_payload(&registered.token, title, body, data, options);
+                let payload = &payload;
+                let endpoints = &endpoints;
+                send_to_environment(
+                    registered.environment,
+                    self.retry_other_environment,
+                    |environment| {
+                        send_with_retry(
+                            move || endpoints.client(environment).send(payload.clone()),
+                            is_retryable,
+                            RETRY_BACKOFF,
+                        )
+                    },
+                    is_bad_device_token,
+                )
+                .await
+            },
+            auth_error,
+            || connection.refresh(),
+        )
+        .await;
 
-            match self.client.send(payload).await {
-                Ok(response) => {
+        for (registered, result) in tokens.iter().zip(results) {
+            METRICS.record_notification(result.is_ok());
+            match result {
+                Ok((response, environment)) => {
+                    if environment != registered.environment {
+                        tracing::info!(
+                            "📱 Device token belongs to {:?}, not {:?}",
+                            environment,
+                            registered.environment
+                        );
+                        self.device_tokens
+                            .write()
+                            .await
+                            .set_environment(&registered.token, environment);
+                    }
                     tracing::info!("✅ Notification sent: {:?}", response);
                 }
                 Err(e) => {
@@ -124,34 +627,450 @@ impl ApnsClient {
     }
 
     /// Send buy order filled notification
-    pub async fn notify_buy_filled(&self, price: f64, quantity: f64) {
-        let usd_value = price * quantity;
-        let title = "🟢 BUY Order Filled";
-        let body = format!(
-            "Bought {:.5} BTC @ ${:.0} (${:.0})",
-            quantity, price, usd_value
-        );
+    pub async fn notify_buy_filled(
+        &self,
+        asset: &str,
+        price: f64,
+        quantity: f64,
+        open_orders: Option<u32>,
+        client_tag: Option<&str>,
+    ) {
+        let (title, body) = self.templates.buy_filled(asset, price, quantity);
+        let title = fill_title(&title, client_tag);
 
-        if let Err(e) = self.send_notification(&title, &body, None).await {
+        let options = PushOptions::fill(open_orders);
+        if let Err(e) = self.send_notification(&title, &body, None, &options).await {
             tracing::error!("Failed to send buy notification: {:?}", e);
         }
     }
 
     /// Send sell order filled notification with profit
-    pub async fn notify_sell_filled(&self, price: f64, quantity: f64, profit: Option<f64>) {
-        let usd_value = price * quantity;
-        let title = "🔴 SELL Order Filled";
-        let body = if let Some(p) = profit {
-            format!(
-                "Sold {:.5} BTC @ ${:.0} (${:.0}) +${:.2} profit!",
-                quantity, price, usd_value, p
-            )
-        } else {
-            format!("Sold {:.5} BTC @ ${:.0} (${:.0})", quantity, price, usd_value)
-        };
+    pub async fn notify_sell_filled(
+        &self,
+        asset: &str,
+        price: f64,
+        quantity: f64,
+        profit: Option<f64>,
+        open_orders: Option<u32>,
+        client_tag: Option<&str>,
+    ) {
+        let (title, body) = self.templates.sell_filled(asset, price, quantity, profit);
+        let title = fill_title(&title, client_tag);
 
-        if let Err(e) = self.send_notification(&title, &body, None).await {
+        let options = PushOptions::fill(open_orders);
+        if let Err(e) = self.send_notification(&title, &body, None, &options).await {
             tracing::error!("Failed to send sell notification: {:?}", e);
         }
     }
+
+    /// Push fills collected by a `FillBatcher`: a lone fill as usual, several
+    /// as one summary
+    ///
+    /// The summary carries the strategy tag only when every fill shares it.
+    /// `open_orders` becomes the badge.
+    pub async fn notify_fills(&self, fills: &[BatchedFill], open_orders: Option<u32>) {
+        let [first, rest @ ..] = fills else {
+            return;
+        };
+        if rest.is_empty() {
+            let client_tag = first.client_tag.as_deref();
+            let asset = &first.asset;
+            match first.notification {
+                FillNotification::Buy { price, quantity } => {
+                    self.notify_buy_filled(asset, price, quantity, open_orders, client_tag)
+                        .await
+                }
+                FillNotification::Sell { price, quantity } => {
+                    let profit = first.profit;
+                    self.notify_sell_filled(asset, price, quantity, profit, open_orders, client_tag)
+                        .await
+                }
+            }
+            return;
+        }
+
+        let (buys, sells, net_profit) = batch_totals(fills);
+        let (title, body) = self.templates.batch_filled(buys, sells, net_profit);
+        let shared_tag = first
+            .client_tag
+            .as_deref()
+            .filter(|tag| rest.iter().all(|f| f.client_tag.as_deref() == Some(*tag)));
+        let title = fill_title(&title, shared_tag);
+
+        let options = PushOptions::fill(open_orders);
+        if let Err(e) = self.send_notification(&title, &body, None, &options).await {
+            tracing::error!("Failed to send batched fill notification: {:?}", e);
+        }
+    }
+
+    /// Alert that the daily-loss kill switch has halted new orders
+    pub async fn notify_kill_switch(&self, net_profit: f64, limit_usd: f64) {
+        let body = format!(
+            "Today's realized loss of ${:.2} passed the ${:.2} daily limit. \
+             New orders are blocked until 00:00 UTC.",
+            -net_profit, limit_usd
+        );
+
+        let options = PushOptions {
+            priority: PushPriority::High,
+            ..PushOptions::default()
+        };
+        if let Err(e) = self.send_notification("🛑 Trading halted", &body, None, &options).await {
+            tracing::error!("Failed to send kill switch notification: {:?}", e);
+        }
+    }
+}
+
+/// Fill title, labelled with the order's strategy tag when it has one
+fn fill_title(title: &str, client_tag: Option<&str>) -> String {
+    match client_tag {
+        Some(tag) => format!("{} [{}]", title, tag),
+        None => title.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_priority_and_collapse_id_reach_apns_options() {
+        let options = PushOptions {
+            priority: PushPriority::Normal,
+            collapse_id: Some("BTCUSDT".to_string()),
+            badge: None,
+        };
+
+        let apns = apns_options(&options);
+        assert!(matches!(apns.apns_priority, Some(Priority::Normal)));
+        assert_eq!(apns.apns_collapse_id.map(|id| id.value), Some("BTCUSDT"));
+        assert_eq!(apns.apns_topic, Some(APNS_TOPIC));
+    }
+
+    #[test]
+    fn test_fills_are_high_priority_and_never_collapse() {
+        let options = PushOptions::fill(None);
+
+        let apns = apns_options(&options);
+        assert!(matches!(apns.apns_priority, Some(Priority::High)));
+        assert!(apns.apns_collapse_id.is_none());
+    }
+
+    /// Stand-in for APNs failures without a real a2 client
+    #[derive(Debug, PartialEq)]
+    enum FakeError {
+        ServiceUnavailable,
+        BadDeviceToken,
+        ExpiredProviderToken,
+    }
+
+    fn fake_retryable(error: &FakeError) -> bool {
+        *error == FakeError::ServiceUnavailable
+    }
+
+    fn fake_bad_token(error: &FakeError) -> bool {
+        *error == FakeError::BadDeviceToken
+    }
+
+    fn fake_auth(error: &FakeError) -> bool {
+        *error == FakeError::ExpiredProviderToken
+    }
+
+    #[tokio::test]
+    async fn test_flaky_sender_succeeds_on_second_attempt() {
+        let attempts = AtomicU32::new(0);
+        let attempts = &attempts;
+
+        let result = send_with_retry(
+            move || async move {
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(FakeError::ServiceUnavailable)
+                } else {
+                    Ok("sent")
+                }
+            },
+            fake_retryable,
+            Duration::from_millis(1),
+        )
+        .await;
+
+        assert_eq!(result, Ok("sent"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_bad_device_token_is_not_retried() {
+        let attempts = AtomicU32::new(0);
+        let attempts = &attempts;
+
+        let result: Result<(), _> = send_with_retry(
+            move || async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(FakeError::BadDeviceToken)
+            },
+            fake_retryable,
+            Duration::from_millis(1),
+        )
+        .await;
+
+        assert_eq!(result, Err(FakeError::BadDeviceToken));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_stop_after_max_attempts() {
+        let attempts = AtomicU32::new(0);
+        let attempts = &attempts;
+
+        let result: Result<(), _> = send_with_retry(
+            move || async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(FakeError::ServiceUnavailable)
+            },
+            fake_retryable,
+            Duration::from_millis(1),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), SEND_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn test_auth_error_refreshes_once_and_resends() {
+        let refreshed = AtomicU32::new(0);
+        let sends = AtomicU32::new(0);
+        let (refreshed, sends) = (&refreshed, &sends);
+
+        let results = send_all_with_refresh(
+            &["phone", "tablet"],
+            |token| async move {
+                sends.fetch_add(1, Ordering::SeqCst);
+                if refreshed.load(Ordering::SeqCst) == 0 {
+                    Err(FakeError::ExpiredProviderToken)
+                } else {
+                    Ok(token)
+                }
+            },
+            fake_auth,
+            || async move {
+                refreshed.fetch_add(1, Ordering::SeqCst);
+                true
+            },
+        )
+        .await;
+
+        assert_eq!(results, vec![Ok("phone"), Ok("tablet")]);
+        assert_eq!(refreshed.load(Ordering::SeqCst), 1);
+        assert_eq!(sends.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_do_not_refresh() {
+        let refreshed = AtomicU32::new(0);
+        let refreshed = &refreshed;
+
+        let results: Vec<Result<(), _>> = send_all_with_refresh(
+            &["phone"],
+            |_| async { Err(FakeError::BadDeviceToken) },
+            fake_auth,
+            || async move {
+                refreshed.fetch_add(1, Ordering::SeqCst);
+                true
+            },
+        )
+        .await;
+
+        assert_eq!(results, vec![Err(FakeError::BadDeviceToken)]);
+        assert_eq!(refreshed.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_keeps_auth_error() {
+        let sends = AtomicU32::new(0);
+        let sends = &sends;
+
+        let results: Vec<Result<(), _>> = send_all_with_refresh(
+            &["phone"],
+            |_| async move {
+                sends.fetch_add(1, Ordering::SeqCst);
+                Err(FakeError::ExpiredProviderToken)
+            },
+            fake_auth,
+            || async { false },
+        )
+        .await;
+
+        assert_eq!(results, vec![Err(FakeError::ExpiredProviderToken)]);
+        assert_eq!(sends.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_badge_flows_into_payload() {
+        let options = PushOptions::fill(Some(3));
+
+        let payload = build_payload("token", "Filled", "Bought", None, &options);
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["aps"]["badge"], 3);
+    }
+
+    #[test]
+    fn test_custom_data_is_added_beside_aps() {
+        let data = serde_json::json!({"order_id": "42"});
+        let options = PushOptions::default();
+
+        let payload = build_payload("token", "Filled", "Bought", Some(&data), &options);
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["order_id"], "42");
+        assert_eq!(json["aps"]["content-available"], 1);
+    }
+
+    #[test]
+    fn test_no_badge_leaves_it_unset() {
+        let options = PushOptions::default();
+
+        let payload = build_payload("token", "Test", "Hello", None, &options);
+        let json = serde_json::to_value(&payload).unwrap();
+        assert!(json["aps"]["badge"].is_null());
+    }
+
+    #[test]
+    fn test_oversized_collapse_id_is_dropped() {
+        let options = PushOptions {
+            collapse_id: Some("x".repeat(65)),
+            ..Default::default()
+        };
+
+        assert!(apns_options(&options).apns_collapse_id.is_none());
+    }
+
+    const SANDBOX: ApnsEnvironment = ApnsEnvironment::Sandbox;
+    const PRODUCTION: ApnsEnvironment = ApnsEnvironment::Production;
+
+    fn token_values(tokens: &DeviceTokens) -> Vec<String> {
+        tokens.tokens().into_iter().map(|registered| registered.token).collect()
+    }
+
+    #[test]
+    fn test_reregistering_device_replaces_its_token() {
+        let mut tokens = DeviceTokens::default();
+
+        assert_eq!(tokens.register("phone", "old".to_string(), SANDBOX), None);
+        assert_eq!(tokens.register("tablet", "tablet-token".to_string(), SANDBOX), None);
+        // Same token again is not a replacement
+        assert_eq!(tokens.register("phone", "old".to_string(), SANDBOX), None);
+
+        // Reinstall: new token for the same device
+        assert_eq!(
+            tokens.register("phone", "new".to_string(), SANDBOX),
+            Some("old".to_string())
+        );
+        assert_eq!(token_values(&tokens), vec!["new", "tablet-token"]);
+    }
+
+    #[test]
+    fn test_token_moves_between_devices_instead_of_duplicating() {
+        let mut tokens = DeviceTokens::default();
+        tokens.register("old-device-id", "token".to_string(), SANDBOX);
+        tokens.register("new-device-id", "token".to_string(), SANDBOX);
+        assert_eq!(token_values(&tokens), vec!["token"]);
+
+        tokens.unregister("token");
+        assert!(tokens.tokens().is_empty());
+    }
+
+    #[test]
+    fn test_environment_is_parsed_from_register_requests() {
+        let environment: ApnsEnvironment = serde_json::from_str(r#""sandbox""#).unwrap();
+        assert_eq!(environment, SANDBOX);
+        let environment: ApnsEnvironment = serde_json::from_str(r#""production""#).unwrap();
+        assert_eq!(environment, PRODUCTION);
+        assert!(serde_json::from_str::<ApnsEnvironment>(r#""staging""#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tokens_are_dispatched_to_their_environment() {
+        let mut tokens = DeviceTokens::default();
+        tokens.register("app-store-phone", "prod-token".to_string(), PRODUCTION);
+        tokens.register("testflight-phone", "sandbox-token".to_string(), SANDBOX);
+
+        let sent = std::sync::Mutex::new(Vec::new());
+        let sent = &sent;
+        for registered in tokens.tokens() {
+            let token = registered.token.as_str();
+            let result = send_to_environment(
+                registered.environment,
+                true,
+                move |environment| async move {
+                    sent.lock().unwrap().push((token.to_string(), environment));
+                    Ok::<_, FakeError>(())
+                },
+                fake_bad_token,
+            )
+            .await;
+            assert_eq!(result, Ok(((), registered.environment)));
+        }
+
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                ("prod-token".to_string(), PRODUCTION),
+                ("sandbox-token".to_string(), SANDBOX),
+            ]
+        );
+    }
+
+    /// Endpoint that only accepts production tokens, recording where each send went
+    fn production_only<'a>(
+        sent: &'a std::sync::Mutex<Vec<ApnsEnvironment>>,
+    ) -> impl FnMut(ApnsEnvironment) -> std::future::Ready<Result<(), FakeError>> + 'a {
+        move |environment| {
+            sent.lock().unwrap().push(environment);
+            std::future::ready(match environment {
+                ApnsEnvironment::Production => Ok(()),
+                ApnsEnvironment::Sandbox => Err(FakeError::BadDeviceToken),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bad_device_token_retries_the_other_environment() {
+        let sent = std::sync::Mutex::new(Vec::new());
+
+        let result =
+            send_to_environment(SANDBOX, true, production_only(&sent), fake_bad_token).await;
+
+        assert_eq!(result, Ok(((), PRODUCTION)));
+        assert_eq!(*sent.lock().unwrap(), vec![SANDBOX, PRODUCTION]);
+    }
+
+    #[tokio::test]
+    async fn test_other_environment_is_not_tried_when_disabled() {
+        let sent = std::sync::Mutex::new(Vec::new());
+
+        let result =
+            send_to_environment(SANDBOX, false, production_only(&sent), fake_bad_token).await;
+
+        assert_eq!(result, Err(FakeError::BadDeviceToken));
+        assert_eq!(*sent.lock().unwrap(), vec![SANDBOX]);
+    }
+
+    #[test]
+    fn test_environment_follows_the_endpoint_that_accepted_the_token() {
+        let mut tokens = DeviceTokens::default();
+        tokens.register("phone", "token".to_string(), SANDBOX);
+
+        tokens.set_environment("token", PRODUCTION);
+        assert_eq!(tokens.tokens()[0].environment, PRODUCTION);
+    }
+
+    #[test]
+    fn test_fill_title_shows_client_tag() {
+        assert_eq!(fill_title("🟢 BUY Order Filled", None), "🟢 BUY Order Filled");
+        assert_eq!(
+            fill_title("🟢 BUY Order Filled", Some("grid-A")),
+            "🟢 BUY Order Filled [grid-A]"
+        );
+    }
 }
@@ -0,0 +1,213 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use super::user_stream::FillNotification;
+
+/// A fill waiting to be pushed, with what its notification needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchedFill {
+    pub notification: FillNotification,
+    /// Base asset of the filled symbol (e.g. "BTC")
+    pub asset: String,
+    /// Matched net profit, for sells
+    pub profit: Option<f64>,
+    pub client_tag: Option<String>,
+}
+
+/// Collects fills arriving within a window so a burst is pushed as one
+/// summary instead of one notification per fill
+///
+/// The first fill opens the window; everything queued until it closes goes
+/// out together. A zero window disables batching.
+pub struct FillBatcher {
+    window: Duration,
+    pending: Mutex<Vec<BatchedFill>>,
+    /// Closes the open window early
+    close_now: Notify,
+    /// Task that sends the open window's batch
+    sender: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FillBatcher {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Mutex::new(Vec::new()),
+            close_now: Notify::new(),
+            sender: Mutex::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    /// Queue `fill`, and when it opens a new window hand the whole batch to
+    /// `send` once the window closes
+    pub fn push<F, Fut>(self: &Arc<Self>, fill: BatchedFill, send: F)
+    where
+        F: FnOnce(Vec<BatchedFill>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let opened = {
+            let mut pending = self.pending.lock().unwrap();
+            pending.push(fill);
+            pending.len() == 1
+        };
+        if !opened {
+            return;
+        }
+
+        let batcher = self.clone();
+        let sender = tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep(batcher.window) => {}
+                _ = batcher.close_now.notified() => {}
+            }
+            let fills = std::mem::take(&mut *batcher.pending.lock().unwrap());
+            send(fills).await;
+        });
+        *self.sender.lock().unwrap() = Some(sender);
+    }
+
+    /// Send the open batch now rather than when its window closes, returning
+    /// once it's out, so fills queued at shutdown aren't lost
+    pub async fn flush(&self) {
+        let Some(sender) = self.sender.lock().unwrap().take() else {
+            return;
+        };
+        if sender.is_finished() {
+            return;
+        }
+        self.close_now.notify_one();
+        if let Err(e) = sender.await {
+            tracing::error!("Failed to flush batched fills: {}", e);
+        }
+    }
+}
+
+/// Buys, sells, and the summed profit of sells whose profit is known
+pub fn batch_totals(fills: &[BatchedFill]) -> (usize, usize, Option<f64>) {
+    let buys = fills
+        .iter()
+        .filter(|f| matches!(f.notification, FillNotification::Buy { .. }))
+        .count();
+    let net_profit = fills
+        .iter()
+        .filter_map(|f| f.profit)
+        .fold(None, |total: Option<f64>, profit| Some(total.unwrap_or(0.0) + profit));
+    (buys, fills.len() - buys, net_profit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationTemplates;
+
+    fn sell(profit: f64) -> BatchedFill {
+        BatchedFill {
+            notification: FillNotification::Sell {
+                price: 51000.0,
+                quantity: 0.001,
+            },
+            asset: "BTC".to_string(),
+            profit: Some(profit),
+            client_tag: None,
+        }
+    }
+
+    fn buy() -> BatchedFill {
+        BatchedFill {
+            notification: FillNotification::Buy {
+                price: 50000.0,
+                quantity: 0.001,
+            },
+            asset: "BTC".to_string(),
+            profit: None,
+            client_tag: None,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fills_within_window_are_sent_as_one_summary() {
+        let batcher = Arc::new(FillBatcher::new(Duration::from_millis(100)));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+
+        for fill in [sell(5.0), sell(7.4), buy()] {
+            let sent = sent.clone();
+            batcher.push(fill, move |fills| async move {
+                sent.lock().unwrap().push(fills);
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let batches = sent.lock().unwrap().clone();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].len(), 3);
+
+        let (buys, sells, net_profit) = batch_totals(&batches[0]);
+        assert_eq!((buys, sells), (1, 2));
+        let (title, body) =
+            NotificationTemplates::default().batch_filled(buys, sells, net_profit);
+        assert_eq!(title, "✅ 3 Orders Filled");
+        assert_eq!(body, "3 orders filled (1 buy, 2 sells), +$12.40 net");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fill_after_window_starts_a_new_batch() {
+        let batcher = Arc::new(FillBatcher::new(Duration::from_millis(50)));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let push = |fill| {
+            let sent = sent.clone();
+            batcher.push(fill, move |fills: Vec<BatchedFill>| async move {
+                sent.lock().unwrap().push(fills.len());
+            });
+        };
+
+        push(buy());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        push(buy());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(*sent.lock().unwrap(), vec![1, 1]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flush_sends_the_open_batch_before_its_window_closes() {
+        let batcher = Arc::new(FillBatcher::new(Duration::from_secs(60)));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+
+        for fill in [buy(), sell(2.0)] {
+            let sent = sent.clone();
+            batcher.push(fill, move |fills: Vec<BatchedFill>| async move {
+                sent.lock().unwrap().push(fills.len());
+            });
+        }
+        let started = tokio::time::Instant::now();
+        batcher.flush().await;
+
+        assert_eq!(*sent.lock().unwrap(), vec![2]);
+        assert!(started.elapsed() < Duration::from_secs(60));
+
+        // Nothing open: returns straight away
+        batcher.flush().await;
+        assert_eq!(*sent.lock().unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn test_batch_without_known_profit_has_no_net() {
+        let (buys, sells, net_profit) = batch_totals(&[buy(), buy()]);
+        assert_eq!((buys, sells, net_profit), (2, 0, None));
+
+        let (_, body) = NotificationTemplates::default().batch_filled(buys, sells, net_profit);
+        assert_eq!(body, "2 orders filled (2 buys, 0 sells)");
+
+        let (_, _, net_profit) = batch_totals(&[sell(-3.1), buy()]);
+        let (_, body) = NotificationTemplates::default().batch_filled(1, 1, net_profit);
+        assert_eq!(body, "2 orders filled (1 buy, 1 sell), -$3.10 net");
+    }
+}
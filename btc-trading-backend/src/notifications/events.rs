@@ -0,0 +1,120 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::user_stream::FillNotification;
+
+/// Events buffered per subscriber before a slow one starts missing them
+const EVENT_BUFFER: usize = 100;
+
+/// Order activity detected by the order monitor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OrderEvent {
+    Fill {
+        symbol: String,
+        side: String,
+        price: f64,
+        quantity: f64,
+        client_tag: Option<String>,
+    },
+    /// An open order went away without a fill (cancelled or expired)
+    Cancel { symbol: String, order_id: i64 },
+}
+
+impl OrderEvent {
+    pub fn fill(symbol: &str, notification: &FillNotification, client_tag: Option<&str>) -> Self {
+        let (side, price, quantity) = match *notification {
+            FillNotification::Buy { price, quantity } => ("BUY", price, quantity),
+            FillNotification::Sell { price, quantity } => ("SELL", price, quantity),
+        };
+
+        OrderEvent::Fill {
+            symbol: symbol.to_string(),
+            side: side.to_string(),
+            price,
+            quantity,
+            client_tag: client_tag.map(str::to_string),
+        }
+    }
+
+    /// SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            OrderEvent::Fill { .. } => "fill",
+            OrderEvent::Cancel { .. } => "cancel",
+        }
+    }
+}
+
+/// Fans order events out to every live subscriber (e.g. `/order/events` streams)
+#[derive(Clone)]
+pub struct OrderEvents {
+    sender: broadcast::Sender<OrderEvent>,
+}
+
+impl Default for OrderEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    /// Send `event` to current subscribers; dropped if nobody is listening
+    pub fn publish(&self, event: OrderEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_event_serialization() {
+        let notification = FillNotification::Buy {
+            price: 50000.0,
+            quantity: 0.002,
+        };
+        let event = OrderEvent::fill("BTCUSDT", &notification, Some("grid-A"));
+
+        assert_eq!(event.name(), "fill");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "type": "fill",
+                "symbol": "BTCUSDT",
+                "side": "BUY",
+                "price": 50000.0,
+                "quantity": 0.002,
+                "client_tag": "grid-A"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let events = OrderEvents::new();
+        // Publishing with nobody listening is a no-op
+        events.publish(OrderEvent::Cancel {
+            symbol: "BTCUSDT".to_string(),
+            order_id: 1,
+        });
+
+        let mut receiver = events.subscribe();
+        let event = OrderEvent::Cancel {
+            symbol: "BTCUSDT".to_string(),
+            order_id: 2,
+        };
+        events.publish(event.clone());
+        assert_eq!(receiver.recv().await.unwrap(), event);
+    }
+}
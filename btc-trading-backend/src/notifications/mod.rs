@@ -1,5 +1,16 @@
 mod apns;
+mod batch;
+mod events;
 mod monitor;
+mod stream_health;
+mod take_profit;
+mod templates;
+mod user_stream;
 
-pub use apns::ApnsClient;
-pub use monitor::OrderMonitor;
+pub use apns::{ApnsClient, ApnsEnvironment, PushOptions};
+pub use batch::{BatchedFill, FillBatcher};
+pub use events::{OrderEvent, OrderEvents};
+pub use monitor::{simulated_trade, OrderMonitor};
+pub use user_stream::FillNotification;
+pub use stream_health::{StreamSnapshot, BOOK_STREAM, USER_STREAM};
+pub use templates::NotificationTemplates;
@@ -1,72 +1,718 @@
-use crate::binance::BinanceClient;
+use crate::binance::{decode_client_tag, BinanceClient, ExecutionReport, Trade, BREAKER};
 use crate::config::Config;
-use crate::notifications::ApnsClient;
-use std::collections::HashSet;
+use crate::notifications::{ApnsClient, BatchedFill, FillBatcher};
+use crate::trading::{
+    base_asset, day_net_profit, match_completed_pairs_within, order_fills, sell_order_profit,
+    sell_trade_profit, FILL_COOLDOWN, KILL_SWITCH,
+};
+use super::events::{OrderEvent, OrderEvents};
+use super::stream_health::USER_STREAM;
+use super::take_profit::{base_commission, place_take_profit, take_profit_target, SellTarget};
+use super::user_stream::{fill_notification, run_user_stream, FillNotification};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, RwLock};
+use tokio_util::sync::CancellationToken;
+
+/// Recent trades searched for the buys a filled sell closed
+const PROFIT_TRADE_LOOKBACK: u32 = 500;
+
+/// How far before midnight the daily loss check looks for the buys today's
+/// sells closed, when PROFIT_MATCH_WINDOW_HOURS doesn't limit it
+const DAILY_LOSS_BUY_LOOKBACK_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+/// Pause before reopening a user data stream that dropped
+const STREAM_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Monitor state persisted between restarts
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonitorState {
+    /// Last notified trade id per symbol (Binance trade ids are per market)
+    #[serde(default)]
+    pub last_trade_ids: HashMap<String, i64>,
+    /// Open order ids per symbol
+    #[serde(default)]
+    pub known_order_ids: HashMap<String, HashSet<i64>>,
+    /// Auto-sells waiting out a no-trade window or fill cooldown
+    #[serde(default)]
+    pub deferred_sells: Vec<DeferredSell>,
+}
+
+/// Take-profit SELL for a filled BUY, held back until it may go out
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeferredSell {
+    pub symbol: String,
+    pub fill_price: f64,
+    pub quantity: f64,
+    /// The BUY's fee in the base asset
+    pub commission: f64,
+    pub order_id: Option<i64>,
+    pub client_tag: Option<String>,
+}
+
+impl MonitorState {
+    /// Load state from `path`, or None if missing or unreadable
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                tracing::warn!("Ignoring corrupt monitor state {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Write state to `path` via a temp file so a crash can't truncate it
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)?;
+        std::fs::rename(tmp, path)
+    }
+}
+
+/// Trades newer than `last_trade_id`, oldest first
+fn unseen_trades(trades: &[Trade], last_trade_id: Option<i64>) -> Vec<&Trade> {
+    let mut unseen: Vec<&Trade> = trades
+        .iter()
+        .filter(|t| last_trade_id.is_none_or(|last| t.id > last))
+        .collect();
+    unseen.sort_by_key(|t| t.id);
+    unseen
+}
+
+/// Notification for a trade reported by `myTrades`
+fn trade_notification(trade: &Trade) -> FillNotification {
+    let (price, quantity) = (trade.price_f64(), trade.quantity_f64());
+    if trade.is_buyer {
+        FillNotification::Buy { price, quantity }
+    } else {
+        FillNotification::Sell { price, quantity }
+    }
+}
+
+/// Orders that left the open list without a fill among `trades`
+fn cancelled_orders(missing: &[i64], trades: &[Trade]) -> Vec<i64> {
+    missing
+        .iter()
+        .copied()
+        .filter(|id| !trades.iter().any(|t| t.order_id == *id))
+        .collect()
+}
+
+/// Synthetic trade for `/debug/simulate-fill`; ids are negative so they can
+/// never collide with real Binance trades
+pub fn simulated_trade(symbol: &str, is_buyer: bool, price: f64, quantity: f64) -> Trade {
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default();
+
+    Trade {
+        id: -1,
+        order_id: -1,
+        symbol: symbol.to_string(),
+        price: price.to_string(),
+        qty: quantity.to_string(),
+        quote_qty: (price * quantity).to_string(),
+        commission: "0".to_string(),
+        commission_asset: "USDT".to_string(),
+        time,
+        is_buyer,
+        is_maker: true,
+        client_tag: None,
+    }
+}
 
 pub struct OrderMonitor {
     config: Config,
     apns: Arc<ApnsClient>,
-    known_order_ids: Arc<RwLock<HashSet<i64>>>,
-    last_trade_id: Arc<RwLock<Option<i64>>>,
+    /// None when persistence is off
+    state_path: Option<PathBuf>,
+    known_order_ids: Arc<RwLock<HashMap<String, HashSet<i64>>>>,
+    last_trade_ids: Arc<RwLock<HashMap<String, i64>>>,
+    deferred_sells: RwLock<Vec<DeferredSell>>,
+    /// Wakes the deferred sell loop when a sell is deferred
+    deferred_added: Notify,
+    events: OrderEvents,
+    /// Coalesces fill pushes when NOTIFICATION_COALESCE_SECS is set
+    batcher: Arc<FillBatcher>,
 }
 
 impl OrderMonitor {
     pub fn new(config: Config, apns: Arc<ApnsClient>) -> Self {
+        let state_path = (!config.monitor_state_path.is_empty())
+            .then(|| PathBuf::from(&config.monitor_state_path));
+        let coalesce_window = Duration::from_secs(config.notification_coalesce_secs);
         Self {
             config,
             apns,
-            known_order_ids: Arc::new(RwLock::new(HashSet::new())),
-            last_trade_id: Arc::new(RwLock::new(None)),
+            state_path,
+            known_order_ids: Arc::new(RwLock::new(HashMap::new())),
+            last_trade_ids: Arc::new(RwLock::new(HashMap::new())),
+            deferred_sells: RwLock::new(Vec::new()),
+            deferred_added: Notify::new(),
+            events: OrderEvents::new(),
+            batcher: Arc::new(FillBatcher::new(coalesce_window)),
+        }
+    }
+
+    /// Handle for subscribing to the fills and cancels this monitor detects
+    pub fn events(&self) -> OrderEvents {
+        self.events.clone()
+    }
+
+    /// Start the order monitor, returning once `shutdown` is cancelled
+    ///
+    /// Prefers the real-time user data stream, reconnecting when an established
+    /// socket drops, and falls back to polling if one can't be established.
+    /// Either way, fills missed while down are replayed from persisted state,
+    /// and auto-sells deferred before a restart are picked up again.
+    pub async fn start(&self, shutdown: CancellationToken) {
+        self.check_daily_loss().await;
+        self.restore_state().await;
+
+        tokio::join!(self.watch_fills(shutdown.clone()), self.run_deferred_sells(&shutdown));
+        self.batcher.flush().await;
+    }
+
+    /// Watch for fills, over the user data stream or by polling, until
+    /// `shutdown` is cancelled
+    async fn watch_fills(&self, shutdown: CancellationToken) {
+        let client = BinanceClient::new(&self.config);
+        loop {
+            let connections = USER_STREAM.connections();
+            match run_user_stream(&client, self, &shutdown).await {
+                Ok(()) => return,
+                Err(e) if USER_STREAM.connections() > connections => {
+                    tracing::warn!("User data stream dropped ({}), reconnecting", e);
+                    tokio::select! {
+                        _ = shutdown.cancelled() => return,
+                        _ = tokio::time::sleep(STREAM_RECONNECT_DELAY) => {}
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("User data stream unavailable ({}), falling back to polling", e);
+                    break;
+                }
+            }
         }
+
+        self.poll(shutdown).await;
     }
 
-    /// Start the order monitoring loop
-    pub async fn start(&self) {
+    /// Poll open orders and trades for fills until `shutdown` is cancelled
+    async fn poll(&self, shutdown: CancellationToken) {
         tracing::info!("🔄 Starting order monitor (checking every 30 seconds)");
 
-        // Initialize known orders
-        self.initialize_known_orders().await;
+        let client = BinanceClient::new(&self.config);
+        self.catch_up(&client).await;
+
+        loop {
+            if BREAKER.get(false).allow_request() {
+                self.check_for_fills().await;
+                self.check_daily_loss().await;
+            } else {
+                tracing::debug!("Order monitor paused: circuit breaker open");
+            }
+
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_secs(30)) => {}
+            }
+        }
+
+        tracing::info!("Order monitor stopped");
+    }
+
+    /// Restore the last notified trade ids and deferred auto-sells persisted
+    /// before a restart
+    async fn restore_state(&self) {
+        let persisted = self.state_path.as_deref().and_then(MonitorState::load).unwrap_or_default();
+        *self.last_trade_ids.write().await = persisted.last_trade_ids;
+        *self.deferred_sells.write().await = persisted.deferred_sells;
+    }
 
+    /// Place deferred auto-sells as their waits end, until `shutdown` is cancelled
+    ///
+    /// Sells still waiting at shutdown stay in the persisted state.
+    async fn run_deferred_sells(&self, shutdown: &CancellationToken) {
         loop {
-            self.check_for_fills().await;
-            tokio::time::sleep(Duration::from_secs(30)).await;
+            let next = self.place_due_sells().await;
+            let wait = async {
+                match next {
+                    Some(wait) => tokio::time::sleep(wait).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = self.deferred_added.notified() => {}
+                _ = wait => {}
+            }
+        }
+    }
+
+    /// Place each deferred sell whose wait has ended, returning the shortest
+    /// wait left among the rest
+    ///
+    /// Another fill during a sell's wait restarts its symbol's cooldown.
+    async fn place_due_sells(&self) -> Option<Duration> {
+        let deferred = self.deferred_sells.read().await.clone();
+        let mut next: Option<Duration> = None;
+        let mut placed = false;
+        for sell in deferred {
+            if let Some(wait) = auto_sell_wait(&self.config, &sell.symbol) {
+                next = Some(next.map_or(wait, |next| next.min(wait)));
+                continue;
+            }
+            place_auto_sell(
+                &self.config,
+                &sell.symbol,
+                sell.fill_price,
+                sell.quantity,
+                sell.commission,
+                sell.order_id,
+                sell.client_tag.as_deref(),
+            )
+            .await;
+            let mut deferred_sells = self.deferred_sells.write().await;
+            if let Some(index) = deferred_sells.iter().position(|s| *s == sell) {
+                deferred_sells.remove(index);
+            }
+            placed = true;
+        }
+        if placed {
+            self.persist().await;
+        }
+        next
+    }
+
+    /// Seed open orders and notify fills missed while nothing was listening
+    ///
+    /// Runs when polling starts and each time the user data stream connects.
+    /// Symbols without a known trade id start from their current open orders
+    /// and latest trade so nothing historical is notified.
+    pub(super) async fn catch_up(&self, client: &BinanceClient) {
+        for symbol in &self.config.symbols {
+            // Get current open orders
+            if let Ok(orders) = client.get_open_orders(symbol).await {
+                let ids: HashSet<i64> = orders.iter().map(|o| o.order_id).collect();
+                tracing::info!("📋 {}: initialized with {} known orders", symbol, ids.len());
+                self.known_order_ids.write().await.insert(symbol.clone(), ids);
+            }
+
+            let restored = self.last_trade_ids.read().await.get(symbol).copied();
+            match restored {
+                Some(last_trade_id) => {
+                    tracing::info!("📋 {}: catching up after trade ID {}", symbol, last_trade_id);
+
+                    // Replay fills that happened while we weren't listening
+                    if let Ok(trades) = client.get_trades(symbol, 50).await {
+                        self.notify_new_trades(symbol, &trades).await;
+                    }
+                }
+                None => {
+                    if let Ok(trades) = client.get_trades(symbol, 1).await {
+                        if let Some(trade) = trades.iter().max_by_key(|t| t.id) {
+                            self.advance_last_trade_id(symbol, trade.id).await;
+                            tracing::info!("📋 {}: last trade ID {}", symbol, trade.id);
+                        }
+                    }
+                }
+            }
         }
+
+        self.persist().await;
     }
 
-    /// Initialize with current open orders so we don't notify on startup
-    async fn initialize_known_orders(&self) {
+    /// Notify each trade newer than the symbol's last seen one, then advance it
+    async fn notify_new_trades(&self, symbol: &str, trades: &[Trade]) {
+        let last_id = self.last_trade_ids.read().await.get(symbol).copied();
+        let mut unseen: Vec<Trade> =
+            unseen_trades(trades, last_id).into_iter().cloned().collect();
+        if unseen.is_empty() {
+            return;
+        }
+
         let client = BinanceClient::new(&self.config);
+        if let Err(e) = client.tag_trades(symbol, &mut unseen).await {
+            tracing::warn!("Failed to look up client tags for {} fills: {}", symbol, e);
+        }
 
-        // Get current open orders
-        if let Ok(orders) = client.get_open_orders().await {
-            let mut known = self.known_order_ids.write().await;
-            for order in orders {
-                known.insert(order.order_id);
+        // Each sell trade is notified on its own, so its profit covers just that
+        // trade; one lookup serves the whole batch
+        let sold = unseen.iter().any(|t| !t.is_buyer);
+        let recent = if sold { self.recent_trades(&client, symbol).await } else { None };
+
+        let mut buy_orders: Vec<(i64, Option<String>)> = Vec::new();
+        for trade in &unseen {
+            let client_tag = trade.client_tag.as_deref();
+            let notification = trade_notification(trade);
+            let profit = match &recent {
+                Some(recent) if !trade.is_buyer => sell_trade_profit(
+                    recent,
+                    trade.id,
+                    self.config.quantity_match_tolerance,
+                    self.profit_window_ms(),
+                ),
+                _ => None,
+            };
+            self.handle_fill(symbol, notification, profit, client_tag).await;
+            self.advance_last_trade_id(symbol, trade.id).await;
+            if trade.is_buyer && !buy_orders.iter().any(|(id, _)| *id == trade.order_id) {
+                buy_orders.push((trade.order_id, trade.client_tag.clone()));
+            }
+        }
+
+        if sold {
+            self.check_daily_loss().await;
+        }
+        for (order_id, client_tag) in buy_orders {
+            self.auto_sell_order(&client, symbol, order_id, client_tag.as_deref(), false)
+                .await;
+        }
+    }
+
+    /// Notify a fill (or publish a cancel) reported by the user data stream
+    ///
+    /// Every trade the stream reports counts as seen, so a later catch-up
+    /// doesn't notify it again; one the catch-up already notified is skipped.
+    pub(super) async fn handle_execution_report(&self, report: &ExecutionReport) {
+        if !self.config.symbols.contains(&report.symbol) {
+            return;
+        }
+
+        if matches!(report.order_status.as_str(), "CANCELED" | "EXPIRED") {
+            self.events.publish(OrderEvent::Cancel {
+                symbol: report.symbol.clone(),
+                order_id: report.order_id,
+            });
+            return;
+        }
+
+        if report.execution_type != "TRADE" {
+            return;
+        }
+        let last_id = self.last_trade_ids.read().await.get(&report.symbol).copied();
+        if last_id.is_some_and(|last| report.trade_id <= last) {
+            return;
+        }
+
+        if let Some(notification) = fill_notification(report) {
+            // Only FILLED reports get this far, so a sell's profit covers the whole order
+            let client_tag = decode_client_tag(&report.client_order_id);
+            let client = BinanceClient::new(&self.config);
+            let symbol = &report.symbol;
+            let profit = match report.is_buy() {
+                true => None,
+                false => self.sell_order_profit(&client, symbol, report.order_id).await,
+            };
+            self.handle_fill(symbol, notification, profit, client_tag.as_deref()).await;
+            if report.is_buy() {
+                self.auto_sell_order(&client, symbol, report.order_id, client_tag.as_deref(), true)
+                    .await;
+            } else {
+                self.check_daily_loss().await;
+            }
+        }
+        self.advance_last_trade_id(&report.symbol, report.trade_id).await;
+        self.persist().await;
+    }
+
+    /// Run a synthetic trade through the fill path, returning what was sent
+    ///
+    /// The last seen trade id is left alone so a simulated fill can't hide a
+    /// real one. A simulated BUY only places its auto-sell, a real SELL on
+    /// Binance, when `auto_sell` asks for it.
+    pub async fn simulate_fill(&self, trade: &Trade, auto_sell: bool) -> FillNotification {
+        let notification = trade_notification(trade);
+        tracing::info!("🧪 Simulating {} fill: {:?}", trade.symbol, notification);
+        let client_tag = trade.client_tag.as_deref();
+        // Not a real order, so there's nothing on Binance to match profit against
+        self.handle_fill(&trade.symbol, notification.clone(), None, client_tag).await;
+        if let (true, FillNotification::Buy { price, quantity }) = (auto_sell, &notification) {
+            self.auto_sell(&trade.symbol, *price, *quantity, 0.0, None, client_tag).await;
+        }
+        notification
+    }
+
+    /// Publish and push the fill notification, with a SELL's matched `profit`
+    async fn handle_fill(
+        &self,
+        symbol: &str,
+        notification: FillNotification,
+        profit: Option<f64>,
+        client_tag: Option<&str>,
+    ) {
+        FILL_COOLDOWN.record(symbol, Instant::now());
+        self.events.publish(OrderEvent::fill(symbol, &notification, client_tag));
+
+        let fill = BatchedFill {
+            notification,
+            asset: base_asset(symbol).unwrap_or(symbol).to_string(),
+            profit,
+            client_tag: client_tag.map(str::to_string),
+        };
+        self.notify_fill(fill).await;
+    }
+
+    /// Push a fill now, or queue it into the current batch when coalescing
+    ///
+    /// The badge's open-order count is looked up once per push, as it goes out.
+    async fn notify_fill(&self, fill: BatchedFill) {
+        if !self.batcher.is_enabled() {
+            let open_orders = fill_badge(&self.apns, &self.config).await;
+            self.apns.notify_fills(&[fill], open_orders).await;
+            return;
+        }
+
+        let apns = self.apns.clone();
+        let config = self.config.clone();
+        self.batcher.push(fill, move |fills| async move {
+            let open_orders = fill_badge(&apns, &config).await;
+            apns.notify_fills(&fills, open_orders).await;
+        });
+    }
+
+    /// Net profit of a filled sell order, matched against buys inside the
+    /// configured attribution window; None if none match or trades can't be fetched
+    async fn sell_order_profit(
+        &self,
+        client: &BinanceClient,
+        symbol: &str,
+        order_id: i64,
+    ) -> Option<f64> {
+        let trades = self.recent_trades(client, symbol).await?;
+        let tolerance = self.config.quantity_match_tolerance;
+        sell_order_profit(&trades, order_id, tolerance, self.profit_window_ms())
+    }
+
+    /// Recent trades to match sell profit against, or None if they can't be fetched
+    async fn recent_trades(&self, client: &BinanceClient, symbol: &str) -> Option<Vec<Trade>> {
+        match client.get_trades(symbol, PROFIT_TRADE_LOOKBACK).await {
+            Ok(trades) => Some(trades),
+            Err(e) => {
+                tracing::warn!("Failed to get {} trades for sell profit: {}", symbol, e);
+                None
+            }
+        }
+    }
+
+    /// How far back a sell's profit looks for its buy; None means no limit
+    fn profit_window_ms(&self) -> Option<i64> {
+        let hours = self.config.profit_match_window_hours;
+        (hours > 0).then(|| hours as i64 * 60 * 60 * 1000)
+    }
+
+    /// Feed each environment's realized net profit for the UTC day to its kill
+    /// switch, alerting when one trips; production only when it's configured
+    async fn check_daily_loss(&self) {
+        let limit_usd = self.config.daily_loss_limit_usd;
+        if limit_usd <= 0.0 {
+            return;
+        }
+
+        for use_production in [false, true] {
+            if let Ok(client) = BinanceClient::for_environment(&self.config, use_production) {
+                self.check_environment_loss(&client, use_production, limit_usd).await;
+            }
+        }
+    }
+
+    /// Match every trade since midnight UTC, across all symbols, against the
+    /// buys before it; a failed trade lookup leaves the switch as is
+    async fn check_environment_loss(
+        &self,
+        client: &BinanceClient,
+        use_production: bool,
+        limit_usd: f64,
+    ) {
+        let environment = if use_production { "production" } else { "testnet" };
+        let today = chrono::Utc::now().date_naive();
+        let midnight = today.and_time(chrono::NaiveTime::MIN).and_utc().timestamp_millis();
+        let window_ms = self.profit_window_ms();
+        // Today's first sells may close buys from before midnight
+        let since = midnight - window_ms.unwrap_or(DAILY_LOSS_BUY_LOOKBACK_MS);
+
+        let tolerance = self.config.quantity_match_tolerance;
+        let mut net_profit = 0.0;
+        for symbol in &self.config.symbols {
+            match client.get_trades_since(symbol, Some(since)).await {
+                Ok(trades) => {
+                    let pairs = match_completed_pairs_within(&trades, tolerance, window_ms);
+                    net_profit += day_net_profit(&pairs, today);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to get {} {} trades for daily loss check: {}",
+                        environment,
+                        symbol,
+                        e
+                    );
+                    return;
+                }
+            }
+        }
+
+        if KILL_SWITCH.get(use_production).record(today, net_profit, limit_usd) {
+            tracing::error!(
+                "🛑 Daily {} loss ${:.2} passed the ${:.2} limit, halting orders until 00:00 UTC",
+                environment,
+                -net_profit,
+                limit_usd
+            );
+            self.apns.notify_kill_switch(net_profit, limit_usd).await;
+        }
+    }
+
+    /// Take profit on BUY `order_id` once it has fully filled, sized and priced
+    /// from all of its trades
+    ///
+    /// A BUY filling across several trades gets one SELL, and one grid lookup,
+    /// when its last trade lands; `filled` skips the status check when the
+    /// caller already knows. One cancelled after a partial fill is left alone.
+    async fn auto_sell_order(
+        &self,
+        client: &BinanceClient,
+        symbol: &str,
+        order_id: i64,
+        client_tag: Option<&str>,
+        filled: bool,
+    ) {
+        if !self.config.auto_sell_enabled {
+            return;
+        }
+
+        if !filled {
+            match client.get_order(symbol, order_id).await {
+                Ok(order) if order.status == "FILLED" => {}
+                Ok(order) => {
+                    tracing::debug!("Auto-sell: BUY {} is {}, not yet", order_id, order.status);
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!("Auto-sell: status of BUY {} unavailable: {}", order_id, e);
+                    return;
+                }
             }
-            tracing::info!("📋 Initialized with {} known orders", known.len());
         }
 
-        // Get last trade ID
-        if let Ok(trades) = client.get_trades(1).await {
-            if let Some(trade) = trades.first() {
-                *self.last_trade_id.write().await = Some(trade.id);
-                tracing::info!("📋 Last trade ID: {}", trade.id);
+        let trades = match client.get_order_trades(symbol, order_id).await {
+            Ok(trades) => trades,
+            Err(e) => {
+                tracing::warn!("Auto-sell: fills of BUY {} unavailable: {}", order_id, e);
+                return;
             }
+        };
+        let fills = order_fills(&trades);
+        let Some(price) = fills.avg_price else {
+            return;
+        };
+        let commission = base_commission(symbol, &trades);
+        let quantity = fills.filled_quantity;
+        self.auto_sell(symbol, price, quantity, commission, Some(order_id), client_tag)
+            .await;
+    }
+
+    /// Place the take-profit SELL for a filled BUY when auto-sell is enabled
+    ///
+    /// Inside a no-trade window, or while the symbol is cooling down after a
+    /// fill, the SELL is queued in the persisted state and placed by the
+    /// deferred sell loop once both have passed.
+    async fn auto_sell(
+        &self,
+        symbol: &str,
+        fill_price: f64,
+        quantity: f64,
+        commission: f64,
+        order_id: Option<i64>,
+        client_tag: Option<&str>,
+    ) {
+        if !self.config.auto_sell_enabled {
+            return;
+        }
+
+        let Some(wait) = auto_sell_wait(&self.config, symbol) else {
+            let config = &self.config;
+            place_auto_sell(config, symbol, fill_price, quantity, commission, order_id, client_tag)
+                .await;
+            return;
+        };
+
+        match self.config.no_trade_schedule.active_window(chrono::Utc::now()) {
+            Some(window) => tracing::info!(
+                "No-trade window {}: deferring auto-sell for {} BUY @ {} by {:?}",
+                window,
+                symbol,
+                fill_price,
+                wait
+            ),
+            None => tracing::info!(
+                "Fill cooldown: deferring auto-sell for {} BUY @ {} by {:?}",
+                symbol,
+                fill_price,
+                wait
+            ),
         }
+        self.deferred_sells.write().await.push(DeferredSell {
+            symbol: symbol.to_string(),
+            fill_price,
+            quantity,
+            commission,
+            order_id,
+            client_tag: client_tag.map(str::to_string),
+        });
+        self.persist().await;
+        self.deferred_added.notify_one();
     }
 
-    /// Check for newly filled orders
+    /// Move a symbol's last seen trade id forward (never backward)
+    async fn advance_last_trade_id(&self, symbol: &str, trade_id: i64) {
+        let mut last_ids = self.last_trade_ids.write().await;
+        let last = last_ids.entry(symbol.to_string()).or_insert(trade_id);
+        if trade_id > *last {
+            *last = trade_id;
+        }
+    }
+
+    /// Write the current state to disk, if persistence is on
+    async fn persist(&self) {
+        let Some(state_path) = &self.state_path else {
+            return;
+        };
+        let state = MonitorState {
+            last_trade_ids: self.last_trade_ids.read().await.clone(),
+            known_order_ids: self.known_order_ids.read().await.clone(),
+            deferred_sells: self.deferred_sells.read().await.clone(),
+        };
+
+        if let Err(e) = state.save(state_path) {
+            tracing::error!("Failed to persist monitor state: {}", e);
+        }
+    }
+
+    /// Check every configured symbol for newly filled orders
     async fn check_for_fills(&self) {
         let client = BinanceClient::new(&self.config);
 
+        for symbol in &self.config.symbols {
+            self.check_symbol_for_fills(&client, symbol).await;
+        }
+
+        self.persist().await;
+    }
+
+    /// Check one symbol for newly filled orders
+    async fn check_symbol_for_fills(&self, client: &BinanceClient, symbol: &str) {
         // Get current open orders
-        let current_orders = match client.get_open_orders().await {
+        let current_orders = match client.get_open_orders(symbol).await {
             Ok(orders) => orders,
             Err(e) => {
-                tracing::error!("Failed to get orders: {:?}", e);
+                tracing::error!("Failed to get {} orders: {:?}", symbol, e);
                 return;
             }
         };
@@ -75,42 +721,539 @@ impl OrderMonitor {
 
         // Find orders that disappeared (filled or cancelled)
         let known = self.known_order_ids.read().await;
-        let missing_ids: Vec<i64> = known
-            .iter()
-            .filter(|id| !current_order_ids.contains(id))
-            .cloned()
-            .collect();
+        let missing: Vec<i64> = known
+            .get(symbol)
+            .map(|ids| {
+                ids.iter()
+                    .copied()
+                    .filter(|id| !current_order_ids.contains(id))
+                    .collect()
+            })
+            .unwrap_or_default();
         drop(known);
 
-        // Check recent trades to see if orders were filled
-        if !missing_ids.is_empty() {
-            if let Ok(trades) = client.get_trades(20).await {
-                let last_id = self.last_trade_id.read().await.unwrap_or(0);
-
-                for trade in trades.iter().filter(|t| t.id > last_id) {
-                    // This is a new trade - send notification
-                    if trade.is_buyer {
-                        self.apns
-                            .notify_buy_filled(trade.price_f64(), trade.quantity_f64())
-                            .await;
-                    } else {
-                        // For sells, try to calculate profit
-                        // (simplified - just notify without profit for now)
-                        self.apns
-                            .notify_sell_filled(trade.price_f64(), trade.quantity_f64(), None)
-                            .await;
-                    }
-                }
-
-                // Update last trade ID
-                if let Some(latest) = trades.first() {
-                    *self.last_trade_id.write().await = Some(latest.id);
+        // Check recent trades to see if orders were filled; the rest were cancelled
+        if !missing.is_empty() {
+            if let Ok(trades) = client.get_trades(symbol, 20).await {
+                self.notify_new_trades(symbol, &trades).await;
+                for order_id in cancelled_orders(&missing, &trades) {
+                    self.events.publish(OrderEvent::Cancel {
+                        symbol: symbol.to_string(),
+                        order_id,
+                    });
                 }
             }
         }
 
         // Update known orders
-        let mut known = self.known_order_ids.write().await;
-        *known = current_order_ids;
+        self.known_order_ids
+            .write()
+            .await
+            .insert(symbol.to_string(), current_order_ids);
+    }
+}
+
+/// How long an auto-sell on `symbol` has to wait: until any no-trade window
+/// has ended and fills on the symbol have quieted down
+fn auto_sell_wait(config: &Config, symbol: &str) -> Option<Duration> {
+    let cooldown = Duration::from_secs(config.fill_cooldown_secs);
+    let cooling = FILL_COOLDOWN.remaining(symbol, Instant::now(), cooldown);
+    let no_trade = config.no_trade_schedule.resumes_in(chrono::Utc::now());
+    cooling.max(no_trade)
+}
+
+/// Place the take-profit SELL for a filled BUY
+///
+/// A grid BUY re-arms at its paired SELL's price; other BUYs sell at the
+/// flat target. The SELL carries the BUY's tag so the pair shows up under
+/// one strategy. `commission` is the BUY's fee in the base asset.
+async fn place_auto_sell(
+    config: &Config,
+    symbol: &str,
+    fill_price: f64,
+    quantity: f64,
+    commission: f64,
+    order_id: Option<i64>,
+    client_tag: Option<&str>,
+) {
+    let client = BinanceClient::new(config);
+    let target = take_profit_target(
+        &client,
+        symbol,
+        order_id,
+        fill_price,
+        config.auto_sell_target_percent,
+        config.quantity_match_tolerance,
+    )
+    .await;
+    let price = match target {
+        SellTarget::Price(price) => price,
+        SellTarget::Armed { order_id } => {
+            tracing::info!(
+                "Auto-sell: {} BUY @ {} already has grid SELL {} open",
+                symbol,
+                fill_price,
+                order_id
+            );
+            return;
+        }
+    };
+
+    let placed = place_take_profit(&client, symbol, price, quantity, commission, client_tag).await;
+    if let Err(e) = placed {
+        tracing::error!("Auto-sell for {} BUY @ {} failed: {}", symbol, fill_price, e);
+    }
+}
+
+/// Open orders across all symbols, for a fill push's badge
+///
+/// None, without asking Binance, when the push won't reach any device, and
+/// None if any lookup fails.
+async fn fill_badge(apns: &ApnsClient, config: &Config) -> Option<u32> {
+    if !apns.has_recipients().await {
+        return None;
+    }
+
+    let client = BinanceClient::new(config);
+    let mut count = 0;
+    for symbol in &config.symbols {
+        match client.get_open_orders(symbol).await {
+            Ok(orders) => count += orders.len() as u32,
+            Err(e) => {
+                tracing::warn!("Badge: failed to get {} open orders: {}", symbol, e);
+                return None;
+            }
+        }
+    }
+
+    Some(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_config;
+    use axum::{
+        routing::{get, post},
+        Json, Router,
+    };
+
+    fn trade(id: i64) -> Trade {
+        Trade {
+            id,
+            order_id: id,
+            symbol: "BTCUSDT".to_string(),
+            price: "50000".to_string(),
+            qty: "0.001".to_string(),
+            quote_qty: "50".to_string(),
+            commission: "0".to_string(),
+            commission_asset: "USDT".to_string(),
+            time: id * 1000,
+            is_buyer: true,
+            is_maker: true,
+            client_tag: None,
+        }
+    }
+
+    fn temp_state_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("monitor_state_{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let path = temp_state_path("roundtrip");
+        let state = MonitorState {
+            last_trade_ids: HashMap::from([("BTCUSDT".to_string(), 5)]),
+            known_order_ids: HashMap::from([("BTCUSDT".to_string(), HashSet::from([10, 11]))]),
+            deferred_sells: Vec::new(),
+        };
+
+        state.save(&path).unwrap();
+        assert_eq!(MonitorState::load(&path), Some(state));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_missing_state_loads_none() {
+        assert_eq!(MonitorState::load(&temp_state_path("missing")), None);
+    }
+
+    #[test]
+    fn test_restart_replays_downtime_fill_without_duplicates() {
+        let path = temp_state_path("restart");
+
+        // Before shutdown we had notified up to trade 5
+        MonitorState {
+            last_trade_ids: HashMap::from([("BTCUSDT".to_string(), 5)]),
+            known_order_ids: HashMap::new(),
+            deferred_sells: Vec::new(),
+        }
+        .save(&path)
+        .unwrap();
+
+        // Trade 6 filled while the backend was down
+        let trades = vec![trade(4), trade(5), trade(6)];
+        let restored = MonitorState::load(&path).unwrap();
+        let replay: Vec<i64> = unseen_trades(&trades, restored.last_trade_ids.get("BTCUSDT").copied())
+            .iter()
+            .map(|t| t.id)
+            .collect();
+
+        assert_eq!(replay, vec![6]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_simulated_buy_produces_buy_notification() {
+        let trade = simulated_trade("BTCUSDT", true, 50000.0, 0.002);
+        assert_eq!(
+            trade_notification(&trade),
+            FillNotification::Buy { price: 50000.0, quantity: 0.002 }
+        );
+    }
+
+    #[test]
+    fn test_simulated_sell_produces_sell_notification() {
+        let trade = simulated_trade("BTCUSDT", false, 51000.0, 0.002);
+        assert_eq!(
+            trade_notification(&trade),
+            FillNotification::Sell { price: 51000.0, quantity: 0.002 }
+        );
+    }
+
+    #[test]
+    fn test_orders_gone_without_a_trade_are_cancelled() {
+        assert_eq!(cancelled_orders(&[3, 5, 8], &[trade(5), trade(6)]), vec![3, 8]);
+        assert!(cancelled_orders(&[5], &[trade(5)]).is_empty());
+    }
+
+    #[test]
+    fn test_unseen_trades_oldest_first() {
+        let trades = vec![trade(9), trade(7), trade(8)];
+        let ids: Vec<i64> = unseen_trades(&trades, Some(7)).iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![8, 9]);
+    }
+
+    fn trade_json(id: i64) -> serde_json::Value {
+        serde_json::json!({
+            "id": id, "orderId": id, "symbol": "BTCUSDT",
+            "price": "50000.00", "qty": "0.00100", "quoteQty": "50",
+            "commission": "0", "commissionAsset": "USDT",
+            "time": id * 1000, "isBuyer": true, "isMaker": true
+        })
+    }
+
+    fn filled_report(trade_id: i64) -> ExecutionReport {
+        serde_json::from_value(serde_json::json!({
+            "s": "BTCUSDT", "S": "BUY", "i": trade_id, "c": "",
+            "x": "TRADE", "X": "FILLED", "p": "50000.00",
+            "z": "0.00100", "Z": "50", "t": trade_id
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stream_connect_catches_up_without_duplicates() {
+        let path = temp_state_path("catch_up");
+        MonitorState {
+            last_trade_ids: HashMap::from([("BTCUSDT".to_string(), 5)]),
+            known_order_ids: HashMap::new(),
+            deferred_sells: Vec::new(),
+        }
+        .save(&path)
+        .unwrap();
+
+        // Trade 6 filled while the stream was down
+        let router = Router::new()
+            .route("/api/v3/openOrders", get(|| async { Json(serde_json::json!([])) }))
+            .route(
+                "/api/v3/myTrades",
+                get(|| async { Json(serde_json::json!([trade_json(5), trade_json(6)])) }),
+            );
+        let config = Config {
+            monitor_state_path: path.to_string_lossy().into_owned(),
+            ..mock_config(router).await
+        };
+        let monitor = OrderMonitor::new(config.clone(), Arc::new(ApnsClient::disabled()));
+        let mut events = monitor.events().subscribe();
+
+        monitor.restore_state().await;
+        monitor.catch_up(&BinanceClient::new(&config)).await;
+        assert!(matches!(events.try_recv(), Ok(OrderEvent::Fill { .. })));
+        assert!(events.try_recv().is_err());
+        let persisted = MonitorState::load(&path).unwrap();
+        assert_eq!(persisted.last_trade_ids["BTCUSDT"], 6);
+
+        // The reconnected stream then delivers the same fill, and a new one
+        monitor.handle_execution_report(&filled_report(6)).await;
+        assert!(events.try_recv().is_err());
+        monitor.handle_execution_report(&filled_report(7)).await;
+        assert!(matches!(events.try_recv(), Ok(OrderEvent::Fill { .. })));
+        assert_eq!(MonitorState::load(&path).unwrap().last_trade_ids["BTCUSDT"], 7);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_badge_is_not_looked_up_without_recipients() {
+        let lookups = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = lookups.clone();
+        let router = Router::new().route(
+            "/api/v3/openOrders",
+            get(move || async move {
+                counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Json(serde_json::json!([]))
+            }),
+        );
+        let config = mock_config(router).await;
+        let apns = ApnsClient::disabled();
+
+        assert_eq!(fill_badge(&apns, &config).await, None);
+        let monitor = OrderMonitor::new(config, Arc::new(apns));
+        monitor.simulate_fill(&trade(1), false).await;
+
+        assert_eq!(lookups.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_buy_is_only_auto_sold_when_asked() {
+        let placed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = placed.clone();
+        let router = Router::new()
+            .route(
+                "/api/v3/account",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "balances": [{ "asset": "BTC", "free": "0.01000000", "locked": "0" }],
+                        "canTrade": true, "canWithdraw": false, "canDeposit": true
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/order",
+                post(move || async move {
+                    counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT", "orderId": 99, "clientOrderId": "tp",
+                        "transactTime": 1700000000000i64, "price": "50500.00",
+                        "origQty": "0.00200", "executedQty": "0", "status": "NEW",
+                        "type": "LIMIT", "side": "SELL"
+                    }))
+                }),
+            );
+        let config = Config {
+            auto_sell_enabled: true,
+            ..mock_config(router).await
+        };
+        let monitor = OrderMonitor::new(config, Arc::new(ApnsClient::disabled()));
+        let buy = simulated_trade("BTCUSDT", true, 50000.0, 0.002);
+
+        monitor.simulate_fill(&buy, false).await;
+        assert_eq!(placed.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        monitor.simulate_fill(&buy, true).await;
+        assert_eq!(placed.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_buy_filled_across_trades_is_auto_sold_once() {
+        use axum::extract::RawQuery;
+
+        let grid_lookups = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let placed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let (counted, recorded) = (grid_lookups.clone(), placed.clone());
+        let order = |order_id: i64, side: &str, price: &str, status: &str| {
+            serde_json::json!({
+                "symbol": "BTCUSDT", "orderId": order_id, "clientOrderId": "",
+                "price": price, "origQty": "0.00200", "executedQty": "0.00200",
+                "status": status, "type": "LIMIT", "side": side,
+                "time": 1700000000000i64 + order_id
+            })
+        };
+        // BUY 10 filled in two trades, each paying its fee in BTC
+        let fill = |id: i64, price: &str| {
+            serde_json::json!({
+                "id": id, "orderId": 10, "symbol": "BTCUSDT",
+                "price": price, "qty": "0.00100", "quoteQty": "50",
+                "commission": "0.00000100", "commissionAsset": "BTC",
+                "time": id * 1000, "isBuyer": true, "isMaker": true
+            })
+        };
+        let fills = serde_json::json!([fill(1, "50000.00"), fill(2, "50020.00")]);
+        let trades: Vec<Trade> = serde_json::from_value(fills.clone()).unwrap();
+
+        let router = Router::new()
+            .route(
+                "/api/v3/exchangeInfo",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "symbols": [{
+                            "symbol": "BTCUSDT",
+                            "filters": [
+                                { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                                { "filterType": "LOT_SIZE", "stepSize": "0.00001000" }
+                            ]
+                        }]
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/account",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "balances": [{ "asset": "BTC", "free": "0.00500000", "locked": "0" }],
+                        "canTrade": true, "canWithdraw": false, "canDeposit": true
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/allOrders",
+                get(move |RawQuery(query): RawQuery| async move {
+                    // Tagging asks for 1000 orders; the grid lookup for the SELL asks for 100
+                    if query.unwrap_or_default().contains("limit=100&") {
+                        counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    }
+                    Json(serde_json::json!([
+                        order(10, "BUY", "50000.00", "FILLED"),
+                        order(11, "SELL", "51200.00", "CANCELED")
+                    ]))
+                }),
+            )
+            .route("/api/v3/myTrades", get(move || async move { Json(fills.clone()) }))
+            .route(
+                "/api/v3/order",
+                get(move || async move { Json(order(10, "BUY", "50000.00", "FILLED")) }).post(
+                    move |body: String| async move {
+                        recorded.lock().unwrap().push(body);
+                        Json(serde_json::json!({
+                            "symbol": "BTCUSDT", "orderId": 99, "clientOrderId": "tp",
+                            "transactTime": 1700000000000i64, "price": "51200.00",
+                            "origQty": "0.00199", "executedQty": "0", "status": "NEW",
+                            "type": "LIMIT", "side": "SELL"
+                        }))
+                    },
+                ),
+            );
+        let config = Config {
+            auto_sell_enabled: true,
+            ..mock_config(router).await
+        };
+        let monitor = OrderMonitor::new(config, Arc::new(ApnsClient::disabled()));
+
+        monitor.notify_new_trades("BTCUSDT", &trades).await;
+
+        // One SELL at the grid pair's level for both fills, less the BTC fees
+        let placed = placed.lock().unwrap();
+        assert_eq!(placed.len(), 1);
+        assert!(placed[0].contains("price=51200.00&"), "{}", placed[0]);
+        assert!(placed[0].contains("quantity=0.00199&"), "{}", placed[0]);
+        assert_eq!(grid_lookups.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_auto_sell_in_cooldown_is_deferred_across_a_restart() {
+        let path = temp_state_path("deferred_sell");
+        let placed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = placed.clone();
+        let router = Router::new()
+            .route(
+                "/api/v3/account",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "balances": [{ "asset": "ETH", "free": "0.10000000", "locked": "0" }],
+                        "canTrade": true, "canWithdraw": false, "canDeposit": true
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/order",
+                post(move |body: String| async move {
+                    recorded.lock().unwrap().push(body);
+                    Json(serde_json::json!({
+                        "symbol": "ETHUSDT", "orderId": 99, "clientOrderId": "tp",
+                        "transactTime": 1700000000000i64, "price": "3030.00",
+                        "origQty": "0.05000", "executedQty": "0", "status": "NEW",
+                        "type": "LIMIT", "side": "SELL"
+                    }))
+                }),
+            );
+        let config = Config {
+            auto_sell_enabled: true,
+            fill_cooldown_secs: 1,
+            monitor_state_path: path.to_string_lossy().into_owned(),
+            ..mock_config(router).await
+        };
+        FILL_COOLDOWN.record("ETHUSDT", Instant::now());
+
+        // The BUY fills while the symbol is cooling down, just before a restart
+        let monitor = OrderMonitor::new(config.clone(), Arc::new(ApnsClient::disabled()));
+        monitor.auto_sell("ETHUSDT", 3000.0, 0.05, 0.0, None, None).await;
+        assert!(placed.lock().unwrap().is_empty());
+        assert_eq!(MonitorState::load(&path).unwrap().deferred_sells.len(), 1);
+
+        // The restarted monitor places it once the cooldown is over
+        let monitor = OrderMonitor::new(config, Arc::new(ApnsClient::disabled()));
+        monitor.restore_state().await;
+        let shutdown = CancellationToken::new();
+        let stop = shutdown.clone();
+        let deferred = async {
+            while placed.lock().unwrap().is_empty() {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            stop.cancel();
+        };
+        let ran = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(monitor.run_deferred_sells(&shutdown), deferred)
+        })
+        .await;
+
+        assert!(ran.is_ok(), "deferred sell loop didn't stop on shutdown");
+        assert_eq!(placed.lock().unwrap().len(), 1);
+        assert!(placed.lock().unwrap()[0].contains("side=SELL&"));
+        assert!(MonitorState::load(&path).unwrap().deferred_sells.is_empty());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_partial_sell_fills_share_one_profit_lookup() {
+        use axum::extract::RawQuery;
+
+        let lookups = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counted = lookups.clone();
+        // SELL order 2 filled in two trades against one earlier buy
+        let sell = |id: i64| {
+            serde_json::json!({
+                "id": id, "orderId": 2, "symbol": "BTCUSDT",
+                "price": "51000.00", "qty": "0.00100", "quoteQty": "51",
+                "commission": "0", "commissionAsset": "USDT",
+                "time": id * 1000, "isBuyer": false, "isMaker": true
+            })
+        };
+        let history = serde_json::json!([trade_json(1), sell(2), sell(3)]);
+        let trades: Vec<Trade> = serde_json::from_value(history.clone()).unwrap();
+        let router = Router::new()
+            .route("/api/v3/allOrders", get(|| async { Json(serde_json::json!([])) }))
+            .route(
+                "/api/v3/myTrades",
+                get(move |RawQuery(query): RawQuery| async move {
+                    if query.unwrap_or_default().contains("limit=500&") {
+                        counted.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    }
+                    Json(history.clone())
+                }),
+            );
+        let monitor =
+            OrderMonitor::new(mock_config(router).await, Arc::new(ApnsClient::disabled()));
+        monitor.last_trade_ids.write().await.insert("BTCUSDT".to_string(), 1);
+        let mut events = monitor.events().subscribe();
+
+        monitor.notify_new_trades("BTCUSDT", &trades).await;
+
+        assert!(matches!(events.try_recv(), Ok(OrderEvent::Fill { .. })));
+        assert!(matches!(events.try_recv(), Ok(OrderEvent::Fill { .. })));
+        assert_eq!(lookups.load(std::sync::atomic::Ordering::SeqCst), 1);
     }
 }
@@ -0,0 +1,112 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+
+/// Health of the Binance user data stream that delivers real-time fills
+pub static USER_STREAM: StreamHealth = StreamHealth::new("user_data");
+
+/// Health of the bookTicker stream that market orders check slippage against
+pub static BOOK_STREAM: StreamHealth = StreamHealth::new("book_ticker");
+
+/// Connection state of a long-lived background socket
+pub struct StreamHealth {
+    name: &'static str,
+    connected: AtomicBool,
+    connections: AtomicU64,
+    /// Unix millis, 0 until the first message
+    last_message_at: AtomicI64,
+    /// Unix millis of the last successful listenKey keepalive, 0 if none yet
+    last_keepalive_at: AtomicI64,
+}
+
+/// Point-in-time view of a stream
+#[derive(Debug, Serialize)]
+pub struct StreamSnapshot {
+    pub name: &'static str,
+    pub connected: bool,
+    pub last_message_at: Option<i64>,
+    pub last_keepalive_at: Option<i64>,
+    /// Connections made after the first one
+    pub reconnects: u64,
+}
+
+impl StreamHealth {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            connected: AtomicBool::new(false),
+            connections: AtomicU64::new(0),
+            last_message_at: AtomicI64::new(0),
+            last_keepalive_at: AtomicI64::new(0),
+        }
+    }
+
+    pub fn record_connected(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+        self.connected.store(true, Ordering::Relaxed);
+    }
+
+    pub fn record_disconnected(&self) {
+        self.connected.store(false, Ordering::Relaxed);
+    }
+
+    pub fn record_message(&self) {
+        self.last_message_at.store(now_ms(), Ordering::Relaxed);
+    }
+
+    pub fn record_keepalive(&self) {
+        self.last_keepalive_at.store(now_ms(), Ordering::Relaxed);
+    }
+
+    /// Successful connections so far, including the first
+    pub fn connections(&self) -> u64 {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> StreamSnapshot {
+        let timestamp = |value: &AtomicI64| Some(value.load(Ordering::Relaxed)).filter(|&t| t > 0);
+
+        StreamSnapshot {
+            name: self.name,
+            connected: self.connected.load(Ordering::Relaxed),
+            last_message_at: timestamp(&self.last_message_at),
+            last_keepalive_at: timestamp(&self.last_keepalive_at),
+            reconnects: self.connections().saturating_sub(1),
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_serializes_stream_state() {
+        let health = StreamHealth::new("user_data");
+        let snapshot = serde_json::to_value(health.snapshot()).unwrap();
+        assert_eq!(
+            snapshot,
+            serde_json::json!({
+                "name": "user_data",
+                "connected": false,
+                "last_message_at": null,
+                "last_keepalive_at": null,
+                "reconnects": 0
+            })
+        );
+
+        health.record_connected();
+        health.record_message();
+        health.record_disconnected();
+        health.record_connected();
+
+        let snapshot = health.snapshot();
+        assert!(snapshot.connected);
+        assert_eq!(snapshot.reconnects, 1);
+        assert!(snapshot.last_message_at.is_some());
+        assert_eq!(snapshot.last_keepalive_at, None);
+    }
+}
@@ -0,0 +1,361 @@
+use crate::binance::{BinanceApiError, BinanceClient, NewOrderResponse, Order, TimeInForce, Trade};
+use crate::trading::{base_asset, match_grid_pairs};
+
+/// Orders fetched from a filled BUY onward to find the SELL it was paired with
+const GRID_ORDER_LOOKUP: u32 = 100;
+
+/// Price for the take-profit SELL that completes a filled BUY
+/// (`target_percent` 1.0 = 1%)
+pub fn take_profit_price(fill_price: f64, target_percent: f64) -> f64 {
+    fill_price * (1.0 + target_percent / 100.0)
+}
+
+/// Commission `trades` paid in `symbol`'s base asset, which never reached the
+/// balance and so can't be sold
+pub fn base_commission(symbol: &str, trades: &[Trade]) -> f64 {
+    let Some(asset) = base_asset(symbol) else {
+        return 0.0;
+    };
+    trades
+        .iter()
+        .filter(|t| t.commission_asset == asset)
+        .map(|t| t.commission_f64())
+        .sum()
+}
+
+/// Where the SELL for a filled BUY goes
+#[derive(Debug, PartialEq)]
+pub enum SellTarget {
+    /// The BUY's grid SELL is still open, so there's nothing to place
+    Armed { order_id: i64 },
+    /// Sell at this price
+    Price(f64),
+}
+
+/// Pick the SELL for filled BUY `buy_order_id` out of `orders`
+///
+/// A BUY that was part of a grid pair re-arms at its SELL's level; any other
+/// BUY gets the flat `target_percent` above its fill.
+fn sell_target(
+    orders: &[Order],
+    buy_order_id: i64,
+    fill_price: f64,
+    target_percent: f64,
+    tolerance: f64,
+) -> SellTarget {
+    let (pairs, _) = match_grid_pairs(orders, tolerance);
+    match pairs.iter().find(|pair| pair.buy_order.order_id == buy_order_id) {
+        Some(pair) if pair.sell_order.is_open() => SellTarget::Armed {
+            order_id: pair.sell_order.order_id,
+        },
+        Some(pair) => SellTarget::Price(pair.sell_order.price_f64()),
+        None => SellTarget::Price(take_profit_price(fill_price, target_percent)),
+    }
+}
+
+/// Look up where the SELL for a filled BUY should go
+///
+/// Grid pairs are placed BUY first, so the paired SELL is among the orders
+/// from the BUY's id onward. Without an order id, or if the lookup fails,
+/// the flat target is used.
+pub async fn take_profit_target(
+    client: &BinanceClient,
+    symbol: &str,
+    buy_order_id: Option<i64>,
+    fill_price: f64,
+    target_percent: f64,
+    tolerance: f64,
+) -> SellTarget {
+    let flat = SellTarget::Price(take_profit_price(fill_price, target_percent));
+    let Some(buy_order_id) = buy_order_id else {
+        return flat;
+    };
+
+    match client.get_all_orders(symbol, buy_order_id, GRID_ORDER_LOOKUP).await {
+        Ok(orders) => sell_target(&orders, buy_order_id, fill_price, target_percent, tolerance),
+        Err(e) => {
+            tracing::warn!("Auto-sell: grid lookup for order {} failed: {}", buy_order_id, e);
+            flat
+        }
+    }
+}
+
+/// Place the take-profit SELL for a filled BUY at `price`
+///
+/// Sells what the BUY actually added: `filled` less the `commission` taken in
+/// the base asset, capped at the free balance (some may already have been sold
+/// manually) and floored to the symbol's step size. Returns `Ok(None)` without
+/// placing anything when that leaves nothing to sell.
+pub async fn place_take_profit(
+    client: &BinanceClient,
+    symbol: &str,
+    price: f64,
+    filled: f64,
+    commission: f64,
+    client_tag: Option<&str>,
+) -> Result<Option<NewOrderResponse>, BinanceApiError> {
+    let Some(asset) = base_asset(symbol) else {
+        tracing::warn!("Auto-sell: {} is not a USDT pair, skipping", symbol);
+        return Ok(None);
+    };
+
+    // Fresh, so the balance includes the BUY that just filled
+    let account = client.get_fresh_account().await?;
+    let free = account
+        .balances
+        .iter()
+        .find(|b| b.asset == asset)
+        .map(|b| b.free_f64())
+        .unwrap_or(0.0);
+
+    let received = filled - commission;
+    if free < received {
+        tracing::warn!(
+            "Auto-sell: only {} {} free of the {} bought, selling what's free",
+            free,
+            asset,
+            received
+        );
+    }
+    let quantity = received.min(free);
+    let quantity = match client.get_precision(symbol).await {
+        Ok(precision) => precision.floor_quantity(quantity),
+        Err(e) => {
+            tracing::debug!("Auto-sell: exchangeInfo unavailable ({}), quantity unrounded", e);
+            quantity
+        }
+    };
+    if quantity <= 0.0 {
+        tracing::warn!("Auto-sell: no {} left to sell below the quantity step, skipping", asset);
+        return Ok(None);
+    }
+
+    let order = client
+        .create_limit_order(symbol, "SELL", price, quantity, TimeInForce::Gtc, client_tag)
+        .await?;
+
+    tracing::info!(
+        "Auto-sell: placed {} SELL {} @ {} (order {})",
+        symbol,
+        quantity,
+        price,
+        order.order_id
+    );
+
+    Ok(Some(order))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_client;
+    use axum::{
+        routing::{get, post},
+        Json, Router,
+    };
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    fn order_json(order_id: i64, side: &str, price: &str, status: &str) -> serde_json::Value {
+        serde_json::json!({
+            "symbol": "BTCUSDT",
+            "orderId": order_id,
+            "clientOrderId": format!("web_{}", order_id),
+            "price": price,
+            "origQty": "0.00200",
+            "executedQty": if status == "FILLED" { "0.00200" } else { "0" },
+            "status": status,
+            "type": "LIMIT",
+            "side": side,
+            "time": 1700000000000i64 + order_id
+        })
+    }
+
+    /// Mock Binance with `btc_free` BTC, recording each order's form body
+    ///
+    /// Its order history holds two grid pairs: BUY 10 @ 50000 whose SELL 11 @ 51200
+    /// was cancelled, and BUY 12 @ 52000 whose SELL 13 @ 53000 is still open.
+    async fn mock_binance(btc_free: &'static str) -> (BinanceClient, Arc<Mutex<Vec<String>>>) {
+        let orders = Arc::new(Mutex::new(Vec::new()));
+        let recorded = orders.clone();
+
+        let mock = Router::new()
+            .route(
+                "/api/v3/exchangeInfo",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "symbols": [{
+                            "symbol": "BTCUSDT",
+                            "filters": [
+                                { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                                { "filterType": "LOT_SIZE", "stepSize": "0.00001000" }
+                            ]
+                        }]
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/account",
+                get(move || async move {
+                    Json(serde_json::json!({
+                        "balances": [
+                            { "asset": "BTC", "free": btc_free, "locked": "0" },
+                            { "asset": "USDT", "free": "1000", "locked": "0" }
+                        ],
+                        "canTrade": true,
+                        "canWithdraw": false,
+                        "canDeposit": true
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/allOrders",
+                get(|| async {
+                    Json(vec![
+                        order_json(10, "BUY", "50000.00", "FILLED"),
+                        order_json(11, "SELL", "51200.00", "CANCELED"),
+                        order_json(12, "BUY", "52000.00", "FILLED"),
+                        order_json(13, "SELL", "53000.00", "NEW"),
+                    ])
+                }),
+            )
+            .route(
+                "/api/v3/order",
+                post(move |body: String| async move {
+                    recorded.lock().unwrap().push(body);
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": 99,
+                        "clientOrderId": "tp",
+                        "transactTime": 1700000000000i64,
+                        "price": "50500.00",
+                        "origQty": "0.00200",
+                        "executedQty": "0",
+                        "status": "NEW",
+                        "type": "LIMIT",
+                        "side": "SELL"
+                    }))
+                }),
+            );
+
+        let client = mock_client(mock).await;
+
+        (client, orders)
+    }
+
+    fn form(body: &str) -> HashMap<String, String> {
+        body.split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_take_profit_price() {
+        assert!((take_profit_price(50000.0, 1.0) - 50500.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_buy_fill_places_priced_sell() {
+        let (client, orders) = mock_binance("0.00500000").await;
+
+        let order = place_take_profit(&client, "BTCUSDT", 50500.0, 0.002, 0.0, None)
+            .await
+            .unwrap();
+        assert_eq!(order.map(|o| o.order_id), Some(99));
+
+        let orders = orders.lock().unwrap();
+        assert_eq!(orders.len(), 1);
+        let params = form(&orders[0]);
+        assert_eq!(params["symbol"], "BTCUSDT");
+        assert_eq!(params["side"], "SELL");
+        assert_eq!(params["type"], "LIMIT");
+        assert_eq!(params["price"], "50500.00");
+        assert_eq!(params["quantity"], "0.00200");
+    }
+
+    #[tokio::test]
+    async fn test_fee_taken_in_base_asset_is_not_sold() {
+        let (client, orders) = mock_binance("0.00500000").await;
+
+        // 0.1% of 0.002 BTC went to fees, leaving 0.001998: 0.00199 in whole steps
+        place_take_profit(&client, "BTCUSDT", 50500.0, 0.002, 0.000002, None)
+            .await
+            .unwrap();
+        let params = form(&orders.lock().unwrap()[0]);
+        assert_eq!(params["quantity"], "0.00199");
+    }
+
+    #[tokio::test]
+    async fn test_short_balance_sells_what_is_free() {
+        let (client, orders) = mock_binance("0.00100000").await;
+
+        let order = place_take_profit(&client, "BTCUSDT", 50500.0, 0.002, 0.0, None)
+            .await
+            .unwrap();
+        assert!(order.is_some());
+        let params = form(&orders.lock().unwrap()[0]);
+        assert_eq!(params["quantity"], "0.00100");
+    }
+
+    #[tokio::test]
+    async fn test_no_free_balance_skips_sell() {
+        let (client, orders) = mock_binance("0.00000000").await;
+
+        let order = place_take_profit(&client, "BTCUSDT", 50500.0, 0.002, 0.0, None)
+            .await
+            .unwrap();
+        assert!(order.is_none());
+        assert!(orders.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_base_commission_counts_only_the_base_asset() {
+        let trade = |commission: &str, asset: &str| Trade {
+            id: 1,
+            order_id: 10,
+            symbol: "BTCUSDT".to_string(),
+            price: "50000.00".to_string(),
+            qty: "0.00100".to_string(),
+            quote_qty: "50".to_string(),
+            commission: commission.to_string(),
+            commission_asset: asset.to_string(),
+            time: 1700000000000,
+            is_buyer: true,
+            is_maker: true,
+            client_tag: None,
+        };
+        let trades = [trade("0.000001", "BTC"), trade("0.05", "USDT"), trade("0.000001", "BTC")];
+
+        assert!((base_commission("BTCUSDT", &trades) - 0.000002).abs() < 1e-12);
+        assert_eq!(base_commission("ETHUSDT", &trades), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_grid_buy_rearms_at_paired_sell() {
+        let (client, _) = mock_binance("0.00500000").await;
+
+        let target = take_profit_target(&client, "BTCUSDT", Some(10), 50010.0, 1.0, 0.01).await;
+        assert_eq!(target, SellTarget::Price(51200.0));
+    }
+
+    #[tokio::test]
+    async fn test_grid_buy_with_open_sell_is_already_armed() {
+        let (client, _) = mock_binance("0.00500000").await;
+
+        let target = take_profit_target(&client, "BTCUSDT", Some(12), 52000.0, 1.0, 0.01).await;
+        assert_eq!(target, SellTarget::Armed { order_id: 13 });
+    }
+
+    #[tokio::test]
+    async fn test_unpaired_buy_uses_flat_target() {
+        let (client, _) = mock_binance("0.00500000").await;
+
+        let target = take_profit_target(&client, "BTCUSDT", None, 50000.0, 1.0, 0.01).await;
+        assert_eq!(target, SellTarget::Price(50500.0));
+
+        // An order id the history doesn't pair (e.g. a manual market BUY)
+        let target = take_profit_target(&client, "BTCUSDT", Some(99), 50000.0, 1.0, 0.01).await;
+        assert_eq!(target, SellTarget::Price(50500.0));
+    }
+}
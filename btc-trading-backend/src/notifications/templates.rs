@@ -0,0 +1,210 @@
+use serde::Deserialize;
+
+/// Title and body of one notification, with `{name}` placeholders
+///
+/// Fill templates can use `{asset}` (the symbol's base asset, e.g. "BTC"),
+/// `{qty}`, `{price}`, `{value}` (price x qty) and, for sells with matched
+/// profit, `{profit}` (signed, e.g. "+$1.23" or "-$0.40"). Batch templates use
+/// `{count}`, `{buys}` and `{sells}` (counted, e.g. "1 buy", "2 sells") and,
+/// when any sell's profit is known, `{net}` (signed like `{profit}`). Unknown
+/// placeholders are left as written.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    pub title: String,
+    pub body: String,
+}
+
+impl Template {
+    fn new(title: &str, body: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Rendered (title, body)
+    fn render(&self, vars: &[(&str, String)]) -> (String, String) {
+        let fill = |text: &str| {
+            vars.iter().fold(text.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+        };
+        (fill(&self.title), fill(&self.body))
+    }
+}
+
+/// Copy for fill notifications, overridable from a JSON file
+///
+/// Templates the file leaves out keep their defaults.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationTemplates {
+    pub buy: Template,
+    pub sell: Template,
+    /// A sell whose profit was matched against earlier buys
+    pub sell_with_profit: Template,
+    /// Several fills coalesced into one notification
+    pub batch: Template,
+    /// Coalesced fills including sells with matched profit
+    pub batch_with_profit: Template,
+}
+
+impl Default for NotificationTemplates {
+    fn default() -> Self {
+        Self {
+            buy: Template::new(
+                "🟢 BUY Order Filled",
+                "Bought {qty} {asset} @ ${price} (${value})",
+            ),
+            sell: Template::new(
+                "🔴 SELL Order Filled",
+                "Sold {qty} {asset} @ ${price} (${value})",
+            ),
+            sell_with_profit: Template::new(
+                "🔴 SELL Order Filled",
+                "Sold {qty} {asset} @ ${price} (${value}), {profit} net",
+            ),
+            batch: Template::new(
+                "✅ {count} Orders Filled",
+                "{count} orders filled ({buys}, {sells})",
+            ),
+            batch_with_profit: Template::new(
+                "✅ {count} Orders Filled",
+                "{count} orders filled ({buys}, {sells}), {net} net",
+            ),
+        }
+    }
+}
+
+impl NotificationTemplates {
+    /// Load templates from a JSON file
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// (title, body) for a filled buy of `asset`
+    pub fn buy_filled(&self, asset: &str, price: f64, quantity: f64) -> (String, String) {
+        self.buy.render(&fill_vars(asset, price, quantity))
+    }
+
+    /// (title, body) for a filled sell of `asset`, mentioning profit when it's known
+    pub fn sell_filled(
+        &self,
+        asset: &str,
+        price: f64,
+        quantity: f64,
+        profit: Option<f64>,
+    ) -> (String, String) {
+        let mut vars = fill_vars(asset, price, quantity);
+        match profit {
+            Some(profit) => {
+                vars.push(("profit", signed_usd(profit)));
+                self.sell_with_profit.render(&vars)
+            }
+            None => self.sell.render(&vars),
+        }
+    }
+
+    /// (title, body) summarizing fills pushed together
+    pub fn batch_filled(
+        &self,
+        buys: usize,
+        sells: usize,
+        net_profit: Option<f64>,
+    ) -> (String, String) {
+        let mut vars = vec![
+            ("count", (buys + sells).to_string()),
+            ("buys", counted(buys, "buy")),
+            ("sells", counted(sells, "sell")),
+        ];
+        match net_profit {
+            Some(net) => {
+                vars.push(("net", signed_usd(net)));
+                self.batch_with_profit.render(&vars)
+            }
+            None => self.batch.render(&vars),
+        }
+    }
+}
+
+/// "1 buy", "2 buys"
+fn counted(count: usize, noun: &str) -> String {
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{}", count, noun, plural)
+}
+
+/// "+$12.40", "-$3.10"
+fn signed_usd(amount: f64) -> String {
+    let sign = if amount < 0.0 { "-" } else { "+" };
+    format!("{}${:.2}", sign, amount.abs())
+}
+
+fn fill_vars(asset: &str, price: f64, quantity: f64) -> Vec<(&'static str, String)> {
+    vec![
+        ("asset", asset.to_string()),
+        ("qty", format!("{:.5}", quantity)),
+        ("price", format!("{:.0}", price)),
+        ("value", format!("{:.0}", price * quantity)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_copy() {
+        let templates = NotificationTemplates::default();
+
+        let (title, body) = templates.buy_filled("BTC", 50000.0, 0.002);
+        assert_eq!(title, "🟢 BUY Order Filled");
+        assert_eq!(body, "Bought 0.00200 BTC @ $50000 ($100)");
+
+        let (_, body) = templates.sell_filled("BTC", 51000.0, 0.002, Some(1.234));
+        assert_eq!(body, "Sold 0.00200 BTC @ $51000 ($102), +$1.23 net");
+
+        let (_, body) = templates.sell_filled("BTC", 51000.0, 0.002, None);
+        assert_eq!(body, "Sold 0.00200 BTC @ $51000 ($102)");
+    }
+
+    #[test]
+    fn test_fill_names_its_asset_and_signs_a_loss() {
+        let templates = NotificationTemplates::default();
+
+        let (_, body) = templates.buy_filled("ETH", 3000.0, 0.5);
+        assert_eq!(body, "Bought 0.50000 ETH @ $3000 ($1500)");
+
+        let (_, body) = templates.sell_filled("ETH", 2990.0, 0.5, Some(-6.5));
+        assert_eq!(body, "Sold 0.50000 ETH @ $2990 ($1495), -$6.50 net");
+    }
+
+    #[test]
+    fn test_custom_template_renders_body() {
+        let templates: NotificationTemplates = serde_json::from_str(
+            r#"{
+                "sell_with_profit": {
+                    "title": "Verkauf",
+                    "body": "{qty} {asset} zu {price} $ verkauft, Gewinn {profit}"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let (title, body) = templates.sell_filled("BTC", 51000.0, 0.002, Some(1.5));
+        assert_eq!(title, "Verkauf");
+        assert_eq!(body, "0.00200 BTC zu 51000 $ verkauft, Gewinn +$1.50");
+
+        // Templates the file leaves out keep their defaults
+        assert_eq!(templates.buy, NotificationTemplates::default().buy);
+    }
+
+    #[test]
+    fn test_unknown_template_is_rejected() {
+        let result = serde_json::from_str::<NotificationTemplates>(
+            r#"{ "trailing": { "title": "Moved", "body": "{price}" } }"#,
+        );
+        assert!(result.is_err());
+    }
+}
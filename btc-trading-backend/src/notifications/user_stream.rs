@@ -0,0 +1,159 @@
+use futures::StreamExt;
+use std::time::Duration;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_util::sync::CancellationToken;
+
+use super::stream_health::USER_STREAM;
+use super::OrderMonitor;
+use crate::binance::{BinanceClient, ExecutionReport};
+
+/// listenKeys expire after 60 minutes without a keepalive
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// Notification to send for a fill
+#[derive(Debug, Clone, PartialEq)]
+pub enum FillNotification {
+    Buy { price: f64, quantity: f64 },
+    Sell { price: f64, quantity: f64 },
+}
+
+/// Parse a stream message, keeping only `executionReport` events
+pub fn parse_execution_report(text: &str) -> Option<ExecutionReport> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    if value.get("e").and_then(|e| e.as_str()) != Some("executionReport") {
+        return None;
+    }
+    serde_json::from_value(value).ok()
+}
+
+/// Decide whether an execution report should notify (only on the trade that
+/// completes the order)
+pub fn fill_notification(report: &ExecutionReport) -> Option<FillNotification> {
+    if report.execution_type != "TRADE" || report.order_status != "FILLED" {
+        return None;
+    }
+
+    let price = report.average_price_f64();
+    let quantity = report.filled_qty_f64();
+
+    if report.is_buy() {
+        Some(FillNotification::Buy { price, quantity })
+    } else {
+        Some(FillNotification::Sell { price, quantity })
+    }
+}
+
+/// Consume the user data stream and notify on fills, after a REST catch-up
+///
+/// Returns `Ok(())` once `shutdown` is cancelled, or an error if the stream
+/// can't be opened or drops so the caller can fall back to polling.
+pub async fn run_user_stream(
+    client: &BinanceClient,
+    monitor: &OrderMonitor,
+    shutdown: &CancellationToken,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let listen_key = client.start_user_data_stream().await?;
+    let (mut socket, _) = connect_async(client.user_stream_url(&listen_key)).await?;
+    tracing::info!("⚡ Connected to Binance user data stream");
+    USER_STREAM.record_connected();
+
+    // Fills from before this connection (startup or a drop) only show up over REST;
+    // anything arriving meanwhile waits in the socket and is deduplicated by trade id
+    monitor.catch_up(client).await;
+
+    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+    keepalive.tick().await;
+
+    let result: Result<(), Box<dyn std::error::Error + Send + Sync>> = loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => break Ok(()),
+            _ = keepalive.tick() => {
+                match client.keepalive_user_data_stream(&listen_key).await {
+                    Ok(()) => USER_STREAM.record_keepalive(),
+                    Err(e) => tracing::warn!("listenKey keepalive failed: {}", e),
+                }
+            }
+            message = socket.next() => {
+                if let Some(Ok(_)) = message {
+                    USER_STREAM.record_message();
+                }
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        if let Some(report) = parse_execution_report(&text) {
+                            monitor.handle_execution_report(&report).await;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        break Err("user data stream closed".into());
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => break Err(e.into()),
+                }
+            }
+        }
+    };
+
+    USER_STREAM.record_disconnected();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execution_report(side: &str, status: &str) -> String {
+        report_with_type(side, "TRADE", status)
+    }
+
+    fn report_with_type(side: &str, execution_type: &str, status: &str) -> String {
+        format!(
+            r#"{{"e":"executionReport","E":1700000000000,"s":"BTCUSDT","c":"abc","S":"{}","o":"LIMIT","f":"GTC","q":"0.00200000","p":"50000.00","i":42,"x":"{}","X":"{}","l":"0.00200000","L":"50000.00","z":"0.00200000","Z":"100.00000000","t":7}}"#,
+            side, execution_type, status
+        )
+    }
+
+    #[test]
+    fn test_filled_buy_triggers_buy_notification() {
+        let report = parse_execution_report(&execution_report("BUY", "FILLED")).unwrap();
+        assert_eq!(report.order_id, 42);
+
+        assert_eq!(
+            fill_notification(&report),
+            Some(FillNotification::Buy {
+                price: 50000.0,
+                quantity: 0.002
+            })
+        );
+    }
+
+    #[test]
+    fn test_filled_sell_triggers_sell_notification() {
+        let report = parse_execution_report(&execution_report("SELL", "FILLED")).unwrap();
+
+        assert!(matches!(
+            fill_notification(&report),
+            Some(FillNotification::Sell { .. })
+        ));
+    }
+
+    #[test]
+    fn test_partial_fill_does_not_notify() {
+        let report =
+            parse_execution_report(&execution_report("BUY", "PARTIALLY_FILLED")).unwrap();
+        assert_eq!(fill_notification(&report), None);
+    }
+
+    #[test]
+    fn test_filled_status_without_a_trade_does_not_notify() {
+        // Only a TRADE execution carries a fill
+        let report =
+            parse_execution_report(&report_with_type("BUY", "REPLACED", "FILLED")).unwrap();
+        assert_eq!(fill_notification(&report), None);
+    }
+
+    #[test]
+    fn test_other_events_are_ignored() {
+        let text = r#"{"e":"outboundAccountPosition","E":1700000000000,"B":[]}"#;
+        assert!(parse_execution_report(text).is_none());
+    }
+}
@@ -0,0 +1,333 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::binance::{http_client, BinanceApiError, BinanceClient};
+use crate::config::Config;
+
+const COINBASE_BASE_URL: &str = "https://api.coinbase.com";
+const KRAKEN_BASE_URL: &str = "https://api.kraken.com";
+
+/// Where a spot price can be fetched from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    Binance,
+    Coinbase,
+    Kraken,
+}
+
+impl PriceSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PriceSource::Binance => "binance",
+            PriceSource::Coinbase => "coinbase",
+            PriceSource::Kraken => "kraken",
+        }
+    }
+}
+
+impl fmt::Display for PriceSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for PriceSource {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "binance" => Ok(PriceSource::Binance),
+            "coinbase" => Ok(PriceSource::Coinbase),
+            "kraken" => Ok(PriceSource::Kraken),
+            other => Err(format!("Unknown price source '{}'", other)),
+        }
+    }
+}
+
+/// Coinbase `/v2/prices/{pair}/spot` response
+#[derive(Debug, Deserialize)]
+struct CoinbaseSpot {
+    data: CoinbaseAmount,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinbaseAmount {
+    amount: String,
+}
+
+impl CoinbaseSpot {
+    fn price(&self) -> Result<f64, String> {
+        parse_price(&self.data.amount)
+    }
+}
+
+/// Kraken `/0/public/Ticker` response, keyed by Kraken's own pair name
+#[derive(Debug, Deserialize)]
+struct KrakenTicker {
+    error: Vec<String>,
+    #[serde(default)]
+    result: HashMap<String, KrakenPair>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KrakenPair {
+    /// Last trade closed: [price, lot volume]
+    c: Vec<String>,
+}
+
+impl KrakenTicker {
+    fn price(&self) -> Result<f64, String> {
+        if !self.error.is_empty() {
+            return Err(self.error.join(", "));
+        }
+        let pair = self
+            .result
+            .values()
+            .next()
+            .ok_or("Kraken returned no pairs")?;
+        let last = pair.c.first().ok_or("Kraken returned no last trade")?;
+        parse_price(last)
+    }
+}
+
+fn parse_price(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(price) if price.is_finite() && price > 0.0 => Ok(price),
+        _ => Err(format!("Invalid price '{}'", value)),
+    }
+}
+
+/// Split a Binance symbol into base and quote, treating dollar stablecoins as USD
+///
+/// Coinbase and Kraken quote the majors in USD, which is close enough to USDT for
+/// a fallback display price.
+fn split_symbol(symbol: &str) -> Option<(&str, &str)> {
+    ["USDT", "USDC", "USD"].iter().find_map(|quote| {
+        let base = symbol.strip_suffix(quote)?;
+        (!base.is_empty()).then_some((base, "USD"))
+    })
+}
+
+/// Every configured price source failed
+#[derive(Debug)]
+pub struct PriceUnavailable {
+    /// What Binance answered, when it was one of the sources tried
+    pub binance: Option<BinanceApiError>,
+    /// Each source's failure, labelled with the source
+    pub failures: Vec<String>,
+}
+
+impl fmt::Display for PriceUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No price source available ({})", self.failures.join("; "))
+    }
+}
+
+/// Fetches a spot price from the configured sources, in order
+pub struct PriceFeed {
+    sources: Vec<PriceSource>,
+    binance: BinanceClient,
+    http: reqwest::Client,
+    coinbase_url: String,
+    kraken_url: String,
+}
+
+impl PriceFeed {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            sources: config.price_sources.clone(),
+            binance: BinanceClient::new(config),
+            http: http_client(
+                config.http_connect_timeout_ms,
+                config.http_request_timeout_ms,
+            ),
+            coinbase_url: COINBASE_BASE_URL.to_string(),
+            kraken_url: KRAKEN_BASE_URL.to_string(),
+        }
+    }
+
+    /// Price from the first source that answers, and which source that was
+    pub async fn get_price(&self, symbol: &str) -> Result<(f64, PriceSource), PriceUnavailable> {
+        let mut binance = None;
+        let mut failures = Vec::new();
+
+        for (i, &source) in self.sources.iter().enumerate() {
+            let price = match source {
+                // Kept typed, so callers can answer with Binance's own status
+                PriceSource::Binance => self.binance.get_price(symbol).await.map_err(|e| {
+                    let message = e.to_string();
+                    binance = Some(e);
+                    message
+                }),
+                PriceSource::Coinbase => self.fetch_coinbase(symbol).await,
+                PriceSource::Kraken => self.fetch_kraken(symbol).await,
+            };
+            match price {
+                Ok(price) => {
+                    if i == 0 {
+                        tracing::debug!("{} price {} served by {}", symbol, price, source);
+                    } else {
+                        tracing::info!("{} price {} served by fallback {}", symbol, price, source);
+                    }
+                    return Ok((price, source));
+                }
+                Err(e) => {
+                    tracing::warn!("{} price from {} failed: {}", symbol, source, e);
+                    failures.push(format!("{}: {}", source, e));
+                }
+            }
+        }
+
+        Err(PriceUnavailable { binance, failures })
+    }
+
+    async fn fetch_coinbase(&self, symbol: &str) -> Result<f64, String> {
+        let (base, quote) = split_symbol(symbol).ok_or("Unsupported symbol")?;
+        let url = format!("{}/v2/prices/{}-{}/spot", self.coinbase_url, base, quote);
+
+        let spot: CoinbaseSpot = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        spot.price()
+    }
+
+    async fn fetch_kraken(&self, symbol: &str) -> Result<f64, String> {
+        let (base, quote) = split_symbol(symbol).ok_or("Unsupported symbol")?;
+        // Kraken still calls bitcoin XBT
+        let base = if base == "BTC" { "XBT" } else { base };
+        let url = format!("{}/0/public/Ticker", self.kraken_url);
+
+        let ticker: KrakenTicker = self
+            .http
+            .get(&url)
+            .query(&[("pair", format!("{}{}", base, quote))])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        ticker.price()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::{credentials, serve};
+    use axum::{http::StatusCode, routing::get, Json, Router};
+
+    #[test]
+    fn test_parse_price_source() {
+        assert_eq!("Binance".parse(), Ok(PriceSource::Binance));
+        assert_eq!(" kraken ".parse(), Ok(PriceSource::Kraken));
+        assert!("bitstamp".parse::<PriceSource>().is_err());
+    }
+
+    #[test]
+    fn test_split_symbol() {
+        assert_eq!(split_symbol("BTCUSDT"), Some(("BTC", "USD")));
+        assert_eq!(split_symbol("ETHUSDC"), Some(("ETH", "USD")));
+        assert_eq!(split_symbol("ETHBTC"), None);
+        assert_eq!(split_symbol("USDT"), None);
+    }
+
+    #[test]
+    fn test_coinbase_deserialization() {
+        let json = r#"{"data":{"amount":"95012.34","base":"BTC","currency":"USD"}}"#;
+        let spot: CoinbaseSpot = serde_json::from_str(json).unwrap();
+        assert_eq!(spot.price(), Ok(95012.34));
+    }
+
+    #[test]
+    fn test_kraken_deserialization() {
+        let json = r#"{
+            "error": [],
+            "result": {
+                "XXBTZUSD": {
+                    "a": ["95010.00000", "1", "1.000"],
+                    "b": ["95009.90000", "2", "2.000"],
+                    "c": ["95010.10000", "0.00050000"]
+                }
+            }
+        }"#;
+        let ticker: KrakenTicker = serde_json::from_str(json).unwrap();
+        assert_eq!(ticker.price(), Ok(95010.1));
+    }
+
+    #[test]
+    fn test_kraken_error_is_reported() {
+        let json = r#"{"error":["EQuery:Unknown asset pair"]}"#;
+        let ticker: KrakenTicker = serde_json::from_str(json).unwrap();
+        assert_eq!(ticker.price(), Err("EQuery:Unknown asset pair".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_when_first_source_fails() {
+        // Binance geo-blocks the request; Coinbase answers
+        let mock = Router::new()
+            .route(
+                "/api/v3/ticker/price",
+                get(|| async {
+                    (
+                        StatusCode::from_u16(451).unwrap(),
+                        Json(serde_json::json!({
+                            "code": 0,
+                            "msg": "Service unavailable from a restricted location"
+                        })),
+                    )
+                }),
+            )
+            .route(
+                "/v2/prices/BTC-USD/spot",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "data": { "amount": "95000.50", "base": "BTC", "currency": "USD" }
+                    }))
+                }),
+            );
+        let base_url = serve(mock).await;
+
+        let config = Config::for_tests();
+        let feed = PriceFeed {
+            sources: vec![
+                PriceSource::Binance,
+                PriceSource::Coinbase,
+                PriceSource::Kraken,
+            ],
+            binance: BinanceClient::from_credentials(&credentials(base_url)),
+            http: http_client(
+                config.http_connect_timeout_ms,
+                config.http_request_timeout_ms,
+            ),
+            coinbase_url: base_url.to_string(),
+            kraken_url: base_url.to_string(),
+        };
+
+        assert_eq!(
+            feed.get_price("BTCUSDT").await.unwrap(),
+            (95000.5, PriceSource::Coinbase)
+        );
+
+        // With nothing answering, every failure is reported
+        let error = PriceFeed {
+            sources: vec![PriceSource::Kraken],
+            ..feed
+        }
+        .get_price("BTCUSDT")
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("kraken"));
+        assert!(error.binance.is_none());
+    }
+}
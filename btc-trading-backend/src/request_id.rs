@@ -0,0 +1,171 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{self, header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Response header carrying the request id
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Error bodies larger than this are passed through without a request id
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Correlation id for a single request, available to handlers via extensions
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Tag each request with a UUID and emit one structured access log line
+///
+/// The id goes into the request's extensions (where `request_span` picks it
+/// up), is echoed in `X-Request-Id`, and is added to JSON error bodies so users
+/// can quote it in bug reports.
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = Uuid::new_v4().to_string();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    request
+        .extensions_mut()
+        .insert(RequestId(request_id.clone()));
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let status = response.status();
+
+    let mut response = if status.is_client_error() || status.is_server_error() {
+        add_request_id_to_error(response, &request_id).await
+    } else {
+        response
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+    }
+
+    tracing::info!(
+        target: "access_log",
+        request_id = %request_id,
+        method = %method,
+        path = %path,
+        status = status.as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        "request completed"
+    );
+
+    response
+}
+
+/// Span for the `TraceLayer`, tagged with the id `request_id_middleware` assigned
+pub fn request_span<B>(request: &http::Request<B>) -> tracing::Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map_or("-", |id| id.0.as_str());
+    tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path()
+    )
+}
+
+/// Insert `request_id` into a JSON object error body, leaving others untouched
+async fn add_request_id_to_error(response: Response, request_id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Failed to buffer error body: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut object)) => {
+            object.insert("request_id".to_string(), request_id.into());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(serde_json::Value::Object(object).to_string())
+        }
+        _ => Body::from(bytes),
+    };
+
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, middleware, routing::get, Json, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route(
+                "/fail",
+                get(|| async {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({ "error": "bad input" })),
+                    )
+                }),
+            )
+            .layer(middleware::from_fn(request_id_middleware))
+    }
+
+    async fn call(uri: &str) -> Response {
+        app()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_every_response_carries_request_id() {
+        for uri in ["/ok", "/fail", "/missing"] {
+            let response = call(uri).await;
+            let id = response
+                .headers()
+                .get(&REQUEST_ID_HEADER)
+                .unwrap_or_else(|| panic!("{} has no X-Request-Id", uri));
+            assert!(Uuid::parse_str(id.to_str().unwrap()).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_body_includes_request_id() {
+        let response = call("/fail").await;
+        let header_id = response.headers()[&REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let bytes = to_bytes(response.into_body(), MAX_ERROR_BODY_BYTES)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"], "bad input");
+        assert_eq!(body["request_id"], header_id);
+    }
+
+    #[tokio::test]
+    async fn test_success_body_is_untouched() {
+        let response = call("/ok").await;
+        let bytes = to_bytes(response.into_body(), MAX_ERROR_BODY_BYTES)
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"ok");
+    }
+}
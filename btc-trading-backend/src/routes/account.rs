@@ -1,25 +1,40 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::{HeaderMap, StatusCode},
     middleware,
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 
+use super::{binance_error_status, SymbolQuery};
 use crate::auth::auth_middleware;
-use crate::binance::{Balance, BinanceClient, Order};
+use crate::binance::{
+    Balance, BinanceApiError, BinanceClient, CommissionRates, MarketQuantity, NewOrderResponse,
+    Order,
+};
 use crate::config::Config;
-use crate::trading::{match_grid_pairs, GridPair};
+use crate::trading::{
+    base_asset, calculate_pnl, match_completed_pairs, match_grid_pairs, GridPair, PnlSummary,
+};
+use crate::trailing::{TrailingMonitor, TrailingOutcome};
 
-pub fn account_routes() -> Router<Config> {
+pub fn account_routes(
+    config: Arc<Config>,
+    trailing_monitor: Arc<TrailingMonitor>,
+) -> Router<Arc<Config>> {
     Router::new()
         .route("/balance", get(get_balance))
+        .route("/balances", get(get_all_balances))
+        .route("/allocation", get(get_allocation))
         .route("/orders", get(get_orders))
-        .route_layer(middleware::from_fn_with_state(
-            Config::from_env(),
-            auth_middleware,
-        ))
+        .route("/pnl", get(get_pnl))
+        .route("/fees", get(get_fees))
+        .route("/liquidate", post(liquidate))
+        .layer(axum::Extension(trailing_monitor))
+        .route_layer(middleware::from_fn_with_state(config, auth_middleware))
 }
 
 /// Extract use_production flag from X-Use-Production header
@@ -35,14 +50,19 @@ fn use_production_from_headers(headers: &HeaderMap) -> bool {
 pub struct BalanceResponse {
     usdt: BalanceInfo,
     btc: BalanceInfo,
+    #[serde(serialize_with = "crate::display::usd")]
     btc_value_usd: f64,
+    #[serde(serialize_with = "crate::display::usd")]
     total_usd: f64,
 }
 
 #[derive(Serialize)]
 pub struct BalanceInfo {
+    #[serde(serialize_with = "crate::display::asset")]
     free: f64,
+    #[serde(serialize_with = "crate::display::asset")]
     locked: f64,
+    #[serde(serialize_with = "crate::display::asset")]
     total: f64,
 }
 
@@ -53,13 +73,13 @@ pub struct ErrorResponse {
 
 /// Get account balance
 async fn get_balance(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
     headers: HeaderMap,
 ) -> Result<Json<BalanceResponse>, (StatusCode, Json<ErrorResponse>)> {
     let use_production = use_production_from_headers(&headers);
     let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
         (
-            StatusCode::BAD_REQUEST,
+            binance_error_status(&e),
             Json(ErrorResponse {
                 error: e.to_string(),
             }),
@@ -67,11 +87,12 @@ async fn get_balance(
     })?;
 
     // Get balance and current price concurrently
-    let (account_result, price_result) = tokio::join!(client.get_account(), client.get_price());
+    let (account_result, price_result) =
+        tokio::join!(client.get_account(), client.get_price("BTCUSDT"));
 
     let account = account_result.map_err(|e| {
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            binance_error_status(&e),
             Json(ErrorResponse {
                 error: e.to_string(),
             }),
@@ -122,42 +143,918 @@ async fn get_balance(
     }))
 }
 
+#[derive(Serialize)]
+pub struct AssetBalance {
+    asset: String,
+    free: f64,
+    locked: f64,
+    total: f64,
+    /// USD value, or None when the asset has no USDT market
+    usd_value: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct AllBalancesResponse {
+    balances: Vec<AssetBalance>,
+    total_usd: f64,
+}
+
+/// Value non-zero balances in USD using `<ASSET>USDT` prices
+///
+/// Assets without a USDT market are still listed but left unvalued and
+/// excluded from the total.
+fn value_balances(balances: &[Balance], prices: &HashMap<String, f64>) -> AllBalancesResponse {
+    let balances: Vec<AssetBalance> = balances
+        .iter()
+        .filter(|b| b.total() > 0.0)
+        .map(|b| {
+            let price = if b.asset == "USDT" {
+                Some(1.0)
+            } else {
+                prices.get(&format!("{}USDT", b.asset)).copied()
+            };
+
+            AssetBalance {
+                asset: b.asset.clone(),
+                free: b.free_f64(),
+                locked: b.locked_f64(),
+                total: b.total(),
+                usd_value: price.map(|p| b.total() * p),
+            }
+        })
+        .collect();
+
+    let total_usd = balances.iter().filter_map(|b| b.usd_value).sum();
+
+    AllBalancesResponse {
+        balances,
+        total_usd,
+    }
+}
+
+/// Get every non-zero balance valued in USD
+async fn get_all_balances(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+) -> Result<Json<AllBalancesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let use_production = use_production_from_headers(&headers);
+    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let (account_result, prices_result) =
+        tokio::join!(client.get_account(), client.get_all_prices());
+
+    let account = account_result.map_err(|e| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    // Missing prices only leave assets unvalued
+    let prices: HashMap<String, f64> = prices_result
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| {
+            let price = t.price_f64();
+            (t.symbol, price)
+        })
+        .collect();
+
+    Ok(Json(value_balances(&account.balances, &prices)))
+}
+
+#[derive(Serialize)]
+pub struct AssetAllocation {
+    asset: String,
+    #[serde(serialize_with = "crate::display::usd")]
+    usd_value: f64,
+    /// Share of the total value, 0-100
+    percent: f64,
+}
+
+#[derive(Serialize)]
+pub struct AllocationResponse {
+    /// BTC and USDT always, then other valued assets; largest first
+    allocations: Vec<AssetAllocation>,
+    #[serde(serialize_with = "crate::display::usd")]
+    total_usd: f64,
+}
+
+/// Split the valued balances into percentages of their total
+///
+/// Assets without a USDT market can't be valued, so they're left out. With
+/// nothing of value every share is 0.
+fn allocation(balances: &[Balance], prices: &HashMap<String, f64>) -> AllocationResponse {
+    let valued = value_balances(balances, prices);
+    let mut values: Vec<(String, f64)> = valued
+        .balances
+        .into_iter()
+        .filter_map(|b| Some((b.asset, b.usd_value?)))
+        .collect();
+    for asset in ["BTC", "USDT"] {
+        if !values.iter().any(|(a, _)| a == asset) {
+            values.push((asset.to_string(), 0.0));
+        }
+    }
+    values.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let total_usd = valued.total_usd;
+    let allocations = values
+        .into_iter()
+        .map(|(asset, usd_value)| AssetAllocation {
+            asset,
+            usd_value,
+            percent: if total_usd > 0.0 {
+                crate::display::round_to(usd_value / total_usd * 100.0, 2)
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    AllocationResponse {
+        allocations,
+        total_usd,
+    }
+}
+
+/// Get the share of total value held in each asset, BTC vs USDT first of all
+async fn get_allocation(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+) -> Result<Json<AllocationResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let use_production = use_production_from_headers(&headers);
+    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let (account_result, prices_result) =
+        tokio::join!(client.get_account(), client.get_all_prices());
+
+    let account = account_result.map_err(|e| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+    // Without prices the split would silently read 100% USDT
+    let prices: HashMap<String, f64> = prices_result
+        .map_err(|e| {
+            (
+                binance_error_status(&e),
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                }),
+            )
+        })?
+        .into_iter()
+        .map(|t| {
+            let price = t.price_f64();
+            (t.symbol, price)
+        })
+        .collect();
+
+    Ok(Json(allocation(&account.balances, &prices)))
+}
+
 #[derive(Serialize)]
 pub struct OrdersResponse {
     grid_pairs: Vec<GridPair>,
     unpaired_orders: Vec<Order>,
     total_orders: usize,
+    /// Open orders left out by the side, price or limit filters
+    filtered_out: usize,
+}
+
+/// Optional filters for `GET /account/orders`, applied before pairing
+#[derive(Debug, Default, Deserialize)]
+pub struct OpenOrdersQuery {
+    pub symbol: Option<String>,
+    /// "BUY" or "SELL"
+    pub side: Option<String>,
+    /// Inclusive lower bound on the limit price
+    pub min_price: Option<f64>,
+    /// Inclusive upper bound on the limit price
+    pub max_price: Option<f64>,
+    /// Keep at most this many orders, oldest first
+    pub limit: Option<usize>,
+}
+
+impl OpenOrdersQuery {
+    /// Keep the orders matching every filter, or explain which one is invalid
+    fn apply(&self, orders: Vec<Order>) -> Result<Vec<Order>, String> {
+        let side = match self.side.as_deref().map(str::to_uppercase) {
+            Some(side) if side != "BUY" && side != "SELL" => {
+                return Err("side must be BUY or SELL".to_string());
+            }
+            side => side,
+        };
+        if let (Some(min), Some(max)) = (self.min_price, self.max_price) {
+            if min > max {
+                return Err("min_price must not exceed max_price".to_string());
+            }
+        }
+        if self.limit == Some(0) {
+            return Err("limit must be at least 1".to_string());
+        }
+
+        let mut kept: Vec<Order> = orders
+            .into_iter()
+            .filter(|o| side.as_ref().is_none_or(|side| o.side == *side))
+            .filter(|o| self.min_price.is_none_or(|min| o.price_f64() >= min))
+            .filter(|o| self.max_price.is_none_or(|max| o.price_f64() <= max))
+            .collect();
+        if let Some(limit) = self.limit {
+            kept.sort_by_key(|o| o.time);
+            kept.truncate(limit);
+        }
+        Ok(kept)
+    }
 }
 
 /// Get open orders (matched into grid pairs)
 async fn get_orders(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
+    Query(query): Query<OpenOrdersQuery>,
     headers: HeaderMap,
 ) -> Result<Json<OrdersResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e }))
+    })?;
     let use_production = use_production_from_headers(&headers);
     let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
         (
-            StatusCode::BAD_REQUEST,
+            binance_error_status(&e),
             Json(ErrorResponse {
                 error: e.to_string(),
             }),
         )
     })?;
 
-    let orders = client.get_open_orders().await.map_err(|e| {
+    let orders = client.get_open_orders(&symbol).await.map_err(|e| {
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            binance_error_status(&e),
             Json(ErrorResponse {
                 error: e.to_string(),
             }),
         )
     })?;
 
-    let (pairs, unpaired) = match_grid_pairs(&orders);
+    let open = orders.len();
+    let orders = query.apply(orders).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e }))
+    })?;
+    let (pairs, unpaired) = match_grid_pairs(&orders, config.quantity_match_tolerance);
 
     Ok(Json(OrdersResponse {
         total_orders: orders.len(),
+        filtered_out: open - orders.len(),
         grid_pairs: pairs,
         unpaired_orders: unpaired,
     }))
 }
+
+/// Get realized plus unrealized profit
+async fn get_pnl(
+    State(config): State<Arc<Config>>,
+    Query(query): Query<SymbolQuery>,
+    headers: HeaderMap,
+) -> Result<Json<PnlSummary>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e }))
+    })?;
+    let use_production = use_production_from_headers(&headers);
+    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let (orders_result, trades_result, price_result) = tokio::join!(
+        client.get_open_orders(&symbol),
+        client.get_trades(&symbol, 100),
+        client.get_price(&symbol)
+    );
+
+    let to_error = |e: crate::binance::BinanceApiError| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    };
+    let orders = orders_result.map_err(to_error)?;
+    let trades = trades_result.map_err(to_error)?;
+    let market_price = price_result.map_err(to_error)?;
+
+    let completed = match_completed_pairs(&trades, config.quantity_match_tolerance);
+    let (open_pairs, _) = match_grid_pairs(&orders, config.quantity_match_tolerance);
+
+    Ok(Json(calculate_pnl(&completed, &open_pairs, market_price)))
+}
+
+#[derive(Serialize)]
+pub struct FeesResponse {
+    maker_fee: f64,
+    taker_fee: f64,
+    /// "binance" when the account's own rates were used, otherwise "config"
+    source: &'static str,
+}
+
+/// Prefer the account's rates from Binance, falling back to the configured ones
+fn fees_response(config: &Config, rates: Option<&CommissionRates>) -> FeesResponse {
+    let binance = rates.and_then(|r| r.maker_f64().zip(r.taker_f64()));
+
+    match binance {
+        Some((maker_fee, taker_fee)) => FeesResponse {
+            maker_fee,
+            taker_fee,
+            source: "binance",
+        },
+        None => FeesResponse {
+            maker_fee: config.maker_fee,
+            taker_fee: config.taker_fee,
+            source: "config",
+        },
+    }
+}
+
+/// Get the maker/taker fee rates used for estimates
+async fn get_fees(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+) -> Result<Json<FeesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let use_production = use_production_from_headers(&headers);
+    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    // The configured rates are a fine answer when Binance is unavailable
+    let rates = match client.get_account().await {
+        Ok(account) => account.commission_rates,
+        Err(e) => {
+            tracing::warn!("Account unavailable ({}), reporting configured fees", e);
+            None
+        }
+    };
+
+    Ok(Json(fees_response(&config, rates.as_ref())))
+}
+
+/// Phrase `POST /account/liquidate` requires, so a stray request can't sell
+const LIQUIDATE_CONFIRMATION: &str = "LIQUIDATE";
+
+/// Client tag on the liquidation sell, so it's recognizable in trade history
+const LIQUIDATE_TAG: &str = "liquidate";
+
+#[derive(Deserialize)]
+pub struct LiquidateRequest {
+    /// Must be exactly "LIQUIDATE"
+    #[serde(default)]
+    confirm: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FailedCancel {
+    order_id: i64,
+    error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LiquidateResponse {
+    symbol: String,
+    trailing_stopped: usize,
+    cancelled_order_ids: Vec<i64>,
+    /// Orders Binance refused to cancel; their quantity stays locked and unsold
+    failed_cancels: Vec<FailedCancel>,
+    /// Base asset sold at market, 0 when there was nothing to sell
+    sold_quantity: f64,
+    sell_order: Option<NewOrderResponse>,
+}
+
+/// Stop trailing orders, cancel open orders, then market-sell the free base asset
+///
+/// Trailing orders go first so the monitor can't re-place an order that was
+/// just cancelled. Orders already gone from the book count as cancelled; other
+/// cancel failures are reported without stopping the sell. Errors carry the
+/// status to answer with.
+async fn liquidate_symbol(
+    client: &BinanceClient,
+    trailing_monitor: &TrailingMonitor,
+    symbol: &str,
+) -> Result<LiquidateResponse, (StatusCode, String)> {
+    let failed = |e: BinanceApiError| (binance_error_status(&e), e.to_string());
+    let asset = base_asset(symbol)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("{} is not a USDT pair", symbol)))?;
+    // Looked up before anything is cancelled, so a missing step size changes nothing
+    let step_size = client.get_step_size(symbol).await.map_err(failed)?;
+    let trailing_stopped = trailing_monitor.remove_by_symbol(symbol, TrailingOutcome::Cancelled).await;
+
+    let open_orders = client.get_open_orders(symbol).await.map_err(failed)?;
+    let mut cancelled_order_ids = Vec::new();
+    let mut failed_cancels = Vec::new();
+    for order in open_orders {
+        match client.cancel_order(symbol, order.order_id).await {
+            Ok(_) | Err(BinanceApiError::UnknownOrder { .. }) => {
+                cancelled_order_ids.push(order.order_id)
+            }
+            Err(e) => failed_cancels.push(FailedCancel {
+                order_id: order.order_id,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    // Read the balance only now, once cancelled sells have released their quantity
+    let account = client.get_fresh_account().await.map_err(failed)?;
+    let free = account
+        .balances
+        .iter()
+        .find(|b| b.asset == asset)
+        .map(|b| b.free_f64())
+        .unwrap_or(0.0);
+
+    // At a price of 1.0 this just floors the balance to whole steps
+    let quantity = BinanceClient::calculate_quantity(free, 1.0, step_size);
+
+    let sell_order = if quantity > 0.0 {
+        let base = MarketQuantity::Base(quantity);
+        let order = client
+            .create_market_order(symbol, "SELL", base, Some(LIQUIDATE_TAG))
+            .await
+            .map_err(|e| {
+                let message = format!(
+                    "Cancelled {} orders but the sell failed: {}",
+                    cancelled_order_ids.len(),
+                    e
+                );
+                (binance_error_status(&e), message)
+            })?;
+        Some(order)
+    } else {
+        None
+    };
+
+    Ok(LiquidateResponse {
+        symbol: symbol.to_string(),
+        trailing_stopped,
+        cancelled_order_ids,
+        failed_cancels,
+        sold_quantity: if sell_order.is_some() { quantity } else { 0.0 },
+        sell_order,
+    })
+}
+
+/// Panic sell: flatten a symbol's position at market
+async fn liquidate(
+    State(config): State<Arc<Config>>,
+    axum::Extension(trailing_monitor): axum::Extension<Arc<TrailingMonitor>>,
+    Query(query): Query<SymbolQuery>,
+    headers: HeaderMap,
+    Json(request): Json<LiquidateRequest>,
+) -> Result<Json<LiquidateResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if request.confirm != LIQUIDATE_CONFIRMATION {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!(
+                    "Send \"confirm\": \"{}\" to sell everything at market",
+                    LIQUIDATE_CONFIRMATION
+                ),
+            }),
+        ));
+    }
+
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e }))
+    })?;
+    let use_production = use_production_from_headers(&headers);
+    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse {
+                error: e.to_string(),
+            }),
+        )
+    })?;
+
+    let summary = liquidate_symbol(&client, &trailing_monitor, &symbol)
+        .await
+        .map_err(|(status, error)| (status, Json(ErrorResponse { error })))?;
+
+    tracing::warn!(
+        "Liquidated {}: stopped {} trailing, cancelled {} orders, sold {}",
+        symbol,
+        summary.trailing_stopped,
+        summary.cancelled_order_ids.len(),
+        summary.sold_quantity
+    );
+
+    Ok(Json(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_client;
+    use crate::trailing::{OrderSide, TrailingOrder};
+    use std::sync::Mutex;
+
+    fn balance(asset: &str, free: &str, locked: &str) -> Balance {
+        Balance {
+            asset: asset.to_string(),
+            free: free.to_string(),
+            locked: locked.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_zero_balances_are_filtered() {
+        let balances = vec![
+            balance("BTC", "0.5", "0.1"),
+            balance("ETH", "0.00000000", "0.00000000"),
+            balance("USDT", "100", "0"),
+            balance("BNB", "0", "0"),
+        ];
+        let prices = HashMap::from([
+            ("BTCUSDT".to_string(), 50000.0),
+            ("ETHUSDT".to_string(), 3000.0),
+        ]);
+
+        let response = value_balances(&balances, &prices);
+        let assets: Vec<&str> = response.balances.iter().map(|b| b.asset.as_str()).collect();
+        assert_eq!(assets, vec!["BTC", "USDT"]);
+        assert!((response.total_usd - 30100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_asset_without_usdt_market_is_unvalued() {
+        let balances = vec![balance("XYZ", "10", "0"), balance("USDT", "5", "0")];
+
+        let response = value_balances(&balances, &HashMap::new());
+        assert_eq!(response.balances.len(), 2);
+        assert_eq!(response.balances[0].usd_value, None);
+        assert_eq!(response.total_usd, 5.0);
+    }
+
+    fn shares(response: &AllocationResponse) -> Vec<(&str, f64)> {
+        response
+            .allocations
+            .iter()
+            .map(|a| (a.asset.as_str(), a.percent))
+            .collect()
+    }
+
+    #[test]
+    fn test_allocation_splits_btc_and_usdt_by_value() {
+        // 0.12 BTC at 50000 is $6000 against $4000 USDT
+        let balances = vec![balance("USDT", "3000", "1000"), balance("BTC", "0.1", "0.02")];
+        let prices = HashMap::from([("BTCUSDT".to_string(), 50000.0)]);
+
+        let response = allocation(&balances, &prices);
+        assert_eq!(shares(&response), vec![("BTC", 60.0), ("USDT", 40.0)]);
+        assert!((response.total_usd - 10000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_allocation_includes_other_valued_assets() {
+        let balances = vec![
+            balance("BTC", "0.1", "0"),
+            balance("ETH", "1", "0"),
+            balance("XYZ", "10", "0"),
+        ];
+        let prices = HashMap::from([
+            ("BTCUSDT".to_string(), 50000.0),
+            ("ETHUSDT".to_string(), 3000.0),
+        ]);
+
+        let response = allocation(&balances, &prices);
+        assert_eq!(
+            shares(&response),
+            vec![("BTC", 62.5), ("ETH", 37.5), ("USDT", 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_allocation_of_empty_account_is_zero() {
+        let response = allocation(&[balance("BTC", "0", "0")], &HashMap::new());
+        assert_eq!(shares(&response), vec![("BTC", 0.0), ("USDT", 0.0)]);
+        assert_eq!(response.total_usd, 0.0);
+    }
+
+    #[test]
+    fn test_fees_prefer_binance_rates() {
+        let config = Config::for_tests();
+        let rates = CommissionRates {
+            maker: "0.00075000".to_string(),
+            taker: "0.00090000".to_string(),
+        };
+
+        let fees = fees_response(&config, Some(&rates));
+        assert_eq!(fees.source, "binance");
+        assert_eq!(fees.maker_fee, 0.00075);
+        assert_eq!(fees.taker_fee, 0.0009);
+    }
+
+    #[test]
+    fn test_fees_fall_back_to_config() {
+        let mut config = Config::for_tests();
+        config.maker_fee = 0.0008;
+        config.taker_fee = 0.0012;
+
+        let fees = fees_response(&config, None);
+        assert_eq!(fees.source, "config");
+        assert_eq!(fees.maker_fee, 0.0008);
+        assert_eq!(fees.taker_fee, 0.0012);
+    }
+
+    fn open_order(order_id: i64, side: &str, price: &str) -> Order {
+        serde_json::from_value(serde_json::json!({
+            "orderId": order_id,
+            "symbol": "BTCUSDT",
+            "side": side,
+            "type": "LIMIT",
+            "price": price,
+            "origQty": "0.00100",
+            "executedQty": "0",
+            "status": "NEW",
+            "time": 1700000000000i64 + order_id
+        }))
+        .unwrap()
+    }
+
+    fn order_ids(orders: &[Order]) -> Vec<i64> {
+        orders.iter().map(|o| o.order_id).collect()
+    }
+
+    #[test]
+    fn test_open_orders_filter_by_side() {
+        let orders = vec![
+            open_order(1, "BUY", "49000.00"),
+            open_order(2, "SELL", "51000.00"),
+            open_order(3, "BUY", "48000.00"),
+        ];
+
+        let query = OpenOrdersQuery {
+            side: Some("buy".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(order_ids(&query.apply(orders.clone()).unwrap()), vec![1, 3]);
+
+        let query = OpenOrdersQuery {
+            side: Some("SELL".to_string()),
+            limit: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(order_ids(&query.apply(orders.clone()).unwrap()), vec![2]);
+
+        let query = OpenOrdersQuery {
+            side: Some("HOLD".to_string()),
+            ..Default::default()
+        };
+        assert!(query.apply(orders).is_err());
+    }
+
+    #[test]
+    fn test_open_orders_filter_by_price_range() {
+        let orders = vec![
+            open_order(1, "BUY", "48000.00"),
+            open_order(2, "BUY", "49000.00"),
+            open_order(3, "SELL", "51000.00"),
+            open_order(4, "SELL", "52000.00"),
+        ];
+
+        let query = OpenOrdersQuery {
+            min_price: Some(49000.0),
+            max_price: Some(51000.0),
+            ..Default::default()
+        };
+        assert_eq!(order_ids(&query.apply(orders.clone()).unwrap()), vec![2, 3]);
+
+        let query = OpenOrdersQuery {
+            min_price: Some(50000.0),
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(order_ids(&query.apply(orders.clone()).unwrap()), vec![3]);
+
+        let query = OpenOrdersQuery {
+            min_price: Some(52000.0),
+            max_price: Some(48000.0),
+            ..Default::default()
+        };
+        assert!(query.apply(orders).is_err());
+    }
+
+    async fn mock_binance(free_btc: &'static str) -> (BinanceClient, Arc<Mutex<Vec<String>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let on_cancel = events.clone();
+        let on_account = events.clone();
+        let on_sell = events.clone();
+
+        let open_order = |order_id: i64, side: &str| {
+            serde_json::json!({
+                "orderId": order_id,
+                "symbol": "BTCUSDT",
+                "side": side,
+                "type": "LIMIT",
+                "price": "50000.00",
+                "origQty": "0.00100",
+                "executedQty": "0",
+                "status": "NEW",
+                "time": 1700000000000i64
+            })
+        };
+        let open_orders = serde_json::json!([open_order(11, "BUY"), open_order(12, "SELL")]);
+
+        let mock = Router::new()
+            .route(
+                "/api/v3/openOrders",
+                get(move || async move { Json(open_orders) }),
+            )
+            .route(
+                "/api/v3/exchangeInfo",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "symbols": [{
+                            "symbol": "BTCUSDT",
+                            "filters": [{ "filterType": "LOT_SIZE", "stepSize": "0.00001000" }]
+                        }]
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/account",
+                get(move || async move {
+                    on_account.lock().unwrap().push("account".to_string());
+                    Json(serde_json::json!({
+                        "balances": [
+                            { "asset": "BTC", "free": free_btc, "locked": "0" },
+                            { "asset": "USDT", "free": "100", "locked": "0" }
+                        ],
+                        "canTrade": true,
+                        "canWithdraw": true,
+                        "canDeposit": true
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/order",
+                post(move |body: String| async move {
+                    let quantity = param(&body, "quantity");
+                    on_sell.lock().unwrap().push(format!("sell {}", quantity));
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": 99,
+                        "clientOrderId": "liquidate",
+                        "transactTime": 1700000000000i64,
+                        "price": "0.00",
+                        "origQty": quantity,
+                        "executedQty": quantity,
+                        "status": "FILLED",
+                        "type": "MARKET",
+                        "side": "SELL"
+                    }))
+                })
+                .delete(move |uri: axum::http::Uri| async move {
+                    let order_id = param(uri.query().unwrap_or_default(), "orderId");
+                    on_cancel.lock().unwrap().push(format!("cancel {}", order_id));
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": order_id.parse::<i64>().unwrap(),
+                        "status": "CANCELED"
+                    }))
+                }),
+            );
+
+        let client = mock_client(mock).await;
+
+        (client, events)
+    }
+
+    fn param(query: &str, name: &str) -> String {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(&format!("{}=", name)))
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    async fn monitor_with_trailing() -> TrailingMonitor {
+        let monitor = TrailingMonitor::new(Config::for_tests());
+        for symbol in ["BTCUSDT", "ETHUSDT"] {
+            let order = TrailingOrder::new(
+                12,
+                symbol.to_string(),
+                OrderSide::Sell,
+                1.0,
+                50000.0,
+                0.001,
+                false,
+            );
+            monitor.add_order(order).await;
+        }
+        monitor
+    }
+
+    #[tokio::test]
+    async fn test_liquidate_cancels_before_selling() {
+        let (client, events) = mock_binance("0.12345678").await;
+        let monitor = monitor_with_trailing().await;
+
+        let summary = liquidate_symbol(&client, &monitor, "BTCUSDT").await.unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["cancel 11", "cancel 12", "account", "sell 0.12345"]
+        );
+        assert_eq!(summary.trailing_stopped, 1);
+        assert_eq!(monitor.get_all_orders().await.len(), 1);
+        assert_eq!(summary.cancelled_order_ids, vec![11, 12]);
+        assert!(summary.failed_cancels.is_empty());
+        assert_eq!(summary.sold_quantity, 0.12345);
+        assert_eq!(summary.sell_order.unwrap().order_id, 99);
+    }
+
+    #[tokio::test]
+    async fn test_liquidate_without_balance_sells_nothing() {
+        let (client, events) = mock_binance("0.00000900").await;
+        let monitor = TrailingMonitor::new(Config::for_tests());
+
+        let summary = liquidate_symbol(&client, &monitor, "BTCUSDT").await.unwrap();
+
+        // Dust below one step can't be sold, but the orders are still cancelled
+        assert_eq!(*events.lock().unwrap(), vec!["cancel 11", "cancel 12", "account"]);
+        assert_eq!(summary.trailing_stopped, 0);
+        assert_eq!(summary.cancelled_order_ids, vec![11, 12]);
+        assert_eq!(summary.sold_quantity, 0.0);
+        assert!(summary.sell_order.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_liquidate_requires_confirmation() {
+        let monitor = Arc::new(TrailingMonitor::new(Config::for_tests()));
+        let (status, Json(body)) = liquidate(
+            State(Arc::new(Config::for_tests())),
+            axum::Extension(monitor),
+            Query(SymbolQuery { symbol: None }),
+            HeaderMap::new(),
+            Json(LiquidateRequest {
+                confirm: "yes".to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.error.contains("LIQUIDATE"));
+    }
+
+    #[tokio::test]
+    async fn test_liquidate_without_production_keys_is_unavailable() {
+        let monitor = Arc::new(TrailingMonitor::new(Config::for_tests()));
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Use-Production", "true".parse().unwrap());
+
+        let (status, Json(body)) = liquidate(
+            State(Arc::new(Config::for_tests())),
+            axum::Extension(monitor),
+            Query(SymbolQuery { symbol: None }),
+            headers,
+            Json(LiquidateRequest {
+                confirm: LIQUIDATE_CONFIRMATION.to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+
+        // The server's missing keys aren't the client's to fix
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.error.contains("Production keys"), "{}", body.error);
+    }
+}
@@ -5,37 +5,45 @@ use axum::{
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
 
+use super::ErrorResponse;
 use crate::auth::{create_token, validate_token};
 use crate::config::Config;
 
-pub fn auth_routes() -> Router<Config> {
+pub fn auth_routes() -> Router<Arc<Config>> {
     Router::new()
         .route("/login", post(login))
         .route("/refresh", post(refresh_token))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct LoginRequest {
     device_id: String,
     device_name: String,
     app_secret: String, // Shared secret embedded in app
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct LoginResponse {
     token: String,
     expires_in: i64, // seconds
 }
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    error: String,
-}
-
 /// Login endpoint - authenticates device and returns JWT
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Token issued", body = LoginResponse),
+        (status = 401, description = "Invalid app secret", body = ErrorResponse),
+    )
+)]
 async fn login(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
     Json(request): Json<LoginRequest>,
 ) -> Result<Json<LoginResponse>, (StatusCode, Json<ErrorResponse>)> {
     // Verify app secret
@@ -43,9 +51,7 @@ async fn login(
         tracing::warn!("Invalid app secret from device: {}", request.device_id);
         return Err((
             StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Invalid credentials".to_string(),
-            }),
+            Json(ErrorResponse::new("Invalid credentials")),
         ));
     }
 
@@ -55,6 +61,8 @@ async fn login(
         &request.device_name,
         &config.jwt_secret,
         config.jwt_expiry_minutes,
+        &config.jwt_issuer,
+        &config.jwt_audience,
     ) {
         Ok(token) => {
             tracing::info!("Login successful for device: {}", request.device_name);
@@ -67,33 +75,44 @@ async fn login(
             tracing::error!("Failed to create token: {:?}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "Failed to create token".to_string(),
-                }),
+                Json(ErrorResponse::new("Failed to create token")),
             ))
         }
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct RefreshRequest {
     token: String,
 }
 
 /// Refresh token endpoint - exchanges valid token for a new one
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "Token refreshed", body = LoginResponse),
+        (status = 401, description = "Invalid or expired token", body = ErrorResponse),
+    )
+)]
 async fn refresh_token(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
     Json(request): Json<RefreshRequest>,
 ) -> Result<Json<LoginResponse>, (StatusCode, Json<ErrorResponse>)> {
     // Validate existing token
-    let claims = match validate_token(&request.token, &config.jwt_secret) {
+    let claims = match validate_token(
+        &request.token,
+        &config.jwt_secret,
+        &config.jwt_issuer,
+        &config.jwt_audience,
+    ) {
         Ok(c) => c,
         Err(_) => {
             return Err((
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "Invalid token".to_string(),
-                }),
+                Json(ErrorResponse::new("Invalid token")),
             ));
         }
     };
@@ -104,6 +123,8 @@ async fn refresh_token(
         &claims.device_name,
         &config.jwt_secret,
         config.jwt_expiry_minutes,
+        &config.jwt_issuer,
+        &config.jwt_audience,
     ) {
         Ok(token) => Ok(Json(LoginResponse {
             token,
@@ -111,9 +132,7 @@ async fn refresh_token(
         })),
         Err(_) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "Failed to refresh token".to_string(),
-            }),
+            Json(ErrorResponse::new("Failed to refresh token")),
         )),
     }
 }
@@ -1,15 +1,72 @@
 use axum::{
-    routing::get,
+    extract::{FromRef, Query, State},
+    http::{HeaderMap, StatusCode},
+    middleware,
+    routing::{get, post},
     Json, Router,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
 
+use super::{binance_error_status, SymbolQuery};
+use crate::auth::auth_middleware;
+use crate::binance::{
+    http_client, normalize_client_tag, BinanceClient, EnvironmentBreakerSnapshot, StatsSnapshot,
+    Trade, BREAKER, STATS,
+};
 use crate::config::Config;
+use crate::metrics::{PushSnapshot, METRICS};
+use crate::notifications::{
+    simulated_trade, FillNotification, OrderMonitor, StreamSnapshot, BOOK_STREAM, USER_STREAM,
+};
+
+/// Leading signature characters `/debug/sign-preview` reveals
+const SIGNATURE_PREVIEW_CHARS: usize = 8;
+
+/// Parameters `build_signed_query` appends itself
+const RESERVED_SIGNING_PARAMS: [&str; 3] = ["timestamp", "recvWindow", "signature"];
+
+/// State for the debug routes that reach into the order monitor
+#[derive(Clone)]
+pub struct DebugAppState {
+    pub config: Arc<Config>,
+    pub order_monitor: Arc<OrderMonitor>,
+}
+
+impl FromRef<DebugAppState> for Arc<Config> {
+    fn from_ref(state: &DebugAppState) -> Self {
+        state.config.clone()
+    }
+}
+
+pub fn debug_routes(config: Arc<Config>, order_monitor: Arc<OrderMonitor>) -> Router<Arc<Config>> {
+    let state = DebugAppState {
+        config: config.clone(),
+        order_monitor,
+    };
+    let protected = Router::new()
+        .route("/simulate-fill", post(simulate_fill))
+        .route("/sign-preview", post(preview_signature))
+        .route_layer(middleware::from_fn_with_state(config, auth_middleware))
+        .with_state(state);
 
-pub fn debug_routes() -> Router<Config> {
     Router::new()
         .route("/outbound-ip", get(get_outbound_ip))
         .route("/health", get(health_check))
+        .route("/ready", get(readiness_check))
+        .route("/stats", get(get_stats))
+        .route("/streams", get(get_streams))
+        .merge(protected)
+}
+
+/// Extract use_production flag from X-Use-Production header
+fn use_production_from_headers(headers: &HeaderMap) -> bool {
+    headers
+        .get("X-Use-Production")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
 }
 
 #[derive(Serialize)]
@@ -18,13 +75,86 @@ pub struct OutboundIpResponse {
     pub message: String,
 }
 
+#[derive(Serialize)]
+pub struct DebugStatsResponse {
+    /// Binance counters stay at the top level for existing consumers
+    #[serde(flatten)]
+    pub binance: StatsSnapshot,
+    pub push: PushSnapshot,
+}
+
+#[derive(Serialize)]
+pub struct StreamsResponse {
+    pub streams: Vec<StreamSnapshot>,
+}
+
+#[derive(Deserialize)]
+pub struct SimulateFillRequest {
+    pub side: String,
+    pub price: f64,
+    pub quantity: f64,
+    /// Tag to label the notification with, as if the order carried it
+    pub client_tag: Option<String>,
+    /// Place the auto-sell a real BUY would get, a real SELL on Binance;
+    /// off by default so a simulated fill only notifies
+    #[serde(default)]
+    pub auto_sell: bool,
+}
+
+#[derive(Serialize)]
+pub struct SimulateFillResponse {
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+#[derive(Deserialize)]
+pub struct SignPreviewRequest {
+    /// Parameters as [name, value] pairs, in the order they'd be sent
+    #[serde(default)]
+    pub params: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignPreviewResponse {
+    /// Exactly what was signed: the params, then timestamp and recvWindow
+    pub query: String,
+    /// Enough of the signature to compare against one computed locally
+    pub signature_prefix: String,
+    pub signature_length: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
 #[derive(Serialize)]
 pub struct HealthResponse {
     pub status: String,
+    /// Testnet and production trip separately
+    pub circuit_breaker: EnvironmentBreakerSnapshot,
+}
+
+#[derive(Serialize)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub status: String,
+    pub latency_ms: u128,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    pub status: String,
+    pub dependencies: Vec<DependencyStatus>,
 }
 
 /// Get the outbound IP that this server uses when making external requests
-async fn get_outbound_ip() -> Json<OutboundIpResponse> {
+async fn get_outbound_ip(State(config): State<Arc<Config>>) -> Json<OutboundIpResponse> {
+    let client = http_client(config.http_connect_timeout_ms, config.http_request_timeout_ms);
+
     // Try multiple IP checking services
     let services = [
         "https://api.ipify.org",
@@ -33,7 +163,7 @@ async fn get_outbound_ip() -> Json<OutboundIpResponse> {
     ];
 
     for service in services {
-        match reqwest::get(service).await {
+        match client.get(service).send().await {
             Ok(response) => {
                 if let Ok(ip) = response.text().await {
                     let ip = ip.trim().to_string();
@@ -53,9 +183,348 @@ async fn get_outbound_ip() -> Json<OutboundIpResponse> {
     })
 }
 
-/// Simple health check
+/// Simple health check, including whether trading is paused
 async fn health_check() -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok".to_string(),
+        circuit_breaker: BREAKER.snapshot(),
+    })
+}
+
+/// Binance request counts and estimated weight usage, plus push delivery counts
+async fn get_stats() -> Json<DebugStatsResponse> {
+    Json(DebugStatsResponse {
+        binance: STATS.snapshot(),
+        push: METRICS.push_snapshot(),
+    })
+}
+
+/// State of each background socket, to see why real-time fills stopped arriving
+/// or why market orders fall back to REST for the book
+///
+/// Ticker prices are still polled over REST.
+async fn get_streams() -> Json<StreamsResponse> {
+    Json(StreamsResponse {
+        streams: vec![USER_STREAM.snapshot(), BOOK_STREAM.snapshot()],
     })
 }
+
+/// Build the synthetic trade for a simulate-fill request
+fn simulated_fill_trade(symbol: &str, request: &SimulateFillRequest) -> Result<Trade, String> {
+    let is_buyer = match request.side.to_uppercase().as_str() {
+        "BUY" => true,
+        "SELL" => false,
+        _ => return Err("Side must be BUY or SELL".to_string()),
+    };
+    if !(request.price.is_finite() && request.price > 0.0) {
+        return Err("Price must be positive".to_string());
+    }
+    if !(request.quantity.is_finite() && request.quantity > 0.0) {
+        return Err("Quantity must be positive".to_string());
+    }
+
+    let mut trade = simulated_trade(symbol, is_buyer, request.price, request.quantity);
+    trade.client_tag = request.client_tag.as_deref().and_then(normalize_client_tag);
+    Ok(trade)
+}
+
+/// 404 unless DEBUG_ENDPOINTS is set
+fn ensure_debug_endpoints(config: &Config) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if !config.debug_endpoints {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: "Debug endpoints are disabled".to_string(),
+            }),
+        ));
+    }
+    Ok(())
+}
+
+/// Preview of a signed query with the signature cut down to its prefix
+fn sign_preview(signed_query: &str) -> SignPreviewResponse {
+    let (query, signature) = signed_query
+        .rsplit_once("&signature=")
+        .unwrap_or((signed_query, ""));
+
+    SignPreviewResponse {
+        query: query.to_string(),
+        signature_prefix: signature.chars().take(SIGNATURE_PREVIEW_CHARS).collect(),
+        signature_length: signature.len(),
+    }
+}
+
+/// Show what gets signed for `params` (requires DEBUG_ENDPOINTS)
+///
+/// For chasing -1022 "Signature invalid": compare `query` and the signature
+/// prefix with what the integrator signs. Neither the secret nor the full
+/// signature is returned.
+async fn preview_signature(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    Json(request): Json<SignPreviewRequest>,
+) -> Result<Json<SignPreviewResponse>, (StatusCode, Json<ErrorResponse>)> {
+    ensure_debug_endpoints(&config)?;
+
+    if let Some((name, _)) = request
+        .params
+        .iter()
+        .find(|(name, _)| RESERVED_SIGNING_PARAMS.contains(&name.as_str()))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("{} is added by the signer; leave it out of params", name),
+            }),
+        ));
+    }
+
+    let use_production = use_production_from_headers(&headers);
+    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse { error: e.to_string() }))
+    })?;
+
+    let params: Vec<(&str, &str)> = request
+        .params
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    Ok(Json(sign_preview(&client.preview_signed_query(&params))))
+}
+
+/// Inject a synthetic fill into the order monitor (requires DEBUG_ENDPOINTS)
+///
+/// Sends the same push notification a real fill would, and the auto-sell
+/// only when the request asks for it.
+async fn simulate_fill(
+    State(state): State<DebugAppState>,
+    Query(query): Query<SymbolQuery>,
+    Json(request): Json<SimulateFillRequest>,
+) -> Result<Json<SimulateFillResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let config = &state.config;
+    ensure_debug_endpoints(config)?;
+
+    let trade = config
+        .resolve_symbol(query.symbol.as_deref())
+        .and_then(|symbol| simulated_fill_trade(&symbol, &request))
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })))?;
+
+    let notification = state.order_monitor.simulate_fill(&trade, request.auto_sell).await;
+    let (side, price, quantity) = match notification {
+        FillNotification::Buy { price, quantity } => ("BUY", price, quantity),
+        FillNotification::Sell { price, quantity } => ("SELL", price, quantity),
+    };
+
+    Ok(Json(SimulateFillResponse {
+        symbol: trade.symbol,
+        side: side.to_string(),
+        price,
+        quantity,
+    }))
+}
+
+/// Probe a Binance environment by fetching its server time
+async fn probe_binance(name: &str, client: &BinanceClient) -> DependencyStatus {
+    let started = Instant::now();
+    let result = client.get_server_time().await;
+    let latency_ms = started.elapsed().as_millis();
+
+    match result {
+        Ok(_) => DependencyStatus {
+            name: name.to_string(),
+            status: "up".to_string(),
+            latency_ms,
+            error: None,
+        },
+        Err(e) => DependencyStatus {
+            name: name.to_string(),
+            status: "down".to_string(),
+            latency_ms,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Overall readiness: degraded (503) if any dependency is down
+fn readiness(dependencies: Vec<DependencyStatus>) -> (StatusCode, ReadinessResponse) {
+    let all_up = dependencies.iter().all(|d| d.status == "up");
+    let (code, status) = if all_up {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded")
+    };
+
+    (
+        code,
+        ReadinessResponse {
+            status: status.to_string(),
+            dependencies,
+        },
+    )
+}
+
+/// Readiness check - probes every configured Binance environment
+async fn readiness_check(
+    State(config): State<Arc<Config>>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let testnet = BinanceClient::new(&config);
+    let production = BinanceClient::for_environment(&config, true).ok();
+
+    let (testnet_status, production_status) = tokio::join!(
+        probe_binance("binance_testnet", &testnet),
+        async {
+            match &production {
+                Some(client) => Some(probe_binance("binance_production", client).await),
+                None => None,
+            }
+        }
+    );
+
+    let mut dependencies = vec![testnet_status];
+    dependencies.extend(production_status);
+
+    let (code, response) = readiness(dependencies);
+    (code, Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_client;
+
+    fn fill_request(side: &str, price: f64, quantity: f64) -> SimulateFillRequest {
+        SimulateFillRequest {
+            side: side.to_string(),
+            price,
+            quantity,
+            client_tag: None,
+            auto_sell: false,
+        }
+    }
+
+    #[test]
+    fn test_simulated_fill_trade() {
+        let trade = simulated_fill_trade("BTCUSDT", &fill_request("buy", 50000.0, 0.002)).unwrap();
+        assert!(trade.is_buyer);
+        assert_eq!(trade.price_f64(), 50000.0);
+        assert_eq!(trade.quantity_f64(), 0.002);
+
+        let trade = simulated_fill_trade("BTCUSDT", &fill_request("SELL", 51000.0, 0.002)).unwrap();
+        assert!(!trade.is_buyer);
+        assert_eq!(trade.client_tag, None);
+
+        let mut request = fill_request("BUY", 50000.0, 0.002);
+        request.client_tag = Some("grid A".to_string());
+        let trade = simulated_fill_trade("BTCUSDT", &request).unwrap();
+        assert_eq!(trade.client_tag.as_deref(), Some("grid_A"));
+    }
+
+    #[test]
+    fn test_simulated_fill_rejects_invalid_input() {
+        for request in [
+            fill_request("HOLD", 50000.0, 0.002),
+            fill_request("BUY", 0.0, 0.002),
+            fill_request("BUY", f64::NAN, 0.002),
+            fill_request("BUY", 50000.0, -1.0),
+        ] {
+            assert!(simulated_fill_trade("BTCUSDT", &request).is_err());
+        }
+    }
+
+    fn sign_preview_request(params: &[(&str, &str)]) -> Json<SignPreviewRequest> {
+        Json(SignPreviewRequest {
+            params: params
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_sign_preview_cuts_signature_to_prefix() {
+        let signature = "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71";
+        let preview = sign_preview(&format!("symbol=BTCUSDT&timestamp=1&signature={}", signature));
+
+        assert_eq!(preview.query, "symbol=BTCUSDT&timestamp=1");
+        assert_eq!(preview.signature_prefix, "c8db5682");
+        assert_eq!(preview.signature_length, 64);
+    }
+
+    #[tokio::test]
+    async fn test_sign_preview_never_returns_secret_or_signature() {
+        let mut config = Config::for_tests();
+        config.debug_endpoints = true;
+        let secret = config.binance_testnet_secret_key.clone();
+        let config = Arc::new(config);
+
+        let request = sign_preview_request(&[("symbol", "BTCUSDT"), ("side", "BUY")]);
+        let Json(preview) = preview_signature(State(config), HeaderMap::new(), request)
+            .await
+            .unwrap();
+        assert!(preview.query.starts_with("symbol=BTCUSDT&side=BUY&timestamp="));
+        assert!(preview.query.contains("&recvWindow="));
+        assert_eq!(preview.signature_length, 64);
+
+        // The full HMAC is 64 hex characters; nothing that long may come back
+        let body = serde_json::to_string(&preview).unwrap();
+        assert!(!body.contains(&secret), "{}", body);
+        assert!(!body.contains("signature="), "{}", body);
+        let longest_hex_run = body
+            .split(|c: char| !c.is_ascii_hexdigit())
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        assert!(longest_hex_run < 64, "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_sign_preview_requires_debug_endpoints_and_rejects_reserved_params() {
+        let mut config = Config::for_tests();
+        let request = sign_preview_request(&[("symbol", "BTCUSDT")]);
+        let disabled = State(Arc::new(config.clone()));
+        let (status, _) = preview_signature(disabled, HeaderMap::new(), request)
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        config.debug_endpoints = true;
+        let request = sign_preview_request(&[("timestamp", "1")]);
+        let (status, _) = preview_signature(State(Arc::new(config)), HeaderMap::new(), request)
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_degraded_when_binance_returns_500() {
+        let router = Router::new().route(
+            "/api/v3/time",
+            get(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "boom") }),
+        );
+        let client = mock_client(router).await;
+
+        let dependency = probe_binance("binance_testnet", &client).await;
+        assert_eq!(dependency.status, "down");
+        assert!(dependency.error.is_some());
+
+        let (code, response) = readiness(vec![dependency]);
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.status, "degraded");
+    }
+
+    #[tokio::test]
+    async fn test_ok_when_binance_responds() {
+        let router = Router::new().route(
+            "/api/v3/time",
+            get(|| async { r#"{"serverTime":1700000000000}"# }),
+        );
+        let client = mock_client(router).await;
+
+        let dependency = probe_binance("binance_testnet", &client).await;
+        assert_eq!(dependency.status, "up");
+
+        let (code, response) = readiness(vec![dependency]);
+        assert_eq!(code, StatusCode::OK);
+        assert_eq!(response.status, "ok");
+    }
+}
@@ -1,26 +1,66 @@
 use axum::{
-    extract::{Path, State},
+    extract::{FromRef, Path, Query, State},
     http::{HeaderMap, StatusCode},
     middleware,
     routing::{delete, post},
     Json, Router,
 };
 use serde::Serialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
 
-use crate::auth::auth_middleware;
-use crate::binance::{BinanceClient, NewOrderResponse};
+use super::{
+    allow_large_order_from_headers, binance_error_status, ensure_trading_allowed,
+    normalize_precision, symbol_error, ErrorResponse, ProductionHeader, SymbolQuery,
+    ValidationError,
+};
+use crate::auth::{auth_middleware, AuthedDevice};
+use crate::binance::{
+    BinanceApiError, BinanceClient, CancelOrderResponse, NewOrderResponse, SymbolPrecision,
+};
 use crate::config::Config;
-use crate::trading::{CreateGridRequest, ModifyOrderRequest};
+use crate::trading::{
+    check_order_size, estimate_grid_profit, match_grid_pairs, recenter_grid, CreateGridRequest,
+    GridPair, GridProfitEstimate, ModifyOrderRequest, KILL_SWITCH,
+};
+use crate::trailing::{TrailingMonitor, TrailingOutcome};
+
+/// State for grid routes that includes trailing monitor
+#[derive(Clone)]
+pub struct GridAppState {
+    pub config: Arc<Config>,
+    pub trailing_monitor: Arc<TrailingMonitor>,
+}
+
+impl FromRef<GridAppState> for Arc<Config> {
+    fn from_ref(state: &GridAppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<GridAppState> for Arc<TrailingMonitor> {
+    fn from_ref(state: &GridAppState) -> Self {
+        state.trailing_monitor.clone()
+    }
+}
+
+pub fn grid_routes(
+    config: Arc<Config>,
+    trailing_monitor: Arc<TrailingMonitor>,
+) -> Router<Arc<Config>> {
+    let state = GridAppState {
+        config: config.clone(),
+        trailing_monitor,
+    };
 
-pub fn grid_routes() -> Router<Config> {
     Router::new()
         .route("/create", post(create_grid_pair))
+        .route("/estimate", post(estimate_grid_pair))
         .route("/modify", post(modify_order))
+        .route("/rebalance", post(rebalance_grid))
         .route("/:order_id", delete(cancel_order))
-        .route_layer(middleware::from_fn_with_state(
-            Config::from_env(),
-            auth_middleware,
-        ))
+        .route_layer(middleware::from_fn_with_state(config, auth_middleware))
+        .with_state(state)
 }
 
 /// Extract use_production flag from X-Use-Production header
@@ -32,183 +72,1137 @@ fn use_production_from_headers(headers: &HeaderMap) -> bool {
         .unwrap_or(false)
 }
 
-#[derive(Serialize)]
+/// Extract X-Strict-Precision header: reject over-precise prices instead of rounding them
+fn strict_precision_from_headers(headers: &HeaderMap) -> bool {
+    headers
+        .get("X-Strict-Precision")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct GridPairResponse {
     buy_order: NewOrderResponse,
     sell_order: NewOrderResponse,
+    #[serde(serialize_with = "crate::display::usd")]
     estimated_profit_usd: f64,
     estimated_profit_percent: f64,
+    /// Estimated profit after buy and sell commissions
+    #[serde(serialize_with = "crate::display::usd")]
+    net_profit_usd: f64,
 }
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    error: String,
+/// 400 for a rejected field, keeping its message as the top-level `error`
+fn validation_error(error: ValidationError) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse {
+            error: error.message.clone(),
+            errors: vec![error],
+        }),
+    )
 }
 
-/// Create a new grid pair (BUY + SELL orders)
-async fn create_grid_pair(
-    State(config): State<Config>,
-    headers: HeaderMap,
-    Json(request): Json<CreateGridRequest>,
-) -> Result<Json<GridPairResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Validate request
+/// Validate a grid request and return its profit estimate
+///
+/// Rejects grids whose spread doesn't cover commissions on both sides, or whose
+/// amount doesn't buy a single `step_size` of BTC.
+fn validate_grid_request(
+    request: &CreateGridRequest,
+    fee_rate: f64,
+    step_size: f64,
+) -> Result<GridProfitEstimate, ValidationError> {
     if request.buy_price >= request.sell_price {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Buy price must be less than sell price".to_string(),
-            }),
+        return Err(ValidationError::new(
+            "buy_price",
+            "must_be_less_than_sell_price",
+            "Buy price must be less than sell price",
         ));
     }
 
     if request.amount_usd < 1.0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Minimum amount is $1".to_string(),
-            }),
+        return Err(ValidationError::new("amount_usd", "below_minimum", "Minimum amount is $1"));
+    }
+
+    let estimate = estimate_grid_profit(
+        request.buy_price,
+        request.sell_price,
+        request.amount_usd,
+        fee_rate,
+        step_size,
+    );
+
+    if estimate.quantity <= 0.0 {
+        return Err(ValidationError::new(
+            "amount_usd",
+            "below_quantity_step",
+            "Amount is too small for the minimum quantity step",
         ));
     }
 
+    if estimate.net_profit_usd < 0.0 {
+        return Err(ValidationError::new(
+            "sell_price",
+            "spread_below_fees",
+            format!(
+                "Spread too tight: estimated net profit ${:.4} after ${:.4} commission",
+                estimate.net_profit_usd, estimate.commission_usd
+            ),
+        ));
+    }
+
+    Ok(estimate)
+}
+
+/// Round both grid prices to the symbol's tick size
+///
+/// The quantity needs no normalizing: it's derived from `amount_usd` and
+/// floored to the step size already.
+fn normalize_grid_prices(
+    request: &mut CreateGridRequest,
+    precision: &SymbolPrecision,
+    strict: bool,
+) -> Result<(), ValidationError> {
+    let tick = precision.tick_size;
+    request.buy_price = normalize_precision(
+        "buy_price",
+        request.buy_price,
+        precision.round_price(request.buy_price),
+        tick,
+        strict,
+    )?;
+    request.sell_price = normalize_precision(
+        "sell_price",
+        request.sell_price,
+        precision.round_price(request.sell_price),
+        tick,
+        strict,
+    )?;
+    Ok(())
+}
+
+/// Round a grid's prices to the symbol's tick size and validate it with the
+/// symbol's step size, exactly as `/grid/create` places it
+async fn prepare_grid(
+    client: &BinanceClient,
+    symbol: &str,
+    headers: &HeaderMap,
+    request: &mut CreateGridRequest,
+    maker_fee: f64,
+) -> Result<GridProfitEstimate, (StatusCode, Json<ErrorResponse>)> {
+    let precision = client.get_precision(symbol).await.map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+    let strict = strict_precision_from_headers(headers);
+    normalize_grid_prices(request, &precision, strict).map_err(validation_error)?;
+
+    validate_grid_request(request, maker_fee, precision.step_size).map_err(validation_error)
+}
+
+/// Reject a grid whose amount exceeds `max_order_usd` unless the client overrides the cap
+fn check_grid_size(
+    headers: &HeaderMap,
+    request: &CreateGridRequest,
+    max_order_usd: f64,
+) -> Result<(), ValidationError> {
+    if allow_large_order_from_headers(headers) {
+        return Ok(());
+    }
+
+    check_order_size(request.amount_usd, max_order_usd)
+        .map_err(|e| ValidationError::new("amount_usd", "above_max_order_usd", e))
+}
+
+/// Reject a grid whose orders would take the symbol past `max_open_orders`; 0 allows any count
+fn check_open_orders(
+    open_orders: usize,
+    adding: usize,
+    max_open_orders: usize,
+) -> Result<(), ValidationError> {
+    if max_open_orders > 0 && open_orders + adding > max_open_orders {
+        return Err(ValidationError::new(
+            "symbol",
+            "too_many_open_orders",
+            format!(
+                "Grid adds {} orders to {} already open, \
+                 exceeding the limit of {} (MAX_OPEN_ORDERS)",
+                adding, open_orders, max_open_orders
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reject grids that can't work at the current market price
+///
+/// Both orders on the same side of the market means one of them fills
+/// immediately, and a spread below `min_spread_percent` (0.2 = 0.2%) barely moves.
+fn check_grid_against_market(
+    request: &CreateGridRequest,
+    market_price: f64,
+    min_spread_percent: f64,
+) -> Result<(), ValidationError> {
+    if request.buy_price >= market_price && request.sell_price >= market_price {
+        return Err(ValidationError::new(
+            "buy_price",
+            "above_market_price",
+            format!(
+                "Buy {} and sell {} are both at or above the market price {:.2}; \
+                 the BUY would fill immediately",
+                request.buy_price, request.sell_price, market_price
+            ),
+        ));
+    }
+
+    if request.buy_price <= market_price && request.sell_price <= market_price {
+        return Err(ValidationError::new(
+            "sell_price",
+            "below_market_price",
+            format!(
+                "Buy {} and sell {} are both at or below the market price {:.2}; \
+                 the SELL would fill immediately",
+                request.buy_price, request.sell_price, market_price
+            ),
+        ));
+    }
+
+    let spread_percent = (request.sell_price - request.buy_price) / request.buy_price * 100.0;
+    if spread_percent < min_spread_percent {
+        return Err(ValidationError::new(
+            "sell_price",
+            "spread_below_minimum",
+            format!(
+                "Spread {:.3}% is below the minimum {:.3}% (market price {:.2})",
+                spread_percent,
+                min_spread_percent,
+                market_price
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Create a new grid pair (BUY + SELL orders)
+#[utoipa::path(
+    post,
+    path = "/grid/create",
+    tag = "grid",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    request_body = CreateGridRequest,
+    responses(
+        (status = 200, description = "Grid pair placed", body = GridPairResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 423, description = "Daily loss limit hit, trading halted", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Binance circuit breaker is open", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn create_grid_pair(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
+    Json(mut request): Json<CreateGridRequest>,
+) -> Result<Json<GridPairResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
     let use_production = use_production_from_headers(&headers);
+    ensure_trading_allowed(KILL_SWITCH.get(use_production))
+        .map_err(|(status, e)| (status, Json(ErrorResponse::new(e))))?;
+    check_grid_size(&headers, &request, config.max_order_usd).map_err(validation_error)?;
+
     let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
     })?;
 
-    let (buy_order, sell_order) = client
-        .create_grid_pair(request.buy_price, request.sell_price, request.amount_usd)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                }),
-            )
+    let estimate = prepare_grid(&client, &symbol, &headers, &mut request, config.maker_fee).await?;
+
+    let market_price = client.get_price(&symbol).await.map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+    check_grid_against_market(&request, market_price, config.grid_min_spread_percent)
+        .map_err(validation_error)?;
+
+    if config.max_open_orders > 0 {
+        let open_orders = client.get_open_orders(&symbol).await.map_err(|e| {
+            (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
         })?;
+        check_open_orders(open_orders.len(), 2, config.max_open_orders)
+            .map_err(validation_error)?;
+    }
 
-    // Calculate estimated profit
-    let quantity = BinanceClient::calculate_quantity(request.amount_usd, request.buy_price);
-    let profit_usd = (request.sell_price - request.buy_price) * quantity;
-    let profit_percent = (request.sell_price - request.buy_price) / request.buy_price * 100.0;
+    let (buy_order, sell_order) = client
+        .create_grid_pair(
+            &symbol,
+            request.buy_price,
+            request.sell_price,
+            estimate.quantity,
+            request.client_tag.as_deref(),
+        )
+        .await
+        .map_err(|e| (binance_error_status(&e), Json(ErrorResponse::new(e.to_string()))))?;
 
     tracing::info!(
-        "Created grid pair: BUY @ {} / SELL @ {} (profit: ${:.2})",
+        "[{}] Created grid pair: BUY @ {} / SELL @ {} (profit: ${:.2}, net: ${:.2})",
+        device.device_name,
         request.buy_price,
         request.sell_price,
-        profit_usd
+        estimate.gross_profit_usd,
+        estimate.net_profit_usd
     );
 
     Ok(Json(GridPairResponse {
         buy_order,
         sell_order,
-        estimated_profit_usd: profit_usd,
-        estimated_profit_percent: profit_percent,
+        estimated_profit_usd: estimate.gross_profit_usd,
+        estimated_profit_percent: estimate.profit_percent,
+        net_profit_usd: estimate.net_profit_usd,
     }))
 }
 
-#[derive(Serialize)]
+#[derive(Debug, PartialEq, Serialize, ToSchema)]
+pub struct GridEstimateResponse {
+    /// BTC bought and sold, floored to the quantity step
+    quantity: f64,
+    gross_profit: f64,
+    /// Maker commission on both the buy and the sell
+    commission: f64,
+    net_profit: f64,
+    profit_percent: f64,
+}
+
+impl From<GridProfitEstimate> for GridEstimateResponse {
+    fn from(estimate: GridProfitEstimate) -> Self {
+        Self {
+            quantity: estimate.quantity,
+            gross_profit: estimate.gross_profit_usd,
+            commission: estimate.commission_usd,
+            net_profit: estimate.net_profit_usd,
+            profit_percent: estimate.profit_percent,
+        }
+    }
+}
+
+/// Estimate a grid pair's profit without placing anything
+///
+/// Rounds and validates the grid exactly as `/grid/create` does, using the
+/// symbol's tick and step size from exchangeInfo and the configured maker fee,
+/// but places nothing and doesn't check the prices against the market.
+#[utoipa::path(
+    post,
+    path = "/grid/estimate",
+    tag = "grid",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    request_body = CreateGridRequest,
+    responses(
+        (status = 200, description = "Projected profit", body = GridEstimateResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Binance circuit breaker is open", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn estimate_grid_pair(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    Query(query): Query<SymbolQuery>,
+    Json(mut request): Json<CreateGridRequest>,
+) -> Result<Json<GridEstimateResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
+    let use_production = use_production_from_headers(&headers);
+    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+
+    let estimate = prepare_grid(&client, &symbol, &headers, &mut request, config.maker_fee).await?;
+
+    Ok(Json(estimate.into()))
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct ModifyResponse {
+    /// The order that was replaced
+    cancelled_order_id: i64,
     new_order: NewOrderResponse,
 }
 
-/// Modify an existing order (cancel + recreate at new price)
+/// Modify an existing order, replacing it atomically via cancelReplace
+#[utoipa::path(
+    post,
+    path = "/grid/modify",
+    tag = "grid",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    request_body = ModifyOrderRequest,
+    responses(
+        (status = 200, description = "Order replaced", body = ModifyResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Binance circuit breaker is open", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn modify_order(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
     headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
     Json(request): Json<ModifyOrderRequest>,
 ) -> Result<Json<ModifyResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
     let use_production = use_production_from_headers(&headers);
     let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
     })?;
 
     // First get the existing order to know its side and quantity
-    let orders = client.get_open_orders().await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
+    let orders = client.get_open_orders(&symbol).await.map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
     })?;
 
     let existing_order = orders.iter().find(|o| o.order_id == request.order_id).ok_or((
         StatusCode::NOT_FOUND,
-        Json(ErrorResponse {
-            error: "Order not found".to_string(),
-        }),
+        Json(ErrorResponse::new("Order not found")),
     ))?;
 
     let side = &existing_order.side;
     let quantity = existing_order.quantity_f64();
+    let client_tag = existing_order.client_tag.as_deref();
 
-    let new_order = client
-        .modify_order(request.order_id, side, request.new_price, quantity)
+    let replaced = client
+        .modify_order(&symbol, request.order_id, side, request.new_price, quantity, client_tag)
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                }),
-            )
-        })?;
+        .map_err(|e| (binance_error_status(&e), Json(ErrorResponse::new(e.to_string()))))?;
 
     tracing::info!(
-        "Modified order {}: new price {}",
-        request.order_id,
+        "[{}] Modified order {} -> {}: new price {}",
+        device.device_name,
+        replaced.cancelled.order_id,
+        replaced.new_order.order_id,
         request.new_price
     );
 
-    Ok(Json(ModifyResponse { new_order }))
+    Ok(Json(ModifyResponse {
+        cancelled_order_id: replaced.cancelled.order_id,
+        new_order: replaced.new_order,
+    }))
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CancelResponse {
     success: bool,
     order_id: i64,
+    /// The order had already filled or been cancelled before this request
+    already_absent: bool,
+}
+
+/// Map a cancel result, treating an order that's already gone as cancelled
+///
+/// Cancelling races with fills, so "unknown order" is the outcome the caller
+/// wanted anyway and shouldn't surface as a failure.
+fn cancel_response(
+    result: Result<CancelOrderResponse, BinanceApiError>,
+    order_id: i64,
+) -> Result<Json<CancelResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let already_absent = match result {
+        Ok(_) => false,
+        Err(BinanceApiError::UnknownOrder { .. }) => true,
+        Err(e) => {
+            return Err((binance_error_status(&e), Json(ErrorResponse::new(e.to_string()))))
+        }
+    };
+
+    Ok(Json(CancelResponse {
+        success: true,
+        order_id,
+        already_absent,
+    }))
 }
 
 /// Cancel an order
+#[utoipa::path(
+    delete,
+    path = "/grid/{order_id}",
+    tag = "grid",
+    params(
+        ("order_id" = i64, Path, description = "Binance order id"),
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    responses(
+        (status = 200, description = "Order cancelled", body = CancelResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Binance circuit breaker is open", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn cancel_order(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
+    State(trailing_monitor): State<Arc<TrailingMonitor>>,
     headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
     Path(order_id): Path<i64>,
 ) -> Result<Json<CancelResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
+    let use_production = use_production_from_headers(&headers);
+    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+
+    let response = cancel_response(client.cancel_order(&symbol, order_id).await, order_id)?;
+
+    // Either way the order is off the book, so stop trailing it
+    trailing_monitor.remove_by_order_id(order_id, TrailingOutcome::Cancelled).await;
+
+    if response.already_absent {
+        tracing::info!(
+            "[{}] Order {} was already gone, treating cancel as done",
+            device.device_name,
+            order_id
+        );
+    } else {
+        tracing::info!("[{}] Cancelled order {}", device.device_name, order_id);
+    }
+
+    Ok(response)
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RebalanceResponse {
+    market_price: f64,
+    /// Orders of the old layout taken off the book
+    cancelled_order_ids: Vec<i64>,
+    /// New pairs, in the order of the pairs they replace
+    pairs: Vec<GridPairResponse>,
+    /// A cancel that failed partway through the old layout, leaving nothing
+    /// placed, or new pairs Binance rejected after it was cancelled
+    errors: Vec<String>,
+}
+
+/// Validate every recentered level, so nothing is cancelled for a layout that
+/// can't be placed
+fn validate_rebalance(
+    levels: &[CreateGridRequest],
+    market_price: f64,
+    config: &Config,
+    step_size: f64,
+) -> Result<Vec<GridProfitEstimate>, String> {
+    levels
+        .iter()
+        .enumerate()
+        .map(|(i, level)| {
+            check_grid_against_market(level, market_price, config.grid_min_spread_percent)
+                .and_then(|_| validate_grid_request(level, config.maker_fee, step_size))
+                .map_err(|e| {
+                    format!(
+                        "Level {} ({} / {}): {}",
+                        i + 1,
+                        level.buy_price,
+                        level.sell_price,
+                        e.message
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Cancel both orders of every pair, treating already-gone orders as cancelled
+///
+/// Stops at the first cancel that fails, returning it along with the orders
+/// already cancelled.
+async fn cancel_grid_pairs(
+    client: &BinanceClient,
+    symbol: &str,
+    pairs: &[GridPair],
+    trailing_monitor: &TrailingMonitor,
+) -> Result<Vec<i64>, (Vec<i64>, BinanceApiError)> {
+    let mut cancelled = Vec::new();
+
+    for pair in pairs {
+        for order_id in [pair.buy_order.order_id, pair.sell_order.order_id] {
+            match client.cancel_order(symbol, order_id).await {
+                Ok(_) | Err(BinanceApiError::UnknownOrder { .. }) => {}
+                Err(e) => return Err((cancelled, e)),
+            }
+            trailing_monitor.remove_by_order_id(order_id, TrailingOutcome::Cancelled).await;
+            cancelled.push(order_id);
+        }
+    }
+
+    Ok(cancelled)
+}
+
+/// Recenter every grid pair on the current market price
+///
+/// The new layout is validated before anything is cancelled; only then are
+/// the old pairs cancelled and the new ones placed. If a cancel fails, nothing
+/// is placed and the orders already cancelled are listed with the error.
+#[utoipa::path(
+    post,
+    path = "/grid/rebalance",
+    tag = "grid",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    responses(
+        (status = 200, description = "Grid rebuilt at market", body = RebalanceResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Binance circuit breaker is open", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn rebalance_grid(
+    State(config): State<Arc<Config>>,
+    State(trailing_monitor): State<Arc<TrailingMonitor>>,
+    headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
+) -> Result<Json<RebalanceResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
     let use_production = use_production_from_headers(&headers);
     let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
-        (
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+    let to_error = |e: BinanceApiError| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    };
+
+    let orders = client.get_open_orders(&symbol).await.map_err(to_error)?;
+    let (pairs, _) = match_grid_pairs(&orders, config.quantity_match_tolerance);
+    if pairs.is_empty() {
+        return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
+            Json(ErrorResponse::new(format!("No {} grid pairs to rebalance", symbol))),
+        ));
+    }
+
+    let precision = client.get_precision(&symbol).await.map_err(to_error)?;
+    let market_price = client.get_price(&symbol).await.map_err(to_error)?;
+
+    let mut levels = recenter_grid(&pairs, market_price);
+    for level in &mut levels {
+        level.buy_price = precision.round_price(level.buy_price);
+        level.sell_price = precision.round_price(level.sell_price);
+    }
+    let estimates = validate_rebalance(&levels, market_price, &config, precision.step_size)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+
+    let cancelled = cancel_grid_pairs(&client, &symbol, &pairs, &trailing_monitor).await;
+    let cancelled_order_ids = match cancelled {
+        Ok(cancelled) => cancelled,
+        Err((cancelled_order_ids, e)) => {
+            tracing::warn!(
+                "[{}] Rebalancing {} stopped after cancelling {} orders: {}",
+                device.device_name,
+                symbol,
+                cancelled_order_ids.len(),
+                e
+            );
+            return Ok(Json(RebalanceResponse {
+                market_price,
+                cancelled_order_ids,
+                pairs: Vec::new(),
+                errors: vec![format!("Cancel failed, nothing placed: {}", e)],
+            }));
+        }
+    };
+
+    let mut new_pairs = Vec::with_capacity(levels.len());
+    let mut errors = Vec::new();
+    for (level, estimate) in levels.iter().zip(estimates) {
+        let placed = client
+            .create_grid_pair(
+                &symbol,
+                level.buy_price,
+                level.sell_price,
+                estimate.quantity,
+                level.client_tag.as_deref(),
+            )
+            .await;
+
+        match placed {
+            Ok((buy_order, sell_order)) => new_pairs.push(GridPairResponse {
+                buy_order,
+                sell_order,
+                estimated_profit_usd: estimate.gross_profit_usd,
+                estimated_profit_percent: estimate.profit_percent,
+                net_profit_usd: estimate.net_profit_usd,
             }),
+            Err(e) => errors.push(format!("{} / {}: {}", level.buy_price, level.sell_price, e)),
+        }
+    }
+
+    tracing::info!(
+        "[{}] Rebalanced {} {} grid pairs around {:.2} ({} failed)",
+        device.device_name,
+        pairs.len(),
+        symbol,
+        market_price,
+        errors.len()
+    );
+
+    Ok(Json(RebalanceResponse {
+        market_price,
+        cancelled_order_ids,
+        pairs: new_pairs,
+        errors,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_config;
+    use axum::{body::Body, http::Request, routing::get};
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+
+    /// BTCUSDT's LOT_SIZE step
+    const BTC_STEP_SIZE: f64 = 0.00001;
+
+    fn request(buy_price: f64, sell_price: f64, amount_usd: f64) -> CreateGridRequest {
+        CreateGridRequest {
+            buy_price,
+            sell_price,
+            amount_usd,
+            client_tag: None,
+        }
+    }
+
+    fn field_and_code(error: &ValidationError) -> (&str, &str) {
+        (&error.field, &error.code)
+    }
+
+    #[test]
+    fn test_validation_error_keeps_top_level_message() {
+        let error = ValidationError::new("buy_price", "must_be_positive", "Price must be positive");
+        let (status, Json(body)) = validation_error(error);
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let body = serde_json::to_value(body).unwrap();
+        assert_eq!(body["error"], "Price must be positive");
+        assert_eq!(body["errors"][0]["field"], "buy_price");
+        assert_eq!(body["errors"][0]["code"], "must_be_positive");
+
+        let body = serde_json::to_value(ErrorResponse::new("Order not found")).unwrap();
+        assert!(body.get("errors").is_none());
+    }
+
+    #[test]
+    fn test_tight_spread_is_rejected() {
+        let result =
+            validate_grid_request(&request(50000.0, 50050.0, 100.0), 0.001, BTC_STEP_SIZE);
+        let error = result.unwrap_err();
+        assert_eq!(field_and_code(&error), ("sell_price", "spread_below_fees"));
+        assert!(error.message.contains("Spread too tight"));
+    }
+
+    #[test]
+    fn test_profitable_spread_is_accepted() {
+        let estimate =
+            validate_grid_request(&request(50000.0, 51000.0, 100.0), 0.001, BTC_STEP_SIZE)
+                .unwrap();
+        assert!(estimate.net_profit_usd > 0.0);
+        assert!(estimate.net_profit_usd < estimate.gross_profit_usd);
+    }
+
+    #[test]
+    fn test_amount_below_one_step_is_rejected() {
+        let result = validate_grid_request(&request(50000.0, 60000.0, 1.0), 0.001, 0.001);
+        let error = result.unwrap_err();
+        assert_eq!(field_and_code(&error), ("amount_usd", "below_quantity_step"));
+        assert!(error.message.contains("too small"));
+    }
+
+    #[test]
+    fn test_inverted_prices_are_rejected() {
+        let result =
+            validate_grid_request(&request(51000.0, 50000.0, 100.0), 0.001, BTC_STEP_SIZE);
+        let error = result.unwrap_err();
+        assert_eq!(field_and_code(&error), ("buy_price", "must_be_less_than_sell_price"));
+    }
+
+    #[test]
+    fn test_amount_below_minimum_is_rejected() {
+        let result =
+            validate_grid_request(&request(50000.0, 51000.0, 0.5), 0.001, BTC_STEP_SIZE);
+        let error = result.unwrap_err();
+        assert_eq!(field_and_code(&error), ("amount_usd", "below_minimum"));
+    }
+
+    #[test]
+    fn test_estimate_uses_configured_maker_fee() {
+        let mut config = Config::for_tests();
+        config.maker_fee = 0.002;
+
+        let estimate = validate_grid_request(
+            &request(50000.0, 51000.0, 100.0),
+            config.maker_fee,
+            BTC_STEP_SIZE,
         )
-    })?;
+        .unwrap();
+
+        // 0.002 BTC bought at 50000 and sold at 51000, 0.2% on each side:
+        // $100 + $102 traded, $0.404 in fees against $2 gross
+        assert_eq!(estimate.quantity, 0.002);
+        assert!((estimate.gross_profit_usd - 2.0).abs() < 1e-9);
+        assert!((estimate.commission_usd - 0.404).abs() < 1e-9);
+        assert!((estimate.net_profit_usd - 1.596).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_grid_over_order_cap_is_rejected() {
+        let headers = HeaderMap::new();
+
+        let error = check_grid_size(&headers, &request(49000.0, 51000.0, 5000.0), 1000.0)
+            .unwrap_err();
+        assert_eq!(field_and_code(&error), ("amount_usd", "above_max_order_usd"));
+        assert!(error.message.contains("$1000.00 per-order limit"), "{}", error.message);
+
+        assert!(check_grid_size(&headers, &request(49000.0, 51000.0, 100.0), 1000.0).is_ok());
 
-    client.cancel_order(order_id).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: e.to_string(),
+        let mut trusted = HeaderMap::new();
+        trusted.insert("X-Allow-Large-Order", "1".parse().unwrap());
+        assert!(check_grid_size(&trusted, &request(49000.0, 51000.0, 5000.0), 1000.0).is_ok());
+    }
+
+    #[test]
+    fn test_grid_past_open_order_cap_is_rejected() {
+        let error = check_open_orders(199, 2, 200).unwrap_err();
+        assert_eq!(field_and_code(&error), ("symbol", "too_many_open_orders"));
+        assert!(error.message.contains("2 orders to 199 already open"), "{}", error.message);
+        assert!(error.message.contains("limit of 200"), "{}", error.message);
+        assert_eq!(validation_error(error).0, StatusCode::BAD_REQUEST);
+
+        assert!(check_open_orders(198, 2, 200).is_ok());
+        assert!(check_open_orders(500, 2, 0).is_ok());
+    }
+
+    #[test]
+    fn test_grid_prices_are_rounded_to_the_tick() {
+        let precision = SymbolPrecision {
+            tick_size: 0.01,
+            step_size: BTC_STEP_SIZE,
+        };
+
+        let mut grid = request(42000.123456789, 43000.006, 100.0);
+        normalize_grid_prices(&mut grid, &precision, false).unwrap();
+        assert_eq!(grid.buy_price, 42000.12);
+        assert_eq!(grid.sell_price, 43000.01);
+
+        let mut grid = request(42000.123456789, 43000.0, 100.0);
+        let error = normalize_grid_prices(&mut grid, &precision, true).unwrap_err();
+        assert_eq!(field_and_code(&error), ("buy_price", "too_precise"));
+    }
+
+    #[test]
+    fn test_grid_straddling_market_is_accepted() {
+        let grid = request(49000.0, 51000.0, 100.0);
+        assert!(check_grid_against_market(&grid, 50000.0, 0.2).is_ok());
+    }
+
+    #[test]
+    fn test_grid_above_market_is_rejected() {
+        let error = check_grid_against_market(&request(52000.0, 53000.0, 100.0), 50000.0, 0.2)
+            .unwrap_err();
+        assert_eq!(field_and_code(&error), ("buy_price", "above_market_price"));
+        assert!(error.message.contains("above the market price 50000.00"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_grid_below_market_is_rejected() {
+        let error = check_grid_against_market(&request(47000.0, 48000.0, 100.0), 50000.0, 0.2)
+            .unwrap_err();
+        assert_eq!(field_and_code(&error), ("sell_price", "below_market_price"));
+        assert!(error.message.contains("below the market price 50000.00"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_spread_below_minimum_is_rejected() {
+        // 0.1% spread against a 0.2% minimum
+        let grid = request(49975.0, 50025.0, 100.0);
+        let error = check_grid_against_market(&grid, 50000.0, 0.2).unwrap_err();
+        assert_eq!(field_and_code(&error), ("sell_price", "spread_below_minimum"));
+        assert!(error.message.contains("below the minimum 0.200%"), "{}", error.message);
+        assert!(error.message.contains("market price 50000.00"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_rebalance_layout_is_validated_as_a_whole() {
+        let config = Config::for_tests();
+        let levels = [request(99000.0, 101000.0, 100.0)];
+        let estimates = validate_rebalance(&levels, 100000.0, &config, 0.00001).unwrap();
+        assert_eq!(estimates.len(), 1);
+
+        // 0.02% spread is below the 0.2% minimum, so the whole layout is refused
+        let levels = [request(99000.0, 101000.0, 100.0), request(99990.0, 100010.0, 100.0)];
+        let error = validate_rebalance(&levels, 100000.0, &config, 0.00001).unwrap_err();
+        assert!(error.starts_with("Level 2"), "{}", error);
+    }
+
+    #[test]
+    fn test_cancel_of_unknown_order_succeeds() {
+        let error = BinanceApiError::from_body(r#"{"code":-2011,"msg":"Unknown order sent."}"#);
+
+        let Ok(Json(response)) = cancel_response(Err(error), 42) else {
+            panic!("unknown order should count as cancelled");
+        };
+        assert!(response.success);
+        assert!(response.already_absent);
+        assert_eq!(response.order_id, 42);
+    }
+
+    #[test]
+    fn test_successful_cancel_is_not_already_absent() {
+        let cancelled = CancelOrderResponse {
+            symbol: "BTCUSDT".to_string(),
+            order_id: 42,
+            status: "CANCELED".to_string(),
+            executed_qty: "0".to_string(),
+        };
+
+        let Ok(Json(response)) = cancel_response(Ok(cancelled), 42) else {
+            panic!("cancel should succeed");
+        };
+        assert!(!response.already_absent);
+    }
+
+    #[test]
+    fn test_other_cancel_errors_still_fail() {
+        let error = BinanceApiError::from_body(r#"{"code":-1100,"msg":"Illegal characters."}"#);
+
+        let Err((status, _)) = cancel_response(Err(error), 42) else {
+            panic!("other errors should fail");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_failed_cancel_returns_the_orders_already_cancelled() {
+        use axum::extract::RawQuery;
+
+        let mock = Router::new().route(
+            "/api/v3/order",
+            axum::routing::delete(|RawQuery(query): RawQuery| async move {
+                if query.unwrap_or_default().contains("orderId=3&") {
+                    let error = serde_json::json!({ "code": -1021, "msg": "Timestamp outside" });
+                    return (StatusCode::BAD_REQUEST, Json(error));
+                }
+                let cancelled = serde_json::json!({
+                    "symbol": "BTCUSDT", "orderId": 1, "status": "CANCELED", "executedQty": "0"
+                });
+                (StatusCode::OK, Json(cancelled))
             }),
+        );
+        let client = crate::binance::test_support::mock_client(mock).await;
+        let order = |order_id: i64, side: &str, price: &str| -> crate::binance::Order {
+            serde_json::from_value(serde_json::json!({
+                "symbol": "BTCUSDT", "orderId": order_id, "clientOrderId": "",
+                "price": price, "origQty": "0.00100", "executedQty": "0",
+                "status": "NEW", "type": "LIMIT", "side": side,
+                "time": 1700000000000i64
+            }))
+            .unwrap()
+        };
+        let pairs = [
+            GridPair::new(order(1, "BUY", "49000.00"), order(2, "SELL", "51000.00")),
+            GridPair::new(order(3, "BUY", "48000.00"), order(4, "SELL", "52000.00")),
+        ];
+        let monitor = TrailingMonitor::new(Config::for_tests());
+
+        let Err((cancelled, _)) = cancel_grid_pairs(&client, "BTCUSDT", &pairs, &monitor).await
+        else {
+            panic!("a failed cancel should stop the rebalance");
+        };
+        assert_eq!(cancelled, vec![1, 2]);
+    }
+
+    fn eth_exchange_info() -> serde_json::Value {
+        serde_json::json!({
+            "symbols": [{
+                "symbol": "ETHUSDT",
+                "filters": [
+                    { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                    { "filterType": "LOT_SIZE", "stepSize": "0.00010000" }
+                ]
+            }]
+        })
+    }
+
+    /// POST `grid` to the ETHUSDT grid route at `path` against a mock Binance
+    /// whose exchangeInfo answers with `exchange_info`, returning the status,
+    /// the body and each placed order's `quantity` parameter
+    async fn grid_on_mock(
+        exchange_info: Result<serde_json::Value, StatusCode>,
+        path: &str,
+        grid: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value, Vec<String>) {
+        let placed = Arc::new(Mutex::new(Vec::new()));
+        let on_order = placed.clone();
+        let mock = Router::new()
+            .route(
+                "/api/v3/exchangeInfo",
+                get(move || async move { exchange_info.map(Json) }),
+            )
+            .route(
+                "/api/v3/ticker/price",
+                get(|| async {
+                    Json(serde_json::json!({ "symbol": "ETHUSDT", "price": "3000.00" }))
+                }),
+            )
+            .route("/api/v3/openOrders", get(|| async { Json(serde_json::json!([])) }))
+            .route(
+                "/api/v3/order",
+                post(move |body: String| async move {
+                    let quantity = body
+                        .split('&')
+                        .find_map(|pair| pair.strip_prefix("quantity="))
+                        .unwrap_or_default()
+                        .to_string();
+                    on_order.lock().unwrap().push(quantity.clone());
+                    Json(serde_json::json!({
+                        "symbol": "ETHUSDT",
+                        "orderId": 7,
+                        "clientOrderId": "grid",
+                        "transactTime": 1700000000000i64,
+                        "price": "3000.00",
+                        "origQty": quantity,
+                        "executedQty": "0",
+                        "status": "NEW",
+                        "type": "LIMIT",
+                        "side": "BUY"
+                    }))
+                }),
+            );
+
+        let config = Arc::new(mock_config(mock).await);
+        let monitor = Arc::new(TrailingMonitor::new(Config::clone(&config)));
+        let token = crate::auth::create_token(
+            "device-1",
+            "Test iPhone",
+            &config.jwt_secret,
+            config.jwt_expiry_minutes,
+            &config.jwt_issuer,
+            &config.jwt_audience,
         )
-    })?;
+        .unwrap();
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("{}?symbol=ETHUSDT", path))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .body(Body::from(grid.to_string()))
+            .unwrap();
 
-    tracing::info!("Cancelled order {}", order_id);
+        let response = grid_routes(config.clone(), monitor)
+            .with_state(config)
+            .oneshot(request)
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let placed = placed.lock().unwrap().clone();
+        (status, serde_json::from_slice(&body).unwrap(), placed)
+    }
 
-    Ok(Json(CancelResponse {
-        success: true,
-        order_id,
-    }))
+    #[tokio::test]
+    async fn test_create_uses_the_symbols_own_step_size() {
+        let grid =
+            serde_json::json!({ "buy_price": 2990.0, "sell_price": 3010.0, "amount_usd": 100.0 });
+
+        let (status, _, placed) = grid_on_mock(Ok(eth_exchange_info()), "/create", grid).await;
+
+        // $100 at 2990 is 0.033444 ETH: floored to ETH's 0.0001 step, not BTC's 0.00001
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(placed.len(), 2);
+        for quantity in placed {
+            assert_eq!(quantity.parse::<f64>().unwrap(), 0.0334);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_without_step_size_places_nothing() {
+        let grid =
+            serde_json::json!({ "buy_price": 2990.0, "sell_price": 3010.0, "amount_usd": 100.0 });
+
+        let (status, _, placed) =
+            grid_on_mock(Err(StatusCode::INTERNAL_SERVER_ERROR), "/create", grid).await;
+
+        assert!(status.is_server_error(), "{}", status);
+        assert!(placed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_matches_create() {
+        // Over-precise prices, rounded to the tick before either route does the math
+        let grid = serde_json::json!({
+            "buy_price": 2990.004,
+            "sell_price": 3009.996,
+            "amount_usd": 100.0
+        });
+
+        let (status, estimate, placed) =
+            grid_on_mock(Ok(eth_exchange_info()), "/estimate", grid.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(placed.is_empty());
+
+        let (status, created, placed) = grid_on_mock(Ok(eth_exchange_info()), "/create", grid).await;
+        assert_eq!(status, StatusCode::OK);
+
+        assert_eq!(estimate["quantity"].as_f64().unwrap(), 0.0334);
+        assert_eq!(placed[0].parse::<f64>().unwrap(), estimate["quantity"].as_f64().unwrap());
+        // The create response rounds its profit to cents
+        let net_profit = estimate["net_profit"].as_f64().unwrap();
+        assert!((net_profit - created["net_profit_usd"].as_f64().unwrap()).abs() < 0.005);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_reuses_create_validation() {
+        let grid =
+            serde_json::json!({ "buy_price": 2999.0, "sell_price": 3001.0, "amount_usd": 100.0 });
+
+        let (status, body, _) = grid_on_mock(Ok(eth_exchange_info()), "/estimate", grid).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"][0]["code"], "spread_below_fees");
+    }
 }
@@ -1,26 +1,34 @@
 use axum::{
-    extract::State,
-    http::{HeaderMap, StatusCode},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     middleware,
+    response::IntoResponse,
     routing::get,
     Json, Router,
 };
-use serde::Serialize;
+use chrono::{Days, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-use crate::auth::auth_middleware;
-use crate::binance::{BinanceClient, Trade};
+use super::{binance_error_status, ErrorResponse, ProductionHeader, SymbolQuery};
+use crate::auth::{auth_middleware, AuthedDevice};
+use crate::binance::{BinanceClient, Trade, MAX_TRADE_WINDOW_MS};
 use crate::config::Config;
-use crate::trading::{calculate_profit_summary, match_completed_pairs, CompletedPair, ProfitSummary};
+use crate::trading::{
+    calculate_profit_summary, daily_profit, fill_missing_days, match_completed_pairs, order_fills,
+    CompletedPair, DailyProfit, OrderFills, ProfitSummary,
+};
 
-pub fn history_routes() -> Router<Config> {
+pub fn history_routes(config: Arc<Config>) -> Router<Arc<Config>> {
     Router::new()
         .route("/trades", get(get_trade_history))
         .route("/trades/raw", get(get_raw_trades))
         .route("/profit", get(get_profit_summary))
-        .route_layer(middleware::from_fn_with_state(
-            Config::from_env(),
-            auth_middleware,
-        ))
+        .route("/daily", get(get_daily_profit))
+        .route("/export.csv", get(export_csv))
+        .route("/order/:order_id/trades", get(get_order_trades))
+        .route_layer(middleware::from_fn_with_state(config, auth_middleware))
 }
 
 /// Extract use_production flag from X-Use-Production header
@@ -32,81 +40,402 @@ fn use_production_from_headers(headers: &HeaderMap) -> bool {
         .unwrap_or(false)
 }
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    error: String,
+/// Default number of trades fetched when no limit is given
+const DEFAULT_TRADE_LIMIT: u32 = 100;
+/// Binance caps `myTrades` at 1000 results per request
+const MAX_TRADE_LIMIT: u32 = 1000;
+
+/// Optional paging and time-window parameters for trade history
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TradeHistoryQuery {
+    /// Market to fetch, defaults to the first configured symbol
+    pub symbol: Option<String>,
+    /// Inclusive start of the window (milliseconds since epoch)
+    pub start_time: Option<i64>,
+    /// Inclusive end of the window (milliseconds since epoch)
+    pub end_time: Option<i64>,
+    /// Maximum number of trades to fetch (1..=1000)
+    pub limit: Option<u32>,
+    /// `next_cursor` of the previous page: fetch the trades older than it
+    pub cursor: Option<i64>,
+}
+
+impl TradeHistoryQuery {
+    /// Validate the parameters and return the effective limit
+    fn validate(&self) -> Result<u32, String> {
+        let limit = self.limit.unwrap_or(DEFAULT_TRADE_LIMIT);
+        if limit == 0 || limit > MAX_TRADE_LIMIT {
+            return Err(format!("limit must be between 1 and {}", MAX_TRADE_LIMIT));
+        }
+
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            if start >= end {
+                return Err("start_time must be before end_time".to_string());
+            }
+            if end - start > MAX_TRADE_WINDOW_MS {
+                return Err("start_time and end_time must be at most 24 hours apart".to_string());
+            }
+        }
+
+        if self.cursor.is_some() && (self.start_time.is_some() || self.end_time.is_some()) {
+            return Err("cursor can't be combined with start_time or end_time".to_string());
+        }
+
+        Ok(limit)
+    }
+}
+
+/// Cursor for the next page: the oldest trade id, only when the page was full
+fn next_cursor(trades: &[Trade], limit: u32) -> Option<i64> {
+    if trades.len() < limit as usize {
+        return None;
+    }
+    trades.iter().map(|t| t.id).min()
 }
 
-#[derive(Serialize)]
+/// Fetch trades for a validated history query
+async fn fetch_trades(
+    config: &Config,
+    headers: &HeaderMap,
+    query: &TradeHistoryQuery,
+) -> Result<(Vec<Trade>, u32), (StatusCode, Json<ErrorResponse>)> {
+    let limit = query.validate().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
+    })?;
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
+    })?;
+
+    let use_production = use_production_from_headers(headers);
+    let client = BinanceClient::for_environment(config, use_production).map_err(|e| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse::new(e.to_string())),
+        )
+    })?;
+
+    let trades = match query.cursor {
+        Some(cursor) => client.get_trades_before(&symbol, cursor, limit).await,
+        None => {
+            client
+                .get_trades_in_range(&symbol, limit, query.start_time, query.end_time)
+                .await
+        }
+    };
+    let mut trades = trades.map_err(|e| {
+            (
+                binance_error_status(&e),
+                Json(ErrorResponse::new(e.to_string())),
+            )
+        })?;
+
+    // Tags are cosmetic, so history still loads if the order lookup fails
+    if let Err(e) = client.tag_trades(&symbol, &mut trades).await {
+        tracing::warn!("Failed to look up client tags for {} trades: {}", symbol, e);
+    }
+
+    Ok((trades, limit))
+}
+
+/// Optional filters narrowing which completed pairs are listed
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PairFilterQuery {
+    /// Only pairs whose profit percent is at least this (may be negative)
+    pub min_profit_percent: Option<f64>,
+    /// Only pairs with a negative net profit
+    #[serde(default)]
+    pub only_losses: bool,
+}
+
+impl PairFilterQuery {
+    fn validate(&self) -> Result<(), String> {
+        match self.min_profit_percent {
+            Some(percent) if !percent.is_finite() => {
+                Err("min_profit_percent must be a finite number".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn matches(&self, pair: &CompletedPair) -> bool {
+        if self.only_losses && pair.net_profit_usd >= 0.0 {
+            return false;
+        }
+        self.min_profit_percent.is_none_or(|min| pair.profit_percent >= min)
+    }
+
+    /// Keep the pairs passing every given filter, order preserved
+    fn apply(&self, pairs: Vec<CompletedPair>) -> Vec<CompletedPair> {
+        pairs.into_iter().filter(|pair| self.matches(pair)).collect()
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct TradeHistoryResponse {
+    /// Pairs passing the filters, newest first
     completed_pairs: Vec<CompletedPair>,
+    /// Net profit of the listed pairs only
     total_net_profit: f64,
+    /// Totals across every matched pair, ignoring the filters
+    summary: ProfitSummary,
+    next_cursor: Option<i64>,
 }
 
 /// Get trade history with completed pairs
+#[utoipa::path(
+    get,
+    path = "/history/trades",
+    tag = "history",
+    params(
+        TradeHistoryQuery,
+        PairFilterQuery,
+        ProductionHeader,
+    ),
+    responses(
+        (status = 200, description = "Completed grid pairs", body = TradeHistoryResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn get_trade_history(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
     headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<TradeHistoryQuery>,
+    Query(filter): Query<PairFilterQuery>,
 ) -> Result<Json<TradeHistoryResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let use_production = use_production_from_headers(&headers);
-    let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
-    })?;
-
-    let trades = client.get_trades(100).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
+    filter.validate().map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
     })?;
+    let (trades, limit) = fetch_trades(&config, &headers, &query).await?;
+    tracing::debug!("[{}] Fetched {} trades for history", device.device_name, trades.len());
 
-    let pairs = match_completed_pairs(&trades);
+    let pairs = match_completed_pairs(&trades, config.quantity_match_tolerance);
+    let summary = calculate_profit_summary(&pairs);
+    let pairs = filter.apply(pairs);
     let total_profit: f64 = pairs.iter().map(|p| p.net_profit_usd).sum();
 
     Ok(Json(TradeHistoryResponse {
         completed_pairs: pairs,
         total_net_profit: total_profit,
+        summary,
+        next_cursor: next_cursor(&trades, limit),
     }))
 }
 
 /// Get profit summary
+#[utoipa::path(
+    get,
+    path = "/history/profit",
+    tag = "history",
+    params(
+        TradeHistoryQuery,
+        ProductionHeader,
+    ),
+    responses(
+        (status = 200, description = "Profit totals", body = ProfitSummary),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn get_profit_summary(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
     headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<TradeHistoryQuery>,
 ) -> Result<Json<ProfitSummary>, (StatusCode, Json<ErrorResponse>)> {
+    let (trades, _) = fetch_trades(&config, &headers, &query).await?;
+    tracing::debug!("[{}] Fetched {} trades for profit summary", device.device_name, trades.len());
+
+    let pairs = match_completed_pairs(&trades, config.quantity_match_tolerance);
+    let summary = calculate_profit_summary(&pairs);
+
+    Ok(Json(summary))
+}
+
+/// Longest `?days=` window for the daily summary
+const MAX_DAILY_WINDOW: u32 = 365;
+
+/// Parameters for the per-day profit summary
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DailyProfitQuery {
+    /// Market to summarize, defaults to the first configured symbol
+    pub symbol: Option<String>,
+    /// Only the last N UTC days, today included (1..=365)
+    pub days: Option<u32>,
+    /// Include days without completed pairs as zero rows
+    #[serde(default)]
+    pub fill: bool,
+}
+
+impl DailyProfitQuery {
+    /// First UTC day of the window ending `today`, None when unbounded
+    fn first_day(&self, today: NaiveDate) -> Result<Option<NaiveDate>, String> {
+        let Some(days) = self.days else {
+            return Ok(None);
+        };
+        if days == 0 || days > MAX_DAILY_WINDOW {
+            return Err(format!("days must be between 1 and {}", MAX_DAILY_WINDOW));
+        }
+
+        Ok(today.checked_sub_days(Days::new(u64::from(days) - 1)))
+    }
+}
+
+/// Net profit, completed pairs, and commission per UTC day, oldest first
+#[utoipa::path(
+    get,
+    path = "/history/daily",
+    tag = "history",
+    params(
+        DailyProfitQuery,
+        ProductionHeader,
+    ),
+    responses(
+        (status = 200, description = "Profit per UTC day", body = Vec<DailyProfit>),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn get_daily_profit(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<DailyProfitQuery>,
+) -> Result<Json<Vec<DailyProfit>>, (StatusCode, Json<ErrorResponse>)> {
+    let today = chrono::Utc::now().date_naive();
+    let first_day = query.first_day(today).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
+    })?;
+
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
+    })?;
     let use_production = use_production_from_headers(&headers);
     let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
         (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
+            binance_error_status(&e),
+            Json(ErrorResponse::new(e.to_string())),
         )
     })?;
 
-    let trades = client.get_trades(100).await.map_err(|e| {
+    // The whole window, not just the first day or the latest 1000 trades
+    let start_time = first_day.map(|day| day.and_time(NaiveTime::MIN).and_utc().timestamp_millis());
+    let trades = client.get_trades_since(&symbol, start_time).await.map_err(|e| {
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
+            binance_error_status(&e),
+            Json(ErrorResponse::new(e.to_string())),
         )
     })?;
+    tracing::debug!("[{}] Fetched {} trades for daily profit", device.device_name, trades.len());
 
-    let pairs = match_completed_pairs(&trades);
-    let summary = calculate_profit_summary(&pairs);
+    let pairs = match_completed_pairs(&trades, config.quantity_match_tolerance);
+    let mut days = daily_profit(&pairs);
+    if let Some(first_day) = first_day {
+        days.retain(|day| day.date >= first_day);
+    }
 
-    Ok(Json(summary))
+    if query.fill {
+        if let Some(from) = first_day.or_else(|| days.first().map(|day| day.date)) {
+            days = fill_missing_days(days, from, today);
+        }
+    }
+
+    Ok(Json(days))
 }
 
-#[derive(Serialize)]
+/// Column headers for the completed-pairs CSV export
+const CSV_HEADER: &str =
+    "buy_time,buy_price,sell_time,sell_price,quantity,gross_profit_usd,commission_usd,net_profit_usd";
+
+/// Format a millisecond timestamp as RFC 3339 (UTC)
+fn format_timestamp(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+/// Render completed pairs as CSV, header row first
+fn completed_pairs_to_csv(pairs: &[CompletedPair]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+
+    for pair in pairs {
+        csv.push_str(&format!(
+            "{},{:.2},{},{:.2},{:.8},{:.8},{:.8},{:.8}\n",
+            format_timestamp(pair.buy_trade.time),
+            pair.buy_price,
+            format_timestamp(pair.sell_trade.time),
+            pair.sell_price,
+            pair.quantity,
+            pair.gross_profit_usd,
+            pair.commission_usd,
+            pair.net_profit_usd,
+        ));
+    }
+
+    csv
+}
+
+/// Export completed pairs as a CSV attachment
+#[utoipa::path(
+    get,
+    path = "/history/export.csv",
+    tag = "history",
+    params(
+        TradeHistoryQuery,
+        ProductionHeader,
+    ),
+    responses(
+        (status = 200, description = "Completed pairs", body = String, content_type = "text/csv"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn export_csv(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<TradeHistoryQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    let (trades, _) = fetch_trades(&config, &headers, &query).await?;
+
+    let pairs = match_completed_pairs(&trades, config.quantity_match_tolerance);
+    let csv = completed_pairs_to_csv(&pairs);
+    tracing::info!("[{}] Exported {} completed pairs as CSV", device.device_name, pairs.len());
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"trade-history.csv\"",
+            ),
+        ],
+        csv,
+    ))
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct RawTradesResponse {
     trades: Vec<Trade>,
     buy_trades: Vec<Trade>,
@@ -114,28 +443,53 @@ pub struct RawTradesResponse {
 }
 
 /// Get raw trades (not matched into pairs)
+#[utoipa::path(
+    get,
+    path = "/history/trades/raw",
+    tag = "history",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    responses(
+        (status = 200, description = "Raw Binance trades", body = RawTradesResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn get_raw_trades(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
     headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
 ) -> Result<Json<RawTradesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
+    })?;
+
     let use_production = use_production_from_headers(&headers);
     let client = BinanceClient::for_environment(&config, use_production).map_err(|e| {
         (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
+            binance_error_status(&e),
+            Json(ErrorResponse::new(e.to_string())),
         )
     })?;
 
-    let trades = client.get_trades(100).await.map_err(|e| {
+    let mut trades = client.get_trades(&symbol, 100).await.map_err(|e| {
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
+            binance_error_status(&e),
+            Json(ErrorResponse::new(e.to_string())),
         )
     })?;
+    if let Err(e) = client.tag_trades(&symbol, &mut trades).await {
+        tracing::warn!("Failed to look up client tags for {} trades: {}", symbol, e);
+    }
+
+    tracing::debug!("[{}] Fetched {} raw trades", device.device_name, trades.len());
 
     let buy_trades: Vec<Trade> = trades.iter().filter(|t| t.is_buyer).cloned().collect();
     let sell_trades: Vec<Trade> = trades.iter().filter(|t| !t.is_buyer).cloned().collect();
@@ -146,3 +500,336 @@ async fn get_raw_trades(
         sell_trades,
     }))
 }
+
+#[derive(Serialize, ToSchema)]
+pub struct OrderTradesResponse {
+    order_id: i64,
+    /// The order's fills, oldest first; empty if nothing has filled
+    trades: Vec<Trade>,
+    #[serde(flatten)]
+    summary: OrderFills,
+}
+
+/// Get the individual fills of one order
+#[utoipa::path(
+    get,
+    path = "/history/order/{order_id}/trades",
+    tag = "history",
+    params(
+        ("order_id" = i64, Path, description = "Binance order id"),
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    responses(
+        (status = 200, description = "The order's fills", body = OrderTradesResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn get_order_trades(
+    State(config): State<Arc<Config>>,
+    headers: HeaderMap,
+    Path(order_id): Path<i64>,
+    Query(query): Query<SymbolQuery>,
+) -> Result<Json<OrderTradesResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
+    })?;
+
+    let use_production = use_production_from_headers(&headers);
+    let to_error = |e: crate::binance::BinanceApiError| {
+        (
+            binance_error_status(&e),
+            Json(ErrorResponse::new(e.to_string())),
+        )
+    };
+    let client = BinanceClient::for_environment(&config, use_production).map_err(to_error)?;
+    let trades = client.get_order_trades(&symbol, order_id).await.map_err(to_error)?;
+
+    Ok(Json(OrderTradesResponse {
+        order_id,
+        summary: order_fills(&trades),
+        trades,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Uri;
+
+    fn trade(id: i64) -> Trade {
+        Trade {
+            id,
+            order_id: id,
+            symbol: "BTCUSDT".to_string(),
+            price: "50000".to_string(),
+            qty: "0.001".to_string(),
+            quote_qty: "50".to_string(),
+            commission: "0".to_string(),
+            commission_asset: "USDT".to_string(),
+            time: id * 1000,
+            is_buyer: true,
+            is_maker: true,
+            client_tag: None,
+        }
+    }
+
+    fn completed_pair() -> CompletedPair {
+        let mut buy = trade(1);
+        buy.price = "50000".to_string();
+        buy.time = 1_700_000_000_000;
+        let mut sell = trade(2);
+        sell.price = "51000".to_string();
+        sell.time = 1_700_000_060_000;
+        sell.is_buyer = false;
+
+        CompletedPair {
+            buy_trade: buy,
+            sell_trade: sell,
+            quantity: 0.001,
+            buy_price: 50000.0,
+            sell_price: 51000.0,
+            gross_profit_usd: 1.0,
+            commission_usd: 0.1,
+            net_profit_usd: 0.9,
+            profit_percent: 2.0,
+            completed_at: 1_700_000_060_000,
+            client_tag: None,
+        }
+    }
+
+    fn parse(uri: &str) -> TradeHistoryQuery {
+        let uri: Uri = uri.parse().unwrap();
+        Query::<TradeHistoryQuery>::try_from_uri(&uri).unwrap().0
+    }
+
+    fn parse_filter(uri: &str) -> PairFilterQuery {
+        let uri: Uri = uri.parse().unwrap();
+        Query::<PairFilterQuery>::try_from_uri(&uri).unwrap().0
+    }
+
+    fn pair_with(profit_percent: f64, net_profit_usd: f64) -> CompletedPair {
+        CompletedPair {
+            profit_percent,
+            net_profit_usd,
+            ..completed_pair()
+        }
+    }
+
+    fn parse_daily(uri: &str) -> DailyProfitQuery {
+        let uri: Uri = uri.parse().unwrap();
+        Query::<DailyProfitQuery>::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn test_query_defaults() {
+        let query = parse("/history/trades");
+        assert_eq!(query.start_time, None);
+        assert_eq!(query.end_time, None);
+        assert_eq!(query.validate(), Ok(DEFAULT_TRADE_LIMIT));
+    }
+
+    #[test]
+    fn test_query_parsing() {
+        let query = parse("/history/trades?start_time=1000&end_time=2000&limit=500");
+        assert_eq!(query.start_time, Some(1000));
+        assert_eq!(query.end_time, Some(2000));
+        assert_eq!(query.validate(), Ok(500));
+    }
+
+    #[test]
+    fn test_query_rejects_large_limit() {
+        let query = parse("/history/trades?limit=1001");
+        assert!(query.validate().is_err());
+    }
+
+    #[test]
+    fn test_query_rejects_inverted_window() {
+        let query = parse("/history/trades?start_time=2000&end_time=1000");
+        assert!(query.validate().is_err());
+
+        let query = parse("/history/trades?start_time=1000&end_time=1000");
+        assert!(query.validate().is_err());
+    }
+
+    #[test]
+    fn test_query_rejects_window_over_a_day() {
+        let end = 1_700_000_000_000;
+        let query = parse(&format!(
+            "/history/trades?start_time={}&end_time={}",
+            end - MAX_TRADE_WINDOW_MS,
+            end
+        ));
+        assert!(query.validate().is_ok());
+
+        let query = parse(&format!(
+            "/history/trades?start_time={}&end_time={}",
+            end - MAX_TRADE_WINDOW_MS - 1,
+            end
+        ));
+        assert!(query.validate().is_err());
+    }
+
+    #[test]
+    fn test_query_cursor() {
+        let query = parse("/history/trades?cursor=10&limit=3");
+        assert_eq!(query.cursor, Some(10));
+        assert_eq!(query.validate(), Ok(3));
+
+        let query = parse("/history/trades?cursor=10&start_time=1000");
+        assert!(query.validate().is_err());
+    }
+
+    #[test]
+    fn test_next_cursor_is_oldest_trade_on_full_page() {
+        let trades = vec![trade(12), trade(10), trade(11)];
+        assert_eq!(next_cursor(&trades, 3), Some(10));
+    }
+
+    #[test]
+    fn test_next_cursor_none_on_partial_page() {
+        let trades = vec![trade(12), trade(10)];
+        assert_eq!(next_cursor(&trades, 3), None);
+        assert_eq!(next_cursor(&[], 3), None);
+    }
+
+    #[test]
+    fn test_daily_window() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+
+        let query = parse_daily("/history/daily");
+        assert_eq!(query.first_day(today), Ok(None));
+        assert!(!query.fill);
+
+        let query = parse_daily("/history/daily?days=30&fill=true");
+        assert_eq!(query.first_day(today), Ok(NaiveDate::from_ymd_opt(2024, 2, 10)));
+        assert!(query.fill);
+
+        // Today alone
+        let query = parse_daily("/history/daily?days=1");
+        assert_eq!(query.first_day(today), Ok(Some(today)));
+
+        assert!(parse_daily("/history/daily?days=0").first_day(today).is_err());
+        assert!(parse_daily("/history/daily?days=366").first_day(today).is_err());
+    }
+
+    #[test]
+    fn test_csv_empty_history_is_header_only() {
+        let csv = completed_pairs_to_csv(&[]);
+        assert_eq!(csv, format!("{}\n", CSV_HEADER));
+    }
+
+    #[test]
+    fn test_csv_header_and_row() {
+        let csv = completed_pairs_to_csv(&[completed_pair()]);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "buy_time,buy_price,sell_time,sell_price,quantity,gross_profit_usd,commission_usd,net_profit_usd"
+        );
+        assert_eq!(
+            lines[1],
+            "2023-11-14T22:13:20+00:00,50000.00,2023-11-14T22:14:20+00:00,51000.00,0.00100000,1.00000000,0.10000000,0.90000000"
+        );
+    }
+
+    #[test]
+    fn test_min_profit_percent_filter() {
+        let pairs = vec![pair_with(2.0, 0.9), pair_with(0.5, 0.2), pair_with(-1.0, -0.6)];
+
+        let filter = parse_filter("/history/trades?min_profit_percent=1");
+        assert!(filter.validate().is_ok());
+        let kept = filter.apply(pairs.clone());
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].profit_percent, 2.0);
+
+        // Threshold is inclusive and may be negative
+        let kept = parse_filter("/history/trades?min_profit_percent=-1").apply(pairs.clone());
+        assert_eq!(kept.len(), 3);
+
+        // No filters keep everything
+        assert_eq!(parse_filter("/history/trades").apply(pairs).len(), 3);
+
+        assert!(parse_filter("/history/trades?min_profit_percent=NaN").validate().is_err());
+    }
+
+    #[test]
+    fn test_only_losses_filter() {
+        // A small positive move can still lose money once commission is paid
+        let pairs = vec![pair_with(2.0, 0.9), pair_with(0.1, -0.05), pair_with(-1.0, -0.6)];
+
+        let filter = parse_filter("/history/trades?only_losses=true");
+        assert!(filter.only_losses);
+        let kept = filter.apply(pairs.clone());
+        let nets: Vec<f64> = kept.iter().map(|p| p.net_profit_usd).collect();
+        assert_eq!(nets, vec![-0.05, -0.6]);
+
+        // Combined filters must all pass
+        let kept = parse_filter("/history/trades?only_losses=true&min_profit_percent=0")
+            .apply(pairs);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].net_profit_usd, -0.05);
+    }
+
+    #[tokio::test]
+    async fn test_filtered_history_totals_only_listed_pairs() {
+        // A +$1 round trip followed by a -$1 one
+        let fill = |id: i64, price: &str, is_buyer: bool| {
+            serde_json::json!({
+                "symbol": "BTCUSDT",
+                "id": id,
+                "orderId": id,
+                "price": price,
+                "qty": "0.001",
+                "quoteQty": "50",
+                "commission": "0",
+                "commissionAsset": "USDT",
+                "time": 1_700_000_000_000i64 + id * 1000,
+                "isBuyer": is_buyer,
+                "isMaker": true
+            })
+        };
+        let trades = serde_json::json!([
+            fill(1, "50000", true),
+            fill(2, "51000", false),
+            fill(3, "50000", true),
+            fill(4, "49000", false),
+        ]);
+        let mock = axum::Router::new().route(
+            "/api/v3/myTrades",
+            axum::routing::get(move || async move { Json(trades) }),
+        );
+        let config = Arc::new(crate::binance::test_support::mock_config(mock).await);
+        let device = AuthedDevice(crate::auth::Claims {
+            sub: "phone".to_string(),
+            exp: 0,
+            iat: 0,
+            iss: String::new(),
+            aud: String::new(),
+            device_name: "Phone".to_string(),
+        });
+
+        let Json(history) = get_trade_history(
+            State(config),
+            HeaderMap::new(),
+            device,
+            Query(TradeHistoryQuery::default()),
+            Query(parse_filter("/history/trades?only_losses=true")),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(history.completed_pairs.len(), 1);
+        assert_eq!(history.total_net_profit, -1.0);
+        // The summary still covers both pairs
+        assert_eq!(history.summary.total_trades, 2);
+        assert_eq!(history.summary.total_net_profit, 0.0);
+    }
+}
@@ -0,0 +1,17 @@
+use axum::{http::header, response::IntoResponse, routing::get, Router};
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::metrics::METRICS;
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+pub fn metrics_routes() -> Router<Arc<Config>> {
+    Router::new().route("/metrics", get(get_metrics))
+}
+
+/// Prometheus scrape endpoint
+async fn get_metrics() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], METRICS.render())
+}
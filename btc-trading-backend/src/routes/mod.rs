@@ -1,9 +1,20 @@
+use axum::http::{HeaderMap, StatusCode};
+use serde::{Deserialize, Serialize};
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::{ObjectBuilder, Required, SchemaType};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::binance::BinanceApiError;
+use crate::trading::KillSwitch;
+
 mod account;
 mod auth;
 mod debug;
 mod grid;
 mod history;
+mod metrics;
 mod notifications;
+mod openapi;
 mod order;
 mod price;
 mod trailing;
@@ -13,7 +24,271 @@ pub use auth::auth_routes;
 pub use debug::debug_routes;
 pub use grid::grid_routes;
 pub use history::history_routes;
+pub use metrics::metrics_routes;
 pub use notifications::notification_routes;
+pub use openapi::openapi_routes;
 pub use order::order_routes;
 pub use price::price_routes;
 pub use trailing::trailing_routes;
+
+/// Optional `?symbol=` accepted by market-specific routes
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SymbolQuery {
+    pub symbol: Option<String>,
+}
+
+/// Documents the optional `X-Use-Production` header of routes that reach Binance
+pub struct ProductionHeader;
+
+impl IntoParams for ProductionHeader {
+    fn into_params(_: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        vec![ParameterBuilder::new()
+            .name("X-Use-Production")
+            .parameter_in(ParameterIn::Header)
+            .required(Required::False)
+            .description(Some("Use production Binance instead of testnet"))
+            .schema(Some(ObjectBuilder::new().schema_type(SchemaType::Boolean)))
+            .build()]
+    }
+}
+
+/// Error body of the documented routes
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    error: String,
+    /// Offending fields when the request failed validation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<ValidationError>,
+}
+
+impl ErrorResponse {
+    pub fn new(error: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// A rejected request field, so the app can highlight and localize it
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ValidationError {
+    /// Request field at fault (e.g. "buy_price", "orders[2].quantity")
+    pub field: String,
+    /// Stable machine-readable reason (e.g. "must_be_less_than_sell_price")
+    pub code: String,
+    /// English description, also used for the response's top-level `error`
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: &str, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// `value` snapped to the symbol's precision (`normalized`), or why it can't be
+///
+/// Over-precise values are adjusted and logged, unless `strict` asks for them
+/// to be rejected. A value that snaps to zero is always rejected.
+pub fn normalize_precision(
+    field: &str,
+    value: f64,
+    normalized: f64,
+    increment: f64,
+    strict: bool,
+) -> Result<f64, ValidationError> {
+    if (normalized - value).abs() <= value.abs() * 1e-12 {
+        return Ok(value);
+    }
+
+    if normalized <= 0.0 {
+        return Err(ValidationError::new(
+            field,
+            "below_precision",
+            format!("{} {} is smaller than the symbol's {} increment", field, value, increment),
+        ));
+    }
+    if strict {
+        return Err(ValidationError::new(
+            field,
+            "too_precise",
+            format!(
+                "{} {} is not a multiple of the symbol's {} increment (nearest: {})",
+                field, value, increment, normalized
+            ),
+        ));
+    }
+
+    tracing::info!("Normalized {} {} to {} (increment {})", field, value, normalized, increment);
+    Ok(normalized)
+}
+
+/// Validation error for an unknown or disabled `?symbol=`
+pub fn symbol_error(message: String) -> ValidationError {
+    ValidationError::new("symbol", "symbol_not_enabled", message)
+}
+
+/// Binance codes for requests too fast for the account's limits
+/// (-1003 too many requests, -1015 too many new orders)
+const RATE_LIMIT_CODES: [i32; 2] = [-1003, -1015];
+
+/// Binance codes for an upstream or credentials failure rather than a bad request
+/// (-1000 unknown, -1001 disconnected, -1021 clock skew, -1022 bad signature,
+/// -2014/-2015 bad API key, IP, or permissions)
+const UPSTREAM_CODES: [i32; 6] = [-1000, -1001, -1021, -1022, -2014, -2015];
+
+/// Binance codes rejecting the request itself: -1013 filter failure (price,
+/// lot size, notional), -1100..=-1199 malformed or invalid parameters, and
+/// -2010 new order rejected (e.g. insufficient balance)
+fn is_request_rejection(code: i32) -> bool {
+    code == -1013 || (-1199..=-1100).contains(&code) || code == -2010
+}
+
+/// HTTP status for a failed Binance call, shared by every route
+///
+/// Rejections of the request are the client's to fix (400), a vanished order
+/// is 404, rate limits pass through as 429, and Binance being unreachable or
+/// refusing our credentials is a bad gateway (502). A server without
+/// production keys can't serve production requests, like one whose circuit
+/// breaker is open (503).
+pub fn binance_error_status(error: &BinanceApiError) -> StatusCode {
+    match error {
+        BinanceApiError::UnknownOrder { .. } => StatusCode::NOT_FOUND,
+        BinanceApiError::ProductionNotConfigured
+        | BinanceApiError::CircuitOpen
+        | BinanceApiError::Throttled(_) => StatusCode::SERVICE_UNAVAILABLE,
+        BinanceApiError::Network(_) | BinanceApiError::Timeout(_) => StatusCode::BAD_GATEWAY,
+        BinanceApiError::Api { code, .. } if RATE_LIMIT_CODES.contains(code) => {
+            StatusCode::TOO_MANY_REQUESTS
+        }
+        BinanceApiError::Api { code, .. } if UPSTREAM_CODES.contains(code) => {
+            StatusCode::BAD_GATEWAY
+        }
+        BinanceApiError::Api { code, .. } | BinanceApiError::ReplacementFailed { code, .. }
+            if is_request_rejection(*code) =>
+        {
+            StatusCode::BAD_REQUEST
+        }
+        BinanceApiError::Api { .. }
+        | BinanceApiError::ReplacementFailed { .. }
+        | BinanceApiError::Parse(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Extract override flag from X-Allow-Large-Order header (trusted clients), which
+/// lifts MAX_ORDER_USD for single orders and grids alike
+pub fn allow_large_order_from_headers(headers: &HeaderMap) -> bool {
+    headers
+        .get("X-Allow-Large-Order")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+/// 423 while `kill_switch` has halted trading for the rest of the UTC day,
+/// shared by every route that places orders
+pub fn ensure_trading_allowed(kill_switch: &KillSwitch) -> Result<(), (StatusCode, String)> {
+    kill_switch
+        .check(chrono::Utc::now().date_naive())
+        .map_err(|e| (StatusCode::LOCKED, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::create_token;
+    use crate::config::Config;
+    use crate::trailing::TrailingMonitor;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        Router,
+    };
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn bearer(config: &Config) -> String {
+        let token = create_token(
+            "device-1",
+            "Test iPhone",
+            &config.jwt_secret,
+            config.jwt_expiry_minutes,
+            &config.jwt_issuer,
+            &config.jwt_audience,
+        )
+        .unwrap();
+        format!("Bearer {}", token)
+    }
+
+    #[tokio::test]
+    async fn test_router_builds_from_one_shared_config() {
+        // A secret the environment can't supply, so auth only passes if the
+        // middleware checks tokens against this exact instance
+        let config = Arc::new(Config {
+            jwt_secret: "shared-instance-secret".to_string(),
+            ..Config::for_tests()
+        });
+        let monitor = Arc::new(TrailingMonitor::new(Config::for_tests()));
+        let app: Router = Router::new()
+            .nest("/account", account_routes(config.clone(), monitor.clone()))
+            .nest("/trailing", trailing_routes(config.clone(), monitor))
+            .with_state(config.clone());
+
+        let request = |authorization: String| {
+            Request::builder()
+                .uri("/trailing/orders")
+                .header("Authorization", authorization)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request(bearer(&config))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.oneshot(request(bearer(&Config::for_tests()))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_binance_errors_map_to_status() {
+        let status = |body: &str| binance_error_status(&BinanceApiError::from_body(body));
+
+        let insufficient = r#"{"code":-2010,"msg":"Account has insufficient balance."}"#;
+        assert_eq!(status(insufficient), StatusCode::BAD_REQUEST);
+        let filter = r#"{"code":-1013,"msg":"Filter failure: LOT_SIZE"}"#;
+        assert_eq!(status(filter), StatusCode::BAD_REQUEST);
+        let bad_param = r#"{"code":-1102,"msg":"Mandatory parameter 'price' was not sent."}"#;
+        assert_eq!(status(bad_param), StatusCode::BAD_REQUEST);
+        let unknown_order = r#"{"code":-2013,"msg":"Order does not exist."}"#;
+        assert_eq!(status(unknown_order), StatusCode::NOT_FOUND);
+        let rate_limited = r#"{"code":-1003,"msg":"Too many requests."}"#;
+        assert_eq!(status(rate_limited), StatusCode::TOO_MANY_REQUESTS);
+        let bad_key = r#"{"code":-2015,"msg":"Invalid API-key, IP, or permissions."}"#;
+        assert_eq!(status(bad_key), StatusCode::BAD_GATEWAY);
+        assert_eq!(status("<html>oops</html>"), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let network = BinanceApiError::Network("connection reset".to_string());
+        assert_eq!(binance_error_status(&network), StatusCode::BAD_GATEWAY);
+        let production = BinanceApiError::ProductionNotConfigured;
+        assert_eq!(binance_error_status(&production), StatusCode::SERVICE_UNAVAILABLE);
+        let circuit = BinanceApiError::CircuitOpen;
+        assert_eq!(binance_error_status(&circuit), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_tripped_kill_switch_locks_order_placement() {
+        let kill_switch = KillSwitch::new();
+        assert!(ensure_trading_allowed(&kill_switch).is_ok());
+
+        kill_switch.record(chrono::Utc::now().date_naive(), -120.0, 100.0);
+        let (status, error) = ensure_trading_allowed(&kill_switch).unwrap_err();
+        assert_eq!(status, StatusCode::LOCKED);
+        assert!(error.starts_with("Trading halted"), "{}", error);
+    }
+}
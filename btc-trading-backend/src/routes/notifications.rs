@@ -1,33 +1,29 @@
-use axum::{
-    extract::State,
-    http::StatusCode,
-    middleware,
-    routing::post,
-    Json, Router,
-};
+use axum::{http::StatusCode, middleware, routing::post, Json, Router};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::Arc;
 
-use crate::auth::auth_middleware;
+use crate::auth::{auth_middleware, AuthedDevice};
 use crate::config::Config;
-use crate::notifications::ApnsClient;
+use crate::notifications::{ApnsClient, ApnsEnvironment, PushOptions};
 
-pub fn notification_routes(apns: Arc<ApnsClient>) -> Router<Config> {
+pub fn notification_routes(config: Arc<Config>, apns: Arc<ApnsClient>) -> Router<Arc<Config>> {
     Router::new()
         .route("/register", post(register_token))
+        .route("/register-batch", post(register_token_batch))
         .route("/unregister", post(unregister_token))
         .route("/test", post(test_notification))
         .layer(axum::Extension(apns))
-        .route_layer(middleware::from_fn_with_state(
-            Config::from_env(),
-            auth_middleware,
-        ))
+        .route_layer(middleware::from_fn_with_state(config, auth_middleware))
 }
 
 #[derive(Deserialize)]
 pub struct RegisterTokenRequest {
     device_token: String,
     platform: String, // "ios" or "android"
+    /// APNs endpoint the token was issued for; None uses APNS_PRODUCTION's
+    #[serde(default)]
+    environment: Option<ApnsEnvironment>,
 }
 
 #[derive(Serialize)]
@@ -41,25 +37,127 @@ pub struct ErrorResponse {
     error: String,
 }
 
-/// Register device token for push notifications
+/// Register the calling device's push token, replacing any it registered before
 async fn register_token(
+    AuthedDevice(device): AuthedDevice,
     axum::Extension(apns): axum::Extension<Arc<ApnsClient>>,
     Json(request): Json<RegisterTokenRequest>,
 ) -> Result<Json<RegisterResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let token = validate_device_token(&request)
+        .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })))?;
+
+    apns.register_token(&device.sub, token, request.environment)
+        .await;
+
+    Ok(Json(RegisterResponse {
+        success: true,
+        message: "Device registered for notifications".to_string(),
+    }))
+}
+
+/// APNs device tokens are 32 bytes, sent as hex
+const APNS_TOKEN_LEN: usize = 64;
+const MAX_BATCH_TOKENS: usize = 20;
+
+#[derive(Deserialize)]
+pub struct RegisterBatchRequest {
+    tokens: Vec<RegisterTokenRequest>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TokenResult {
+    Accepted {
+        device_token: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        environment: Option<ApnsEnvironment>,
+    },
+    Rejected { device_token: String, error: String },
+}
+
+#[derive(Serialize)]
+pub struct RegisterBatchResponse {
+    results: Vec<TokenResult>,
+    accepted: usize,
+    rejected: usize,
+}
+
+/// Normalized token if it's a well-formed APNs token for a supported platform
+fn validate_device_token(request: &RegisterTokenRequest) -> Result<String, String> {
     if request.platform != "ios" {
+        return Err("Only iOS is supported".to_string());
+    }
+
+    let token = normalize_device_token(&request.device_token);
+    if token.len() != APNS_TOKEN_LEN || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("APNs tokens are {} hex characters", APNS_TOKEN_LEN));
+    }
+    Ok(token)
+}
+
+/// Tokens are stored trimmed and lowercase, so either case names one device
+fn normalize_device_token(token: &str) -> String {
+    token.trim().to_lowercase()
+}
+
+/// Validate each token in request order, dropping repeats of an earlier token
+fn validate_batch(requests: &[RegisterTokenRequest]) -> Vec<TokenResult> {
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+
+    for request in requests {
+        match validate_device_token(request) {
+            Ok(token) if !seen.insert(token.clone()) => {}
+            Ok(device_token) => results.push(TokenResult::Accepted {
+                device_token,
+                environment: request.environment,
+            }),
+            Err(error) => results.push(TokenResult::Rejected {
+                device_token: request.device_token.clone(),
+                error,
+            }),
+        }
+    }
+
+    results
+}
+
+/// Register several device tokens at once, reporting which were accepted
+async fn register_token_batch(
+    axum::Extension(apns): axum::Extension<Arc<ApnsClient>>,
+    Json(request): Json<RegisterBatchRequest>,
+) -> Result<Json<RegisterBatchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if request.tokens.is_empty() || request.tokens.len() > MAX_BATCH_TOKENS {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: "Only iOS is supported".to_string(),
+                error: format!("A batch needs 1 to {} tokens", MAX_BATCH_TOKENS),
             }),
         ));
     }
 
-    apns.register_token(request.device_token).await;
+    let results = validate_batch(&request.tokens);
+    for result in &results {
+        if let TokenResult::Accepted {
+            device_token,
+            environment,
+        } = result
+        {
+            // Batch tokens belong to devices other than the caller, so each
+            // token stands in for its own device id
+            apns.register_token(device_token, device_token.clone(), *environment)
+                .await;
+        }
+    }
 
-    Ok(Json(RegisterResponse {
-        success: true,
-        message: "Device registered for notifications".to_string(),
+    let accepted = results
+        .iter()
+        .filter(|r| matches!(r, TokenResult::Accepted { .. }))
+        .count();
+    Ok(Json(RegisterBatchResponse {
+        rejected: results.len() - accepted,
+        accepted,
+        results,
     }))
 }
 
@@ -73,7 +171,8 @@ async fn unregister_token(
     axum::Extension(apns): axum::Extension<Arc<ApnsClient>>,
     Json(request): Json<UnregisterTokenRequest>,
 ) -> Json<RegisterResponse> {
-    apns.unregister_token(&request.device_token).await;
+    apns.unregister_token(&normalize_device_token(&request.device_token))
+        .await;
 
     Json(RegisterResponse {
         success: true,
@@ -85,10 +184,22 @@ async fn unregister_token(
 async fn test_notification(
     axum::Extension(apns): axum::Extension<Arc<ApnsClient>>,
 ) -> Result<Json<RegisterResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if !apns.is_enabled() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: "Notifications disabled: the server has no APNs key \
+                        (APNS_KEY_CONTENT or APNS_KEY_PATH)"
+                    .to_string(),
+            }),
+        ));
+    }
+
     apns.send_notification(
         "🧪 Test Notification",
         "Push notifications are working!",
         None,
+        &PushOptions::default(),
     )
     .await
     .map_err(|e| {
@@ -105,3 +216,90 @@ async fn test_notification(
         message: "Test notification sent".to_string(),
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_request(device_token: &str, platform: &str) -> RegisterTokenRequest {
+        RegisterTokenRequest {
+            device_token: device_token.to_string(),
+            platform: platform.to_string(),
+            environment: None,
+        }
+    }
+
+    #[test]
+    fn test_mixed_batch() {
+        let phone = "a1".repeat(32);
+        let ipad = "0F".repeat(32);
+        let results = validate_batch(&[
+            token_request(&phone, "ios"),
+            token_request("not-a-token", "ios"),
+            token_request(&ipad, "ios"),
+            token_request(&phone, "ios"),
+            token_request(&"b2".repeat(32), "android"),
+        ]);
+
+        assert_eq!(
+            results,
+            vec![
+                TokenResult::Accepted {
+                    device_token: phone.clone(),
+                    environment: None,
+                },
+                TokenResult::Rejected {
+                    device_token: "not-a-token".to_string(),
+                    error: "APNs tokens are 64 hex characters".to_string(),
+                },
+                // Stored lowercase, so the same token in either case is one device
+                TokenResult::Accepted {
+                    device_token: ipad.to_lowercase(),
+                    environment: None,
+                },
+                TokenResult::Rejected {
+                    device_token: "b2".repeat(32),
+                    error: "Only iOS is supported".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_single_registration_is_validated_like_a_batch() {
+        let apns = Arc::new(ApnsClient::disabled());
+        let device = || {
+            AuthedDevice(crate::auth::Claims {
+                sub: "phone".to_string(),
+                exp: 0,
+                iat: 0,
+                iss: String::new(),
+                aud: String::new(),
+                device_name: "Phone".to_string(),
+            })
+        };
+        let register = |device_token: String| {
+            register_token(
+                device(),
+                axum::Extension(apns.clone()),
+                Json(token_request(&device_token, "ios")),
+            )
+        };
+
+        assert!(register(format!(" {} ", "AB".repeat(32))).await.is_ok());
+
+        let (status, Json(body)) = register("not-a-token".to_string()).await.err().unwrap();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.error, "APNs tokens are 64 hex characters");
+    }
+
+    #[test]
+    fn test_token_length_is_checked() {
+        assert!(validate_device_token(&token_request(&"a".repeat(63), "ios")).is_err());
+        assert!(validate_device_token(&token_request(&"g".repeat(64), "ios")).is_err());
+        assert_eq!(
+            validate_device_token(&token_request(&format!(" {} ", "c".repeat(64)), "ios")),
+            Ok("c".repeat(64))
+        );
+    }
+}
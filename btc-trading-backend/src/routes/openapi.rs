@@ -0,0 +1,193 @@
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use std::sync::Arc;
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use super::{auth, grid, history, order, price, trailing, ErrorResponse};
+use crate::config::Config;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "BTC Trading Backend", description = "Binance grid trading API for BTCWidget"),
+    paths(
+        auth::login,
+        auth::refresh_token,
+        order::create_limit_order,
+        order::create_market_order,
+        order::create_market_quote_order,
+        order::create_batch_orders,
+        order::order_events_stream,
+        grid::create_grid_pair,
+        grid::estimate_grid_pair,
+        grid::modify_order,
+        grid::rebalance_grid,
+        grid::cancel_order,
+        price::get_current_price,
+        price::convert_amount,
+        price::get_book,
+        history::get_trade_history,
+        history::get_raw_trades,
+        history::get_profit_summary,
+        history::get_daily_profit,
+        history::export_csv,
+        history::get_order_trades,
+        trailing::get_trailing_orders,
+        trailing::get_trailing_history,
+        trailing::delete_trailing_order,
+        trailing::update_trailing_order,
+        trailing::get_trailing_by_order_id,
+        trailing::delete_trailing_by_order_id,
+    ),
+    components(schemas(
+        super::ErrorResponse,
+        super::ValidationError,
+        auth::LoginRequest,
+        auth::RefreshRequest,
+        auth::LoginResponse,
+        order::CreateLimitOrderRequest,
+        order::CreateMarketOrderRequest,
+        order::CreateMarketQuoteOrderRequest,
+        order::BatchLimitOrder,
+        order::CreateBatchOrderRequest,
+        order::BatchItemResult,
+        order::BatchOrderResponse,
+        grid::GridPairResponse,
+        grid::GridEstimateResponse,
+        grid::ModifyResponse,
+        grid::CancelResponse,
+        grid::RebalanceResponse,
+        price::PriceResponse,
+        price::ConvertResponse,
+        price::BookResponse,
+        history::TradeHistoryResponse,
+        history::RawTradesResponse,
+        history::OrderTradesResponse,
+        trailing::TrailingOrdersResponse,
+        trailing::TrailingHistoryResponse,
+        trailing::DeleteResponse,
+        trailing::UpdateTrailingRequest,
+        crate::trading::CreateGridRequest,
+        crate::trading::ModifyOrderRequest,
+        crate::trading::CompletedPair,
+        crate::trading::ProfitSummary,
+        crate::trading::DailyProfit,
+        crate::trading::OrderFills,
+        crate::trailing::TrailingStep,
+        crate::trailing::TrailingSchedule,
+        crate::trailing::ReferenceSeed,
+        crate::trailing::TrailDirection,
+        crate::trailing::TrailingOrderResponse,
+        crate::trailing::CompletedTrailingOrder,
+        crate::trailing::TrailingOutcome,
+        crate::binance::NewOrderResponse,
+        crate::binance::Fill,
+        crate::binance::Trade,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Device login and token refresh"),
+        (name = "order", description = "Limit, market and batch orders"),
+        (name = "grid", description = "Grid pairs"),
+        (name = "price", description = "Public spot price"),
+        (name = "history", description = "Trade history and profit"),
+        (name = "trailing", description = "Trailing orders"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the JWT bearer scheme that `security(("bearer_auth" = []))` refers to
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let mut scheme = Http::new(HttpAuthScheme::Bearer);
+        scheme.bearer_format = Some("JWT".to_string());
+
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme("bearer_auth", SecurityScheme::Http(scheme));
+    }
+}
+
+pub fn openapi_routes() -> Router<Arc<Config>> {
+    Router::new().route("/openapi.json", get(get_openapi))
+}
+
+/// OpenAPI document for the app's routes (404 unless `EXPOSE_OPENAPI` is set)
+async fn get_openapi(
+    State(config): State<Arc<Config>>,
+) -> Result<Json<utoipa::openapi::OpenApi>, (StatusCode, Json<ErrorResponse>)> {
+    if !config.expose_openapi {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("OpenAPI document is disabled")),
+        ));
+    }
+
+    Ok(Json(ApiDoc::openapi()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_lists_routes() {
+        let json = ApiDoc::openapi().to_json().unwrap();
+        let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        for path in [
+            "/auth/login",
+            "/order/limit",
+            "/order/events",
+            "/grid/create",
+            "/grid/{order_id}",
+            "/price/current",
+            "/history/trades",
+            "/history/daily",
+            "/trailing/orders",
+            "/trailing/history",
+            "/trailing/by-order/{order_id}",
+        ] {
+            assert!(document["paths"][path].is_object(), "missing {}", path);
+        }
+
+        let schemas = &document["components"]["schemas"];
+        assert!(schemas["CreateLimitOrderRequest"]["properties"]["price"].is_object());
+        assert!(schemas["ErrorResponse"]["properties"]["errors"].is_object());
+        let params = &document["paths"]["/history/trades"]["get"]["parameters"];
+        let header = params
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["name"] == "X-Use-Production")
+            .expect("X-Use-Production parameter");
+        assert_eq!(header["in"], "header");
+        assert_eq!(header["schema"]["type"], "boolean");
+        assert_eq!(
+            document["components"]["securitySchemes"]["bearer_auth"]["scheme"],
+            "bearer"
+        );
+        // Public routes carry no security requirement
+        assert!(document["paths"]["/price/current"]["get"]["security"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_document_is_disabled_by_default() {
+        let Err((status, Json(body))) = get_openapi(State(Arc::new(Config::for_tests()))).await
+        else {
+            panic!("document served while disabled");
+        };
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body.error, "OpenAPI document is disabled");
+        assert!(body.errors.is_empty());
+
+        let config = Config {
+            expose_openapi: true,
+            ..Config::for_tests()
+        };
+        let Json(document) = get_openapi(State(Arc::new(config))).await.unwrap();
+        assert!(document.paths.paths.contains_key("/order/market"));
+    }
+}
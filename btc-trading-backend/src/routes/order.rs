@@ -1,38 +1,67 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::{HeaderMap, StatusCode},
     middleware,
-    routing::post,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
     Json, Router,
 };
+use futures::{future::join_all, Stream};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::ToSchema;
 
-use crate::auth::auth_middleware;
-use crate::binance::{BinanceClient, NewOrderResponse};
+use super::{
+    allow_large_order_from_headers, binance_error_status, normalize_precision, symbol_error,
+    ErrorResponse, ProductionHeader, SymbolQuery, ValidationError,
+};
+use crate::auth::{auth_middleware, AuthedDevice};
+use crate::binance::{
+    BinanceApiError, BinanceClient, BookCache, BookTicker, MarketQuantity, NewOrderResponse,
+    SymbolPrecision, TimeInForce,
+};
 use crate::config::Config;
-use crate::trailing::TrailingMonitor;
+use crate::notifications::{OrderEvent, OrderEvents};
+use crate::trading::{check_order_balance, check_order_size, KILL_SWITCH};
+use crate::trailing::{
+    validate_activation_price, validate_adjustment_threshold, validate_max_adjustments, OrderSide,
+    ReferenceSeed, TrailDirection, TrailingMonitor, TrailingOrder, TrailingSchedule, TrailingStep,
+    DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT,
+};
 
 /// State for order routes that includes trailing monitor
 #[derive(Clone)]
 pub struct OrderAppState {
-    pub config: Config,
+    pub config: Arc<Config>,
     pub trailing_monitor: Arc<TrailingMonitor>,
 }
 
-pub fn order_routes(trailing_monitor: Arc<TrailingMonitor>) -> Router<Config> {
+/// Comment sent on idle event streams so proxies don't drop the connection
+const SSE_HEARTBEAT: Duration = Duration::from_secs(15);
+
+pub fn order_routes(
+    config: Arc<Config>,
+    trailing_monitor: Arc<TrailingMonitor>,
+    order_events: OrderEvents,
+    book_cache: Arc<BookCache>,
+) -> Router<Arc<Config>> {
     let state = OrderAppState {
-        config: Config::from_env(),
+        config: config.clone(),
         trailing_monitor,
     };
 
     Router::new()
         .route("/limit", post(create_limit_order))
         .route("/market", post(create_market_order))
-        .route_layer(middleware::from_fn_with_state(
-            Config::from_env(),
-            auth_middleware,
-        ))
+        .route("/market-quote", post(create_market_quote_order))
+        .route("/batch", post(create_batch_orders))
+        .route("/events", get(order_events_stream))
+        .layer(axum::Extension(order_events))
+        .layer(axum::Extension(book_cache))
+        .route_layer(middleware::from_fn_with_state(config, auth_middleware))
         .with_state(state)
 }
 
@@ -45,182 +74,1904 @@ fn use_production_from_headers(headers: &HeaderMap) -> bool {
         .unwrap_or(false)
 }
 
-#[derive(Deserialize)]
+/// Extract skip flag from X-Skip-Balance-Check header (advanced users)
+fn skip_balance_check_from_headers(headers: &HeaderMap) -> bool {
+    headers
+        .get("X-Skip-Balance-Check")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+/// Extract X-Strict-Precision header: reject over-precise prices and quantities
+/// instead of rounding them
+fn strict_precision_from_headers(headers: &HeaderMap) -> bool {
+    headers
+        .get("X-Strict-Precision")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false)
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct CreateLimitOrderRequest {
     pub side: String,      // "BUY" or "SELL"
     pub price: f64,
     pub quantity: f64,
     /// Optional trailing percentage (e.g., 1.0 = 1%)
     pub trailing_percent: Option<f64>,
+    /// Optional steps tightening the trailing as profit grows (replaces the flat percent)
+    pub trailing_schedule: Option<Vec<TrailingStep>>,
+    /// Price trailing profit is measured from (defaults to the order price)
+    pub entry_price: Option<f64>,
+    /// Where the trail's reference price starts: "market" (default), "order_price",
+    /// or { "explicit": price } to resume an earlier trail
+    #[serde(default)]
+    pub reference_seed: ReferenceSeed,
+    /// BUY only: "down" (default) places a limit order, "up" a stop-limit above the
+    /// market that fills on a rally
+    #[serde(default)]
+    pub trailing_direction: TrailDirection,
+    /// Smallest move (in %) that re-prices the trailing order; default 0.1, must be
+    /// below the trailing percent
+    pub adjustment_threshold_percent: Option<f64>,
+    /// Seconds the trail's reference may go without moving before it's reset to the
+    /// market price; by default it's kept however old
+    pub reference_max_age_secs: Option<u64>,
+    /// Smallest move (in %) past the reference a new high (or low) needs before the
+    /// reference follows it; default 0 follows every new extreme
+    pub min_reference_move_percent: Option<f64>,
+    /// Moves allowed before the trailing order stays at its last price; unlimited by default
+    pub max_adjustments: Option<u32>,
+    /// Hold the order until the market reaches this price (at or above for a SELL, at
+    /// or below for a BUY), then start trailing
+    pub activation_price: Option<f64>,
+    /// Binance order (same symbol) that must fill before trailing starts, e.g. the
+    /// grid BUY paired with this SELL; the trail then starts from its fill price
+    pub depends_on_order_id: Option<i64>,
+    /// GTC (default), IOC, FOK, or GTX (post-only)
+    pub time_in_force: Option<String>,
+    /// Strategy label (e.g. "grid-A"), shown in history and fill notifications
+    pub client_tag: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreateMarketOrderRequest {
     pub side: String,      // "BUY" or "SELL"
+    #[serde(default)]
     pub quantity: f64,
+    /// BUY only: quote amount (USDT) to spend, sent instead of `quantity`
+    pub quote_order_qty: Option<f64>,
+    /// Strategy label (e.g. "manual"), shown in history and fill notifications
+    pub client_tag: Option<String>,
+}
+
+/// Market BUY sized in USD: "buy $100 of BTC"
+#[derive(Deserialize, ToSchema)]
+pub struct CreateMarketQuoteOrderRequest {
+    /// Quote amount (USDT) to spend
+    pub amount_usd: f64,
+    /// Strategy label (e.g. "manual"), shown in history and fill notifications
+    pub client_tag: Option<String>,
+}
+
+/// One limit order in a batch; the symbol comes from `?symbol=`
+#[derive(Deserialize, ToSchema)]
+pub struct BatchLimitOrder {
+    pub side: String,      // "BUY" or "SELL"
+    pub price: f64,
+    pub quantity: f64,
+    /// GTC (default), IOC, FOK, or GTX (post-only)
+    pub time_in_force: Option<String>,
+    /// Strategy label for this order
+    pub client_tag: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateBatchOrderRequest {
+    pub orders: Vec<BatchLimitOrder>,
+    /// Cancel the orders already placed if any placement fails
+    #[serde(default)]
+    pub rollback_on_error: bool,
+}
+
+/// Outcome of one order in a batch, in request order
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchItemResult {
+    /// Placed on Binance, and cancelled again if `rolled_back`
+    Placed {
+        order: Box<NewOrderResponse>,
+        rolled_back: bool,
+    },
+    Failed {
+        error: String,
+    },
+    /// Not attempted because an earlier order failed and rollback was requested
+    Skipped,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BatchOrderResponse {
+    pub results: Vec<BatchItemResult>,
+    /// Orders from this batch still open on Binance
+    pub placed: usize,
+    pub failed: usize,
+    pub rolled_back: bool,
 }
 
 // Note: Just return NewOrderResponse directly to maintain consistent JSON format
 // NewOrderResponse uses camelCase (orderId, clientOrderId, etc) to match Binance API
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    error: String,
+/// 400 for rejected fields; the top-level `error` joins their messages
+fn validation_errors(errors: Vec<ValidationError>) -> (StatusCode, Json<ErrorResponse>) {
+    let error = errors
+        .iter()
+        .map(|e| e.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error, errors }))
+}
+
+/// 400 for a single rejected field
+fn validation_error(error: ValidationError) -> (StatusCode, Json<ErrorResponse>) {
+    validation_errors(vec![error])
+}
+
+/// Most orders accepted in one batch
+const MAX_BATCH_ORDERS: usize = 20;
+
+/// Orders sent to Binance at once, keeping a batch well inside the order rate limit
+const BATCH_CONCURRENCY: usize = 5;
+
+/// Binance's generic "order rejected" code, returned for post-only orders that would take
+const ORDER_REJECTED_CODE: i32 = -2010;
+
+/// Reject locally with a descriptive 400 when the free balance can't cover the order
+///
+/// Market orders pass the current price, which values a BUY.
+async fn ensure_balance(
+    client: &BinanceClient,
+    symbol: &str,
+    side: &str,
+    quantity: f64,
+    price: f64,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let account = client.get_account().await.map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+
+    check_order_balance(&account.balances, symbol, side, quantity, price)
+        .map_err(|e| validation_error(ValidationError::new("quantity", "insufficient_balance", e)))
+}
+
+/// Reject an order worth more than `max_order_usd` unless the client overrides the cap
+fn ensure_order_size(
+    headers: &HeaderMap,
+    field: &str,
+    notional: f64,
+    max_order_usd: f64,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if allow_large_order_from_headers(headers) {
+        return Ok(());
+    }
+
+    check_order_size(notional, max_order_usd)
+        .map_err(|e| validation_error(ValidationError::new(field, "above_max_order_usd", e)))
+}
+
+/// 423 while the daily-loss kill switch has halted the request's environment
+fn ensure_trading_allowed(headers: &HeaderMap) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let kill_switch = KILL_SWITCH.get(use_production_from_headers(headers));
+    super::ensure_trading_allowed(kill_switch)
+        .map_err(|(status, e)| (status, Json(ErrorResponse::new(e))))
+}
+
+/// Starting reference price for a new trailing order
+async fn seed_reference_price(
+    client: &BinanceClient,
+    symbol: &str,
+    seed: ReferenceSeed,
+    order_price: f64,
+) -> Result<f64, (StatusCode, Json<ErrorResponse>)> {
+    match seed {
+        ReferenceSeed::Market => client.get_price(symbol).await.map_err(|e| {
+            (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+        }),
+        ReferenceSeed::OrderPrice => Ok(order_price),
+        ReferenceSeed::Explicit(price) => Ok(price),
+    }
+}
+
+/// Reject a trailing order with 429 when the device already has `max` active
+///
+/// Checked before placing so a device at its cap places nothing; the slot is
+/// only taken by `TrailingMonitor::add_order_within_cap` once the order exists.
+async fn ensure_trailing_capacity(
+    monitor: &TrailingMonitor,
+    device_id: &str,
+    max: usize,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let active = monitor.count_for_device(device_id).await;
+    if active >= max {
+        return Err(trailing_cap_error(active, max));
+    }
+
+    Ok(())
+}
+
+fn trailing_cap_error(active: usize, max: usize) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(ErrorResponse::new(format!(
+            "Trailing order limit reached ({} of {} active); stop one first",
+            active, max
+        ))),
+    )
+}
+
+/// Check the price a market order would fill at against the last price
+///
+/// A BUY takes the best ask and a SELL the best bid; a thin book can put
+/// either far from the last trade.
+fn check_slippage(
+    side: &str,
+    book: &BookTicker,
+    last_price: f64,
+    max_slippage_percent: f64,
+) -> Result<(), String> {
+    let (quoted, quote_name) = if side == "BUY" {
+        (book.ask_price_f64(), "best ask")
+    } else {
+        (book.bid_price_f64(), "best bid")
+    };
+    if quoted <= 0.0 || last_price <= 0.0 {
+        return Err(format!("No {} available for {}", quote_name, book.symbol));
+    }
+
+    let slippage_percent = (quoted - last_price).abs() / last_price * 100.0;
+    if slippage_percent > max_slippage_percent {
+        return Err(format!(
+            "Market {} would fill around {:.2} ({}) vs last price {:.2}: \
+             {:.2}% slippage exceeds the {:.2}% limit",
+            side,
+            quoted,
+            quote_name,
+            last_price,
+            slippage_percent,
+            max_slippage_percent
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the best bid/ask used for slippage isn't older than `max_age`; zero disables
+fn check_price_age(symbol: &str, age: Duration, max_age: Duration) -> Result<(), String> {
+    if !max_age.is_zero() && age > max_age {
+        return Err(format!(
+            "Best bid/ask for {} is {:.1}s old, past the {:.1}s limit (MAX_PRICE_AGE_MS); \
+             retry once Binance prices are flowing again",
+            symbol,
+            age.as_secs_f64(),
+            max_age.as_secs_f64()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reject a market order with 503 when the book is older than `max_price_age`,
+/// or with 400 when it has moved more than `max_slippage_percent` from
+/// `last_price`; zero disables either check
+async fn ensure_market_book(
+    client: &BinanceClient,
+    book_cache: &BookCache,
+    symbol: &str,
+    side: &str,
+    last_price: f64,
+    max_slippage_percent: f64,
+    max_price_age: Duration,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if max_slippage_percent <= 0.0 && max_price_age.is_zero() {
+        return Ok(());
+    }
+
+    let quote = book_cache.book_ticker(client, symbol).await.map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+    check_price_age(symbol, quote.age, max_price_age)
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, Json(ErrorResponse::new(e))))?;
+    if max_slippage_percent > 0.0 {
+        check_slippage(side, &quote.ticker, last_price, max_slippage_percent)
+            .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+    }
+
+    Ok(())
+}
+
+/// Check a market order's value against the symbol's minimum notional
+fn check_min_notional(symbol: &str, notional: f64, min_notional: f64) -> Result<(), String> {
+    if notional < min_notional {
+        return Err(format!(
+            "Market orders on {} need at least ${:.2}; this one is worth ${:.2}",
+            symbol, min_notional, notional
+        ));
+    }
+    Ok(())
+}
+
+/// Reject a market order with 400 when it's below the symbol's minimum notional
+///
+/// Binance would reject it anyway, but with a bare "Filter failure: NOTIONAL".
+/// If exchangeInfo is unavailable the check is skipped and Binance decides.
+async fn ensure_min_notional(
+    client: &BinanceClient,
+    symbol: &str,
+    quantity: MarketQuantity,
+    price: f64,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let min_notional = match client.get_min_notional(symbol).await {
+        Ok(min_notional) => min_notional,
+        Err(e) => {
+            tracing::warn!("exchangeInfo unavailable ({}), skipping min notional check", e);
+            return Ok(());
+        }
+    };
+
+    let field = market_quantity_field(quantity);
+    check_min_notional(symbol, quantity.notional(price), min_notional)
+        .map_err(|e| validation_error(ValidationError::new(field, "below_min_notional", e)))
+}
+
+/// The symbol's tick and step sizes, or None to leave precision to Binance
+async fn symbol_precision(client: &BinanceClient, symbol: &str) -> Option<SymbolPrecision> {
+    match client.get_precision(symbol).await {
+        Ok(precision) => Some(precision),
+        Err(e) => {
+            tracing::warn!("exchangeInfo unavailable ({}), skipping precision normalization", e);
+            None
+        }
+    }
+}
+
+/// Round a limit order's price to the tick size and floor its quantity to the
+/// step size, reporting both fields if neither can be used
+fn normalize_limit_order(
+    precision: &SymbolPrecision,
+    price: f64,
+    quantity: f64,
+    strict: bool,
+) -> Result<(f64, f64), Vec<ValidationError>> {
+    let price = normalize_precision(
+        "price",
+        price,
+        precision.round_price(price),
+        precision.tick_size,
+        strict,
+    );
+    let quantity = normalize_precision(
+        "quantity",
+        quantity,
+        precision.floor_quantity(quantity),
+        precision.step_size,
+        strict,
+    );
+
+    match (price, quantity) {
+        (Ok(price), Ok(quantity)) => Ok((price, quantity)),
+        (price, quantity) => Err(price.err().into_iter().chain(quantity.err()).collect()),
+    }
+}
+
+/// Request field a market order was sized by
+fn market_quantity_field(quantity: MarketQuantity) -> &'static str {
+    match quantity {
+        MarketQuantity::Base(_) => "quantity",
+        MarketQuantity::Quote(_) => "quote_order_qty",
+    }
+}
+
+/// The adjustment threshold a trailing limit order will use, the default when
+/// none is given, once it's checked to sit inside the trail
+fn adjustment_threshold(
+    request: &CreateLimitOrderRequest,
+    schedule: Option<&TrailingSchedule>,
+) -> Result<f64, ValidationError> {
+    let threshold = request
+        .adjustment_threshold_percent
+        .unwrap_or(DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT);
+    let trail = schedule.cloned().unwrap_or_else(|| {
+        TrailingSchedule::flat(request.trailing_percent.unwrap_or_default())
+    });
+    validate_adjustment_threshold(threshold, &trail)
+        .map(|_| threshold)
+        .map_err(|e| {
+            ValidationError::new("adjustment_threshold_percent", "invalid_adjustment_threshold", e)
+        })
+}
+
+/// Parse the requested time in force, defaulting to GTC
+fn parse_time_in_force(value: Option<&str>) -> Result<TimeInForce, String> {
+    value.map_or(Ok(TimeInForce::default()), str::parse)
+}
+
+/// Check an order's side, price (None for market orders) and quantity,
+/// reporting every bad field rather than just the first
+fn validate_order_fields(side: &str, price: Option<f64>, quantity: f64) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    if side != "BUY" && side != "SELL" {
+        errors.push(ValidationError::new(
+            "side",
+            "must_be_buy_or_sell",
+            "Side must be BUY or SELL",
+        ));
+    }
+    if price.is_some_and(|p| p <= 0.0) {
+        errors.push(ValidationError::new("price", "must_be_positive", "Price must be positive"));
+    }
+    if quantity <= 0.0 {
+        errors.push(ValidationError::new(
+            "quantity",
+            "must_be_positive",
+            "Quantity must be positive",
+        ));
+    }
+    errors
+}
+
+/// Check a market order's side and size: either `quantity`, or `quote_order_qty`
+/// on a BUY
+fn validate_market_order(
+    side: &str,
+    request: &CreateMarketOrderRequest,
+) -> Result<MarketQuantity, Vec<ValidationError>> {
+    let Some(quote_order_qty) = request.quote_order_qty else {
+        let errors = validate_order_fields(side, None, request.quantity);
+        return if errors.is_empty() {
+            Ok(MarketQuantity::Base(request.quantity))
+        } else {
+            Err(errors)
+        };
+    };
+
+    let mut errors: Vec<_> = validate_order_fields(side, None, request.quantity)
+        .into_iter()
+        .filter(|e| e.field != "quantity")
+        .collect();
+    if side == "SELL" {
+        errors.push(ValidationError::new(
+            "quote_order_qty",
+            "buy_only",
+            "quote_order_qty is only supported on BUY market orders",
+        ));
+    }
+    if quote_order_qty <= 0.0 {
+        errors.push(ValidationError::new(
+            "quote_order_qty",
+            "must_be_positive",
+            "quote_order_qty must be positive",
+        ));
+    }
+    if request.quantity != 0.0 {
+        errors.push(ValidationError::new(
+            "quantity",
+            "conflicts_with_quote_order_qty",
+            "Send either quantity or quote_order_qty, not both",
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(MarketQuantity::Quote(quote_order_qty))
+    } else {
+        Err(errors)
+    }
+}
+
+fn time_in_force_error(message: String) -> ValidationError {
+    ValidationError::new("time_in_force", "invalid_time_in_force", message)
+}
+
+/// Map a limit order failure, explaining post-only rejections
+fn limit_order_error(
+    error: BinanceApiError,
+    time_in_force: TimeInForce,
+) -> (StatusCode, Json<ErrorResponse>) {
+    let would_take = matches!(
+        &error,
+        BinanceApiError::Api { code: ORDER_REJECTED_CODE, message }
+            if message.contains("immediately match")
+    );
+
+    if time_in_force == TimeInForce::PostOnly && would_take {
+        return validation_error(ValidationError::new(
+            "price",
+            "post_only_would_match",
+            "Post-only order rejected: the price would immediately match. \
+             Move it away from the market to rest as a maker order",
+        ));
+    }
+
+    (binance_error_status(&error), Json(ErrorResponse::new(error.to_string())))
 }
 
 /// Create a single limit order with optional trailing
+#[utoipa::path(
+    post,
+    path = "/order/limit",
+    tag = "order",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    request_body = CreateLimitOrderRequest,
+    responses(
+        (status = 200, description = "Order placed", body = NewOrderResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 423, description = "Daily loss limit hit, trading halted", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Binance circuit breaker is open", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn create_limit_order(
     State(state): State<OrderAppState>,
     headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
     Json(request): Json<CreateLimitOrderRequest>,
 ) -> Result<Json<NewOrderResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Validate side
+    let symbol = state
+        .config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
+    ensure_trading_allowed(&headers)?;
+
+    // Validate side, price and quantity
     let side = request.side.to_uppercase();
-    if side != "BUY" && side != "SELL" {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Side must be BUY or SELL".to_string(),
-            }),
-        ));
+    let errors = validate_order_fields(&side, Some(request.price), request.quantity);
+    if !errors.is_empty() {
+        return Err(validation_errors(errors));
     }
+    let notional = request.price * request.quantity;
+    ensure_order_size(&headers, "quantity", notional, state.config.max_order_usd)?;
 
-    // Validate price
-    if request.price <= 0.0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Price must be positive".to_string(),
-            }),
-        ));
+    // Validate time in force
+    let time_in_force = parse_time_in_force(request.time_in_force.as_deref())
+        .map_err(|e| validation_error(time_in_force_error(e)))?;
+
+    // Trailing re-creates the order as GTC, so it only makes sense for resting orders
+    let trailing = request.trailing_percent.is_some_and(|p| p > 0.0);
+    if trailing && time_in_force != TimeInForce::Gtc {
+        return Err(validation_error(ValidationError::new(
+            "time_in_force",
+            "trailing_requires_gtc",
+            "Trailing requires a GTC order",
+        )));
     }
 
-    // Validate quantity
-    if request.quantity <= 0.0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Quantity must be positive".to_string(),
-            }),
-        ));
+    let schedule = match request.trailing_schedule.clone() {
+        Some(steps) => Some(TrailingSchedule::new(steps).map_err(|e| {
+            validation_error(ValidationError::new("trailing_schedule", "invalid_schedule", e))
+        })?),
+        None => None,
+    };
+
+    let order_side = if side == "BUY" {
+        OrderSide::Buy
+    } else {
+        OrderSide::Sell
+    };
+
+    // Checked before placing so a rejected request leaves no untrailed order behind
+    let mut threshold_percent = DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT;
+    if trailing {
+        let direction = request.trailing_direction;
+        if order_side == OrderSide::Sell && direction != TrailDirection::Down {
+            return Err(validation_error(ValidationError::new(
+                "trailing_direction",
+                "buy_only",
+                "trailing_direction only applies to BUY orders",
+            )));
+        }
+        request.reference_seed.validate(order_side, request.price).map_err(|e| {
+            validation_error(ValidationError::new("reference_seed", "invalid_reference_seed", e))
+        })?;
+        threshold_percent =
+            adjustment_threshold(&request, schedule.as_ref()).map_err(validation_error)?;
+        if request.reference_max_age_secs == Some(0) {
+            return Err(validation_error(ValidationError::new(
+                "reference_max_age_secs",
+                "invalid_reference_max_age",
+                "reference_max_age_secs must be positive",
+            )));
+        }
+        if let Some(min_move) = request.min_reference_move_percent {
+            if !(min_move.is_finite() && min_move >= 0.0) {
+                return Err(validation_error(ValidationError::new(
+                    "min_reference_move_percent",
+                    "invalid_min_reference_move",
+                    "min_reference_move_percent must be zero or positive",
+                )));
+            }
+        }
+        if let Some(max_adjustments) = request.max_adjustments {
+            validate_max_adjustments(max_adjustments).map_err(|e| {
+                validation_error(ValidationError::new(
+                    "max_adjustments",
+                    "invalid_max_adjustments",
+                    e,
+                ))
+            })?;
+        }
+        if let Some(price) = request.activation_price {
+            validate_activation_price(price).map_err(|e| {
+                validation_error(ValidationError::new(
+                    "activation_price",
+                    "invalid_activation_price",
+                    e,
+                ))
+            })?;
+        }
+
+        let max = state.config.max_trailing_orders_per_device;
+        ensure_trailing_capacity(&state.trailing_monitor, &device.sub, max).await?;
     }
 
     let use_production = use_production_from_headers(&headers);
     let client = BinanceClient::for_environment(&state.config, use_production).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
     })?;
 
-    let order = client
-        .create_limit_order(&side, request.price, request.quantity)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                }),
-            )
-        })?;
+    let (price, quantity) = match symbol_precision(&client, &symbol).await {
+        Some(precision) => {
+            let strict = strict_precision_from_headers(&headers);
+            normalize_limit_order(&precision, request.price, request.quantity, strict)
+                .map_err(validation_errors)?
+        }
+        None => (request.price, request.quantity),
+    };
+
+    if !skip_balance_check_from_headers(&headers) {
+        ensure_balance(&client, &symbol, &side, quantity, price).await?;
+    }
+
+    let reference_price = if trailing {
+        let seed = request.reference_seed;
+        Some(seed_reference_price(&client, &symbol, seed, price).await?)
+    } else {
+        None
+    };
+
+    // A trail-up BUY waits above the market as a stop-limit
+    let tag = request.client_tag.as_deref();
+    let stop_entry = trailing && request.trailing_direction == TrailDirection::Up;
+    let order = if stop_entry {
+        client.create_stop_limit_order(&symbol, &side, price, quantity, tag).await
+    } else {
+        client.create_limit_order(&symbol, &side, price, quantity, time_in_force, tag).await
+    }
+    .map_err(|e| limit_order_error(e, time_in_force))?;
 
     // If trailing_percent is specified, add to trailing monitor
-    if let Some(trailing_percent) = request.trailing_percent {
-        if trailing_percent > 0.0 {
-            let trailing_id = state.trailing_monitor.add_from_request(
-                order.orderId,
-                &side,
-                request.price,
-                request.quantity,
+    match (request.trailing_percent, reference_price) {
+        (Some(trailing_percent), Some(reference_price)) => {
+            let mut trailing_order = TrailingOrder::new(
+                order.order_id,
+                symbol.clone(),
+                order_side,
                 trailing_percent,
+                price,
+                quantity,
                 use_production,
-            ).await;
+            )
+            .with_reference_price(reference_price)
+            .with_direction(request.trailing_direction)
+            .with_adjustment_threshold(threshold_percent);
+            if let Some(max_age_secs) = request.reference_max_age_secs {
+                trailing_order = trailing_order.with_reference_max_age(max_age_secs);
+            }
+            if let Some(min_move) = request.min_reference_move_percent {
+                trailing_order = trailing_order.with_min_reference_move(min_move);
+            }
+            if let Some(max_adjustments) = request.max_adjustments {
+                trailing_order = trailing_order.with_max_adjustments(max_adjustments);
+            }
+            if let Some(price) = request.activation_price {
+                trailing_order = trailing_order.with_activation_price(price);
+            }
+            if let Some(dependency) = request.depends_on_order_id {
+                trailing_order = trailing_order.with_dependency(dependency);
+            }
+            if let Some(schedule) = schedule {
+                let entry_price = request.entry_price.unwrap_or(price);
+                trailing_order = trailing_order.with_schedule(schedule, entry_price);
+            }
+            if let Some(ref tag) = request.client_tag {
+                trailing_order = trailing_order.with_client_tag(tag);
+            }
+            trailing_order = trailing_order.with_device(&device.sub);
+
+            let max = state.config.max_trailing_orders_per_device;
+            let trailing_id =
+                match state.trailing_monitor.add_order_within_cap(trailing_order, max).await {
+                    Ok(id) => id,
+                    Err(active) => {
+                        // Another request took the last slot since the check above
+                        if let Err(e) = client.cancel_order(&symbol, order.order_id).await {
+                            tracing::error!(
+                                "Failed to cancel order {} over the trailing cap: {}",
+                                order.order_id,
+                                e
+                            );
+                        }
+                        return Err(trailing_cap_error(active, max));
+                    }
+                };
 
             tracing::info!(
-                "Created {} limit order @ {} qty {} with {}% trailing ({})",
+                "[{}] Created {} limit order @ {} qty {} with {}% trailing from {} ({})",
+                device.device_name,
                 side,
-                request.price,
-                request.quantity,
+                price,
+                quantity,
                 trailing_percent,
+                reference_price,
                 trailing_id
             );
-        } else {
+        }
+        _ => {
             tracing::info!(
-                "Created {} limit order @ {} qty {}",
+                "[{}] Created {} limit order @ {} qty {}",
+                device.device_name,
                 side,
-                request.price,
-                request.quantity
+                price,
+                quantity
             );
         }
-    } else {
-        tracing::info!(
-            "Created {} limit order @ {} qty {}",
-            side,
-            request.price,
-            request.quantity
-        );
     }
 
     Ok(Json(order))
 }
 
 /// Create a market order (immediate execution at current price)
+#[utoipa::path(
+    post,
+    path = "/order/market",
+    tag = "order",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    request_body = CreateMarketOrderRequest,
+    responses(
+        (status = 200, description = "Order filled", body = NewOrderResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 423, description = "Daily loss limit hit, trading halted", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Breaker open or best bid/ask stale", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn create_market_order(
     State(state): State<OrderAppState>,
+    axum::Extension(book_cache): axum::Extension<Arc<BookCache>>,
     headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
     Json(request): Json<CreateMarketOrderRequest>,
 ) -> Result<Json<NewOrderResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Validate side
+    let symbol = state
+        .config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
+
+    // Validate side and size
     let side = request.side.to_uppercase();
-    if side != "BUY" && side != "SELL" {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Side must be BUY or SELL".to_string(),
-            }),
-        ));
+    let quantity = validate_market_order(&side, &request).map_err(validation_errors)?;
+
+    let (client, quantity) =
+        prepare_market_order(&state, &book_cache, &headers, &symbol, &side, quantity).await?;
+
+    let order = client
+        .create_market_order(&symbol, &side, quantity, request.client_tag.as_deref())
+        .await
+        .map_err(|e| (binance_error_status(&e), Json(ErrorResponse::new(e.to_string()))))?;
+
+    log_market_order(&device.device_name, &side, &order);
+    Ok(Json(order))
+}
+
+/// Buy a USD amount of the base asset at market
+///
+/// The same order as `/order/market` with `quote_order_qty`: Binance works out
+/// the quantity, and the fills in the response show how much was received.
+#[utoipa::path(
+    post,
+    path = "/order/market-quote",
+    tag = "order",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    request_body = CreateMarketQuoteOrderRequest,
+    responses(
+        (status = 200, description = "Order filled", body = NewOrderResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 423, description = "Daily loss limit hit, trading halted", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Breaker open or best bid/ask stale", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn create_market_quote_order(
+    State(state): State<OrderAppState>,
+    axum::Extension(book_cache): axum::Extension<Arc<BookCache>>,
+    headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
+    Json(request): Json<CreateMarketQuoteOrderRequest>,
+) -> Result<Json<NewOrderResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = state
+        .config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
+
+    let amount_usd = request.amount_usd;
+    if !(amount_usd.is_finite() && amount_usd > 0.0) {
+        return Err(validation_error(ValidationError::new(
+            "amount_usd",
+            "must_be_positive",
+            "amount_usd must be positive",
+        )));
+    }
+
+    let quantity = MarketQuantity::Quote(amount_usd);
+    let (client, _) =
+        prepare_market_order(&state, &book_cache, &headers, &symbol, "BUY", quantity).await?;
+
+    let order = client
+        .create_market_buy_quote(&symbol, amount_usd, request.client_tag.as_deref())
+        .await
+        .map_err(|e| (binance_error_status(&e), Json(ErrorResponse::new(e.to_string()))))?;
+
+    log_market_order(&device.device_name, "BUY", &order);
+    Ok(Json(order))
+}
+
+/// Normalize a validated market order and run the pre-trade checks (min
+/// notional, size cap, balance, slippage), returning the client to place it with
+async fn prepare_market_order(
+    state: &OrderAppState,
+    book_cache: &BookCache,
+    headers: &HeaderMap,
+    symbol: &str,
+    side: &str,
+    quantity: MarketQuantity,
+) -> Result<(BinanceClient, MarketQuantity), (StatusCode, Json<ErrorResponse>)> {
+    ensure_trading_allowed(headers)?;
+    let use_production = use_production_from_headers(headers);
+    let client = BinanceClient::for_environment(&state.config, use_production).map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+
+    // Quote-sized orders are spent by Binance, so only base quantities need a step
+    let quantity = match quantity {
+        MarketQuantity::Base(base) => match symbol_precision(&client, symbol).await {
+            Some(precision) => MarketQuantity::Base(
+                normalize_precision(
+                    "quantity",
+                    base,
+                    precision.floor_quantity(base),
+                    precision.step_size,
+                    strict_precision_from_headers(headers),
+                )
+                .map_err(validation_error)?,
+            ),
+            None => quantity,
+        },
+        MarketQuantity::Quote(_) => quantity,
+    };
+
+    let price = client.get_price(symbol).await.map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+    ensure_min_notional(&client, symbol, quantity, price).await?;
+
+    let field = market_quantity_field(quantity);
+    ensure_order_size(headers, field, quantity.notional(price), state.config.max_order_usd)?;
+
+    if !skip_balance_check_from_headers(headers) {
+        let base_quantity = quantity.base_quantity(price);
+        ensure_balance(&client, symbol, side, base_quantity, price).await?;
+    }
+
+    let max_slippage = state.config.max_slippage_percent;
+    let max_price_age = Duration::from_millis(state.config.max_price_age_ms);
+    ensure_market_book(&client, book_cache, symbol, side, price, max_slippage, max_price_age)
+        .await?;
+
+    Ok((client, quantity))
+}
+
+fn log_market_order(device_name: &str, side: &str, order: &NewOrderResponse) {
+    match order.avg_fill_price() {
+        Some(avg_price) => tracing::info!(
+            "[{}] Created {} market order qty {} filled {} @ avg {:.2}",
+            device_name,
+            side,
+            order.orig_qty,
+            order.executed_qty,
+            avg_price
+        ),
+        None => tracing::info!(
+            "[{}] Created {} market order qty {} (no fills reported)",
+            device_name,
+            side,
+            order.orig_qty
+        ),
+    }
+}
+
+/// A batch order that passed validation
+#[derive(Debug)]
+struct PlannedOrder {
+    side: String,
+    price: f64,
+    quantity: f64,
+    time_in_force: TimeInForce,
+    client_tag: Option<String>,
+}
+
+fn validate_batch_order(order: &BatchLimitOrder) -> Result<PlannedOrder, Vec<ValidationError>> {
+    let side = order.side.to_uppercase();
+    let mut errors = validate_order_fields(&side, Some(order.price), order.quantity);
+    let time_in_force = parse_time_in_force(order.time_in_force.as_deref());
+    if let Err(e) = &time_in_force {
+        errors.push(time_in_force_error(e.clone()));
     }
 
-    // Validate quantity
-    if request.quantity <= 0.0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Quantity must be positive".to_string(),
+    match time_in_force {
+        Ok(time_in_force) if errors.is_empty() => Ok(PlannedOrder {
+            side,
+            price: order.price,
+            quantity: order.quantity,
+            time_in_force,
+            client_tag: order.client_tag.clone(),
+        }),
+        _ => Err(errors),
+    }
+}
+
+/// Validate every order of a batch, naming fields by position (`orders[2].price`)
+fn validate_batch(orders: &[BatchLimitOrder]) -> Result<Vec<PlannedOrder>, Vec<ValidationError>> {
+    let mut planned = Vec::with_capacity(orders.len());
+    let mut errors = Vec::new();
+
+    for (i, order) in orders.iter().enumerate() {
+        match validate_batch_order(order) {
+            Ok(order) => planned.push(order),
+            Err(order_errors) => errors.extend(batch_item_errors(i, order_errors)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(planned)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Name an item's errors by its position in the batch
+fn batch_item_errors(i: usize, errors: Vec<ValidationError>) -> Vec<ValidationError> {
+    errors
+        .into_iter()
+        .map(|e| ValidationError {
+            field: format!("orders[{}].{}", i, e.field),
+            message: format!("Order {}: {}", i, e.message),
+            code: e.code,
+        })
+        .collect()
+}
+
+/// Normalize every order of a batch to the symbol's precision
+fn normalize_batch(
+    precision: &SymbolPrecision,
+    orders: Vec<PlannedOrder>,
+    strict: bool,
+) -> Result<Vec<PlannedOrder>, Vec<ValidationError>> {
+    let mut normalized = Vec::with_capacity(orders.len());
+    let mut errors = Vec::new();
+
+    for (i, order) in orders.into_iter().enumerate() {
+        match normalize_limit_order(precision, order.price, order.quantity, strict) {
+            Ok((price, quantity)) => normalized.push(PlannedOrder {
+                price,
+                quantity,
+                ..order
             }),
-        ));
+            Err(order_errors) => errors.extend(batch_item_errors(i, order_errors)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Reject a batch whose combined BUY cost or SELL quantity exceeds the free balance
+async fn ensure_batch_balance(
+    client: &BinanceClient,
+    symbol: &str,
+    orders: &[PlannedOrder],
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let account = client.get_account().await.map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+
+    let total = |side: &str, amount: fn(&PlannedOrder) -> f64| -> f64 {
+        orders.iter().filter(|o| o.side == side).map(amount).sum()
+    };
+    let buy_cost = total("BUY", |o| o.price * o.quantity);
+    let sell_quantity = total("SELL", |o| o.quantity);
+
+    // At a price of 1 the BUY check needs exactly the combined cost in quote asset
+    let mut checks = Vec::new();
+    if buy_cost > 0.0 {
+        checks.push(check_order_balance(&account.balances, symbol, "BUY", buy_cost, 1.0));
+    }
+    if sell_quantity > 0.0 {
+        checks.push(check_order_balance(&account.balances, symbol, "SELL", sell_quantity, 0.0));
+    }
+
+    checks
+        .into_iter()
+        .collect::<Result<(), String>>()
+        .map_err(|e| validation_error(ValidationError::new("orders", "insufficient_balance", e)))
+}
+
+/// Place `orders` a few at a time, optionally cancelling them all on the first failure
+async fn place_batch(
+    client: &BinanceClient,
+    symbol: &str,
+    orders: &[PlannedOrder],
+    rollback_on_error: bool,
+) -> Vec<BatchItemResult> {
+    let mut results = Vec::with_capacity(orders.len());
+
+    for chunk in orders.chunks(BATCH_CONCURRENCY) {
+        let placed = join_all(chunk.iter().map(|o| {
+            let tag = o.client_tag.as_deref();
+            client.create_limit_order(symbol, &o.side, o.price, o.quantity, o.time_in_force, tag)
+        }))
+        .await;
+
+        let mut failed = false;
+        for (order, result) in chunk.iter().zip(placed) {
+            results.push(match result {
+                Ok(response) => BatchItemResult::Placed {
+                    order: Box::new(response),
+                    rolled_back: false,
+                },
+                Err(e) => {
+                    failed = true;
+                    let (_, Json(body)) = limit_order_error(e, order.time_in_force);
+                    BatchItemResult::Failed { error: body.error }
+                }
+            });
+        }
+
+        if failed && rollback_on_error {
+            results.resize_with(orders.len(), || BatchItemResult::Skipped);
+            rollback_batch(client, symbol, &mut results).await;
+            break;
+        }
+    }
+
+    results
+}
+
+/// Cancel every placed order in `results`, marking the ones that were cancelled
+async fn rollback_batch(client: &BinanceClient, symbol: &str, results: &mut [BatchItemResult]) {
+    for result in results.iter_mut() {
+        if let BatchItemResult::Placed { order, rolled_back } = result {
+            match client.cancel_order(symbol, order.order_id).await {
+                Ok(_) => *rolled_back = true,
+                Err(e) => tracing::error!(
+                    "Batch rollback: failed to cancel order {}: {}",
+                    order.order_id,
+                    e
+                ),
+            }
+        }
+    }
+}
+
+/// Place several limit orders in one request
+#[utoipa::path(
+    post,
+    path = "/order/batch",
+    tag = "order",
+    params(
+        SymbolQuery,
+        ProductionHeader,
+    ),
+    request_body = CreateBatchOrderRequest,
+    responses(
+        (status = 200, description = "Per-order results", body = BatchOrderResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 423, description = "Daily loss limit hit, trading halted", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Binance circuit breaker is open", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn create_batch_orders(
+    State(state): State<OrderAppState>,
+    headers: HeaderMap,
+    AuthedDevice(device): AuthedDevice,
+    Query(query): Query<SymbolQuery>,
+    Json(request): Json<CreateBatchOrderRequest>,
+) -> Result<Json<BatchOrderResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = state
+        .config
+        .resolve_symbol(query.symbol.as_deref())
+        .map_err(|e| validation_error(symbol_error(e)))?;
+    ensure_trading_allowed(&headers)?;
+
+    if request.orders.is_empty() || request.orders.len() > MAX_BATCH_ORDERS {
+        return Err(validation_error(ValidationError::new(
+            "orders",
+            "invalid_count",
+            format!("A batch must contain 1 to {} orders", MAX_BATCH_ORDERS),
+        )));
+    }
+
+    // Validate everything up front so a bad item never leaves half a batch placed
+    let orders = validate_batch(&request.orders).map_err(validation_errors)?;
+    for (i, order) in orders.iter().enumerate() {
+        let field = format!("orders[{}].quantity", i);
+        let notional = order.price * order.quantity;
+        ensure_order_size(&headers, &field, notional, state.config.max_order_usd)?;
     }
 
     let use_production = use_production_from_headers(&headers);
     let client = BinanceClient::for_environment(&state.config, use_production).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: e.to_string(),
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+
+    let orders = match symbol_precision(&client, &symbol).await {
+        Some(precision) => {
+            let strict = strict_precision_from_headers(&headers);
+            normalize_batch(&precision, orders, strict).map_err(validation_errors)?
+        }
+        None => orders,
+    };
+
+    if !skip_balance_check_from_headers(&headers) {
+        ensure_batch_balance(&client, &symbol, &orders).await?;
+    }
+
+    let results = place_batch(&client, &symbol, &orders, request.rollback_on_error).await;
+
+    let placed = results
+        .iter()
+        .filter(|r| matches!(r, BatchItemResult::Placed { rolled_back: false, .. }))
+        .count();
+    let failed = results
+        .iter()
+        .filter(|r| matches!(r, BatchItemResult::Failed { .. }))
+        .count();
+    let rolled_back = results
+        .iter()
+        .any(|r| matches!(r, BatchItemResult::Placed { rolled_back: true, .. }));
+
+    tracing::info!(
+        "[{}] Batch of {} {} orders: {} placed, {} failed{}",
+        device.device_name,
+        orders.len(),
+        symbol,
+        placed,
+        failed,
+        if rolled_back { ", rolled back" } else { "" }
+    );
+
+    Ok(Json(BatchOrderResponse {
+        results,
+        placed,
+        failed,
+        rolled_back,
+    }))
+}
+
+/// SSE frame for an order event, named after its type
+fn sse_event(event: &OrderEvent) -> Event {
+    Event::default()
+        .event(event.name())
+        .data(serde_json::to_string(event).unwrap_or_default())
+}
+
+/// Live `fill` and `cancel` events from the order monitor, as server-sent events
+///
+/// A client too slow to keep up gets a `lagged` event carrying how many events
+/// it missed, and should refetch open orders. The subscription is dropped as
+/// soon as the client disconnects.
+#[utoipa::path(
+    get,
+    path = "/order/events",
+    tag = "order",
+    responses(
+        (status = 200, description = "Fill and cancel events", content_type = "text/event-stream"),
+        (status = 401, description = "Missing or invalid token"),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn order_events_stream(
+    axum::Extension(events): axum::Extension<OrderEvents>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = futures::stream::unfold(events.subscribe(), |mut receiver| async move {
+        let event = match receiver.recv().await {
+            Ok(event) => sse_event(&event),
+            Err(RecvError::Lagged(missed)) => {
+                Event::default().event("lagged").data(missed.to_string())
+            }
+            Err(RecvError::Closed) => return None,
+        };
+        Some((Ok(event), receiver))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(SSE_HEARTBEAT).text("heartbeat"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_client;
+    use crate::notifications::FillNotification;
+    use crate::trailing::TrailingOutcome;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Mutex;
+
+    /// Mock Binance that rejects orders priced at 666 and records cancelled order ids
+    async fn mock_binance() -> (BinanceClient, Arc<Mutex<Vec<String>>>) {
+        let next_id = Arc::new(AtomicI64::new(1));
+        let cancels = Arc::new(Mutex::new(Vec::new()));
+        let recorded = cancels.clone();
+
+        let mock = Router::new().route(
+            "/api/v3/order",
+            post(move |body: String| async move {
+                if body.contains("price=666&") {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({
+                            "code": -2010,
+                            "msg": "Account has insufficient balance for requested action."
+                        })),
+                    );
+                }
+                (
+                    StatusCode::OK,
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": next_id.fetch_add(1, Ordering::SeqCst),
+                        "clientOrderId": "batch",
+                        "transactTime": 1700000000000i64,
+                        "price": "50000.00",
+                        "origQty": "0.00100",
+                        "executedQty": "0",
+                        "status": "NEW",
+                        "type": "LIMIT",
+                        "side": "BUY"
+                    })),
+                )
+            })
+            .delete(move |uri: axum::http::Uri| async move {
+                recorded.lock().unwrap().push(uri.query().unwrap_or_default().to_string());
+                Json(serde_json::json!({
+                    "symbol": "BTCUSDT",
+                    "orderId": 1,
+                    "status": "CANCELED"
+                }))
             }),
+        );
+
+        let client = mock_client(mock).await;
+
+        (client, cancels)
+    }
+
+    fn planned(price: f64) -> PlannedOrder {
+        PlannedOrder {
+            side: "BUY".to_string(),
+            price,
+            quantity: 0.001,
+            time_in_force: TimeInForce::Gtc,
+            client_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_time_in_force_defaults_to_gtc() {
+        assert_eq!(parse_time_in_force(None), Ok(TimeInForce::Gtc));
+    }
+
+    #[test]
+    fn test_each_time_in_force_is_accepted() {
+        let cases = [
+            ("GTC", TimeInForce::Gtc),
+            ("ioc", TimeInForce::Ioc),
+            ("FOK", TimeInForce::Fok),
+            ("GTX", TimeInForce::PostOnly),
+        ];
+
+        for (value, expected) in cases {
+            let request: CreateLimitOrderRequest = serde_json::from_value(serde_json::json!({
+                "side": "BUY",
+                "price": 50000.0,
+                "quantity": 0.001,
+                "time_in_force": value
+            }))
+            .unwrap();
+
+            assert_eq!(
+                parse_time_in_force(request.time_in_force.as_deref()),
+                Ok(expected),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_time_in_force_is_rejected() {
+        assert!(parse_time_in_force(Some("DAY")).is_err());
+    }
+
+    #[test]
+    fn test_default_adjustment_threshold_must_sit_inside_the_trail() {
+        let request = |body: serde_json::Value| -> CreateLimitOrderRequest {
+            serde_json::from_value(body).unwrap()
+        };
+        let base = serde_json::json!({"side": "SELL", "price": 50000.0, "quantity": 0.001});
+
+        let mut tight = base.clone();
+        tight["trailing_percent"] = serde_json::json!(0.05);
+        let error = adjustment_threshold(&request(tight), None).unwrap_err();
+        assert_eq!(error.code, "invalid_adjustment_threshold");
+
+        let mut wide = base.clone();
+        wide["trailing_percent"] = serde_json::json!(1.0);
+        assert_eq!(
+            adjustment_threshold(&request(wide), None),
+            Ok(DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT)
+        );
+
+        let mut explicit = base;
+        explicit["adjustment_threshold_percent"] = serde_json::json!(0.5);
+        let schedule = TrailingSchedule::new(vec![
+            TrailingStep { profit_threshold: 0.0, trailing_percent: 1.0 },
+            TrailingStep { profit_threshold: 5.0, trailing_percent: 0.4 },
+        ])
+        .unwrap();
+        assert!(adjustment_threshold(&request(explicit), Some(&schedule)).is_err());
+    }
+
+    #[test]
+    fn test_post_only_rejection_is_explained() {
+        let error = BinanceApiError::from_body(
+            r#"{"code":-2010,"msg":"Order would immediately match and take."}"#,
+        );
+
+        let (status, Json(body)) = limit_order_error(error, TimeInForce::PostOnly);
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.error.starts_with("Post-only order rejected"));
+        assert_eq!(body.errors[0].field, "price");
+        assert_eq!(body.errors[0].code, "post_only_would_match");
+    }
+
+    #[test]
+    fn test_other_rejections_pass_through() {
+        let error = BinanceApiError::from_body(
+            r#"{"code":-2010,"msg":"Account has insufficient balance for requested action."}"#,
+        );
+
+        let (status, Json(body)) = limit_order_error(error, TimeInForce::PostOnly);
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.errors.is_empty());
+        assert!(body.error.contains("insufficient balance"));
+    }
+
+    fn device_trailing_order(order_id: i64) -> TrailingOrder {
+        TrailingOrder::new(
+            order_id,
+            "BTCUSDT".to_string(),
+            OrderSide::Sell,
+            1.0,
+            50000.0,
+            0.001,
+            false,
         )
-    })?;
+        .with_device("phone")
+    }
 
-    let order = client
-        .create_market_order(&side, request.quantity)
+    #[tokio::test]
+    async fn test_trailing_cap_is_hit_and_cleared() {
+        let monitor = TrailingMonitor::new(Config::for_tests());
+        monitor.add_order(device_trailing_order(1)).await;
+        monitor.add_order(device_trailing_order(2)).await;
+
+        assert!(ensure_trailing_capacity(&monitor, "phone", 3).await.is_ok());
+
+        monitor.add_order(device_trailing_order(3)).await;
+        let Err((status, Json(body))) = ensure_trailing_capacity(&monitor, "phone", 3).await
+        else {
+            panic!("device is at its cap");
+        };
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(body.error.contains("3 of 3"), "{}", body.error);
+
+        // Other devices have their own allowance
+        assert!(ensure_trailing_capacity(&monitor, "tablet", 3).await.is_ok());
+
+        // Stopping (or filling) one frees a slot
+        monitor.remove_by_order_id(2, TrailingOutcome::Stopped).await;
+        assert!(ensure_trailing_capacity(&monitor, "phone", 3).await.is_ok());
+    }
+
+    #[test]
+    fn test_order_size_cap() {
+        let headers = HeaderMap::new();
+
+        // 0.1 BTC at 50000 is $5000
+        let Err((status, Json(body))) = ensure_order_size(&headers, "quantity", 5000.0, 1000.0)
+        else {
+            panic!("order is over the cap");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.error.contains("$1000.00 per-order limit"), "{}", body.error);
+        assert_eq!(body.errors[0].field, "quantity");
+        assert_eq!(body.errors[0].code, "above_max_order_usd");
+
+        assert!(ensure_order_size(&headers, "quantity", 50.0, 1000.0).is_ok());
+        assert!(ensure_order_size(&headers, "quantity", 5000.0, 0.0).is_ok());
+
+        let mut trusted = HeaderMap::new();
+        trusted.insert("X-Allow-Large-Order", "true".parse().unwrap());
+        assert!(ensure_order_size(&trusted, "quantity", 5000.0, 1000.0).is_ok());
+    }
+
+    #[test]
+    fn test_batch_order_validation() {
+        let order = |side: &str, price: f64, quantity: f64| BatchLimitOrder {
+            side: side.to_string(),
+            price,
+            quantity,
+            time_in_force: None,
+            client_tag: None,
+        };
+
+        assert_eq!(validate_batch_order(&order("buy", 50000.0, 0.001)).unwrap().side, "BUY");
+        assert!(validate_batch_order(&order("HOLD", 50000.0, 0.001)).is_err());
+        assert!(validate_batch_order(&order("BUY", 0.0, 0.001)).is_err());
+        assert!(validate_batch_order(&order("BUY", 50000.0, -1.0)).is_err());
+
+        // Every bad field of every order is reported, named by position
+        let orders = [order("BUY", 50000.0, 0.001), order("HOLD", 0.0, 0.001)];
+        let errors = validate_batch(&orders).unwrap_err();
+        let fields: Vec<_> = errors.iter().map(|e| (e.field.as_str(), e.code.as_str())).collect();
+        assert_eq!(
+            fields,
+            [
+                ("orders[1].side", "must_be_buy_or_sell"),
+                ("orders[1].price", "must_be_positive")
+            ]
+        );
+        assert_eq!(errors[1].message, "Order 1: Price must be positive");
+    }
+
+    #[test]
+    fn test_order_fields_report_every_problem() {
+        assert!(validate_order_fields("SELL", Some(50000.0), 0.001).is_empty());
+        assert!(validate_order_fields("SELL", None, 0.001).is_empty());
+
+        let errors = validate_order_fields("HOLD", Some(-1.0), 0.0);
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["side", "price", "quantity"]);
+
+        let (status, Json(body)) = validation_errors(errors);
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body.error,
+            "Side must be BUY or SELL; Price must be positive; Quantity must be positive"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_all_success() {
+        let (client, cancels) = mock_binance().await;
+        let orders: Vec<_> = (0..7).map(|i| planned(50000.0 - i as f64 * 100.0)).collect();
+
+        let results = place_batch(&client, "BTCUSDT", &orders, true).await;
+
+        assert_eq!(results.len(), 7);
+        let mut ids: Vec<i64> = results
+            .iter()
+            .map(|r| match r {
+                BatchItemResult::Placed {
+                    order,
+                    rolled_back: false,
+                } => order.order_id,
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 7);
+        assert!(cancels.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_failure_rolls_back_placed_orders() {
+        let (client, cancels) = mock_binance().await;
+        // Chunks of 5: the failure is in the second chunk, so the third is never sent
+        let mut orders: Vec<_> = (0..12).map(|_| planned(50000.0)).collect();
+        orders[5].price = 666.0;
+
+        let results = place_batch(&client, "BTCUSDT", &orders, true).await;
+
+        assert_eq!(results.len(), 12);
+        for (i, result) in results.iter().enumerate() {
+            match (i, result) {
+                (5, BatchItemResult::Failed { error }) => {
+                    assert!(error.contains("insufficient balance"), "{}", error)
+                }
+                (0..=9, BatchItemResult::Placed { rolled_back, .. }) => assert!(*rolled_back),
+                (10 | 11, BatchItemResult::Skipped) => {}
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        assert_eq!(cancels.lock().unwrap().len(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_batch_failure_without_rollback_keeps_going() {
+        let (client, cancels) = mock_binance().await;
+        let mut orders: Vec<_> = (0..7).map(|_| planned(50000.0)).collect();
+        orders[1].price = 666.0;
+
+        let results = place_batch(&client, "BTCUSDT", &orders, false).await;
+
+        let placed = results
+            .iter()
+            .filter(|r| matches!(r, BatchItemResult::Placed { rolled_back: false, .. }))
+            .count();
+        assert_eq!(placed, 6);
+        assert!(matches!(results[1], BatchItemResult::Failed { .. }));
+        assert!(cancels.lock().unwrap().is_empty());
+    }
+
+    fn book(bid: &str, ask: &str) -> BookTicker {
+        BookTicker {
+            symbol: "BTCUSDT".to_string(),
+            bid_price: bid.to_string(),
+            bid_qty: "1.0".to_string(),
+            ask_price: ask.to_string(),
+            ask_qty: "1.0".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_slippage_uses_the_side_being_taken() {
+        let thin_asks = book("49990.00", "51000.00");
+
+        // 2% above last on the ask side only hurts a BUY
+        assert!(check_slippage("BUY", &thin_asks, 50000.0, 1.0).is_err());
+        assert!(check_slippage("SELL", &thin_asks, 50000.0, 1.0).is_ok());
+        assert!(check_slippage("BUY", &thin_asks, 50000.0, 3.0).is_ok());
+
+        let empty = book("0.00", "0.00");
+        assert!(check_slippage("SELL", &empty, 50000.0, 1.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_market_order_rejected_on_slippage() {
+        let mock = Router::new().route(
+            "/api/v3/ticker/bookTicker",
+            axum::routing::get(|| async {
+                Json(serde_json::json!({
+                    "symbol": "BTCUSDT",
+                    "bidPrice": "47000.00",
+                    "bidQty": "0.01",
+                    "askPrice": "50010.00",
+                    "askQty": "0.01"
+                }))
+            }),
+        );
+        let client = mock_client(mock).await;
+
+        // Nothing streamed, so the book comes from REST
+        let book_cache = BookCache::new(&client, "BTCUSDT", Duration::from_secs(5));
+        let max_age = Duration::from_secs(10);
+        let check = |side| {
+            ensure_market_book(&client, &book_cache, "BTCUSDT", side, 50000.0, 1.0, max_age)
+        };
+        assert!(check("BUY").await.is_ok());
+
+        let (code, Json(body)) = check("SELL").await.unwrap_err();
+        assert_eq!(code, StatusCode::BAD_REQUEST);
+        assert!(body.error.contains("47000.00 (best bid)"), "{}", body.error);
+        assert!(body.error.contains("last price 50000.00"), "{}", body.error);
+    }
+
+    #[test]
+    fn test_price_age_limit() {
+        let limit = Duration::from_secs(10);
+        assert!(check_price_age("BTCUSDT", Duration::ZERO, limit).is_ok());
+        assert!(check_price_age("BTCUSDT", limit, limit).is_ok());
+
+        let error = check_price_age("BTCUSDT", Duration::from_millis(12_500), limit).unwrap_err();
+        assert!(error.contains("12.5s old"), "{}", error);
+        assert!(error.contains("10.0s limit"), "{}", error);
+
+        // Zero disables the guard
+        assert!(check_price_age("BTCUSDT", Duration::from_secs(3600), Duration::ZERO).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_market_order_rejected_on_stale_price() {
+        // The bookTicker fallback 404s
+        let client = mock_client(Router::new()).await;
+
+        // The stream went quiet 30s ago, leaving only an aged book
+        let book_cache = BookCache::new(&client, "BTCUSDT", Duration::from_secs(5));
+        book_cache.update_aged(book("50000.00", "50001.00"), Duration::from_secs(30));
+        let check = |max_slippage, max_age| {
+            ensure_market_book(
+                &client,
+                &book_cache,
+                "BTCUSDT",
+                "BUY",
+                50000.0,
+                max_slippage,
+                max_age,
+            )
+        };
+
+        let (code, Json(body)) = check(1.0, Duration::from_secs(10)).await.unwrap_err();
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.error.contains("BTCUSDT is 30."), "{}", body.error);
+        assert!(body.error.contains("MAX_PRICE_AGE_MS"), "{}", body.error);
+
+        // The age guard holds with the slippage check off
+        let (code, _) = check(0.0, Duration::from_secs(10)).await.unwrap_err();
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+
+        // With the guard off the aged book passes the slippage check
+        assert!(check(1.0, Duration::ZERO).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_delivers_simulated_fill() {
+        let events = OrderEvents::new();
+        let app = Router::new()
+            .route("/events", get(order_events_stream))
+            .layer(axum::Extension(events.clone()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let mut response = reqwest::get(format!("http://{}/events", addr)).await.unwrap();
+        assert_eq!(
+            response.headers()["content-type"].to_str().unwrap(),
+            "text/event-stream"
+        );
+
+        // The handler subscribed before answering, so this fill can't be missed
+        let fill = FillNotification::Buy {
+            price: 50000.0,
+            quantity: 0.002,
+        };
+        events.publish(OrderEvent::fill("BTCUSDT", &fill, Some("grid-A")));
+
+        let received = tokio::time::timeout(Duration::from_secs(5), async {
+            let mut body = String::new();
+            while !body.contains("\n\n") {
+                let chunk = response.chunk().await.unwrap().expect("stream ended");
+                body.push_str(&String::from_utf8_lossy(&chunk));
+            }
+            body
+        })
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: e.to_string(),
+        .expect("no event within 5s");
+
+        assert!(received.contains("event: fill"), "{}", received);
+        assert!(received.contains(r#""side":"BUY""#), "{}", received);
+        assert!(received.contains(r#""client_tag":"grid-A""#), "{}", received);
+    }
+
+    #[tokio::test]
+    async fn test_reference_seed_modes() {
+        let mock = Router::new().route(
+            "/api/v3/ticker/price",
+            get(|| async { Json(serde_json::json!({ "symbol": "BTCUSDT", "price": "52000.00" })) }),
+        );
+        let client = mock_client(mock).await;
+
+        let seed = |seed| seed_reference_price(&client, "BTCUSDT", seed, 50000.0);
+        assert_eq!(seed(ReferenceSeed::Market).await.unwrap(), 52000.0);
+        assert_eq!(seed(ReferenceSeed::OrderPrice).await.unwrap(), 50000.0);
+        assert_eq!(seed(ReferenceSeed::Explicit(51500.0)).await.unwrap(), 51500.0);
+    }
+
+    fn market_request(
+        side: &str,
+        quantity: f64,
+        quote_order_qty: Option<f64>,
+    ) -> CreateMarketOrderRequest {
+        CreateMarketOrderRequest {
+            side: side.to_string(),
+            quantity,
+            quote_order_qty,
+            client_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_market_order_sizing() {
+        let validate = |side, quantity, quote| {
+            validate_market_order(side, &market_request(side, quantity, quote))
+        };
+        assert_eq!(validate("SELL", 0.001, None), Ok(MarketQuantity::Base(0.001)));
+        assert_eq!(validate("BUY", 0.0, Some(25.0)), Ok(MarketQuantity::Quote(25.0)));
+
+        let codes = |result: Result<MarketQuantity, Vec<ValidationError>>| {
+            result.unwrap_err().into_iter().map(|e| e.code).collect::<Vec<_>>()
+        };
+        assert_eq!(codes(validate("BUY", 0.0, None)), ["must_be_positive"]);
+        assert_eq!(codes(validate("SELL", 0.0, Some(25.0))), ["buy_only"]);
+        assert_eq!(
+            codes(validate("BUY", 0.001, Some(25.0))),
+            ["conflicts_with_quote_order_qty"]
+        );
+    }
+
+    #[test]
+    fn test_strict_precision_rejects_instead_of_rounding() {
+        let precision = SymbolPrecision {
+            tick_size: 0.01,
+            step_size: 0.00001,
+        };
+
+        assert_eq!(
+            normalize_limit_order(&precision, 42000.12, 0.00123, true),
+            Ok((42000.12, 0.00123))
+        );
+
+        let errors = normalize_limit_order(&precision, 42000.123, 0.001234, true).unwrap_err();
+        let codes: Vec<_> = errors.iter().map(|e| e.code.as_str()).collect();
+        assert_eq!(codes, ["too_precise", "too_precise"]);
+        assert_eq!(
+            errors[0].message,
+            "price 42000.123 is not a multiple of the symbol's 0.01 increment (nearest: 42000.12)"
+        );
+
+        // Less than one step can't be rounded into a valid order
+        let errors = normalize_limit_order(&precision, 42000.12, 0.000001, false).unwrap_err();
+        assert_eq!(errors[0].field, "quantity");
+        assert_eq!(errors[0].code, "below_precision");
+    }
+
+    #[tokio::test]
+    async fn test_over_precise_order_is_normalized_before_placement() {
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let recorded = bodies.clone();
+        let mock = Router::new()
+            .route(
+                "/api/v3/exchangeInfo",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "symbols": [{
+                            "symbol": "BTCUSDT",
+                            "filters": [
+                                { "filterType": "PRICE_FILTER", "tickSize": "0.10000000" },
+                                { "filterType": "LOT_SIZE", "stepSize": "0.00001000" }
+                            ]
+                        }]
+                    }))
                 }),
             )
-        })?;
+            .route(
+                "/api/v3/order",
+                post(move |body: String| async move {
+                    recorded.lock().unwrap().push(body);
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": 1,
+                        "clientOrderId": "batch",
+                        "transactTime": 1700000000000i64,
+                        "price": "42000.10",
+                        "origQty": "0.00123",
+                        "executedQty": "0",
+                        "status": "NEW",
+                        "type": "LIMIT",
+                        "side": "BUY"
+                    }))
+                }),
+            );
+        let client = mock_client(mock).await;
 
-    tracing::info!(
-        "Created {} market order qty {}",
-        side,
-        request.quantity
-    );
+        let precision = symbol_precision(&client, "BTCUSDT").await.unwrap();
+        let orders = vec![PlannedOrder {
+            quantity: 0.0012399,
+            ..planned(42000.123456789)
+        }];
+        let orders = normalize_batch(&precision, orders, false).unwrap();
+        assert_eq!(orders[0].price, 42000.1);
+        // Floored, where formatting to 5 decimals would have rounded up to 0.00124
+        assert_eq!(orders[0].quantity, 0.00123);
 
-    Ok(Json(order))
+        let results = place_batch(&client, "BTCUSDT", &orders, false).await;
+        assert!(matches!(results[0], BatchItemResult::Placed { .. }));
+        let body = bodies.lock().unwrap()[0].clone();
+        assert!(body.contains("price=42000.1&"), "{}", body);
+        assert!(body.contains("quantity=0.00123"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_market_order_below_min_notional_is_rejected() {
+        let mock = Router::new().route(
+            "/api/v3/exchangeInfo",
+            get(|| async {
+                Json(serde_json::json!({
+                    "symbols": [{
+                        "symbol": "BTCUSDT",
+                        "filters": [
+                            { "filterType": "LOT_SIZE", "stepSize": "0.00001000" },
+                            { "filterType": "NOTIONAL", "minNotional": "5.00000000" }
+                        ]
+                    }]
+                }))
+            }),
+        );
+        let client = mock_client(mock).await;
+        let check = |quantity| ensure_min_notional(&client, "BTCUSDT", quantity, 50000.0);
+
+        // 0.00005 BTC at 50000 is $2.50
+        let (code, Json(body)) = check(MarketQuantity::Base(0.00005)).await.unwrap_err();
+        assert_eq!(code, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body.error,
+            "Market orders on BTCUSDT need at least $5.00; this one is worth $2.50"
+        );
+        assert_eq!(body.errors[0].field, "quantity");
+        assert_eq!(body.errors[0].code, "below_min_notional");
+
+        let (_, Json(body)) = check(MarketQuantity::Quote(4.0)).await.unwrap_err();
+        assert_eq!(body.errors[0].field, "quote_order_qty");
+
+        assert!(check(MarketQuantity::Base(0.001)).await.is_ok());
+        assert!(check(MarketQuantity::Quote(5.0)).await.is_ok());
+
+        // Unknown symbol: exchangeInfo can't answer, so Binance decides
+        assert!(ensure_min_notional(&client, "ETHUSDT", MarketQuantity::Base(0.00005), 3000.0)
+            .await
+            .is_ok());
+    }
 }
@@ -1,50 +1,399 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     routing::get,
     Json, Router,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-use crate::binance::BinanceClient;
+use super::{binance_error_status, ErrorResponse, SymbolQuery};
+use crate::binance::{BinanceClient, BookCache};
 use crate::config::Config;
+use crate::display::{round_to, ASSET_DECIMALS, USD_DECIMALS};
+use crate::pricing::{PriceFeed, PriceUnavailable};
 
-pub fn price_routes() -> Router<Config> {
+pub fn price_routes(book_cache: Arc<BookCache>) -> Router<Arc<Config>> {
     Router::new()
         // Price endpoint is public (no auth required)
         .route("/current", get(get_current_price))
+        .route("/convert", get(convert_amount))
+        .route("/book", get(get_book))
+        .layer(axum::Extension(book_cache))
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PriceResponse {
     symbol: String,
     price: f64,
+    /// Which price source answered
+    source: String,
     timestamp: i64,
 }
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    error: String,
-}
-
-/// Get current BTC price (public endpoint)
+/// Get current price for a symbol (public endpoint)
+///
+/// Tries each of `config.price_sources` in turn, so pricing survives Binance
+/// being unreachable.
+#[utoipa::path(
+    get,
+    path = "/price/current",
+    tag = "price",
+    params(SymbolQuery),
+    responses(
+        (status = 200, description = "Current price", body = PriceResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "No price source available", body = ErrorResponse),
+    )
+)]
 async fn get_current_price(
-    State(config): State<Config>,
+    State(config): State<Arc<Config>>,
+    Query(query): Query<SymbolQuery>,
 ) -> Result<Json<PriceResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let client = BinanceClient::new(&config);
-
-    let price = client.get_price().await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: e.to_string(),
-            }),
-        )
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
     })?;
 
+    let (price, source) = PriceFeed::new(&config)
+        .get_price(&symbol)
+        .await
+        .map_err(price_unavailable)?;
+
     Ok(Json(PriceResponse {
-        symbol: "BTCUSDT".to_string(),
+        symbol,
         price,
+        source: source.to_string(),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BookResponse {
+    symbol: String,
+    bid_price: f64,
+    ask_price: f64,
+    mid_price: f64,
+    /// Ask minus bid, as a percentage of the mid price
+    spread_percent: f64,
+    /// "stream" from the bookTicker stream, "rest" when the stream was stale or down
+    source: String,
+    /// Milliseconds since the bid/ask was received; 0 for a REST fetch
+    age_ms: u64,
+    timestamp: i64,
+}
+
+/// Binance's own status when it was one of the sources, else 502
+fn price_unavailable(error: PriceUnavailable) -> (StatusCode, Json<ErrorResponse>) {
+    let status = error.binance.as_ref().map_or(StatusCode::BAD_GATEWAY, binance_error_status);
+    (status, Json(ErrorResponse::new(error.to_string())))
+}
+
+/// Get the best bid and ask for a symbol (public endpoint)
+#[utoipa::path(
+    get,
+    path = "/price/book",
+    tag = "price",
+    params(SymbolQuery),
+    responses(
+        (status = 200, description = "Best bid and ask", body = BookResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+    )
+)]
+async fn get_book(
+    State(config): State<Arc<Config>>,
+    axum::Extension(book_cache): axum::Extension<Arc<BookCache>>,
+    Query(query): Query<SymbolQuery>,
+) -> Result<Json<BookResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let symbol = config.resolve_symbol(query.symbol.as_deref()).map_err(|e| {
+        (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)))
+    })?;
+
+    let client = BinanceClient::new(&config);
+    let quote = book_cache.book_ticker(&client, &symbol).await.map_err(|e| {
+        (binance_error_status(&e), Json(ErrorResponse::new(e.to_string())))
+    })?;
+
+    let book = &quote.ticker;
+    let (bid_price, ask_price) = (book.bid_price_f64(), book.ask_price_f64());
+    let mid_price = (bid_price + ask_price) / 2.0;
+    let spread_percent = if mid_price > 0.0 {
+        (ask_price - bid_price) / mid_price * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(Json(BookResponse {
+        symbol,
+        bid_price,
+        ask_price,
+        mid_price,
+        spread_percent,
+        source: quote.source.name().to_string(),
+        age_ms: quote.age.as_millis() as u64,
         timestamp: chrono::Utc::now().timestamp_millis(),
     }))
 }
+
+/// Fiat side of a conversion; USDT is accepted as the same thing
+const USD: &str = "USD";
+const USDT: &str = "USDT";
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConvertQuery {
+    /// Asset the amount is in, e.g. BTC or USD
+    from: String,
+    /// Asset to convert into
+    to: String,
+    amount: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ConvertResponse {
+    from: String,
+    to: String,
+    amount: f64,
+    result: f64,
+    /// Symbol whose price was used
+    symbol: String,
+    price: f64,
+    timestamp: i64,
+}
+
+/// A validated conversion between a tradable asset and USD
+#[derive(Debug, PartialEq)]
+struct Conversion {
+    /// USDT market whose price converts between the two
+    symbol: String,
+    /// true for asset -> USD, false for USD -> asset
+    to_usd: bool,
+}
+
+fn is_usd(asset: &str) -> bool {
+    asset == USD || asset == USDT
+}
+
+/// Check the amount and that one side is USD and the other a configured asset
+fn parse_conversion(config: &Config, query: &ConvertQuery) -> Result<Conversion, String> {
+    if !(query.amount.is_finite() && query.amount > 0.0) {
+        return Err("amount must be a positive number".to_string());
+    }
+
+    let from = query.from.trim().to_uppercase();
+    let to = query.to.trim().to_uppercase();
+    let (asset, to_usd) = match (is_usd(&from), is_usd(&to)) {
+        (false, true) => (from, true),
+        (true, false) => (to, false),
+        _ => {
+            return Err(format!(
+                "Unsupported conversion {} -> {}; convert between USD and a traded asset",
+                query.from, query.to
+            ))
+        }
+    };
+
+    let symbol = config
+        .resolve_symbol(Some(&format!("{}{}", asset, USDT)))
+        .map_err(|_| format!("{} is not a traded asset", asset))?;
+    Ok(Conversion { symbol, to_usd })
+}
+
+/// Convert `amount` at `price`, rounded for the side it lands on
+fn convert(conversion: &Conversion, amount: f64, price: f64) -> f64 {
+    if conversion.to_usd {
+        round_to(amount * price, USD_DECIMALS)
+    } else {
+        round_to(amount / price, ASSET_DECIMALS)
+    }
+}
+
+/// Convert between a traded asset and USD at the current price (public endpoint)
+#[utoipa::path(
+    get,
+    path = "/price/convert",
+    tag = "price",
+    params(ConvertQuery),
+    responses(
+        (status = 200, description = "Converted amount", body = ConvertResponse),
+        (status = 400, description = "Invalid amount or unsupported pair", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "No price source available", body = ErrorResponse),
+    )
+)]
+async fn convert_amount(
+    State(config): State<Arc<Config>>,
+    Query(query): Query<ConvertQuery>,
+) -> Result<Json<ConvertResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let conversion = parse_conversion(&config, &query)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e))))?;
+
+    let (price, _) = PriceFeed::new(&config)
+        .get_price(&conversion.symbol)
+        .await
+        .map_err(price_unavailable)?;
+
+    Ok(Json(ConvertResponse {
+        result: convert(&conversion, query.amount, price),
+        from: query.from.to_uppercase(),
+        to: query.to.to_uppercase(),
+        amount: query.amount,
+        symbol: conversion.symbol,
+        price,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_client;
+    use axum::{body::Body, http::Request};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn test_routes() -> Router {
+        let config = Config::for_tests();
+        let client = BinanceClient::new(&config);
+        let book_cache = Arc::new(BookCache::new(&client, "BTCUSDT", Duration::from_secs(5)));
+        price_routes(book_cache).with_state(Arc::new(config))
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_symbol_is_rejected() {
+        let app = test_routes();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/current?symbol=DOGEUSDT")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_listed_symbol_reaches_client() {
+        // Mock Binance that only knows ETHUSDT
+        let mock = Router::new().route(
+            "/api/v3/ticker/price",
+            get(|Query(query): Query<SymbolQuery>| async move {
+                match query.symbol.as_deref() {
+                    Some("ETHUSDT") => (
+                        StatusCode::OK,
+                        Json(serde_json::json!({ "symbol": "ETHUSDT", "price": "3000.00" })),
+                    ),
+                    _ => (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({ "code": -1121, "msg": "Invalid symbol." })),
+                    ),
+                }
+            }),
+        );
+        let symbol = Config::for_tests().resolve_symbol(Some("ethusdt")).unwrap();
+        let client = mock_client(mock).await;
+
+        assert_eq!(symbol, "ETHUSDT");
+        assert_eq!(client.get_price(&symbol).await.unwrap(), 3000.0);
+    }
+
+    #[test]
+    fn test_unavailable_price_answers_with_binance_status() {
+        let rate_limited = r#"{"code":-1003,"msg":"Too many requests."}"#;
+        let (status, Json(body)) = price_unavailable(PriceUnavailable {
+            binance: Some(crate::binance::BinanceApiError::from_body(rate_limited)),
+            failures: vec!["binance: Too many requests.".to_string()],
+        });
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(body.error.contains("binance"), "{}", body.error);
+
+        let (status, _) = price_unavailable(PriceUnavailable {
+            binance: None,
+            failures: vec!["kraken: timed out".to_string()],
+        });
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+    }
+
+    fn convert_query(from: &str, to: &str, amount: f64) -> ConvertQuery {
+        ConvertQuery {
+            from: from.to_string(),
+            to: to.to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_convert_btc_to_usd() {
+        let config = Config::for_tests();
+        let conversion = parse_conversion(&config, &convert_query("btc", "USD", 0.01)).unwrap();
+
+        assert_eq!(conversion.symbol, "BTCUSDT");
+        assert!(conversion.to_usd);
+        assert_eq!(convert(&conversion, 0.01, 95000.555), 950.01);
+    }
+
+    #[test]
+    fn test_convert_usd_to_btc() {
+        let config = Config::for_tests();
+        let conversion = parse_conversion(&config, &convert_query("USD", "BTC", 100.0)).unwrap();
+
+        assert_eq!(conversion.symbol, "BTCUSDT");
+        assert!(!conversion.to_usd);
+        // 100 / 95000 = 0.00105263157..., kept to satoshis
+        assert_eq!(convert(&conversion, 100.0, 95000.0), 0.00105263);
+    }
+
+    #[test]
+    fn test_convert_rejects_bad_amount_and_pairs() {
+        let config = Config::for_tests();
+
+        for amount in [0.0, -1.0, f64::NAN] {
+            assert!(parse_conversion(&config, &convert_query("BTC", "USD", amount)).is_err());
+        }
+        assert!(parse_conversion(&config, &convert_query("BTC", "ETH", 1.0)).is_err());
+        assert!(parse_conversion(&config, &convert_query("USD", "USDT", 1.0)).is_err());
+        assert!(parse_conversion(&config, &convert_query("DOGE", "USD", 1.0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_convert_route_rejects_unsupported_pair() {
+        let app = test_routes();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/convert?from=BTC&to=EUR&amount=0.01")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_book_route_rejects_unlisted_symbol() {
+        let response = test_routes()
+            .oneshot(
+                Request::builder()
+                    .uri("/book?symbol=DOGEUSDT")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
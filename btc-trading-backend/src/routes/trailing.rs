@@ -1,5 +1,5 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     middleware,
     routing::{delete, get},
@@ -7,53 +7,110 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+use super::{binance_error_status, ErrorResponse, ValidationError};
 use crate::auth::auth_middleware;
+use crate::binance::{BinanceApiError, BinanceClient};
 use crate::config::Config;
-use crate::trailing::{TrailingMonitor, TrailingOrderResponse};
+use crate::trailing::{
+    validate_activation_price, validate_max_adjustments, CompletedTrailingOrder, TrailingMonitor,
+    TrailingOrder, TrailingOrderResponse, TrailingOutcome, TrailingUpdate,
+};
 
 /// App state that includes trailing monitor
 #[derive(Clone)]
 pub struct TrailingAppState {
-    pub config: Config,
+    pub config: Arc<Config>,
     pub monitor: Arc<TrailingMonitor>,
 }
 
-pub fn trailing_routes(monitor: Arc<TrailingMonitor>) -> Router<Config> {
+pub fn trailing_routes(config: Arc<Config>, monitor: Arc<TrailingMonitor>) -> Router<Arc<Config>> {
     let state = TrailingAppState {
-        config: Config::from_env(),
+        config: config.clone(),
         monitor,
     };
 
     Router::new()
         .route("/orders", get(get_trailing_orders))
-        .route("/order/:id", delete(delete_trailing_order))
-        .route_layer(middleware::from_fn_with_state(
-            Config::from_env(),
-            auth_middleware,
-        ))
+        .route("/history", get(get_trailing_history))
+        .route(
+            "/order/:id",
+            delete(delete_trailing_order).patch(update_trailing_order),
+        )
+        .route(
+            "/by-order/:order_id",
+            get(get_trailing_by_order_id).delete(delete_trailing_by_order_id),
+        )
+        .route_layer(middleware::from_fn_with_state(config, auth_middleware))
         .with_state(state)
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct TrailingOrdersResponse {
     orders: Vec<TrailingOrderResponse>,
     count: usize,
 }
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    error: String,
+#[derive(Serialize, ToSchema)]
+pub struct TrailingHistoryResponse {
+    /// Most recently completed first
+    orders: Vec<CompletedTrailingOrder>,
+    count: usize,
+}
+
+/// 400 for a rejected field, keeping its message as the top-level `error`
+fn validation_error(error: ValidationError) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse {
+            error: error.message.clone(),
+            errors: vec![error],
+        }),
+    )
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct DeleteResponse {
     success: bool,
     message: String,
 }
 
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteTrailingQuery {
+    /// Also cancel the tracked Binance order instead of leaving it on the book
+    #[serde(default)]
+    cancel_order: bool,
+}
+
+/// Largest trailing distance; at 100% a SELL would trail down to zero
+const MAX_TRAILING_PERCENT: f64 = 100.0;
+
+#[derive(Default, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateTrailingRequest {
+    /// New flat trailing distance in percent; replaces any schedule
+    trailing_percent: Option<f64>,
+    /// Moves allowed in total (counting those already made) before the order stays put
+    max_adjustments: Option<u32>,
+    /// Hold the order until the market reaches this price (at or above for a SELL,
+    /// at or below for a BUY)
+    activation_price: Option<f64>,
+}
+
 /// Get all active trailing orders
+#[utoipa::path(
+    get,
+    path = "/trailing/orders",
+    tag = "trailing",
+    responses(
+        (status = 200, description = "Tracked trailing orders", body = TrailingOrdersResponse),
+        (status = 401, description = "Missing or invalid token"),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn get_trailing_orders(
     State(state): State<TrailingAppState>,
 ) -> Result<Json<TrailingOrdersResponse>, (StatusCode, Json<ErrorResponse>)> {
@@ -63,30 +120,568 @@ async fn get_trailing_orders(
     Ok(Json(TrailingOrdersResponse { orders, count }))
 }
 
-/// Delete a trailing order (stops trailing but doesn't cancel the order)
+/// Get recently completed trailing orders
+///
+/// Kept in memory for the last 100 orders that filled, were stopped or
+/// cancelled, or went missing from Binance, so it starts empty on restart.
+#[utoipa::path(
+    get,
+    path = "/trailing/history",
+    tag = "trailing",
+    responses(
+        (status = 200, description = "Completed trailing orders", body = TrailingHistoryResponse),
+        (status = 401, description = "Missing or invalid token"),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn get_trailing_history(
+    State(state): State<TrailingAppState>,
+) -> Json<TrailingHistoryResponse> {
+    let orders = state.monitor.history().await;
+    let count = orders.len();
+
+    Json(TrailingHistoryResponse { orders, count })
+}
+
+/// Delete a trailing order
+///
+/// Only stops trailing by default, leaving the Binance order open at its last
+/// price; with `?cancel_order=true` the Binance order is cancelled too.
+#[utoipa::path(
+    delete,
+    path = "/trailing/order/{id}",
+    tag = "trailing",
+    params(
+        ("id" = String, Path, description = "Trailing order id"),
+        DeleteTrailingQuery,
+    ),
+    responses(
+        (status = 200, description = "Trailing order removed", body = DeleteResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 500, description = "Binance request failed", body = ErrorResponse),
+        (status = 429, description = "Binance rate limit hit", body = ErrorResponse),
+        (status = 502, description = "Binance unreachable or keys rejected", body = ErrorResponse),
+        (status = 503, description = "Binance circuit breaker is open", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
 async fn delete_trailing_order(
     State(state): State<TrailingAppState>,
     axum::extract::Path(id): axum::extract::Path<String>,
+    Query(query): Query<DeleteTrailingQuery>,
 ) -> Result<Json<DeleteResponse>, (StatusCode, Json<ErrorResponse>)> {
     let uuid = Uuid::parse_str(&id).map_err(|_| {
+        validation_error(ValidationError::new("id", "invalid_uuid", "Invalid UUID format"))
+    })?;
+
+    // Removed first so the monitor can't move the order while it's cancelled
+    let Some(order) = state.monitor.remove_order(uuid).await else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(format!("Trailing order {} not found", id))),
+        ));
+    };
+
+    if !query.cancel_order {
+        state.monitor.record_completed(&order, TrailingOutcome::Stopped).await;
+        return Ok(Json(DeleteResponse {
+            success: true,
+            message: format!(
+                "Trailing order {} stopped; Binance order {} is still open",
+                id, order.order_id
+            ),
+        }));
+    }
+
+    let result = match BinanceClient::for_environment(&state.config, order.use_production) {
+        Ok(client) => cancel_tracked_order(&client, &order).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(message) => {
+            state.monitor.record_completed(&order, TrailingOutcome::Cancelled).await;
+            Ok(Json(DeleteResponse {
+                success: true,
+                message,
+            }))
+        }
+        Err(e) => {
+            let error = format!("Failed to cancel Binance order {}: {}", order.order_id, e);
+            // Keep trailing so the cancel can be retried
+            state.monitor.add_order(order).await;
+            Err((binance_error_status(&e), Json(ErrorResponse::new(error))))
+        }
+    }
+}
+
+/// Change a trailing order's parameters without touching the Binance order
+///
+/// The best price seen is kept, so the order moves to the new distance from
+/// it on the monitor's next check.
+#[utoipa::path(
+    patch,
+    path = "/trailing/order/{id}",
+    tag = "trailing",
+    params(
+        ("id" = String, Path, description = "Trailing order id"),
+    ),
+    request_body = UpdateTrailingRequest,
+    responses(
+        (status = 200, description = "Updated trailing order", body = TrailingOrderResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn update_trailing_order(
+    State(state): State<TrailingAppState>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+    Json(request): Json<UpdateTrailingRequest>,
+) -> Result<Json<TrailingOrderResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let uuid = Uuid::parse_str(&id).map_err(|_| {
+        validation_error(ValidationError::new("id", "invalid_uuid", "Invalid UUID format"))
+    })?;
+
+    let update = TrailingUpdate {
+        trailing_percent: request.trailing_percent,
+        max_adjustments: request.max_adjustments,
+        activation_price: request.activation_price,
+    };
+    if update.is_empty() {
+        return Err(validation_error(ValidationError::new(
+            "trailing_percent",
+            "required",
+            "Nothing to update: send trailing_percent, max_adjustments or activation_price",
+        )));
+    }
+    if let Some(trailing_percent) = update.trailing_percent {
+        if !(trailing_percent > 0.0 && trailing_percent < MAX_TRAILING_PERCENT) {
+            return Err(validation_error(ValidationError::new(
+                "trailing_percent",
+                "out_of_range",
+                format!(
+                    "trailing_percent must be above 0 and below {}",
+                    MAX_TRAILING_PERCENT
+                ),
+            )));
+        }
+    }
+    if let Some(max_adjustments) = update.max_adjustments {
+        validate_max_adjustments(max_adjustments).map_err(|e| {
+            validation_error(ValidationError::new(
+                "max_adjustments",
+                "invalid_max_adjustments",
+                e,
+            ))
+        })?;
+    }
+    if let Some(price) = update.activation_price {
+        validate_activation_price(price).map_err(|e| {
+            validation_error(ValidationError::new(
+                "activation_price",
+                "invalid_activation_price",
+                e,
+            ))
+        })?;
+    }
+
+    let updated = state
+        .monitor
+        .update_order(uuid, update)
+        .await
+        .map_err(|e| {
+            validation_error(ValidationError::new(
+                "trailing_percent",
+                "invalid_adjustment_threshold",
+                e,
+            ))
+        })?;
+    updated.map(Json).ok_or_else(|| {
         (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Invalid UUID format".to_string(),
-            }),
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(format!("Trailing order {} not found", id))),
         )
-    })?;
+    })
+}
+
+/// Cancel the Binance order behind a stopped trailing order
+///
+/// An order that filled or was cancelled in the meantime is reported rather
+/// than treated as a failure.
+async fn cancel_tracked_order(
+    client: &BinanceClient,
+    order: &TrailingOrder,
+) -> Result<String, BinanceApiError> {
+    match client.cancel_order(&order.symbol, order.order_id).await {
+        Ok(_) => Ok(format!(
+            "Trailing order {} stopped and Binance order {} cancelled",
+            order.id, order.order_id
+        )),
+        Err(BinanceApiError::UnknownOrder { .. }) => Ok(format!(
+            "Trailing order {} stopped; Binance order {} had already filled or been cancelled",
+            order.id, order.order_id
+        )),
+        Err(e) => Err(e),
+    }
+}
+
+/// Get the trailing order tracking a Binance order ID
+#[utoipa::path(
+    get,
+    path = "/trailing/by-order/{order_id}",
+    tag = "trailing",
+    params(
+        ("order_id" = i64, Path, description = "Binance order id"),
+    ),
+    responses(
+        (status = 200, description = "Trailing order", body = TrailingOrderResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn get_trailing_by_order_id(
+    State(state): State<TrailingAppState>,
+    axum::extract::Path(order_id): axum::extract::Path<i64>,
+) -> Result<Json<TrailingOrderResponse>, (StatusCode, Json<ErrorResponse>)> {
+    state.monitor.get_by_order_id(order_id).await.map(Json).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new(format!(
+                "No trailing order tracks Binance order {}",
+                order_id
+            ))),
+        )
+    })
+}
 
-    match state.monitor.remove_order(uuid).await {
-        Some(_) => Ok(Json(DeleteResponse {
+/// Stop trailing a Binance order (doesn't cancel the order)
+#[utoipa::path(
+    delete,
+    path = "/trailing/by-order/{order_id}",
+    tag = "trailing",
+    params(
+        ("order_id" = i64, Path, description = "Binance order id"),
+    ),
+    responses(
+        (status = 200, description = "Trailing order removed", body = DeleteResponse),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn delete_trailing_by_order_id(
+    State(state): State<TrailingAppState>,
+    axum::extract::Path(order_id): axum::extract::Path<i64>,
+) -> Result<Json<DeleteResponse>, (StatusCode, Json<ErrorResponse>)> {
+    match state.monitor.remove_by_order_id(order_id, TrailingOutcome::Stopped).await {
+        Some(order) => Ok(Json(DeleteResponse {
             success: true,
-            message: format!("Trailing order {} stopped", id),
+            message: format!("Trailing order {} stopped", order.id),
         })),
         None => Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Trailing order {} not found", id),
-            }),
+            Json(ErrorResponse::new(format!(
+                "No trailing order tracks Binance order {}",
+                order_id
+            ))),
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::mock_client;
+    use crate::trailing::OrderSide;
+    use axum::extract::Path;
+
+    fn trailing_order(order_id: i64, use_production: bool) -> TrailingOrder {
+        TrailingOrder::new(
+            order_id,
+            "BTCUSDT".to_string(),
+            OrderSide::Buy,
+            1.0,
+            50000.0,
+            0.001,
+            use_production,
+        )
+    }
+
+    async fn state_with_order(order_id: i64) -> TrailingAppState {
+        let monitor = Arc::new(TrailingMonitor::new(Config::for_tests()));
+        monitor.add_order(trailing_order(order_id, false)).await;
+
+        TrailingAppState {
+            config: Arc::new(Config::for_tests()),
+            monitor,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_by_order_id() {
+        let state = state_with_order(42).await;
+
+        let Ok(Json(order)) = get_trailing_by_order_id(State(state), Path(42)).await else {
+            panic!("trailing order 42 should be found");
+        };
+        assert_eq!(order.order_id, 42);
+        assert_eq!(order.side, "BUY");
+    }
+
+    #[tokio::test]
+    async fn test_get_unknown_order_id_is_not_found() {
+        let state = state_with_order(42).await;
+
+        let Err((status, _)) = get_trailing_by_order_id(State(state), Path(7)).await else {
+            panic!("order 7 isn't trailed");
+        };
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_order_id() {
+        let state = state_with_order(42).await;
+
+        assert!(delete_trailing_by_order_id(State(state.clone()), Path(42)).await.is_ok());
+
+        let Err((status, _)) = delete_trailing_by_order_id(State(state), Path(42)).await else {
+            panic!("order 42 was already removed");
+        };
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    /// Mock Binance that cancels order 42 and reports any other as unknown
+    async fn mock_binance() -> BinanceClient {
+        let mock = Router::new().route(
+            "/api/v3/order",
+            delete(|uri: axum::http::Uri| async move {
+                if uri.query().unwrap_or_default().contains("orderId=42&") {
+                    return (
+                        StatusCode::OK,
+                        Json(serde_json::json!({
+                            "symbol": "BTCUSDT",
+                            "orderId": 42,
+                            "status": "CANCELED"
+                        })),
+                    );
+                }
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({ "code": -2011, "msg": "Unknown order sent." })),
+                )
+            }),
+        );
+        mock_client(mock).await
+    }
+
+    #[tokio::test]
+    async fn test_delete_only_stops_trailing_by_default() {
+        let state = state_with_order(42).await;
+        let id = state.monitor.get_all_orders().await[0].id.clone();
+
+        let query = Query(DeleteTrailingQuery { cancel_order: false });
+        let Ok(Json(response)) = delete_trailing_order(State(state.clone()), Path(id), query).await
+        else {
+            panic!("trailing order should be stopped");
+        };
+        assert!(response.message.contains("Binance order 42 is still open"));
+        assert!(state.monitor.get_by_order_id(42).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_full_cancel_cancels_the_binance_order() {
+        let client = mock_binance().await;
+
+        let message = cancel_tracked_order(&client, &trailing_order(42, false)).await.unwrap();
+        assert!(message.contains("Binance order 42 cancelled"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_full_cancel_of_filled_order_still_succeeds() {
+        let client = mock_binance().await;
+
+        let message = cancel_tracked_order(&client, &trailing_order(7, false)).await.unwrap();
+        assert!(message.contains("already filled or been cancelled"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_failed_cancel_keeps_trailing() {
+        // Production keys aren't configured in tests, so the cancel can't be sent
+        let state = state_with_order(1).await;
+        let order = trailing_order(42, true);
+        let id = order.id.to_string();
+        state.monitor.add_order(order).await;
+
+        let query = Query(DeleteTrailingQuery { cancel_order: true });
+        let Err((status, _)) = delete_trailing_order(State(state.clone()), Path(id), query).await
+        else {
+            panic!("cancel without production keys should fail");
+        };
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(state.monitor.get_by_order_id(42).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_uuid_names_the_field() {
+        let state = state_with_order(42).await;
+
+        let query = Query(DeleteTrailingQuery { cancel_order: false });
+        let Err((status, Json(body))) =
+            delete_trailing_order(State(state), Path("not-a-uuid".to_string()), query).await
+        else {
+            panic!("a malformed id should be rejected");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.error, "Invalid UUID format");
+        assert_eq!(body.errors[0].field, "id");
+        assert_eq!(body.errors[0].code, "invalid_uuid");
+    }
+
+    #[tokio::test]
+    async fn test_update_trailing_percent() {
+        let state = state_with_order(42).await;
+        let id = state.monitor.get_all_orders().await[0].id.clone();
+
+        let request = UpdateTrailingRequest {
+            trailing_percent: Some(2.5),
+            ..Default::default()
+        };
+        let Ok(Json(order)) =
+            update_trailing_order(State(state.clone()), Path(id.clone()), Json(request)).await
+        else {
+            panic!("trailing order should be updated");
+        };
+        assert_eq!(order.trailing_percent, 2.5);
+        assert_eq!(order.current_trailing_percent, 2.5);
+        assert_eq!(state.monitor.get_by_order_id(42).await.unwrap().trailing_percent, 2.5);
+
+        for percent in [0.0, -1.0, 100.0, f64::NAN] {
+            let request = UpdateTrailingRequest {
+                trailing_percent: Some(percent),
+                ..Default::default()
+            };
+            let Err((status, Json(body))) =
+                update_trailing_order(State(state.clone()), Path(id.clone()), Json(request)).await
+            else {
+                panic!("{}% should be rejected", percent);
+            };
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body.errors[0].code, "out_of_range");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_the_adjustment_threshold_inside_the_trail() {
+        let monitor = Arc::new(TrailingMonitor::new(Config::for_tests()));
+        let order = trailing_order(42, false).with_adjustment_threshold(0.5);
+        let id = order.id.to_string();
+        monitor.add_order(order).await;
+        let state = TrailingAppState {
+            config: Arc::new(Config::for_tests()),
+            monitor,
+        };
+
+        let request = UpdateTrailingRequest {
+            trailing_percent: Some(0.4),
+            ..Default::default()
+        };
+        let Err((status, Json(body))) =
+            update_trailing_order(State(state.clone()), Path(id.clone()), Json(request)).await
+        else {
+            panic!("a trail tighter than the adjustment threshold should be rejected");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.errors[0].field, "trailing_percent");
+        assert_eq!(body.errors[0].code, "invalid_adjustment_threshold");
+        assert_eq!(state.monitor.get_by_order_id(42).await.unwrap().trailing_percent, 1.0);
+
+        let request = UpdateTrailingRequest {
+            trailing_percent: Some(0.6),
+            ..Default::default()
+        };
+        assert!(update_trailing_order(State(state), Path(id), Json(request)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_update_max_adjustments() {
+        let state = state_with_order(42).await;
+        let id = state.monitor.get_all_orders().await[0].id.clone();
+
+        let request = UpdateTrailingRequest {
+            max_adjustments: Some(3),
+            ..Default::default()
+        };
+        let Ok(Json(order)) =
+            update_trailing_order(State(state.clone()), Path(id.clone()), Json(request)).await
+        else {
+            panic!("max_adjustments should be updated");
+        };
+        assert_eq!(order.max_adjustments, Some(3));
+        assert_eq!(order.trailing_percent, 1.0);
+        let live = state.monitor.get_by_order_id(42).await.unwrap();
+        assert_eq!(live.max_adjustments, Some(3));
+
+        let request = UpdateTrailingRequest {
+            max_adjustments: Some(0),
+            ..Default::default()
+        };
+        let Err((status, Json(body))) =
+            update_trailing_order(State(state), Path(id), Json(request)).await
+        else {
+            panic!("a zero cap should be rejected");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.errors[0].field, "max_adjustments");
+        assert_eq!(body.errors[0].code, "invalid_max_adjustments");
+    }
+
+    #[tokio::test]
+    async fn test_update_activation_price() {
+        let state = state_with_order(42).await;
+        let id = state.monitor.get_all_orders().await[0].id.clone();
+
+        let request = serde_json::from_str(r#"{"activation_price": 48000.0}"#).unwrap();
+        let Ok(Json(order)) =
+            update_trailing_order(State(state.clone()), Path(id.clone()), Json(request)).await
+        else {
+            panic!("activation_price should be updated");
+        };
+        assert_eq!(order.activation_price, Some(48000.0));
+        let live = state.monitor.get_by_order_id(42).await.unwrap();
+        assert_eq!(live.activation_price, Some(48000.0));
+
+        for price in [0.0, -1.0, f64::INFINITY] {
+            let request = UpdateTrailingRequest {
+                activation_price: Some(price),
+                ..Default::default()
+            };
+            let Err((status, Json(body))) =
+                update_trailing_order(State(state.clone()), Path(id.clone()), Json(request)).await
+            else {
+                panic!("activation price {} should be rejected", price);
+            };
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body.errors[0].code, "invalid_activation_price");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_empty_update_is_rejected() {
+        let state = state_with_order(42).await;
+        let id = state.monitor.get_all_orders().await[0].id.clone();
+
+        let request = UpdateTrailingRequest::default();
+        let Err((status, Json(body))) =
+            update_trailing_order(State(state), Path(id), Json(request)).await
+        else {
+            panic!("an update with no fields should be rejected");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.errors[0].code, "required");
+    }
+}
@@ -0,0 +1,152 @@
+use crate::binance::Balance;
+
+/// Quote asset every configured symbol trades against
+const QUOTE_ASSET: &str = "USDT";
+
+/// Base asset of a USDT-quoted symbol (BTCUSDT -> BTC)
+pub fn base_asset(symbol: &str) -> Option<&str> {
+    symbol.strip_suffix(QUOTE_ASSET).filter(|asset| !asset.is_empty())
+}
+
+/// Asset and amount an order needs free: quote for BUY, base for SELL
+fn required_balance<'a>(
+    symbol: &'a str,
+    side: &str,
+    quantity: f64,
+    price: f64,
+) -> Result<(&'a str, f64), String> {
+    if side == "BUY" {
+        Ok((QUOTE_ASSET, quantity * price))
+    } else {
+        let asset = base_asset(symbol)
+            .ok_or_else(|| format!("{} is not a {} pair", symbol, QUOTE_ASSET))?;
+        Ok((asset, quantity))
+    }
+}
+
+/// Reject an order the account can't cover
+///
+/// Binance's `free` already excludes funds locked in open orders, so the order
+/// must fit in `free` alone; `locked` is only reported to explain a shortfall.
+pub fn check_order_balance(
+    balances: &[Balance],
+    symbol: &str,
+    side: &str,
+    quantity: f64,
+    price: f64,
+) -> Result<(), String> {
+    let (asset, needed) = required_balance(symbol, side, quantity, price)?;
+    let (free, locked) = match balances.iter().find(|b| b.asset == asset) {
+        Some(b) => (
+            b.try_free_f64()
+                .map_err(|e| format!("Unreadable {} balance: {}", asset, e))?,
+            b.locked_f64(),
+        ),
+        None => (0.0, 0.0),
+    };
+
+    if needed > free {
+        return Err(format!(
+            "Insufficient {}: order needs {} but only {} is free ({} locked in open orders)",
+            asset, needed, free, locked
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reject an order worth more than `max_order_usd`; a cap of 0 allows any size
+pub fn check_order_size(notional: f64, max_order_usd: f64) -> Result<(), String> {
+    if max_order_usd > 0.0 && notional > max_order_usd {
+        return Err(format!(
+            "Order worth ${:.2} exceeds the ${:.2} per-order limit (MAX_ORDER_USD)",
+            notional, max_order_usd
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balances() -> Vec<Balance> {
+        vec![
+            Balance {
+                asset: "BTC".to_string(),
+                free: "0.01000000".to_string(),
+                locked: "0.00500000".to_string(),
+            },
+            Balance {
+                asset: "USDT".to_string(),
+                free: "100.00000000".to_string(),
+                locked: "50.00000000".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_buy_within_free_usdt_passes() {
+        assert!(check_order_balance(&balances(), "BTCUSDT", "BUY", 0.002, 50000.0).is_ok());
+    }
+
+    #[test]
+    fn test_buy_over_free_usdt_is_rejected() {
+        // Needs $150: total USDT is 150 but 50 is locked in open orders
+        let error = check_order_balance(&balances(), "BTCUSDT", "BUY", 0.003, 50000.0)
+            .unwrap_err();
+        assert!(error.starts_with("Insufficient USDT"), "{}", error);
+        assert!(error.contains("50 locked"), "{}", error);
+    }
+
+    #[test]
+    fn test_sell_within_free_btc_passes() {
+        assert!(check_order_balance(&balances(), "BTCUSDT", "SELL", 0.01, 50000.0).is_ok());
+    }
+
+    #[test]
+    fn test_sell_over_free_btc_is_rejected() {
+        // 0.015 BTC total but 0.005 is already committed to an open sell
+        let error = check_order_balance(&balances(), "BTCUSDT", "SELL", 0.015, 50000.0)
+            .unwrap_err();
+        assert!(error.starts_with("Insufficient BTC"), "{}", error);
+    }
+
+    #[test]
+    fn test_missing_asset_counts_as_zero() {
+        let error = check_order_balance(&balances(), "ETHUSDT", "SELL", 0.1, 3000.0)
+            .unwrap_err();
+        assert!(error.starts_with("Insufficient ETH"), "{}", error);
+    }
+
+    #[test]
+    fn test_malformed_balance_is_not_read_as_zero() {
+        let mut balances = balances();
+        balances[1].free = "1e".to_string();
+
+        let error = check_order_balance(&balances, "BTCUSDT", "BUY", 0.001, 50000.0)
+            .unwrap_err();
+        assert!(error.starts_with("Unreadable USDT balance"), "{}", error);
+    }
+
+    #[test]
+    fn test_order_size_cap() {
+        assert!(check_order_size(999.0, 1000.0).is_ok());
+        assert!(check_order_size(1000.0, 1000.0).is_ok());
+        assert!(check_order_size(1_000_000.0, 0.0).is_ok());
+
+        let error = check_order_size(5000.0, 1000.0).unwrap_err();
+        assert_eq!(
+            error,
+            "Order worth $5000.00 exceeds the $1000.00 per-order limit (MAX_ORDER_USD)"
+        );
+    }
+
+    #[test]
+    fn test_base_asset() {
+        assert_eq!(base_asset("BTCUSDT"), Some("BTC"));
+        assert_eq!(base_asset("ETHBTC"), None);
+        assert_eq!(base_asset("USDT"), None);
+    }
+}
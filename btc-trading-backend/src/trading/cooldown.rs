@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Last fill seen per symbol, shared by the order and trailing monitors
+pub static FILL_COOLDOWN: FillCooldown = FillCooldown::new();
+
+/// Holds back automatic orders on a symbol for a while after it fills
+///
+/// When the market whipsaws around a level, acting on every fill the moment
+/// it lands places orders that the next tick cancels again. The trailing
+/// monitor and auto-sell check here first and defer until the symbol has
+/// been quiet for the configured cooldown.
+pub struct FillCooldown {
+    last_fill: Mutex<Option<HashMap<String, Instant>>>,
+}
+
+impl FillCooldown {
+    pub const fn new() -> Self {
+        Self {
+            last_fill: Mutex::new(None),
+        }
+    }
+
+    /// Note a fill on `symbol` at `at`; an earlier time never replaces a later one
+    pub fn record(&self, symbol: &str, at: Instant) {
+        let mut last_fill = self.last_fill.lock().unwrap();
+        let last = last_fill
+            .get_or_insert_with(HashMap::new)
+            .entry(symbol.to_string())
+            .or_insert(at);
+        if at > *last {
+            *last = at;
+        }
+    }
+
+    /// Time left before automatic orders on `symbol` may go out at `now`;
+    /// None once `cooldown` has passed since its last fill, or when it's 0
+    pub fn remaining(&self, symbol: &str, now: Instant, cooldown: Duration) -> Option<Duration> {
+        if cooldown.is_zero() {
+            return None;
+        }
+        let last_fill = self.last_fill.lock().unwrap();
+        let since = now.saturating_duration_since(*last_fill.as_ref()?.get(symbol)?);
+        cooldown.checked_sub(since).filter(|left| !left.is_zero())
+    }
+}
+
+impl Default for FillCooldown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn test_action_within_cooldown_is_deferred() {
+        let cooldown = FillCooldown::new();
+        let fill = Instant::now();
+        cooldown.record("BTCUSDT", fill);
+
+        assert_eq!(
+            cooldown.remaining("BTCUSDT", fill + Duration::from_secs(3), COOLDOWN),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(cooldown.remaining("BTCUSDT", fill + COOLDOWN, COOLDOWN), None);
+        // Other symbols and a disabled cooldown aren't held back
+        assert_eq!(cooldown.remaining("ETHUSDT", fill, COOLDOWN), None);
+        assert_eq!(cooldown.remaining("BTCUSDT", fill, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_later_fill_restarts_cooldown() {
+        let cooldown = FillCooldown::new();
+        let first = Instant::now();
+        cooldown.record("BTCUSDT", first + Duration::from_secs(8));
+        cooldown.record("BTCUSDT", first);
+
+        assert_eq!(
+            cooldown.remaining("BTCUSDT", first + COOLDOWN, COOLDOWN),
+            Some(Duration::from_secs(8))
+        );
+    }
+}
@@ -1,5 +1,7 @@
-use crate::binance::{Order, Trade};
+use crate::binance::{BinanceClient, Order};
+use crate::display::{round_to, USD_DECIMALS};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// A matched grid pair (BUY + SELL orders)
 #[derive(Debug, Clone, Serialize)]
@@ -33,7 +35,12 @@ impl GridPair {
 }
 
 /// Match open orders into grid pairs
-pub fn match_grid_pairs(orders: &[Order]) -> (Vec<GridPair>, Vec<Order>) {
+///
+/// A buy pairs with a sell whose quantity differs by less than `tolerance`
+/// (relative, e.g. 0.01 = 1%) and that is priced strictly above it. Among
+/// eligible sells the closest higher price wins. A wider tolerance lets grids
+/// with slightly uneven per-level quantities pair up.
+pub fn match_grid_pairs(orders: &[Order], tolerance: f64) -> (Vec<GridPair>, Vec<Order>) {
     let buy_orders: Vec<_> = orders.iter().filter(|o| o.is_buy()).collect();
     let sell_orders: Vec<_> = orders.iter().filter(|o| !o.is_buy()).collect();
 
@@ -41,20 +48,24 @@ pub fn match_grid_pairs(orders: &[Order]) -> (Vec<GridPair>, Vec<Order>) {
     let mut matched_sell_ids = std::collections::HashSet::new();
     let mut matched_buy_ids = std::collections::HashSet::new();
 
-    // Match by similar quantity (within 1%)
     for buy in &buy_orders {
-        for sell in &sell_orders {
-            if matched_sell_ids.contains(&sell.order_id) {
-                continue;
-            }
+        let buy_price = buy.price_f64();
+        let buy_qty = buy.quantity_f64();
+        if buy_qty <= 0.0 {
+            continue;
+        }
 
-            let qty_diff = (buy.quantity_f64() - sell.quantity_f64()).abs() / buy.quantity_f64();
-            if qty_diff < 0.01 {
-                pairs.push(GridPair::new((*buy).clone(), (*sell).clone()));
-                matched_buy_ids.insert(buy.order_id);
-                matched_sell_ids.insert(sell.order_id);
-                break;
-            }
+        let best_sell = sell_orders
+            .iter()
+            .filter(|sell| !matched_sell_ids.contains(&sell.order_id))
+            .filter(|sell| sell.price_f64() > buy_price)
+            .filter(|sell| (buy_qty - sell.quantity_f64()).abs() / buy_qty < tolerance)
+            .min_by(|a, b| a.price_f64().total_cmp(&b.price_f64()));
+
+        if let Some(sell) = best_sell {
+            pairs.push(GridPair::new((*buy).clone(), (*sell).clone()));
+            matched_buy_ids.insert(buy.order_id);
+            matched_sell_ids.insert(sell.order_id);
         }
     }
 
@@ -70,17 +81,220 @@ pub fn match_grid_pairs(orders: &[Order]) -> (Vec<GridPair>, Vec<Order>) {
     (pairs, unpaired)
 }
 
+/// Lay out `pairs` again centered on `market_price`
+///
+/// Each pair keeps its spread and its USD amount (the buy side's notional) but
+/// is moved so the buy and sell sit equally far below and above the market.
+/// Nested pairs stay nested, now symmetric around the new price.
+pub fn recenter_grid(pairs: &[GridPair], market_price: f64) -> Vec<CreateGridRequest> {
+    pairs
+        .iter()
+        .map(|pair| {
+            let half_spread = (pair.sell_order.price_f64() - pair.buy_order.price_f64()) / 2.0;
+            CreateGridRequest {
+                buy_price: round_to(market_price - half_spread, USD_DECIMALS),
+                sell_price: round_to(market_price + half_spread, USD_DECIMALS),
+                amount_usd: pair.buy_order.usd_value(),
+                client_tag: pair.buy_order.client_tag.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Expected outcome of a grid pair once both sides fill
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridProfitEstimate {
+    pub quantity: f64,
+    pub gross_profit_usd: f64,
+    pub commission_usd: f64,
+    pub net_profit_usd: f64,
+    pub profit_percent: f64,
+}
+
+/// Estimate grid profit after paying `fee_rate` on both the buy and sell notional
+///
+/// The quantity is `amount_usd` at `buy_price` floored to `step_size`, matching
+/// what will actually be ordered.
+pub fn estimate_grid_profit(
+    buy_price: f64,
+    sell_price: f64,
+    amount_usd: f64,
+    fee_rate: f64,
+    step_size: f64,
+) -> GridProfitEstimate {
+    let quantity = BinanceClient::calculate_quantity(amount_usd, buy_price, step_size);
+    let gross_profit_usd = (sell_price - buy_price) * quantity;
+    let commission_usd = (buy_price + sell_price) * quantity * fee_rate;
+    let profit_percent = if buy_price > 0.0 {
+        (sell_price - buy_price) / buy_price * 100.0
+    } else {
+        0.0
+    };
+
+    GridProfitEstimate {
+        quantity,
+        gross_profit_usd,
+        commission_usd,
+        net_profit_usd: gross_profit_usd - commission_usd,
+        profit_percent,
+    }
+}
+
 /// Request to create a new grid pair
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateGridRequest {
     pub buy_price: f64,
     pub sell_price: f64,
     pub amount_usd: f64,
+    /// Strategy label put on both orders (e.g. "grid-A")
+    #[serde(default)]
+    pub client_tag: Option<String>,
 }
 
 /// Request to modify an order
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ModifyOrderRequest {
     pub order_id: i64,
     pub new_price: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(order_id: i64, side: &str, price: f64, qty: f64) -> Order {
+        Order {
+            order_id,
+            symbol: "BTCUSDT".to_string(),
+            side: side.to_string(),
+            order_type: "LIMIT".to_string(),
+            price: price.to_string(),
+            orig_qty: qty.to_string(),
+            executed_qty: "0".to_string(),
+            status: "NEW".to_string(),
+            time: order_id,
+            client_order_id: String::new(),
+            client_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_buy_above_sell_is_not_paired() {
+        let orders = vec![
+            order(1, "BUY", 52000.0, 0.001),
+            order(2, "SELL", 51000.0, 0.001),
+        ];
+
+        let (pairs, unpaired) = match_grid_pairs(&orders, 0.01);
+        assert!(pairs.is_empty());
+        assert_eq!(unpaired.len(), 2);
+    }
+
+    #[test]
+    fn test_prefers_closest_higher_sell() {
+        let orders = vec![
+            order(1, "BUY", 50000.0, 0.001),
+            order(2, "SELL", 49000.0, 0.001),
+            order(3, "SELL", 53000.0, 0.001),
+            order(4, "SELL", 51000.0, 0.001),
+        ];
+
+        let (pairs, unpaired) = match_grid_pairs(&orders, 0.01);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].buy_order.order_id, 1);
+        assert_eq!(pairs[0].sell_order.order_id, 4);
+
+        let unpaired_ids: Vec<i64> = unpaired.iter().map(|o| o.order_id).collect();
+        assert_eq!(unpaired_ids, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_interleaved_prices_only_valid_pairs() {
+        let orders = vec![
+            order(1, "BUY", 50000.0, 0.001),
+            order(2, "SELL", 50500.0, 0.001),
+            order(3, "BUY", 51000.0, 0.001),
+            order(4, "SELL", 51500.0, 0.001),
+            order(5, "BUY", 52000.0, 0.001),
+        ];
+
+        let (pairs, unpaired) = match_grid_pairs(&orders, 0.01);
+        assert_eq!(pairs.len(), 2);
+        for pair in &pairs {
+            assert!(pair.buy_order.price_f64() < pair.sell_order.price_f64());
+            assert!(pair.profit_usd > 0.0);
+        }
+
+        assert_eq!(unpaired.len(), 1);
+        assert_eq!(unpaired[0].order_id, 5);
+    }
+
+    #[test]
+    fn test_tolerance_controls_quantity_matching() {
+        let orders = vec![
+            order(1, "BUY", 50000.0, 0.001),
+            order(2, "SELL", 51000.0, 0.00103),
+        ];
+
+        let (pairs, _) = match_grid_pairs(&orders, 0.05);
+        assert_eq!(pairs.len(), 1);
+
+        let (pairs, unpaired) = match_grid_pairs(&orders, 0.01);
+        assert!(pairs.is_empty());
+        assert_eq!(unpaired.len(), 2);
+    }
+
+    #[test]
+    fn test_recentered_levels_are_symmetric_around_market() {
+        let pairs = vec![
+            GridPair::new(order(1, "BUY", 94000.0, 0.001), order(2, "SELL", 96000.0, 0.001)),
+            GridPair::new(order(3, "BUY", 93000.0, 0.002), order(4, "SELL", 97000.0, 0.002)),
+        ];
+
+        let levels = recenter_grid(&pairs, 100000.0);
+        assert_eq!(levels.len(), 2);
+
+        for (level, pair) in levels.iter().zip(&pairs) {
+            // Same distance below and above the market
+            assert!((100000.0 - level.buy_price - (level.sell_price - 100000.0)).abs() < 1e-9);
+            // Same spread and per-level amount as before
+            let old_spread = pair.sell_order.price_f64() - pair.buy_order.price_f64();
+            assert!((level.sell_price - level.buy_price - old_spread).abs() < 1e-9);
+            assert!((level.amount_usd - pair.buy_order.usd_value()).abs() < 1e-9);
+        }
+
+        assert_eq!((levels[0].buy_price, levels[0].sell_price), (99000.0, 101000.0));
+        assert_eq!((levels[1].buy_price, levels[1].sell_price), (98000.0, 102000.0));
+    }
+
+    #[test]
+    fn test_recentered_prices_are_rounded_to_cents() {
+        let pairs = vec![GridPair::new(
+            order(1, "BUY", 50000.0, 0.001),
+            order(2, "SELL", 50500.01, 0.001),
+        )];
+
+        let levels = recenter_grid(&pairs, 61234.567);
+        assert_eq!(levels[0].buy_price, 60984.56);
+        assert_eq!(levels[0].sell_price, 61484.57);
+    }
+
+    #[test]
+    fn test_wide_spread_is_profitable_after_fees() {
+        let estimate = estimate_grid_profit(50000.0, 51000.0, 100.0, 0.001, 0.00001);
+
+        assert!((estimate.quantity - 0.002).abs() < 1e-12);
+        assert!((estimate.gross_profit_usd - 2.0).abs() < 1e-9);
+        assert!((estimate.commission_usd - 0.202).abs() < 1e-9);
+        assert!((estimate.net_profit_usd - 1.798).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tight_spread_is_negative_after_fees() {
+        // 0.1% spread doesn't cover 0.1% fees on each side
+        let estimate = estimate_grid_profit(50000.0, 50050.0, 100.0, 0.001, 0.00001);
+
+        assert!(estimate.gross_profit_usd > 0.0);
+        assert!(estimate.net_profit_usd < 0.0);
+    }
+}
@@ -0,0 +1,168 @@
+use super::{daily_profit, CompletedPair};
+use chrono::NaiveDate;
+use std::sync::Mutex;
+
+/// Process-wide switches halting order placement after a bad day, one per environment
+pub static KILL_SWITCH: EnvironmentKillSwitches = EnvironmentKillSwitches {
+    testnet: KillSwitch::new(),
+    production: KillSwitch::new(),
+};
+
+/// A kill switch each for testnet and production, so losses on one account
+/// don't halt the other
+pub struct EnvironmentKillSwitches {
+    testnet: KillSwitch,
+    production: KillSwitch,
+}
+
+impl EnvironmentKillSwitches {
+    /// The switch for orders in the given environment
+    pub fn get(&self, use_production: bool) -> &KillSwitch {
+        if use_production {
+            &self.production
+        } else {
+            &self.testnet
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DayResult {
+    day: NaiveDate,
+    net_profit: f64,
+    tripped: bool,
+}
+
+/// Halts new orders for the rest of a UTC day once its realized loss passes
+/// the configured limit
+///
+/// Once tripped the switch stays on until midnight even if later sells bring
+/// the day back above the limit, so a runaway strategy can't flap it.
+pub struct KillSwitch {
+    today: Mutex<Option<DayResult>>,
+}
+
+impl KillSwitch {
+    pub const fn new() -> Self {
+        Self {
+            today: Mutex::new(None),
+        }
+    }
+
+    /// Record `day`'s realized net profit, returning true if this trips the
+    /// switch; a `limit_usd` of 0 never trips it
+    pub fn record(&self, day: NaiveDate, net_profit: f64, limit_usd: f64) -> bool {
+        let mut today = self.today.lock().unwrap();
+        if today.is_some_and(|result| result.day > day) {
+            return false;
+        }
+
+        let already_tripped = today.is_some_and(|result| result.day == day && result.tripped);
+        let tripped = already_tripped || (limit_usd > 0.0 && -net_profit > limit_usd);
+        *today = Some(DayResult {
+            day,
+            net_profit,
+            tripped,
+        });
+        tripped && !already_tripped
+    }
+
+    /// The day's net profit if trading is halted on `day`; a switch tripped
+    /// on an earlier day has reset
+    pub fn tripped_on(&self, day: NaiveDate) -> Option<f64> {
+        self.today
+            .lock()
+            .unwrap()
+            .filter(|result| result.day == day && result.tripped)
+            .map(|result| result.net_profit)
+    }
+
+    /// Reject new orders while trading is halted on `day`
+    pub fn check(&self, day: NaiveDate) -> Result<(), String> {
+        match self.tripped_on(day) {
+            Some(net_profit) => Err(format!(
+                "Trading halted: today's realized loss of ${:.2} passed the daily limit \
+                 (DAILY_LOSS_LIMIT_USD); new orders resume at 00:00 UTC",
+                -net_profit
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for KillSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Realized net profit of the pairs completed on `day` (UTC)
+pub fn day_net_profit(pairs: &[CompletedPair], day: NaiveDate) -> f64 {
+    daily_profit(pairs)
+        .into_iter()
+        .find(|result| result.date == day)
+        .map_or(0.0, |result| result.net_profit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, d).unwrap()
+    }
+
+    #[test]
+    fn test_crossing_the_limit_trips_once() {
+        let switch = KillSwitch::new();
+
+        assert!(!switch.record(day(3), -40.0, 50.0));
+        assert_eq!(switch.tripped_on(day(3)), None);
+
+        assert!(switch.record(day(3), -60.0, 50.0));
+        assert_eq!(switch.tripped_on(day(3)), Some(-60.0));
+        let error = switch.check(day(3)).unwrap_err();
+        assert!(error.contains("loss of $60.00"), "{}", error);
+
+        // Already tripped: no second alert, and a recovery doesn't re-enable
+        assert!(!switch.record(day(3), -70.0, 50.0));
+        assert!(!switch.record(day(3), 10.0, 50.0));
+        assert_eq!(switch.tripped_on(day(3)), Some(10.0));
+    }
+
+    #[test]
+    fn test_switch_resets_at_midnight() {
+        let switch = KillSwitch::new();
+        assert!(switch.record(day(3), -60.0, 50.0));
+
+        // Orders reopen as soon as the UTC day rolls over, before any new record
+        assert_eq!(switch.tripped_on(day(4)), None);
+        assert!(switch.check(day(4)).is_ok());
+
+        assert!(!switch.record(day(4), 0.0, 50.0));
+        assert_eq!(switch.tripped_on(day(4)), None);
+
+        // A late result for yesterday doesn't re-trip today
+        assert!(!switch.record(day(3), -80.0, 50.0));
+        assert_eq!(switch.tripped_on(day(4)), None);
+    }
+
+    #[test]
+    fn test_environments_trip_independently() {
+        let switches = EnvironmentKillSwitches {
+            testnet: KillSwitch::new(),
+            production: KillSwitch::new(),
+        };
+
+        assert!(switches.get(false).record(day(3), -60.0, 50.0));
+        assert_eq!(switches.get(false).tripped_on(day(3)), Some(-60.0));
+        assert!(switches.get(true).check(day(3)).is_ok());
+    }
+
+    #[test]
+    fn test_zero_limit_never_trips() {
+        let switch = KillSwitch::new();
+        assert!(!switch.record(day(3), -1_000_000.0, 0.0));
+        assert_eq!(switch.tripped_on(day(3)), None);
+    }
+}
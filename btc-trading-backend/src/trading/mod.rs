@@ -1,5 +1,13 @@
+mod balance;
+mod cooldown;
 mod grid;
+mod kill_switch;
 mod profit;
+mod schedule;
 
+pub use balance::*;
+pub use cooldown::*;
 pub use grid::*;
+pub use kill_switch::*;
 pub use profit::*;
+pub use schedule::*;
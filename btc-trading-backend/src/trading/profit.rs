@@ -1,101 +1,265 @@
+use super::GridPair;
 use crate::binance::Trade;
+use chrono::{DateTime, NaiveDate};
 use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use utoipa::ToSchema;
 
 /// A completed grid pair (from trade history)
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct CompletedPair {
     pub buy_trade: Trade,
     pub sell_trade: Trade,
+    #[serde(serialize_with = "crate::display::asset")]
     pub quantity: f64,
     pub buy_price: f64,
     pub sell_price: f64,
+    #[serde(serialize_with = "crate::display::usd")]
     pub gross_profit_usd: f64,
+    #[serde(serialize_with = "crate::display::usd")]
     pub commission_usd: f64,
+    #[serde(serialize_with = "crate::display::usd")]
     pub net_profit_usd: f64,
     pub profit_percent: f64,
     pub completed_at: i64,
+    /// Strategy tag of the buy leg, or of the sell if the buy is untagged
+    pub client_tag: Option<String>,
+}
+
+/// Epoch timestamps below this are taken to be seconds: as milliseconds it's
+/// March 1973, as seconds the year 5138
+const EPOCH_SECONDS_LIMIT: i64 = 100_000_000_000;
+
+/// An epoch timestamp in milliseconds, whether `timestamp` is in seconds or milliseconds
+///
+/// Binance reports milliseconds, but bucketing by day silently lands in 1970
+/// if a source ever hands over seconds.
+pub fn normalize_epoch_millis(timestamp: i64) -> i64 {
+    if timestamp.abs() < EPOCH_SECONDS_LIMIT {
+        timestamp * 1000
+    } else {
+        timestamp
+    }
+}
+
+/// Quantities below this are treated as fully consumed (float dust)
+const QUANTITY_EPSILON: f64 = 1e-12;
+
+/// Commission paid on a trade, approximated to USD
+fn commission_usd(trade: &Trade) -> f64 {
+    let commission = trade.commission_f64();
+    if trade.commission_asset == "USDT" {
+        commission
+    } else {
+        commission * trade.price_f64()
+    }
+}
+
+/// Totals across the fills of a single order
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct OrderFills {
+    #[serde(serialize_with = "crate::display::asset")]
+    pub filled_quantity: f64,
+    /// Quantity-weighted average fill price, None when nothing filled
+    pub avg_price: Option<f64>,
+    /// Commission across all fills, approximated to USD
+    #[serde(serialize_with = "crate::display::usd")]
+    pub total_commission_usd: f64,
+}
+
+/// Sum up an order's fills
+pub fn order_fills(trades: &[Trade]) -> OrderFills {
+    let (cost, quantity, commission) =
+        trades.iter().fold((0.0, 0.0, 0.0), |(cost, quantity, commission), trade| {
+            let qty = trade.quantity_f64();
+            (
+                cost + trade.price_f64() * qty,
+                quantity + qty,
+                commission + commission_usd(trade),
+            )
+        });
+
+    OrderFills {
+        filled_quantity: quantity,
+        avg_price: (quantity > 0.0).then_some(cost / quantity),
+        total_commission_usd: commission,
+    }
+}
+
+/// A buy trade with quantity not yet consumed by sells
+struct OpenLot<'a> {
+    trade: &'a Trade,
+    remaining: f64,
 }
 
 /// Match trades into completed pairs and calculate profit
-pub fn match_completed_pairs(trades: &[Trade]) -> Vec<CompletedPair> {
-    let mut buy_trades: Vec<_> = trades.iter().filter(|t| t.is_buyer).cloned().collect();
-    let mut sell_trades: Vec<_> = trades.iter().filter(|t| !t.is_buyer).cloned().collect();
+///
+/// Buys are consumed first-in-first-out: each sell eats into the oldest open
+/// buy quantity, so one sell can close several buys and one buy can be closed
+/// by several sells. Every matched slice becomes a `CompletedPair` carrying the
+/// sliced quantity, with both legs' commissions prorated to that slice.
+/// Losing slices are kept so totals reflect realized P&L.
+///
+/// A sell whose quantity is within `tolerance` (relative) of the oldest open
+/// lot closes that lot outright, so dust left by commission paid in BTC does
+/// not linger and get paired with a later sell.
+pub fn match_completed_pairs(trades: &[Trade], tolerance: f64) -> Vec<CompletedPair> {
+    match_completed_pairs_within(trades, tolerance, None)
+}
 
-    // Sort by time
-    buy_trades.sort_by_key(|t| t.time);
-    sell_trades.sort_by_key(|t| t.time);
+/// `match_completed_pairs`, but a sell only matches buys made at most
+/// `window_ms` before it; older open lots are dropped unmatched.
+pub fn match_completed_pairs_within(
+    trades: &[Trade],
+    tolerance: f64,
+    window_ms: Option<i64>,
+) -> Vec<CompletedPair> {
+    let mut ordered: Vec<&Trade> = trades.iter().collect();
+    ordered.sort_by_key(|t| (normalize_epoch_millis(t.time), t.id));
 
+    let mut open_lots: VecDeque<OpenLot> = VecDeque::new();
     let mut pairs = Vec::new();
-    let mut matched_buy_ids = std::collections::HashSet::new();
-    let mut matched_sell_ids = std::collections::HashSet::new();
-
-    // Match each sell with a preceding buy of similar quantity
-    for sell in &sell_trades {
-        for buy in &buy_trades {
-            // Skip if already matched or buy happened after sell
-            if matched_buy_ids.contains(&buy.id) || buy.time >= sell.time {
+
+    for trade in ordered {
+        if let Err(e) = trade.try_price_f64().and(trade.try_quantity_f64()) {
+            tracing::warn!("Skipping trade {} in profit matching: {}", trade.id, e);
+            continue;
+        }
+        if trade.is_buyer {
+            if trade.quantity_f64() <= QUANTITY_EPSILON {
                 continue;
             }
+            open_lots.push_back(OpenLot {
+                trade,
+                remaining: trade.quantity_f64(),
+            });
+            continue;
+        }
 
-            // Match by similar quantity (within 5%)
-            let qty_diff = (buy.quantity_f64() - sell.quantity_f64()).abs() / buy.quantity_f64();
-            if qty_diff < 0.05 {
-                // Only count positive trades
-                if sell.price_f64() > buy.price_f64() {
-                    let quantity = buy.quantity_f64().min(sell.quantity_f64());
-                    let buy_price = buy.price_f64();
-                    let sell_price = sell.price_f64();
-
-                    let gross_profit = (sell_price - buy_price) * quantity;
-
-                    // Calculate commission (approximate to USD)
-                    let buy_commission = if buy.commission_asset == "USDT" {
-                        buy.commission.parse().unwrap_or(0.0)
-                    } else {
-                        buy.commission.parse::<f64>().unwrap_or(0.0) * buy_price
-                    };
-                    let sell_commission = if sell.commission_asset == "USDT" {
-                        sell.commission.parse().unwrap_or(0.0)
-                    } else {
-                        sell.commission.parse::<f64>().unwrap_or(0.0) * sell_price
-                    };
-                    let total_commission = buy_commission + sell_commission;
-
-                    let net_profit = gross_profit - total_commission;
-                    let profit_percent = (sell_price - buy_price) / buy_price * 100.0;
-
-                    pairs.push(CompletedPair {
-                        buy_trade: buy.clone(),
-                        sell_trade: sell.clone(),
-                        quantity,
-                        buy_price,
-                        sell_price,
-                        gross_profit_usd: gross_profit,
-                        commission_usd: total_commission,
-                        net_profit_usd: net_profit,
-                        profit_percent,
-                        completed_at: sell.time,
-                    });
-                }
-
-                matched_buy_ids.insert(buy.id);
-                matched_sell_ids.insert(sell.id);
+        let sell = trade;
+        if let Some(window_ms) = window_ms {
+            while open_lots
+                .front()
+                .is_some_and(|lot| {
+                    normalize_epoch_millis(sell.time) - normalize_epoch_millis(lot.trade.time)
+                        > window_ms
+                })
+            {
+                open_lots.pop_front();
+            }
+        }
+        let sell_qty = sell.quantity_f64();
+        let mut sell_remaining = sell_qty;
+
+        while sell_remaining > QUANTITY_EPSILON {
+            let Some(lot) = open_lots.front_mut() else {
                 break;
+            };
+
+            let buy = lot.trade;
+            let closes_lot = (lot.remaining - sell_remaining).abs() / lot.remaining < tolerance;
+            // A closing sell takes the whole lot, so the pair carries what was bought
+            let quantity = if closes_lot {
+                lot.remaining
+            } else {
+                lot.remaining.min(sell_remaining)
+            };
+            let buy_price = buy.price_f64();
+            let sell_price = sell.price_f64();
+
+            let gross_profit = (sell_price - buy_price) * quantity;
+
+            // Prorate each leg's commission to the matched slice
+            let buy_share = quantity / buy.quantity_f64();
+            let sell_share = (quantity / sell_qty).min(1.0);
+            let total_commission =
+                commission_usd(buy) * buy_share + commission_usd(sell) * sell_share;
+
+            let net_profit = gross_profit - total_commission;
+            let profit_percent = if buy_price > 0.0 {
+                (sell_price - buy_price) / buy_price * 100.0
+            } else {
+                0.0
+            };
+
+            pairs.push(CompletedPair {
+                buy_trade: buy.clone(),
+                sell_trade: sell.clone(),
+                quantity,
+                buy_price,
+                sell_price,
+                gross_profit_usd: gross_profit,
+                commission_usd: total_commission,
+                net_profit_usd: net_profit,
+                profit_percent,
+                completed_at: normalize_epoch_millis(sell.time),
+                client_tag: buy.client_tag.clone().or_else(|| sell.client_tag.clone()),
+            });
+
+            lot.remaining -= quantity;
+            sell_remaining -= quantity;
+            if closes_lot {
+                // Whatever the sell had beyond the lot is the same dust
+                sell_remaining = 0.0;
+            }
+            if closes_lot || lot.remaining <= QUANTITY_EPSILON {
+                open_lots.pop_front();
             }
         }
     }
 
     // Sort by completion time (newest first)
-    pairs.sort_by(|a, b| b.completed_at.cmp(&a.completed_at));
+    pairs.sort_by_key(|p| std::cmp::Reverse(p.completed_at));
     pairs
 }
 
+/// Net profit of a filled sell order, for its fill notification
+///
+/// Only buys within `window_ms` of the sell count, so a sell isn't credited
+/// against a long-forgotten buy. None when no buy matches.
+pub fn sell_order_profit(
+    trades: &[Trade],
+    sell_order_id: i64,
+    tolerance: f64,
+    window_ms: Option<i64>,
+) -> Option<f64> {
+    matched_sell_profit(trades, tolerance, window_ms, |sell| sell.order_id == sell_order_id)
+}
+
+/// Net profit of one sell trade, for a fill notification covering only that
+/// trade of a partially filled order; matched like [`sell_order_profit`]
+pub fn sell_trade_profit(
+    trades: &[Trade],
+    sell_trade_id: i64,
+    tolerance: f64,
+    window_ms: Option<i64>,
+) -> Option<f64> {
+    matched_sell_profit(trades, tolerance, window_ms, |sell| sell.id == sell_trade_id)
+}
+
+fn matched_sell_profit(
+    trades: &[Trade],
+    tolerance: f64,
+    window_ms: Option<i64>,
+    is_sell: impl Fn(&Trade) -> bool,
+) -> Option<f64> {
+    let pairs: Vec<CompletedPair> = match_completed_pairs_within(trades, tolerance, window_ms)
+        .into_iter()
+        .filter(|p| is_sell(&p.sell_trade))
+        .collect();
+
+    (!pairs.is_empty()).then(|| pairs.iter().map(|p| p.net_profit_usd).sum())
+}
+
 /// Summary of all trading profits
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ProfitSummary {
     pub total_trades: usize,
+    #[serde(serialize_with = "crate::display::usd")]
     pub total_gross_profit: f64,
+    #[serde(serialize_with = "crate::display::usd")]
     pub total_commission: f64,
+    #[serde(serialize_with = "crate::display::usd")]
     pub total_net_profit: f64,
     pub average_profit_percent: f64,
 }
@@ -119,8 +283,522 @@ pub fn calculate_profit_summary(pairs: &[CompletedPair]) -> ProfitSummary {
     ProfitSummary {
         total_trades: pairs.len(),
         total_gross_profit: total_gross,
-        total_commission: total_commission,
+        total_commission,
         total_net_profit: total_net,
         average_profit_percent: avg_percent,
     }
 }
+
+/// Completed pairs closed on one UTC calendar day
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct DailyProfit {
+    /// Serialized as "YYYY-MM-DD"
+    pub date: NaiveDate,
+    pub trade_count: usize,
+    #[serde(serialize_with = "crate::display::usd")]
+    pub net_profit: f64,
+    #[serde(serialize_with = "crate::display::usd")]
+    pub commission: f64,
+}
+
+impl DailyProfit {
+    fn empty(date: NaiveDate) -> Self {
+        Self {
+            date,
+            trade_count: 0,
+            net_profit: 0.0,
+            commission: 0.0,
+        }
+    }
+}
+
+/// Bucket pairs by the UTC day they completed, oldest day first
+///
+/// Days without a completed pair are absent; see `fill_missing_days`.
+pub fn daily_profit(pairs: &[CompletedPair]) -> Vec<DailyProfit> {
+    let mut days: BTreeMap<NaiveDate, DailyProfit> = BTreeMap::new();
+
+    for pair in pairs {
+        let completed_at = normalize_epoch_millis(pair.completed_at);
+        let Some(completed) = DateTime::from_timestamp_millis(completed_at) else {
+            continue;
+        };
+        let date = completed.date_naive();
+        let day = days.entry(date).or_insert_with(|| DailyProfit::empty(date));
+        day.trade_count += 1;
+        day.net_profit += pair.net_profit_usd;
+        day.commission += pair.commission_usd;
+    }
+
+    days.into_values().collect()
+}
+
+/// Every day from `from` to `to` (inclusive), with zero rows where `days` has none
+pub fn fill_missing_days(
+    days: Vec<DailyProfit>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<DailyProfit> {
+    let mut by_date: BTreeMap<NaiveDate, DailyProfit> =
+        days.into_iter().map(|day| (day.date, day)).collect();
+
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        by_date.entry(date).or_insert_with(|| DailyProfit::empty(date));
+    }
+
+    by_date.into_values().collect()
+}
+
+/// Realized and unrealized profit combined
+#[derive(Debug, Serialize)]
+pub struct PnlSummary {
+    #[serde(serialize_with = "crate::display::usd")]
+    pub realized_net_profit: f64,
+    #[serde(serialize_with = "crate::display::usd")]
+    pub unrealized_profit: f64,
+    #[serde(serialize_with = "crate::display::usd")]
+    pub total_profit: f64,
+    pub completed_pairs: usize,
+    pub open_pairs: usize,
+    pub market_price: f64,
+}
+
+/// Mark open grid pairs to `market_price` and combine with realized profit
+///
+/// An open pair is valued by what its buy leg actually bought: the difference
+/// between the market price and the buy price, times the executed quantity.
+/// A buy still waiting on the book holds nothing yet.
+pub fn calculate_pnl(
+    completed: &[CompletedPair],
+    open: &[GridPair],
+    market_price: f64,
+) -> PnlSummary {
+    let realized: f64 = completed.iter().map(|p| p.net_profit_usd).sum();
+    let unrealized: f64 = open
+        .iter()
+        .map(|p| (market_price - p.buy_order.price_f64()) * p.buy_order.executed_qty_f64())
+        .sum();
+
+    PnlSummary {
+        realized_net_profit: realized,
+        unrealized_profit: unrealized,
+        total_profit: realized + unrealized,
+        completed_pairs: completed.len(),
+        open_pairs: open.len(),
+        market_price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::Order;
+
+    fn trade(
+        id: i64,
+        is_buyer: bool,
+        price: &str,
+        qty: &str,
+        commission: &str,
+        time: i64,
+    ) -> Trade {
+        Trade {
+            id,
+            order_id: id,
+            symbol: "BTCUSDT".to_string(),
+            price: price.to_string(),
+            qty: qty.to_string(),
+            quote_qty: "0".to_string(),
+            commission: commission.to_string(),
+            commission_asset: "USDT".to_string(),
+            time,
+            is_buyer,
+            is_maker: true,
+            client_tag: None,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_order_fills_average_price_and_commission() {
+        let mut btc_fee = trade(3, true, "50200.00", "0.001", "0.000001", 3);
+        btc_fee.commission_asset = "BTC".to_string();
+        let fills = vec![
+            trade(1, true, "50000.00", "0.002", "0.10", 1),
+            trade(2, true, "50100.00", "0.001", "0.05", 2),
+            btc_fee,
+        ];
+
+        let summary = order_fills(&fills);
+        assert_close(summary.filled_quantity, 0.004);
+        // (100 + 50.1 + 50.2) / 0.004
+        assert_close(summary.avg_price.unwrap(), 50075.0);
+        // $0.15 in USDT plus 0.000001 BTC at 50200
+        assert_close(summary.total_commission_usd, 0.2002);
+    }
+
+    #[test]
+    fn test_order_without_fills_has_no_average() {
+        let summary = order_fills(&[]);
+        assert_eq!(summary.filled_quantity, 0.0);
+        assert_eq!(summary.avg_price, None);
+        assert_eq!(summary.total_commission_usd, 0.0);
+    }
+
+    #[test]
+    fn test_one_buy_split_across_two_sells() {
+        let trades = vec![
+            trade(1, true, "50000", "0.002", "0.1", 1000),
+            trade(2, false, "51000", "0.001", "0.051", 2000),
+            trade(3, false, "52000", "0.001", "0.052", 3000),
+        ];
+
+        let pairs = match_completed_pairs(&trades, 0.01);
+        assert_eq!(pairs.len(), 2);
+
+        // Newest first
+        assert_eq!(pairs[0].sell_trade.id, 3);
+        assert_eq!(pairs[1].sell_trade.id, 2);
+        for pair in &pairs {
+            assert_eq!(pair.buy_trade.id, 1);
+            assert_close(pair.quantity, 0.001);
+        }
+
+        assert_close(pairs[1].gross_profit_usd, 1.0);
+        assert_close(pairs[1].commission_usd, 0.05 + 0.051);
+        assert_close(pairs[0].gross_profit_usd, 2.0);
+        assert_close(pairs[0].commission_usd, 0.05 + 0.052);
+
+        let summary = calculate_profit_summary(&pairs);
+        assert_eq!(summary.total_trades, 2);
+        assert_close(summary.total_gross_profit, 3.0);
+        assert_close(summary.total_commission, 0.203);
+        assert_close(summary.total_net_profit, 2.797);
+    }
+
+    #[test]
+    fn test_pair_takes_client_tag_from_buy_then_sell() {
+        let mut trades = vec![
+            trade(1, true, "50000", "0.001", "0", 1000),
+            trade(2, false, "51000", "0.001", "0", 2000),
+            trade(3, true, "50000", "0.001", "0", 3000),
+            trade(4, false, "51000", "0.001", "0", 4000),
+        ];
+        trades[0].client_tag = Some("grid-A".to_string());
+        trades[1].client_tag = Some("manual".to_string());
+        trades[3].client_tag = Some("manual".to_string());
+
+        let pairs = match_completed_pairs(&trades, 0.01);
+        assert_eq!(pairs[1].client_tag.as_deref(), Some("grid-A"));
+        assert_eq!(pairs[0].client_tag.as_deref(), Some("manual"));
+    }
+
+    #[test]
+    fn test_one_sell_covers_two_buys_fifo() {
+        let trades = vec![
+            trade(1, true, "50000", "0.001", "0", 1000),
+            trade(2, true, "49000", "0.001", "0", 2000),
+            trade(3, false, "51000", "0.0015", "0", 3000),
+        ];
+
+        let pairs = match_completed_pairs(&trades, 0.01);
+        assert_eq!(pairs.len(), 2);
+
+        let first = pairs.iter().find(|p| p.buy_trade.id == 1).unwrap();
+        let second = pairs.iter().find(|p| p.buy_trade.id == 2).unwrap();
+        assert_close(first.quantity, 0.001);
+        assert_close(second.quantity, 0.0005);
+        assert_close(first.gross_profit_usd, 1.0);
+        assert_close(second.gross_profit_usd, 1.0);
+    }
+
+    #[test]
+    fn test_sell_before_any_buy_is_unmatched() {
+        let trades = vec![
+            trade(1, false, "51000", "0.001", "0", 1000),
+            trade(2, true, "50000", "0.001", "0", 2000),
+        ];
+
+        assert!(match_completed_pairs(&trades, 0.01).is_empty());
+    }
+
+    #[test]
+    fn test_malformed_trade_is_skipped_not_zeroed() {
+        // A zero-priced buy would book the whole sell as profit
+        let trades = vec![
+            trade(1, true, "50000", "0.001", "0", 1000),
+            trade(2, true, "bad", "0.001", "0", 2000),
+            trade(3, false, "51000", "0.002", "0", 3000),
+        ];
+
+        let pairs = match_completed_pairs(&trades, 0.01);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].buy_trade.id, 1);
+        assert_close(pairs[0].quantity, 0.001);
+        assert_close(pairs[0].gross_profit_usd, 1.0);
+    }
+
+    #[test]
+    fn test_tolerance_controls_lot_closing() {
+        let trades = vec![
+            trade(1, true, "50000", "0.001", "0", 1000),
+            trade(2, false, "51000", "0.00097", "0", 2000),
+            trade(3, true, "50000", "0.001", "0", 3000),
+            trade(4, false, "51000", "0.001", "0", 4000),
+        ];
+
+        // 3% short sell closes the first lot under a 5% tolerance
+        let pairs = match_completed_pairs(&trades, 0.05);
+        assert_eq!(pairs.len(), 2);
+        let closed = pairs
+            .iter()
+            .find(|p| p.buy_trade.id == 1 && p.sell_trade.id == 2)
+            .unwrap();
+        // The pair carries the whole lot, not just the short sell
+        assert_close(closed.quantity, 0.001);
+        assert!(pairs.iter().any(|p| p.buy_trade.id == 3 && p.sell_trade.id == 4));
+
+        // Under 1% the leftover dust is carried into the next sell
+        let pairs = match_completed_pairs(&trades, 0.01);
+        assert_eq!(pairs.len(), 3);
+        let dust = pairs
+            .iter()
+            .find(|p| p.buy_trade.id == 1 && p.sell_trade.id == 4)
+            .unwrap();
+        assert_close(dust.quantity, 0.00003);
+    }
+
+    #[test]
+    fn test_slightly_larger_sell_closes_lot_without_dust_pair() {
+        let trades = vec![
+            trade(1, true, "50000", "0.001", "0", 1000),
+            trade(2, true, "50000", "0.001", "0", 1500),
+            trade(3, false, "51000", "0.00103", "0", 2000),
+        ];
+
+        // Within 5% the sell closes the first lot and leaves the second untouched
+        let pairs = match_completed_pairs(&trades, 0.05);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].buy_trade.id, 1);
+        assert_close(pairs[0].quantity, 0.001);
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_seconds_and_millis_bucket_into_the_same_day() {
+        // 2023-11-14 22:13:20 UTC
+        let millis = 1_700_000_000_000;
+        let seconds = 1_700_000_000;
+        assert_eq!(normalize_epoch_millis(seconds), millis);
+        assert_eq!(normalize_epoch_millis(millis), millis);
+
+        for sell_time in [millis + 60_000, seconds + 60] {
+            let trades = vec![
+                trade(1, true, "50000", "0.001", "0.05", sell_time - 1),
+                trade(2, false, "51000", "0.001", "0.05", sell_time),
+            ];
+
+            let pairs = match_completed_pairs(&trades, 0.01);
+            assert_eq!(pairs[0].completed_at, millis + 60_000);
+
+            let days = daily_profit(&pairs);
+            assert_eq!(days.len(), 1);
+            assert_eq!(days[0].date, date(14));
+        }
+    }
+
+    #[test]
+    fn test_daily_profit_groups_pairs_by_utc_day() {
+        // 2023-11-14 22:13:20 UTC, then the next UTC day at 00:30 and 12:00
+        let nov_14 = 1_700_000_000_000;
+        let nov_15 = 1_700_008_200_000;
+        let trades = vec![
+            trade(1, true, "50000", "0.001", "0.05", nov_14),
+            trade(2, false, "51000", "0.001", "0.05", nov_14 + 60_000),
+            trade(3, true, "50000", "0.002", "0.1", nov_15),
+            trade(4, false, "50500", "0.001", "0.05", nov_15 + 60_000),
+            trade(5, false, "49500", "0.001", "0.05", nov_15 + 41_400_000),
+        ];
+
+        let days = daily_profit(&match_completed_pairs(&trades, 0.01));
+        assert_eq!(days.len(), 2);
+
+        assert_eq!(days[0].date, date(14));
+        assert_eq!(days[0].trade_count, 1);
+        assert_close(days[0].commission, 0.1);
+        assert_close(days[0].net_profit, 0.9);
+
+        // +0.5 and -0.5 gross, with 0.2 commission across both pairs
+        assert_eq!(days[1].date, date(15));
+        assert_eq!(days[1].trade_count, 2);
+        assert_close(days[1].commission, 0.2);
+        assert_close(days[1].net_profit, -0.2);
+
+        assert_eq!(serde_json::to_value(&days[0]).unwrap()["date"], "2023-11-14");
+    }
+
+    #[test]
+    fn test_serialized_amounts_are_rounded_for_display() {
+        let trades = vec![
+            trade(1, true, "50000.00", "0.00300001", "0.1000000001", 1000),
+            trade(2, false, "51000.00", "0.00300001", "0.1000000001", 2000),
+        ];
+        let pairs = match_completed_pairs(&trades, 0.01);
+        let summary = calculate_profit_summary(&pairs);
+        // Full precision is kept for the math
+        assert_close(summary.total_gross_profit, 3.00001);
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["total_gross_profit"], 3.0);
+        assert_eq!(json["total_commission"], 0.2);
+        assert_eq!(json["total_net_profit"], 2.8);
+
+        let json = serde_json::to_value(&pairs[0]).unwrap();
+        assert_eq!(json["quantity"], 0.00300001);
+        assert_eq!(json["net_profit_usd"], 2.8);
+
+        let pair = CompletedPair {
+            quantity: 0.001234567891,
+            net_profit_usd: 123.4500000001,
+            ..pairs[0].clone()
+        };
+        let json = serde_json::to_value(&pair).unwrap();
+        assert_eq!(json["quantity"], 0.00123457);
+        assert_eq!(json["net_profit_usd"], 123.45);
+    }
+
+    #[test]
+    fn test_fill_missing_days_adds_zero_rows() {
+        let mut day = DailyProfit::empty(date(15));
+        day.trade_count = 3;
+
+        let days = fill_missing_days(vec![day.clone()], date(13), date(16));
+        let dates: Vec<NaiveDate> = days.iter().map(|d| d.date).collect();
+        assert_eq!(dates, vec![date(13), date(14), date(15), date(16)]);
+        assert_eq!(days[2], day);
+        assert_eq!(days[0], DailyProfit::empty(date(13)));
+    }
+
+    fn order(order_id: i64, side: &str, price: &str, qty: &str) -> Order {
+        Order {
+            order_id,
+            symbol: "BTCUSDT".to_string(),
+            side: side.to_string(),
+            order_type: "LIMIT".to_string(),
+            price: price.to_string(),
+            orig_qty: qty.to_string(),
+            executed_qty: "0".to_string(),
+            status: "NEW".to_string(),
+            time: 0,
+            client_order_id: String::new(),
+            client_tag: None,
+        }
+    }
+
+    #[test]
+    fn test_pnl_combines_closed_and_open_pairs() {
+        let trades = vec![
+            trade(1, true, "50000", "0.001", "0.05", 1000),
+            trade(2, false, "52000", "0.001", "0.052", 2000),
+        ];
+        let completed = match_completed_pairs(&trades, 0.01);
+        let partly_filled = Order {
+            executed_qty: "0.001".to_string(),
+            status: "PARTIALLY_FILLED".to_string(),
+            ..order(10, "BUY", "60000", "0.002")
+        };
+        let open = vec![
+            GridPair::new(partly_filled, order(11, "SELL", "62000", "0.002")),
+            // Nothing bought yet, so nothing to mark
+            GridPair::new(order(12, "BUY", "59000", "0.002"), order(13, "SELL", "61000", "0.002")),
+        ];
+
+        let pnl = calculate_pnl(&completed, &open, 60500.0);
+
+        // Realized: 2.0 gross - 0.102 commission
+        assert_close(pnl.realized_net_profit, 1.898);
+        // Unrealized: (60500 - 60000) * 0.001 executed
+        assert_close(pnl.unrealized_profit, 0.5);
+        assert_close(pnl.total_profit, 2.398);
+        assert_eq!(pnl.completed_pairs, 1);
+        assert_eq!(pnl.open_pairs, 2);
+    }
+
+    #[test]
+    fn test_pnl_with_no_positions_is_zero() {
+        let pnl = calculate_pnl(&[], &[], 60000.0);
+
+        assert_eq!(pnl.realized_net_profit, 0.0);
+        assert_eq!(pnl.unrealized_profit, 0.0);
+        assert_eq!(pnl.total_profit, 0.0);
+    }
+
+    #[test]
+    fn test_notification_profit_ignores_buys_outside_window() {
+        const HOUR_MS: i64 = 60 * 60 * 1000;
+        let trades = vec![
+            trade(1, true, "50000", "0.001", "0", 1_700_000_000_000),
+            trade(2, false, "51000", "0.001", "0", 1_700_000_000_000 + 30 * 24 * HOUR_MS),
+        ];
+
+        // A week-long window can't reach a buy from a month earlier
+        assert_eq!(sell_order_profit(&trades, 2, 0.01, Some(168 * HOUR_MS)), None);
+
+        // History still pairs them, and so does an unbounded notification
+        assert_eq!(match_completed_pairs(&trades, 0.01).len(), 1);
+        assert_close(sell_order_profit(&trades, 2, 0.01, None).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_each_trade_of_a_sell_order_gets_its_own_profit() {
+        let mut trades = vec![
+            trade(1, true, "50000", "0.002", "0", 1000),
+            trade(2, false, "51000", "0.001", "0", 2000),
+            trade(3, false, "52000", "0.001", "0", 3000),
+        ];
+        // Both sells are trades of the same order
+        trades[2].order_id = 2;
+
+        assert_close(sell_trade_profit(&trades, 2, 0.01, None).unwrap(), 1.0);
+        assert_close(sell_trade_profit(&trades, 3, 0.01, None).unwrap(), 2.0);
+        assert_close(sell_order_profit(&trades, 2, 0.01, None).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn test_window_skips_old_lot_for_recent_one() {
+        const HOUR_MS: i64 = 60 * 60 * 1000;
+        let start = 1_700_000_000_000;
+        let trades = vec![
+            trade(1, true, "40000", "0.001", "0", start),
+            trade(2, true, "50000", "0.001", "0", start + 100 * HOUR_MS),
+            trade(3, false, "51000", "0.001", "0", start + 110 * HOUR_MS),
+        ];
+
+        // FIFO would pair the sell with the 40000 buy; the window leaves only the 50000 one
+        let pairs = match_completed_pairs_within(&trades, 0.01, Some(24 * HOUR_MS));
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].buy_trade.id, 2);
+        assert_close(sell_order_profit(&trades, 3, 0.01, Some(24 * HOUR_MS)).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_window_compares_second_and_millisecond_timestamps() {
+        const HOUR_MS: i64 = 60 * 60 * 1000;
+        let sold_at = 1_700_000_000_000;
+        // The buy, an hour before the sell, reported in seconds
+        let trades = vec![
+            trade(1, true, "50000", "0.001", "0", (sold_at - HOUR_MS) / 1000),
+            trade(2, false, "51000", "0.001", "0", sold_at),
+        ];
+
+        let pairs = match_completed_pairs_within(&trades, 0.01, Some(24 * HOUR_MS));
+        assert_eq!(pairs.len(), 1);
+        assert_close(sell_order_profit(&trades, 2, 0.01, Some(24 * HOUR_MS)).unwrap(), 1.0);
+    }
+}
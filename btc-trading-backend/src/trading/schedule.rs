@@ -0,0 +1,156 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A daily UTC time range (e.g. 13:25-13:45 around a macro release) in which
+/// no orders are sent automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoTradeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl NoTradeWindow {
+    /// Whether `at` falls inside the window; a window ending before it starts
+    /// (22:00-02:00) runs past midnight
+    pub fn contains(&self, at: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= at && at < self.end
+        } else {
+            at >= self.start || at < self.end
+        }
+    }
+
+    /// Time from `at` until the window ends, past midnight if need be
+    fn remaining(&self, at: NaiveTime) -> Duration {
+        let until_end = (self.end - at).num_seconds().rem_euclid(24 * 60 * 60);
+        Duration::from_secs(until_end as u64)
+    }
+}
+
+impl fmt::Display for NoTradeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} UTC",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl FromStr for NoTradeWindow {
+    type Err = String;
+
+    /// Parse `HH:MM-HH:MM`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (start, end) = value
+            .split_once('-')
+            .ok_or_else(|| format!("'{}' is not HH:MM-HH:MM", value))?;
+        let time = |s: &str| {
+            NaiveTime::parse_from_str(s.trim(), "%H:%M")
+                .map_err(|_| format!("'{}' is not an HH:MM time", s.trim()))
+        };
+
+        let window = Self {
+            start: time(start)?,
+            end: time(end)?,
+        };
+        if window.start == window.end {
+            return Err(format!("'{}' is empty", value));
+        }
+        Ok(window)
+    }
+}
+
+/// Windows in which the trailing monitor and auto-sell hold off on orders
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoTradeSchedule {
+    windows: Vec<NoTradeWindow>,
+}
+
+impl NoTradeSchedule {
+    pub fn new(windows: Vec<NoTradeWindow>) -> Self {
+        Self { windows }
+    }
+
+    /// The window `at` falls in, if orders are suspended then
+    pub fn active_window(&self, at: DateTime<Utc>) -> Option<&NoTradeWindow> {
+        self.windows
+            .iter()
+            .find(|window| window.contains(at.time()))
+    }
+
+    /// How long until orders may go out again, if a window is open at `at`
+    ///
+    /// Only the open window's end is counted; a window starting right as it
+    /// ends is found when the caller checks again.
+    pub fn resumes_in(&self, at: DateTime<Utc>) -> Option<Duration> {
+        self.active_window(at).map(|window| window.remaining(at.time()))
+    }
+}
+
+impl FromStr for NoTradeSchedule {
+    type Err = String;
+
+    /// Parse a comma-separated list of windows; empty means always trade
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 3, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_schedule_is_parsed() {
+        let schedule: NoTradeSchedule = "13:25-13:45, 22:00-02:00".parse().unwrap();
+
+        assert_eq!(
+            schedule.active_window(at(13, 30)).unwrap().to_string(),
+            "13:25-13:45 UTC"
+        );
+        assert!(schedule.active_window(at(13, 45)).is_none());
+        assert!(schedule.active_window(at(12, 0)).is_none());
+
+        // Wraps past midnight
+        assert!(schedule.active_window(at(23, 59)).is_some());
+        assert!(schedule.active_window(at(1, 0)).is_some());
+        assert!(schedule.active_window(at(2, 0)).is_none());
+
+        assert_eq!(
+            "".parse::<NoTradeSchedule>(),
+            Ok(NoTradeSchedule::default())
+        );
+    }
+
+    #[test]
+    fn test_resumes_when_the_open_window_ends() {
+        let schedule: NoTradeSchedule = "13:25-13:45, 22:00-02:00".parse().unwrap();
+
+        assert_eq!(schedule.resumes_in(at(13, 30)), Some(Duration::from_secs(15 * 60)));
+        assert_eq!(schedule.resumes_in(at(23, 0)), Some(Duration::from_secs(3 * 60 * 60)));
+        assert_eq!(schedule.resumes_in(at(1, 30)), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(schedule.resumes_in(at(12, 0)), None);
+    }
+
+    #[test]
+    fn test_invalid_windows_are_rejected() {
+        assert!("13:25".parse::<NoTradeSchedule>().is_err());
+        assert!("13:25-25:00".parse::<NoTradeSchedule>().is_err());
+        assert!("13:25-13:25".parse::<NoTradeSchedule>().is_err());
+    }
+}
@@ -3,8 +3,12 @@ mod monitor;
 pub use monitor::TrailingMonitor;
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::binance::RestingPrice;
+use crate::display::{round_to, USD_DECIMALS};
+
 /// Side of the order
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum OrderSide {
@@ -21,6 +25,181 @@ impl OrderSide {
     }
 }
 
+/// Which way a BUY's price has to move to fill it
+///
+/// SELLs always trail rising prices, so this only changes BUYs. Either way a
+/// BUY rests `trailing_percent` above the lowest price seen and follows a
+/// falling market down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailDirection {
+    /// A limit order, filling as price drops to it
+    #[default]
+    Down,
+    /// A stop-limit above the market, filling once price turns and rallies up
+    /// through it: buying a breakout rather than a dip
+    Up,
+}
+
+/// One step of a trailing schedule
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TrailingStep {
+    /// Profit (in %, e.g. 5.0 = +5%) from which this step applies
+    pub profit_threshold: f64,
+    /// Trailing distance (in %) used once the threshold is reached
+    pub trailing_percent: f64,
+}
+
+/// Trailing distances that tighten as profit grows, sorted by threshold
+///
+/// E.g. `[{0, 2.0}, {5, 1.0}]` trails 2% until +5% profit, then 1%.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TrailingSchedule {
+    steps: Vec<TrailingStep>,
+}
+
+impl TrailingSchedule {
+    /// A single step trailing by `trailing_percent` at any profit
+    pub fn flat(trailing_percent: f64) -> Self {
+        Self {
+            steps: vec![TrailingStep {
+                profit_threshold: 0.0,
+                trailing_percent,
+            }],
+        }
+    }
+
+    /// Build a schedule from unsorted steps, rejecting empty or non-positive ones
+    pub fn new(mut steps: Vec<TrailingStep>) -> Result<Self, String> {
+        if steps.is_empty() {
+            return Err("Trailing schedule needs at least one step".to_string());
+        }
+        if steps.iter().any(|s| s.trailing_percent <= 0.0) {
+            return Err("Trailing schedule percents must be positive".to_string());
+        }
+
+        steps.sort_by(|a, b| a.profit_threshold.total_cmp(&b.profit_threshold));
+        Ok(Self { steps })
+    }
+
+    /// Smallest trailing percent of any step
+    pub fn tightest_percent(&self) -> f64 {
+        self.steps.iter().map(|s| s.trailing_percent).fold(f64::INFINITY, f64::min)
+    }
+
+    /// Trailing percent for the highest threshold reached by `profit_percent`
+    ///
+    /// Below the first threshold the first step still applies.
+    pub fn trailing_percent_at(&self, profit_percent: f64) -> f64 {
+        self.steps
+            .iter()
+            .rev()
+            .find(|s| profit_percent >= s.profit_threshold)
+            .unwrap_or(&self.steps[0])
+            .trailing_percent
+    }
+}
+
+/// Where a new trailing order's reference price starts
+///
+/// Deserializes from `"market"`, `"order_price"`, or `{"explicit": 95000.0}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceSeed {
+    /// The market price when the order is placed
+    #[default]
+    Market,
+    /// The order's own price
+    OrderPrice,
+    /// A given price, e.g. the best price seen by a trail being resumed
+    Explicit(f64),
+}
+
+impl ReferenceSeed {
+    /// Reject explicit seeds that aren't positive or sit past the order
+    ///
+    /// A BUY rests above its reference and a SELL below it, so a BUY's seed
+    /// can't exceed the order price and a SELL's can't be under it.
+    pub fn validate(&self, side: OrderSide, order_price: f64) -> Result<(), String> {
+        let ReferenceSeed::Explicit(price) = *self else {
+            return Ok(());
+        };
+
+        if !is_valid_price(price) {
+            return Err("Explicit reference price must be positive".to_string());
+        }
+        match side {
+            OrderSide::Buy if price > order_price => Err(format!(
+                "Explicit reference price for a BUY must be at or below the order price {}",
+                order_price
+            )),
+            OrderSide::Sell if price < order_price => Err(format!(
+                "Explicit reference price for a SELL must be at or above the order price {}",
+                order_price
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How far (in %) the target must move from the order before it's re-priced
+pub const DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT: f64 = 0.1;
+
+fn default_adjustment_threshold_percent() -> f64 {
+    DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT
+}
+
+/// Check an adjustment threshold is positive and tighter than every trailing step
+///
+/// A threshold as wide as the trail itself would leave the order a full
+/// trailing distance behind before it ever moved.
+pub fn validate_adjustment_threshold(
+    threshold_percent: f64,
+    schedule: &TrailingSchedule,
+) -> Result<(), String> {
+    if !(threshold_percent.is_finite() && threshold_percent > 0.0) {
+        return Err("adjustment_threshold_percent must be positive".to_string());
+    }
+    let tightest = schedule.tightest_percent();
+    if threshold_percent >= tightest {
+        return Err(format!(
+            "adjustment_threshold_percent {} must be below the trailing percent {}",
+            threshold_percent, tightest
+        ));
+    }
+    Ok(())
+}
+
+/// Check a cap on how many times an order may be moved allows at least one
+pub fn validate_max_adjustments(max_adjustments: u32) -> Result<(), String> {
+    if max_adjustments == 0 {
+        return Err("max_adjustments must be at least 1".to_string());
+    }
+    Ok(())
+}
+
+/// Check an activation price is one the market could reach
+pub fn validate_activation_price(price: f64) -> Result<(), String> {
+    if !is_valid_price(price) {
+        return Err("activation_price must be positive".to_string());
+    }
+    Ok(())
+}
+
+/// Changes to a live trailing order; None leaves a parameter as it is
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TrailingUpdate {
+    pub trailing_percent: Option<f64>,
+    pub max_adjustments: Option<u32>,
+    pub activation_price: Option<f64>,
+}
+
+impl TrailingUpdate {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// Represents an order with trailing enabled
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrailingOrder {
@@ -28,70 +207,315 @@ pub struct TrailingOrder {
     pub id: Uuid,
     /// Current Binance order ID (changes when order is modified)
     pub order_id: i64,
+    /// Market the order was placed on (e.g. BTCUSDT)
+    pub symbol: String,
     /// Side: BUY or SELL
     pub side: OrderSide,
+    /// How a BUY fills; always `Down` for orders created before it existed
+    #[serde(default)]
+    pub direction: TrailDirection,
     /// Trailing percentage (e.g., 1.0 = 1%)
     pub trailing_percent: f64,
+    /// Trailing distance by profit; flat at `trailing_percent` by default
+    pub schedule: TrailingSchedule,
+    /// Smallest move (in %) of the target away from the order that re-prices it
+    #[serde(default = "default_adjustment_threshold_percent")]
+    pub adjustment_threshold_percent: f64,
+    /// Price profit is measured from (defaults to the initial order price)
+    pub entry_price: f64,
     /// Current order price on Binance
     pub current_order_price: f64,
     /// Reference price (best price seen - lowest for BUY, highest for SELL)
     pub reference_price: f64,
-    /// Order quantity
+    /// When the reference price last moved (ms)
+    #[serde(default)]
+    pub reference_updated_at: i64,
+    /// Reset the reference to the market price once it's this old; None keeps it forever
+    #[serde(default)]
+    pub reference_max_age_secs: Option<u64>,
+    /// Smallest move (in %) past the reference a new extreme needs to replace it;
+    /// 0 takes every new extreme
+    #[serde(default)]
+    pub min_reference_move_percent: f64,
+    /// Unfilled quantity, placed on each replacement order
     pub quantity: f64,
+    /// Quantity already filled on earlier orders before they were moved
+    #[serde(default)]
+    pub filled_quantity: f64,
+    /// Quantity-weighted average price of `filled_quantity`
+    #[serde(default)]
+    pub avg_fill_price: f64,
     /// Whether to use production API
     pub use_production: bool,
+    /// Strategy tag carried over to each replacement order
+    #[serde(default)]
+    pub client_tag: Option<String>,
+    /// Device that created the order (`Claims.sub`), for per-device limits
+    #[serde(default)]
+    pub device_id: Option<String>,
     /// Creation timestamp
     pub created_at: i64,
+    /// When the current Binance order was placed (ms), reset on each move
+    #[serde(default)]
+    pub order_placed_at: i64,
+    /// Times the order has been moved to a new price
+    #[serde(default)]
+    pub adjustments: u32,
+    /// Stop moving the order once it's been moved this many times; None never stops
+    #[serde(default)]
+    pub max_adjustments: Option<u32>,
+    /// Market price to reach (at or above for a SELL, at or below for a BUY)
+    /// before the order is moved. None once reached.
+    #[serde(default)]
+    pub activation_price: Option<f64>,
+    /// Binance order (same symbol) whose fill arms the trail, e.g. a grid
+    /// pair's BUY; the order isn't moved until then. None once armed.
+    #[serde(default)]
+    pub depends_on_order_id: Option<i64>,
 }
 
 impl TrailingOrder {
     pub fn new(
         order_id: i64,
+        symbol: String,
         side: OrderSide,
         trailing_percent: f64,
         current_price: f64,
         quantity: f64,
         use_production: bool,
     ) -> Self {
+        let now = chrono::Utc::now().timestamp_millis();
         Self {
             id: Uuid::new_v4(),
             order_id,
+            symbol,
             side,
+            direction: TrailDirection::default(),
             trailing_percent,
+            schedule: TrailingSchedule::flat(trailing_percent),
+            adjustment_threshold_percent: DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT,
+            entry_price: current_price,
             current_order_price: current_price,
             reference_price: current_price,
+            reference_updated_at: now,
+            reference_max_age_secs: None,
+            min_reference_move_percent: 0.0,
             quantity,
+            filled_quantity: 0.0,
+            avg_fill_price: 0.0,
             use_production,
-            created_at: chrono::Utc::now().timestamp_millis(),
+            client_tag: None,
+            device_id: None,
+            created_at: now,
+            order_placed_at: now,
+            adjustments: 0,
+            max_adjustments: None,
+            activation_price: None,
+            depends_on_order_id: None,
+        }
+    }
+
+    /// Use a tightening schedule measured from `entry_price`
+    pub fn with_schedule(mut self, schedule: TrailingSchedule, entry_price: f64) -> Self {
+        self.schedule = schedule;
+        self.entry_price = entry_price;
+        self
+    }
+
+    /// Re-price only once the target is `threshold_percent` away from the order
+    pub fn with_adjustment_threshold(mut self, threshold_percent: f64) -> Self {
+        self.adjustment_threshold_percent = threshold_percent;
+        self
+    }
+
+    /// Reset the reference to the market price once it's gone `max_age_secs`
+    /// without moving
+    pub fn with_reference_max_age(mut self, max_age_secs: u64) -> Self {
+        self.reference_max_age_secs = Some(max_age_secs);
+        self
+    }
+
+    /// Ignore new extremes less than `move_percent` past the reference, so
+    /// chop doesn't keep nudging it
+    pub fn with_min_reference_move(mut self, move_percent: f64) -> Self {
+        self.min_reference_move_percent = move_percent;
+        self
+    }
+
+    /// Place a BUY as a stop-limit that fills on a rally (ignored for SELLs)
+    pub fn with_direction(mut self, direction: TrailDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Stop moving the order after `max_adjustments` moves
+    pub fn with_max_adjustments(mut self, max_adjustments: u32) -> Self {
+        self.max_adjustments = Some(max_adjustments);
+        self
+    }
+
+    /// Hold the order where it is until the market reaches `price`
+    pub fn with_activation_price(mut self, price: f64) -> Self {
+        self.activation_price = Some(price);
+        self
+    }
+
+    /// Leave the order dormant until Binance order `order_id` has filled
+    pub fn with_dependency(mut self, order_id: i64) -> Self {
+        self.depends_on_order_id = Some(order_id);
+        self
+    }
+
+    /// Whether the trail is live, i.e. not waiting on another order to fill
+    pub fn is_armed(&self) -> bool {
+        self.depends_on_order_id.is_none()
+    }
+
+    /// Start trailing now that the order it depended on filled at `fill_price`
+    ///
+    /// The reference starts from the fill rather than whatever the market did
+    /// while the trail was dormant.
+    pub fn arm(&mut self, fill_price: f64, now_ms: i64) {
+        self.depends_on_order_id = None;
+        if is_valid_price(fill_price) {
+            self.reference_price = fill_price;
+            self.reference_updated_at = now_ms;
+        }
+    }
+
+    /// Whether the reference is the highest price seen (else the lowest)
+    pub fn tracks_highs(&self) -> bool {
+        self.side == OrderSide::Sell
+    }
+
+    /// Binance order for `price`: a stop-limit for a trail-up BUY, else a limit
+    pub fn resting_price(&self, price: f64) -> RestingPrice {
+        if self.side == OrderSide::Buy && self.direction == TrailDirection::Up {
+            RestingPrice::StopLimit(price)
+        } else {
+            RestingPrice::Limit(price)
+        }
+    }
+
+    /// Start the trail from `reference_price` instead of the order price
+    pub fn with_reference_price(mut self, reference_price: f64) -> Self {
+        self.reference_price = reference_price;
+        self
+    }
+
+    /// Trail by a flat `trailing_percent` from now on, replacing any schedule
+    ///
+    /// The reference price is kept, so the next check moves the order to the
+    /// new distance from the best price already seen.
+    pub fn set_trailing_percent(&mut self, trailing_percent: f64) {
+        self.trailing_percent = trailing_percent;
+        self.schedule = TrailingSchedule::flat(trailing_percent);
+    }
+
+    /// Apply `update`, leaving the order unchanged if it's refused
+    ///
+    /// A new percent replaces any schedule and must stay wider than the
+    /// adjustment threshold.
+    pub fn apply_update(&mut self, update: TrailingUpdate) -> Result<(), String> {
+        if let Some(trailing_percent) = update.trailing_percent {
+            let schedule = TrailingSchedule::flat(trailing_percent);
+            validate_adjustment_threshold(self.adjustment_threshold_percent, &schedule)?;
+            self.set_trailing_percent(trailing_percent);
+        }
+        if let Some(max_adjustments) = update.max_adjustments {
+            self.max_adjustments = Some(max_adjustments);
+        }
+        if let Some(price) = update.activation_price {
+            self.activation_price = Some(price);
+        }
+        Ok(())
+    }
+
+    /// Clear the activation price once `market_price` reaches it, returning
+    /// the price if this check did so
+    pub fn check_activation(&mut self, market_price: f64) -> Option<f64> {
+        let activation_price = self.activation_price?;
+        let reached = if self.tracks_highs() {
+            market_price >= activation_price
+        } else {
+            market_price <= activation_price
+        };
+        if !(reached && is_valid_price(market_price)) {
+            return None;
         }
+        self.activation_price = None;
+        Some(activation_price)
+    }
+
+    /// Whether the order has used up its `max_adjustments` moves
+    pub fn adjustments_exhausted(&self) -> bool {
+        self.max_adjustments.is_some_and(|max| self.adjustments >= max)
+    }
+
+    /// Attribute the order to the device that created it
+    pub fn with_device(mut self, device_id: &str) -> Self {
+        self.device_id = Some(device_id.to_string());
+        self
+    }
+
+    /// Keep `tag` on the orders placed when this order is moved
+    pub fn with_client_tag(mut self, tag: &str) -> Self {
+        self.client_tag = Some(tag.to_string());
+        self
+    }
+
+    /// Profit (in %) of the best price seen relative to the entry
+    ///
+    /// A SELL profits as the reference rises above entry, a BUY as it falls below.
+    pub fn profit_percent(&self) -> f64 {
+        if self.entry_price <= 0.0 {
+            return 0.0;
+        }
+        let change = (self.reference_price - self.entry_price) / self.entry_price * 100.0;
+        if self.tracks_highs() {
+            change
+        } else {
+            -change
+        }
+    }
+
+    /// Trailing percent currently in effect according to the schedule
+    pub fn current_trailing_percent(&self) -> f64 {
+        self.schedule.trailing_percent_at(self.profit_percent())
     }
 
     /// Calculate the new order price based on reference price
     /// Returns Some(new_price) if order should be adjusted, None otherwise
     ///
     /// Note: This should be called AFTER update_reference() so reference_price
-    /// reflects the best price seen (lowest for BUY, highest for SELL)
+    /// reflects the best price seen (see `tracks_highs`)
     pub fn calculate_adjustment(&self, _market_price: f64) -> Option<f64> {
-        match self.side {
-            OrderSide::Buy => {
-                // BUY trailing: order should be at reference + trailing%
-                // Reference is the lowest market price seen
-                let target_price = self.reference_price * (1.0 + self.trailing_percent / 100.0);
-                // Only adjust if current order is significantly higher than target (> 0.1%)
-                let price_diff = (self.current_order_price - target_price) / self.current_order_price;
-                if price_diff > 0.001 {
-                    return Some(round_price(target_price));
-                }
+        if !is_valid_price(self.current_order_price) {
+            return None;
+        }
+        if self.activation_price.is_some() || self.adjustments_exhausted() {
+            return None;
+        }
+
+        let trailing_percent = self.current_trailing_percent();
+        let threshold = self.adjustment_threshold_percent / 100.0;
+        if self.tracks_highs() {
+            // SELL trailing: order should be at reference - trailing%
+            // Reference is the highest market price seen
+            let target_price = self.reference_price * (1.0 - trailing_percent / 100.0);
+            // Only adjust if current order is lower than target by more than the threshold
+            let price_diff = (target_price - self.current_order_price) / self.current_order_price;
+            if price_diff > threshold {
+                return round_price(target_price);
             }
-            OrderSide::Sell => {
-                // SELL trailing: order should be at reference - trailing%
-                // Reference is the highest market price seen
-                let target_price = self.reference_price * (1.0 - self.trailing_percent / 100.0);
-                // Only adjust if current order is significantly lower than target (> 0.1%)
-                let price_diff = (target_price - self.current_order_price) / self.current_order_price;
-                if price_diff > 0.001 {
-                    return Some(round_price(target_price));
-                }
+        } else {
+            // BUY trailing: order should be at reference + trailing%
+            // Reference is the lowest market price seen
+            let target_price = self.reference_price * (1.0 + trailing_percent / 100.0);
+            // Only adjust if current order is higher than target by more than the threshold
+            let price_diff = (self.current_order_price - target_price) / self.current_order_price;
+            if price_diff > threshold {
+                return round_price(target_price);
             }
         }
         None
@@ -99,44 +523,128 @@ impl TrailingOrder {
 
     /// Update reference price after market price change
     pub fn update_reference(&mut self, market_price: f64) {
-        match self.side {
-            OrderSide::Buy => {
-                // For BUY, reference is the lowest price seen
-                if market_price < self.reference_price {
-                    self.reference_price = market_price;
-                }
-            }
-            OrderSide::Sell => {
-                // For SELL, reference is the highest price seen
-                if market_price > self.reference_price {
-                    self.reference_price = market_price;
-                }
-            }
+        if !is_valid_price(market_price) {
+            tracing::warn!("Ignoring invalid market price {} for {}", market_price, self.id);
+            return;
+        }
+
+        let min_move = self.reference_price * self.min_reference_move_percent / 100.0;
+        let moved = if self.tracks_highs() {
+            // For SELL, reference is the highest price seen
+            market_price > self.reference_price + min_move
+        } else {
+            // For BUY, reference is the lowest price seen
+            market_price < self.reference_price - min_move
+        };
+        if moved {
+            self.reference_price = market_price;
+            self.reference_updated_at = chrono::Utc::now().timestamp_millis();
         }
     }
 
+    /// Replace a reference older than `reference_max_age_secs` with the market
+    /// price, returning the stale one
+    ///
+    /// A week-old high says little about today's market, and left in place it
+    /// would keep the order pinned to it.
+    pub fn reset_stale_reference(&mut self, market_price: f64, now_ms: i64) -> Option<f64> {
+        let max_age_ms = (self.reference_max_age_secs? as i64).saturating_mul(1000);
+        if now_ms - self.reference_updated_at <= max_age_ms || !is_valid_price(market_price) {
+            return None;
+        }
+
+        let stale = self.reference_price;
+        self.reference_price = market_price;
+        self.reference_updated_at = now_ms;
+        Some(stale)
+    }
+
     /// Update after order modification
     pub fn update_order(&mut self, new_order_id: i64, new_price: f64) {
         self.order_id = new_order_id;
         self.current_order_price = new_price;
+        self.order_placed_at = chrono::Utc::now().timestamp_millis();
+        self.adjustments += 1;
     }
+
+    /// Account for `executed` filled on the current order at `price`
+    ///
+    /// The fill is folded into the weighted average and `quantity` shrinks to
+    /// the remainder, which is what the next replacement order is placed for.
+    pub fn record_partial_fill(&mut self, executed: f64, price: f64) {
+        let executed = executed.min(self.quantity);
+        if executed <= 0.0 {
+            return;
+        }
+
+        let filled = self.filled_quantity + executed;
+        self.avg_fill_price =
+            (self.avg_fill_price * self.filled_quantity + price * executed) / filled;
+        self.filled_quantity = filled;
+        self.quantity = round_quantity(self.quantity - executed);
+    }
+
+    /// Nothing left to place once every unit has filled
+    pub fn is_fully_filled(&self) -> bool {
+        self.quantity <= 0.0
+    }
+}
+
+/// Round a quantity to Binance's 8 decimal places, dropping float residue
+fn round_quantity(quantity: f64) -> f64 {
+    ((quantity * 1e8).round() / 1e8).max(0.0)
+}
+
+/// A price Binance could accept: finite and strictly positive
+fn is_valid_price(price: f64) -> bool {
+    price.is_finite() && price > 0.0
 }
 
 /// Round price to 2 decimal places (BTCUSDT standard)
-fn round_price(price: f64) -> f64 {
-    (price * 100.0).round() / 100.0
+///
+/// Returns None for NaN, infinite, or non-positive input, and for prices that
+/// round down to zero, so a bad reference price never reaches Binance.
+fn round_price(price: f64) -> Option<f64> {
+    if !is_valid_price(price) {
+        return None;
+    }
+    let rounded = round_to(price, USD_DECIMALS);
+    is_valid_price(rounded).then_some(rounded)
 }
 
 /// Response for API endpoints
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TrailingOrderResponse {
     pub id: String,
     pub order_id: i64,
+    pub symbol: String,
     pub side: String,
+    pub direction: TrailDirection,
     pub trailing_percent: f64,
+    /// Trailing percent in effect at the current profit
+    pub current_trailing_percent: f64,
+    pub schedule: TrailingSchedule,
+    pub adjustment_threshold_percent: f64,
+    pub entry_price: f64,
     pub current_order_price: f64,
     pub reference_price: f64,
+    /// When the reference price last moved (ms)
+    pub reference_updated_at: i64,
+    pub reference_max_age_secs: Option<u64>,
+    pub min_reference_move_percent: f64,
     pub quantity: f64,
+    pub filled_quantity: f64,
+    /// Average price of the partial fills so far, None before any
+    pub avg_fill_price: Option<f64>,
+    pub client_tag: Option<String>,
+    /// Times the order has been moved
+    pub adjustments: u32,
+    /// Moves allowed before the order stays put; None for no limit
+    pub max_adjustments: Option<u32>,
+    /// Price the market must reach before the order moves; None once reached
+    pub activation_price: Option<f64>,
+    /// Order whose fill arms the trail; None once trailing
+    pub depends_on_order_id: Option<i64>,
     pub created_at: i64,
 }
 
@@ -145,12 +653,456 @@ impl From<&TrailingOrder> for TrailingOrderResponse {
         Self {
             id: order.id.to_string(),
             order_id: order.order_id,
+            symbol: order.symbol.clone(),
             side: order.side.as_str().to_string(),
+            direction: order.direction,
             trailing_percent: order.trailing_percent,
+            current_trailing_percent: order.current_trailing_percent(),
+            schedule: order.schedule.clone(),
+            adjustment_threshold_percent: order.adjustment_threshold_percent,
+            entry_price: order.entry_price,
             current_order_price: order.current_order_price,
             reference_price: order.reference_price,
+            reference_updated_at: order.reference_updated_at,
+            reference_max_age_secs: order.reference_max_age_secs,
+            min_reference_move_percent: order.min_reference_move_percent,
+            quantity: order.quantity,
+            filled_quantity: order.filled_quantity,
+            avg_fill_price: (order.filled_quantity > 0.0).then_some(order.avg_fill_price),
+            client_tag: order.client_tag.clone(),
+            adjustments: order.adjustments,
+            max_adjustments: order.max_adjustments,
+            activation_price: order.activation_price,
+            depends_on_order_id: order.depends_on_order_id,
+            created_at: order.created_at,
+        }
+    }
+}
+
+/// How a trailing order stopped being tracked
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingOutcome {
+    /// The Binance order filled completely
+    Filled,
+    /// The Binance order was cancelled along with the trail
+    Cancelled,
+    /// Trailing was stopped and the Binance order left open at its last price
+    Stopped,
+    /// Binance no longer knew the order when reconciled
+    Missing,
+}
+
+/// A trailing order that is no longer tracked, for `GET /trailing/history`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CompletedTrailingOrder {
+    pub id: String,
+    /// Binance order id at the end, after any moves
+    pub order_id: i64,
+    pub symbol: String,
+    pub side: String,
+    pub outcome: TrailingOutcome,
+    pub entry_price: f64,
+    /// Price of the last Binance order
+    pub final_price: f64,
+    /// Times the order was moved
+    pub adjustments: u32,
+    /// Remaining quantity when it ended
+    pub quantity: f64,
+    pub filled_quantity: f64,
+    pub client_tag: Option<String>,
+    pub created_at: i64,
+    /// When it stopped being tracked (ms)
+    pub completed_at: i64,
+}
+
+impl CompletedTrailingOrder {
+    pub fn new(order: &TrailingOrder, outcome: TrailingOutcome) -> Self {
+        Self {
+            id: order.id.to_string(),
+            order_id: order.order_id,
+            symbol: order.symbol.clone(),
+            side: order.side.as_str().to_string(),
+            outcome,
+            entry_price: order.entry_price,
+            final_price: order.current_order_price,
+            adjustments: order.adjustments,
             quantity: order.quantity,
+            filled_quantity: order.filled_quantity,
+            client_tag: order.client_tag.clone(),
             created_at: order.created_at,
+            completed_at: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(profit_threshold: f64, trailing_percent: f64) -> TrailingStep {
+        TrailingStep {
+            profit_threshold,
+            trailing_percent,
         }
     }
+
+    fn order(side: OrderSide, trailing_percent: f64, price: f64) -> TrailingOrder {
+        TrailingOrder::new(1, "BTCUSDT".to_string(), side, trailing_percent, price, 0.001, false)
+    }
+
+    /// Trail 2% until +5% profit, then 1%
+    fn tightening_sell(entry_price: f64) -> TrailingOrder {
+        let schedule = TrailingSchedule::new(vec![step(5.0, 1.0), step(0.0, 2.0)]).unwrap();
+        order(OrderSide::Sell, 2.0, entry_price).with_schedule(schedule, entry_price)
+    }
+
+    #[test]
+    fn test_schedule_is_sorted_and_validated() {
+        let schedule = TrailingSchedule::new(vec![step(5.0, 1.0), step(0.0, 2.0)]).unwrap();
+        assert_eq!(schedule.trailing_percent_at(0.0), 2.0);
+        assert_eq!(schedule.trailing_percent_at(4.99), 2.0);
+        assert_eq!(schedule.trailing_percent_at(5.0), 1.0);
+        assert_eq!(schedule.trailing_percent_at(-3.0), 2.0);
+
+        assert!(TrailingSchedule::new(vec![]).is_err());
+        assert!(TrailingSchedule::new(vec![step(0.0, 0.0)]).is_err());
+    }
+
+    #[test]
+    fn test_flat_schedule_matches_fixed_trailing() {
+        let mut sell = order(OrderSide::Sell, 1.0, 100.0);
+        sell.update_reference(110.0);
+
+        assert_eq!(sell.current_trailing_percent(), 1.0);
+        assert_eq!(sell.calculate_adjustment(110.0), Some(108.9));
+    }
+
+    #[test]
+    fn test_crossing_threshold_tightens_trailing() {
+        let mut sell = tightening_sell(100.0);
+
+        // +4%: still trailing 2%
+        sell.update_reference(104.0);
+        assert_eq!(sell.current_trailing_percent(), 2.0);
+        assert_eq!(sell.calculate_adjustment(104.0), Some(101.92));
+
+        // +6%: crossed +5%, now trailing 1%
+        sell.update_reference(106.0);
+        assert_eq!(sell.current_trailing_percent(), 1.0);
+        assert_eq!(sell.calculate_adjustment(106.0), Some(104.94));
+    }
+
+    #[test]
+    fn test_buy_profit_is_measured_downward() {
+        let schedule = TrailingSchedule::new(vec![step(0.0, 2.0), step(5.0, 1.0)]).unwrap();
+        let mut buy = order(OrderSide::Buy, 2.0, 100.0).with_schedule(schedule, 100.0);
+
+        buy.update_reference(94.0);
+        assert!((buy.profit_percent() - 6.0).abs() < 1e-9);
+        assert_eq!(buy.current_trailing_percent(), 1.0);
+    }
+
+    #[test]
+    fn test_reference_seed_deserialization() {
+        let seed = |json: &str| serde_json::from_str::<ReferenceSeed>(json).unwrap();
+        assert_eq!(seed(r#""market""#), ReferenceSeed::Market);
+        assert_eq!(seed(r#""order_price""#), ReferenceSeed::OrderPrice);
+        assert_eq!(seed(r#"{"explicit": 49000.5}"#), ReferenceSeed::Explicit(49000.5));
+        assert_eq!(ReferenceSeed::default(), ReferenceSeed::Market);
+    }
+
+    #[test]
+    fn test_explicit_seed_must_be_positive_and_behind_the_order() {
+        let buy = ReferenceSeed::Explicit(49000.0);
+        assert!(buy.validate(OrderSide::Buy, 50000.0).is_ok());
+        assert!(buy.validate(OrderSide::Sell, 50000.0).is_err());
+
+        let sell = ReferenceSeed::Explicit(51000.0);
+        assert!(sell.validate(OrderSide::Sell, 50000.0).is_ok());
+        assert!(sell.validate(OrderSide::Buy, 50000.0).is_err());
+
+        assert!(ReferenceSeed::Explicit(0.0).validate(OrderSide::Buy, 50000.0).is_err());
+        assert!(ReferenceSeed::Explicit(f64::NAN).validate(OrderSide::Sell, 1.0).is_err());
+
+        // Only explicit seeds are checked
+        assert!(ReferenceSeed::Market.validate(OrderSide::Buy, 50000.0).is_ok());
+        assert!(ReferenceSeed::OrderPrice.validate(OrderSide::Sell, 50000.0).is_ok());
+    }
+
+    #[test]
+    fn test_trail_up_buy_is_a_stop_following_the_market_down() {
+        // Stop-limit BUY at 102, 2% above a market at 100
+        let mut buy = order(OrderSide::Buy, 2.0, 102.0)
+            .with_direction(TrailDirection::Up)
+            .with_reference_price(100.0);
+        assert!(!buy.tracks_highs());
+        assert_eq!(buy.resting_price(102.0), RestingPrice::StopLimit(102.0));
+
+        // A falling market lowers the stop, so a later rally fills nearer the low
+        buy.update_reference(95.0);
+        assert_eq!(buy.calculate_adjustment(95.0), Some(96.9));
+        buy.update_order(2, 96.9);
+
+        // A rally leaves the stop where it is for Binance to trigger
+        buy.update_reference(96.5);
+        assert_eq!(buy.reference_price, 95.0);
+        assert_eq!(buy.calculate_adjustment(96.5), None);
+
+        // A trail-down BUY is a plain limit order
+        let down = order(OrderSide::Buy, 2.0, 102.0);
+        assert_eq!(down.resting_price(102.0), RestingPrice::Limit(102.0));
+    }
+
+    #[test]
+    fn test_trail_down_stays_the_buy_default() {
+        let mut buy = order(OrderSide::Buy, 2.0, 100.0);
+        assert_eq!(buy.direction, TrailDirection::Down);
+
+        // A rally doesn't move a trail-down BUY
+        buy.update_reference(110.0);
+        assert_eq!(buy.reference_price, 100.0);
+        assert_eq!(buy.calculate_adjustment(110.0), None);
+
+        buy.update_reference(95.0);
+        assert_eq!(buy.calculate_adjustment(95.0), Some(96.9));
+    }
+
+    #[test]
+    fn test_adjustment_threshold_sets_sensitivity() {
+        // Reference +1.3%: a 1% SELL targets 100.287, 0.287% above the order
+        let sell = |threshold| {
+            let mut sell = order(OrderSide::Sell, 1.0, 100.0).with_adjustment_threshold(threshold);
+            sell.update_reference(101.3);
+            sell.calculate_adjustment(101.3)
+        };
+        assert_eq!(sell(DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT), Some(100.29));
+        assert_eq!(sell(0.2), Some(100.29));
+        assert_eq!(sell(0.5), None);
+
+        // Reference -1.2%: a 1% BUY targets 99.788, 0.212% below the order
+        let buy = |threshold| {
+            let mut buy = order(OrderSide::Buy, 1.0, 100.0).with_adjustment_threshold(threshold);
+            buy.update_reference(98.8);
+            buy.calculate_adjustment(98.8)
+        };
+        assert_eq!(buy(0.1), Some(99.79));
+        assert_eq!(buy(0.5), None);
+    }
+
+    #[test]
+    fn test_adjustment_threshold_must_be_inside_the_trail() {
+        let flat = TrailingSchedule::flat(1.0);
+        assert!(validate_adjustment_threshold(0.1, &flat).is_ok());
+        assert!(validate_adjustment_threshold(0.99, &flat).is_ok());
+        for threshold in [0.0, -0.1, 1.0, 2.0, f64::NAN] {
+            assert!(validate_adjustment_threshold(threshold, &flat).is_err(), "{}", threshold);
+        }
+
+        // Checked against the tightest step of a schedule
+        let schedule = TrailingSchedule::new(vec![step(5.0, 0.5), step(0.0, 2.0)]).unwrap();
+        assert!(validate_adjustment_threshold(0.4, &schedule).is_ok());
+        assert!(validate_adjustment_threshold(0.5, &schedule).is_err());
+    }
+
+    #[test]
+    fn test_orders_saved_without_threshold_use_the_default() {
+        let mut saved = serde_json::to_value(order(OrderSide::Sell, 1.0, 100.0)).unwrap();
+        saved.as_object_mut().unwrap().remove("adjustment_threshold_percent");
+
+        let restored: TrailingOrder = serde_json::from_value(saved).unwrap();
+        assert_eq!(restored.adjustment_threshold_percent, DEFAULT_ADJUSTMENT_THRESHOLD_PERCENT);
+    }
+
+    #[test]
+    fn test_resumed_reference_moves_order_on_first_check() {
+        // A SELL at 99 resuming a 1% trail whose best price seen was 110
+        let sell = order(OrderSide::Sell, 1.0, 99.0).with_reference_price(110.0);
+        assert_eq!(sell.reference_price, 110.0);
+        assert_eq!(sell.current_order_price, 99.0);
+        assert_eq!(sell.calculate_adjustment(105.0), Some(108.9));
+    }
+
+    #[test]
+    fn test_percent_change_applies_on_next_check() {
+        // A 2% trail from 110 wants 107.8, so the SELL at 108 stays put
+        let mut sell = order(OrderSide::Sell, 2.0, 108.0).with_reference_price(110.0);
+        assert_eq!(sell.calculate_adjustment(109.0), None);
+
+        sell.set_trailing_percent(1.0);
+        assert_eq!(sell.current_trailing_percent(), 1.0);
+        assert_eq!(sell.reference_price, 110.0);
+        assert_eq!(sell.calculate_adjustment(109.0), Some(108.9));
+    }
+
+    #[test]
+    fn test_order_stays_put_after_max_adjustments() {
+        let mut sell = order(OrderSide::Sell, 1.0, 100.0).with_max_adjustments(1);
+        sell.update_reference(110.0);
+        assert_eq!(sell.calculate_adjustment(110.0), Some(108.9));
+        sell.update_order(2, 108.9);
+
+        sell.update_reference(120.0);
+        assert!(sell.adjustments_exhausted());
+        assert_eq!(sell.calculate_adjustment(120.0), None);
+
+        // Raising the cap lets it move again
+        let update = TrailingUpdate {
+            max_adjustments: Some(2),
+            ..TrailingUpdate::default()
+        };
+        sell.apply_update(update).unwrap();
+        assert_eq!(sell.calculate_adjustment(120.0), Some(118.8));
+    }
+
+    #[test]
+    fn test_order_waits_for_its_activation_price() {
+        let mut sell = order(OrderSide::Sell, 1.0, 100.0).with_activation_price(112.0);
+        sell.update_reference(110.0);
+        assert_eq!(sell.check_activation(110.0), None);
+        assert_eq!(sell.activation_price, Some(112.0));
+        assert_eq!(sell.calculate_adjustment(110.0), None);
+
+        sell.update_reference(112.0);
+        assert_eq!(sell.check_activation(112.0), Some(112.0));
+        assert_eq!(sell.activation_price, None);
+        assert_eq!(sell.calculate_adjustment(112.0), Some(110.88));
+
+        // Once reached it stays active, even as the market falls back
+        assert_eq!(sell.check_activation(105.0), None);
+        assert_eq!(sell.activation_price, None);
+
+        // A BUY activates at or below its price
+        let mut buy = order(OrderSide::Buy, 1.0, 100.0).with_activation_price(95.0);
+        assert_eq!(buy.check_activation(96.0), None);
+        assert_eq!(buy.check_activation(95.0), Some(95.0));
+    }
+
+    #[test]
+    fn test_refused_update_leaves_the_order_unchanged() {
+        let mut sell = order(OrderSide::Sell, 1.0, 100.0).with_adjustment_threshold(0.5);
+        let update = TrailingUpdate {
+            trailing_percent: Some(0.4),
+            max_adjustments: Some(3),
+            activation_price: None,
+        };
+        assert!(sell.apply_update(update).is_err());
+        assert_eq!(sell.trailing_percent, 1.0);
+        assert_eq!(sell.max_adjustments, None);
+    }
+
+    #[test]
+    fn test_stale_reference_is_reset_to_market() {
+        let mut sell = order(OrderSide::Sell, 1.0, 100.0).with_reference_max_age(60);
+        sell.reference_price = 110.0;
+        sell.reference_updated_at = 1_000_000;
+
+        // Within the max age, or without a usable price, nothing changes
+        assert_eq!(sell.reset_stale_reference(104.0, 1_060_000), None);
+        assert_eq!(sell.reset_stale_reference(f64::NAN, 1_060_001), None);
+        assert_eq!(sell.reference_price, 110.0);
+
+        assert_eq!(sell.reset_stale_reference(104.0, 1_060_001), Some(110.0));
+        assert_eq!(sell.reference_price, 104.0);
+        assert_eq!(sell.reference_updated_at, 1_060_001);
+
+        // Without a max age the reference is kept however old
+        let mut buy = order(OrderSide::Buy, 1.0, 100.0);
+        buy.reference_updated_at = 0;
+        assert_eq!(buy.reset_stale_reference(120.0, i64::MAX), None);
+        assert_eq!(buy.reference_price, 100.0);
+    }
+
+    #[test]
+    fn test_round_price_rejects_invalid_input() {
+        assert_eq!(round_price(50123.456), Some(50123.46));
+        assert_eq!(round_price(f64::NAN), None);
+        assert_eq!(round_price(f64::INFINITY), None);
+        assert_eq!(round_price(f64::NEG_INFINITY), None);
+        assert_eq!(round_price(-1.0), None);
+        assert_eq!(round_price(0.0), None);
+        assert_eq!(round_price(-0.0), None);
+        // Positive but rounds to zero
+        assert_eq!(round_price(0.004), None);
+    }
+
+    #[test]
+    fn test_invalid_reference_produces_no_adjustment() {
+        for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -100.0] {
+            for side in [OrderSide::Buy, OrderSide::Sell] {
+                let mut order = order(side, 1.0, 100.0);
+                order.reference_price = bad;
+                assert_eq!(order.calculate_adjustment(100.0), None, "{:?} {}", side, bad);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_current_price_produces_no_adjustment() {
+        for bad in [f64::NAN, f64::INFINITY, 0.0, -100.0] {
+            let mut sell = order(OrderSide::Sell, 1.0, 100.0);
+            sell.update_reference(110.0);
+            sell.current_order_price = bad;
+            assert_eq!(sell.calculate_adjustment(110.0), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_invalid_market_price_does_not_move_reference() {
+        let mut sell = order(OrderSide::Sell, 1.0, 100.0);
+        sell.update_reference(f64::INFINITY);
+        sell.update_reference(f64::NAN);
+        assert_eq!(sell.reference_price, 100.0);
+
+        let mut buy = order(OrderSide::Buy, 1.0, 100.0);
+        buy.update_reference(-5.0);
+        buy.update_reference(0.0);
+        assert_eq!(buy.reference_price, 100.0);
+    }
+
+    #[test]
+    fn test_reference_ignores_moves_below_minimum() {
+        let mut sell = order(OrderSide::Sell, 1.0, 100.0).with_min_reference_move(0.5);
+        sell.update_reference(100.4);
+        assert_eq!(sell.reference_price, 100.0);
+        sell.update_reference(100.6);
+        assert_eq!(sell.reference_price, 100.6);
+
+        let mut buy = order(OrderSide::Buy, 1.0, 100.0).with_min_reference_move(0.5);
+        buy.update_reference(99.6);
+        assert_eq!(buy.reference_price, 100.0);
+        buy.update_reference(99.4);
+        assert_eq!(buy.reference_price, 99.4);
+
+        // Default: every new extreme counts
+        let mut sell = order(OrderSide::Sell, 1.0, 100.0);
+        sell.update_reference(100.01);
+        assert_eq!(sell.reference_price, 100.01);
+    }
+
+    #[test]
+    fn test_partial_fill_reduces_remainder() {
+        let mut sell = order(OrderSide::Sell, 1.0, 50000.0);
+        sell.quantity = 0.003;
+
+        sell.record_partial_fill(0.001, 50000.0);
+        assert_eq!(sell.quantity, 0.002);
+        assert_eq!(sell.filled_quantity, 0.001);
+        assert_eq!(sell.avg_fill_price, 50000.0);
+        assert!(!sell.is_fully_filled());
+
+        // Second order, moved up, fills half of the remainder
+        sell.record_partial_fill(0.001, 51000.0);
+        assert_eq!(sell.quantity, 0.001);
+        assert!((sell.avg_fill_price - 50500.0).abs() < 1e-6);
+
+        // Nothing executed leaves the order untouched
+        sell.record_partial_fill(0.0, 52000.0);
+        assert_eq!(sell.quantity, 0.001);
+
+        // Overfill reports are capped at what was outstanding
+        sell.record_partial_fill(0.005, 52000.0);
+        assert!(sell.is_fully_filled());
+        assert!((sell.filled_quantity - 0.003).abs() < 1e-12);
+    }
 }
@@ -1,48 +1,100 @@
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::binance::BinanceClient;
+use crate::binance::{BinanceApiError, BinanceClient, BREAKER};
 use crate::config::Config;
-use super::{OrderSide, TrailingOrder, TrailingOrderResponse};
+use crate::metrics::METRICS;
+use crate::trading::{order_fills, FILL_COOLDOWN};
+use super::{
+    CompletedTrailingOrder, TrailingOrder, TrailingOrderResponse, TrailingOutcome, TrailingUpdate,
+};
+
+/// Orders placed or moved this recently (ms) may be missing from an open-orders
+/// snapshot taken just before, so reconciliation leaves them for the next pass
+const RECONCILE_GRACE_MS: i64 = 60_000;
+
+/// Completed trailing orders kept for `/trailing/history`, oldest dropped first
+const HISTORY_LIMIT: usize = 100;
+
+/// File next to MONITOR_STATE_PATH the trailing history is persisted in
+const HISTORY_FILE_NAME: &str = "trailing_history.json";
 
 /// Manages trailing orders and periodically checks/adjusts them
 pub struct TrailingMonitor {
     config: Config,
     /// Trailing orders indexed by their UUID
     orders: Arc<RwLock<HashMap<Uuid, TrailingOrder>>>,
+    /// Recently completed orders, oldest first
+    history: Arc<RwLock<VecDeque<CompletedTrailingOrder>>>,
+    /// None when persistence is off
+    history_path: Option<PathBuf>,
 }
 
 impl TrailingMonitor {
+    /// Create a monitor, restoring the history persisted before a restart
     pub fn new(config: Config) -> Self {
+        let history_path = (!config.monitor_state_path.is_empty())
+            .then(|| Path::new(&config.monitor_state_path).with_file_name(HISTORY_FILE_NAME));
+        let history = history_path.as_deref().map(load_history).unwrap_or_default();
         Self {
             config,
             orders: Arc::new(RwLock::new(HashMap::new())),
+            history: Arc::new(RwLock::new(history)),
+            history_path,
         }
     }
 
     /// Add a new trailing order to monitor
     pub async fn add_order(&self, order: TrailingOrder) -> Uuid {
-        let id = order.id;
         let mut orders = self.orders.write().await;
-        orders.insert(id, order);
-        tracing::info!("Added trailing order {}", id);
-        id
+        insert_order(&mut orders, order)
+    }
+
+    /// `add_order`, unless the order's device already has `max` active
+    ///
+    /// Counting and inserting share one lock, so concurrent requests can't
+    /// both take a device's last slot. Err carries the active count.
+    pub async fn add_order_within_cap(
+        &self,
+        order: TrailingOrder,
+        max: usize,
+    ) -> Result<Uuid, usize> {
+        let mut orders = self.orders.write().await;
+        if let Some(device_id) = order.device_id.as_deref() {
+            let active = count_for_device(&orders, device_id);
+            if active >= max {
+                return Err(active);
+            }
+        }
+        Ok(insert_order(&mut orders, order))
     }
 
     /// Remove a trailing order
+    ///
+    /// Nothing goes into the history, since the caller may put the order back;
+    /// once its fate is known the caller records it with `record_completed`.
     pub async fn remove_order(&self, id: Uuid) -> Option<TrailingOrder> {
         let mut orders = self.orders.write().await;
         let removed = orders.remove(&id);
+        METRICS.set_active_trailing_orders(orders.len());
         if removed.is_some() {
             tracing::info!("Removed trailing order {}", id);
         }
         removed
     }
 
-    /// Remove trailing order by Binance order ID
-    pub async fn remove_by_order_id(&self, order_id: i64) -> Option<TrailingOrder> {
+    /// Remove trailing order by Binance order ID, recording it as `outcome`
+    pub async fn remove_by_order_id(
+        &self,
+        order_id: i64,
+        outcome: TrailingOutcome,
+    ) -> Option<TrailingOrder> {
         let mut orders = self.orders.write().await;
         let key = orders.iter()
             .find(|(_, o)| o.order_id == order_id)
@@ -50,32 +102,126 @@ impl TrailingMonitor {
 
         if let Some(k) = key {
             let removed = orders.remove(&k);
-            if removed.is_some() {
+            METRICS.set_active_trailing_orders(orders.len());
+            drop(orders);
+            if let Some(order) = &removed {
                 tracing::info!("Removed trailing order for Binance order {}", order_id);
+                self.record_completed(order, outcome).await;
             }
             return removed;
         }
         None
     }
 
+    /// Remove every trailing order on `symbol`, recording each as `outcome`,
+    /// and return how many were stopped
+    pub async fn remove_by_symbol(&self, symbol: &str, outcome: TrailingOutcome) -> usize {
+        let mut orders = self.orders.write().await;
+        let ids: Vec<Uuid> = orders
+            .iter()
+            .filter(|(_, o)| o.symbol == symbol)
+            .map(|(id, _)| *id)
+            .collect();
+        let removed: Vec<TrailingOrder> = ids.iter().filter_map(|id| orders.remove(id)).collect();
+        METRICS.set_active_trailing_orders(orders.len());
+        drop(orders);
+
+        if !removed.is_empty() {
+            tracing::info!("Removed {} trailing orders on {}", removed.len(), symbol);
+        }
+        for order in &removed {
+            self.record_completed(order, outcome).await;
+        }
+        removed.len()
+    }
+
+    /// Remove a trailing order the monitor found finished, recording it as `outcome`
+    async fn remove_completed(&self, id: Uuid, outcome: TrailingOutcome) {
+        let mut orders = self.orders.write().await;
+        let removed = orders.remove(&id);
+        METRICS.set_active_trailing_orders(orders.len());
+        drop(orders);
+        if let Some(order) = removed {
+            self.record_completed(&order, outcome).await;
+        }
+    }
+
+    /// Add a no-longer-tracked order to the history, dropping the oldest past
+    /// `HISTORY_LIMIT`, and persist it if persistence is on
+    pub async fn record_completed(&self, order: &TrailingOrder, outcome: TrailingOutcome) {
+        let mut history = self.history.write().await;
+        if history.len() == HISTORY_LIMIT {
+            history.pop_front();
+        }
+        history.push_back(CompletedTrailingOrder::new(order, outcome));
+        if let Some(path) = &self.history_path {
+            if let Err(e) = save_history(path, &history) {
+                tracing::error!("Failed to persist trailing history: {}", e);
+            }
+        }
+    }
+
+    /// Recently completed trailing orders, newest first
+    pub async fn history(&self) -> Vec<CompletedTrailingOrder> {
+        self.history.read().await.iter().rev().cloned().collect()
+    }
+
     /// Get all trailing orders
     pub async fn get_all_orders(&self) -> Vec<TrailingOrderResponse> {
         let orders = self.orders.read().await;
         orders.values().map(TrailingOrderResponse::from).collect()
     }
 
+    /// Number of active trailing orders created by `device_id`
+    ///
+    /// Derived from the live orders, so it drops as soon as an order is
+    /// stopped or removed after filling.
+    pub async fn count_for_device(&self, device_id: &str) -> usize {
+        count_for_device(&*self.orders.read().await, device_id)
+    }
+
     /// Get a specific trailing order
     pub async fn get_order(&self, id: Uuid) -> Option<TrailingOrderResponse> {
         let orders = self.orders.read().await;
         orders.get(&id).map(TrailingOrderResponse::from)
     }
 
-    /// Start the monitoring loop
-    pub async fn start(self: Arc<Self>) {
+    /// Change a live order's parameters, picked up on the next check
+    ///
+    /// Refused when the order's adjustment threshold wouldn't be below a new
+    /// percent; None if the order isn't tracked.
+    pub async fn update_order(
+        &self,
+        id: Uuid,
+        update: TrailingUpdate,
+    ) -> Result<Option<TrailingOrderResponse>, String> {
+        let mut orders = self.orders.write().await;
+        let Some(order) = orders.get_mut(&id) else {
+            return Ok(None);
+        };
+        order.apply_update(update)?;
+        tracing::info!("Trailing order {} updated: {:?}", id, update);
+        Ok(Some(TrailingOrderResponse::from(&*order)))
+    }
+
+    /// Get the trailing order tracking a Binance order ID
+    pub async fn get_by_order_id(&self, order_id: i64) -> Option<TrailingOrderResponse> {
+        let orders = self.orders.read().await;
+        orders
+            .values()
+            .find(|o| o.order_id == order_id)
+            .map(TrailingOrderResponse::from)
+    }
+
+    /// Start the monitoring loop, returning once `shutdown` is cancelled
+    pub async fn start(self: Arc<Self>, shutdown: CancellationToken) {
         tracing::info!("Starting trailing order monitor (10s interval)");
 
         loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(10)) => {}
+            }
 
             let orders = self.orders.read().await;
             if orders.is_empty() {
@@ -83,39 +229,159 @@ impl TrailingMonitor {
             }
             drop(orders);
 
+            // Prices come from testnet; each move still goes through its own
+            // environment's breaker
+            if !BREAKER.get(false).allow_request() {
+                tracing::debug!("Trailing monitor paused: circuit breaker open");
+                continue;
+            }
+
             if let Err(e) = self.check_and_adjust().await {
                 tracing::error!("Trailing monitor error: {}", e);
             }
         }
+
+        tracing::info!("Trailing order monitor stopped");
     }
 
-    /// Check all trailing orders and adjust if needed
-    async fn check_and_adjust(&self) -> Result<(), String> {
-        // Get current market price (using testnet client for price - it's the same)
-        let price_client = BinanceClient::new(&self.config);
-        let market_price = price_client.get_price().await
-            .map_err(|e| format!("Failed to get price: {}", e))?;
+    /// Drop orders Binance no longer has open every TRAILING_RECONCILE_INTERVAL_SECS,
+    /// returning once `shutdown` is cancelled
+    pub async fn run_reconciliation(self: Arc<Self>, shutdown: CancellationToken) {
+        let interval_secs = self.config.trailing_reconcile_interval_secs;
+        if interval_secs == 0 {
+            tracing::info!("Trailing order reconciliation disabled");
+            return;
+        }
+        let interval = Duration::from_secs(interval_secs);
 
-        tracing::debug!("Checking trailing orders at price {}", market_price);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
+
+            if self.orders.read().await.is_empty() {
+                continue;
+            }
+            self.reconcile().await;
+        }
+    }
 
-        // Get orders that need adjustment
-        let adjustments: Vec<(Uuid, f64, TrailingOrder)> = {
-            let mut orders = self.orders.write().await;
-            let mut adjustments = Vec::new();
+    /// Prune each symbol and environment's orders against its open orders on Binance
+    async fn reconcile(&self) {
+        let markets: HashSet<(String, bool)> = {
+            let orders = self.orders.read().await;
+            orders.values().map(|o| (o.symbol.clone(), o.use_production)).collect()
+        };
 
-            for (id, order) in orders.iter_mut() {
-                // First update reference price
-                order.update_reference(market_price);
+        for (symbol, use_production) in markets {
+            if !BREAKER.get(use_production).allow_request() {
+                continue;
+            }
+            let pruned = match BinanceClient::for_environment(&self.config, use_production) {
+                Ok(client) => self.prune_missing(&client, &symbol, use_production).await,
+                Err(e) => Err(e),
+            };
+            match pruned {
+                Ok(0) => {}
+                Ok(pruned) => tracing::info!(
+                    "Reconciliation dropped {} trailing orders on {}",
+                    pruned,
+                    symbol
+                ),
+                Err(e) => tracing::warn!("Trailing reconciliation on {} failed: {}", symbol, e),
+            }
+        }
+    }
 
-                // Check if adjustment is needed
-                if let Some(new_price) = order.calculate_adjustment(market_price) {
-                    adjustments.push((*id, new_price, order.clone()));
+    /// Remove `symbol`'s trailing orders whose Binance order is no longer open,
+    /// returning how many were removed
+    ///
+    /// Each order's status decides the outcome recorded: filled, cancelled, or
+    /// missing when Binance doesn't know it. One still open after all, or whose
+    /// status can't be read, is left for the next pass.
+    async fn prune_missing(
+        &self,
+        client: &BinanceClient,
+        symbol: &str,
+        use_production: bool,
+    ) -> Result<usize, BinanceApiError> {
+        let open: HashSet<i64> = client
+            .get_open_orders(symbol)
+            .await?
+            .iter()
+            .map(|o| o.order_id)
+            .collect();
+        let placed_before = chrono::Utc::now().timestamp_millis() - RECONCILE_GRACE_MS;
+
+        let gone: Vec<(Uuid, i64)> = {
+            let orders = self.orders.read().await;
+            orders
+                .iter()
+                .filter(|(_, o)| {
+                    o.symbol == symbol
+                        && o.use_production == use_production
+                        && o.order_placed_at < placed_before
+                        && !open.contains(&o.order_id)
+                })
+                .map(|(id, o)| (*id, o.order_id))
+                .collect()
+        };
+
+        let mut pruned = 0;
+        for (id, order_id) in gone {
+            let outcome = match client.get_order(symbol, order_id).await {
+                Ok(order) => match order.status.as_str() {
+                    "FILLED" => TrailingOutcome::Filled,
+                    "CANCELED" | "EXPIRED" | "REJECTED" | "EXPIRED_IN_MATCH" => {
+                        TrailingOutcome::Cancelled
+                    }
+                    _ => continue,
+                },
+                Err(BinanceApiError::UnknownOrder { .. }) => TrailingOutcome::Missing,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to check order {} of trailing order {}: {}",
+                        order_id,
+                        id,
+                        e
+                    );
+                    continue;
                 }
+            };
+            tracing::warn!(
+                "Dropping trailing order {}: Binance order {} is no longer open ({:?})",
+                id,
+                order_id,
+                outcome
+            );
+            // Unless it moved to a new Binance order in the meantime
+            if self.remove_by_order_id(order_id, outcome).await.is_some() {
+                pruned += 1;
             }
+        }
+        Ok(pruned)
+    }
 
-            adjustments
+    /// Check all trailing orders and adjust if needed
+    async fn check_and_adjust(&self) -> Result<(), BinanceApiError> {
+        let symbols: HashSet<String> = {
+            let orders = self.orders.read().await;
+            orders.values().map(|o| o.symbol.clone()).collect()
         };
 
+        // Get current market prices (using testnet client for price - it's the same)
+        let price_client = BinanceClient::new(&self.config);
+        let mut market_prices = HashMap::new();
+        for symbol in symbols {
+            let price = price_client.get_price(&symbol).await?;
+            tracing::debug!("Checking trailing orders on {} at price {}", symbol, price);
+            market_prices.insert(symbol, price);
+        }
+
+        self.arm_dependents().await;
+        let adjustments = self.due_adjustments(&market_prices, Utc::now()).await;
+
         // Process adjustments (outside the lock)
         for (id, new_price, order) in adjustments {
             tracing::info!(
@@ -127,87 +393,988 @@ impl TrailingMonitor {
             );
 
             match self.adjust_order(&order, new_price).await {
-                Ok(new_order_id) => {
-                    // Update the order with new ID and price
+                Ok(Adjustment::Moved { new_order_id, executed }) => {
+                    // Update the order with new ID, price and unfilled remainder
                     let mut orders = self.orders.write().await;
                     if let Some(o) = orders.get_mut(&id) {
+                        o.record_partial_fill(executed, order.current_order_price);
                         o.update_order(new_order_id, new_price);
+                        METRICS.record_trailing_adjustment();
                         tracing::info!(
-                            "Successfully adjusted order {} -> {} at {}",
+                            "Successfully adjusted order {} -> {} at {} for {}",
                             order.order_id,
                             new_order_id,
-                            new_price
+                            new_price,
+                            o.quantity
                         );
                     }
                 }
+                Ok(Adjustment::Filled) | Err(BinanceApiError::UnknownOrder { .. }) => {
+                    // Fully filled, or the cancel was rejected because the order is gone
+                    tracing::info!(
+                        "Order {} appears to be filled, removing from monitor",
+                        order.order_id
+                    );
+                    self.remove_completed(id, TrailingOutcome::Filled).await;
+                }
+                Err(BinanceApiError::ReplacementFailed { cancelled, code, message }) => {
+                    // The old order is gone and nothing replaced it: retrying would
+                    // read the cancelled id as filled
+                    tracing::error!(
+                        "Order {} was cancelled but its replacement failed ({} - {}), \
+                         stopping trailing order {}",
+                        cancelled.order_id,
+                        code,
+                        message,
+                        id
+                    );
+                    self.remove_completed(id, TrailingOutcome::Cancelled).await;
+                }
                 Err(e) => {
-                    // Check if order was filled (Unknown order error)
-                    if e.contains("Unknown order") || e.contains("-2011") {
+                    tracing::error!("Failed to adjust order {}: {}", id, e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Arm dormant orders whose dependency has filled, seeding each reference
+    /// from the fill price
+    ///
+    /// A dependency that ends without filling stops the trail, leaving its
+    /// Binance order open where it is.
+    async fn arm_dependents(&self) {
+        let dormant: Vec<(Uuid, String, bool, i64)> = {
+            let orders = self.orders.read().await;
+            orders
+                .iter()
+                .filter_map(|(id, o)| {
+                    let dependency = o.depends_on_order_id?;
+                    Some((*id, o.symbol.clone(), o.use_production, dependency))
+                })
+                .collect()
+        };
+
+        for (id, symbol, use_production, dependency) in dormant {
+            let status = match BinanceClient::for_environment(&self.config, use_production) {
+                Ok(client) => dependency_status(&client, &symbol, dependency).await,
+                Err(e) => Err(e),
+            };
+            match status {
+                Ok(Dependency::Pending) => {}
+                Ok(Dependency::Filled { price }) => {
+                    let mut orders = self.orders.write().await;
+                    if let Some(order) = orders.get_mut(&id) {
+                        order.arm(price, Utc::now().timestamp_millis());
                         tracing::info!(
-                            "Order {} appears to be filled, removing from monitor",
-                            order.order_id
+                            "Order {} filled at {}: trailing order {} armed",
+                            dependency,
+                            price,
+                            id
                         );
-                        let mut orders = self.orders.write().await;
-                        orders.remove(&id);
-                    } else {
-                        tracing::error!("Failed to adjust order {}: {}", id, e);
                     }
                 }
+                Ok(Dependency::Ended { status }) => {
+                    tracing::warn!(
+                        "Stopping trailing order {}: order {} it waited on is {}",
+                        id,
+                        dependency,
+                        status
+                    );
+                    self.remove_completed(id, TrailingOutcome::Stopped).await;
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to check order {} trailing order {} waits on: {}",
+                    dependency,
+                    id,
+                    e
+                ),
             }
         }
+    }
 
-        Ok(())
+    /// Update every order's reference price and collect the ones due a move
+    ///
+    /// Orders still waiting on a dependency are left alone. Inside a no-trade
+    /// window, or while the symbol is cooling down after a fill, references
+    /// keep following the market but nothing is returned, so due moves go out
+    /// on the first check after it.
+    async fn due_adjustments(
+        &self,
+        market_prices: &HashMap<String, f64>,
+        now: DateTime<Utc>,
+    ) -> Vec<(Uuid, f64, TrailingOrder)> {
+        let no_trade_window = self.config.no_trade_schedule.active_window(now);
+        let cooldown = Duration::from_secs(self.config.fill_cooldown_secs);
+        let now_ms = now.timestamp_millis();
+        let mut orders = self.orders.write().await;
+        let mut adjustments = Vec::new();
+
+        for (id, order) in orders.iter_mut() {
+            // Orders added after the price fetch wait for the next tick
+            let Some(&market_price) = market_prices.get(&order.symbol) else {
+                continue;
+            };
+            if !order.is_armed() {
+                continue;
+            }
+
+            // First update reference price, starting over from the market if it's stale
+            if let Some(stale) = order.reset_stale_reference(market_price, now_ms) {
+                tracing::info!(
+                    "Trailing order {} reference {} unchanged for over {}s, reset to {}",
+                    id,
+                    stale,
+                    order.reference_max_age_secs.unwrap_or_default(),
+                    market_price
+                );
+            }
+            order.update_reference(market_price);
+            if let Some(activation_price) = order.check_activation(market_price) {
+                tracing::info!(
+                    "Trailing order {} activated: market {} reached {}",
+                    id,
+                    market_price,
+                    activation_price
+                );
+            }
+
+            // Check if adjustment is needed
+            if let Some(new_price) = order.calculate_adjustment(market_price) {
+                let cooling = FILL_COOLDOWN.remaining(&order.symbol, Instant::now(), cooldown);
+                match (no_trade_window, cooling) {
+                    (Some(window), _) => tracing::info!(
+                        "No-trade window {}: holding trailing order {} at {} (due {})",
+                        window,
+                        id,
+                        order.current_order_price,
+                        new_price
+                    ),
+                    (None, Some(wait)) => tracing::info!(
+                        "Fill cooldown on {}: holding trailing order {} at {} (due {}) for {:?}",
+                        order.symbol,
+                        id,
+                        order.current_order_price,
+                        new_price,
+                        wait
+                    ),
+                    (None, None) => adjustments.push((*id, new_price, order.clone())),
+                }
+            }
+        }
+
+        adjustments
     }
 
     /// Adjust an order to a new price
-    async fn adjust_order(&self, order: &TrailingOrder, new_price: f64) -> Result<i64, String> {
-        let client = BinanceClient::for_environment(&self.config, order.use_production)
-            .map_err(|e| format!("Client error: {}", e))?;
-
-        // Cancel and recreate at new price
-        let new_order = client
-            .modify_order(
-                order.order_id,
-                order.side.as_str(),
-                new_price,
-                order.quantity,
-            )
-            .await
-            .map_err(|e| format!("Modify order failed: {}", e))?;
+    async fn adjust_order(
+        &self,
+        order: &TrailingOrder,
+        new_price: f64,
+    ) -> Result<Adjustment, BinanceApiError> {
+        let client = BinanceClient::for_environment(&self.config, order.use_production)?;
+        // Snap to the symbol's own tick from the symbol table; the 2-decimal
+        // rounding done when the move was computed only suits BTCUSDT-like symbols
+        let precision = client.get_precision(&order.symbol).await?;
+        move_order(&client, order, precision.round_price(new_price)).await
+    }
+}
 
-        Ok(new_order.orderId)
+/// Where the order a dormant trail depends on stands
+#[derive(Debug, PartialEq)]
+enum Dependency {
+    /// Still open or partially filled
+    Pending,
+    /// Fully filled at `price` (quantity-weighted across its fills)
+    Filled { price: f64 },
+    /// Cancelled, expired or rejected, so it will never fill
+    Ended { status: String },
+}
+
+/// Look up `order_id`'s status, and its average fill price once filled
+async fn dependency_status(
+    client: &BinanceClient,
+    symbol: &str,
+    order_id: i64,
+) -> Result<Dependency, BinanceApiError> {
+    let order = client.get_order(symbol, order_id).await?;
+    match order.status.as_str() {
+        "FILLED" => {
+            // The limit price if the fills can't be read: a BUY fills at or below it
+            let price = client
+                .get_order_trades(symbol, order_id)
+                .await
+                .ok()
+                .and_then(|trades| order_fills(&trades).avg_price)
+                .unwrap_or_else(|| order.price_f64());
+            Ok(Dependency::Filled { price })
+        }
+        "CANCELED" | "EXPIRED" | "REJECTED" | "EXPIRED_IN_MATCH" => Ok(Dependency::Ended {
+            status: order.status,
+        }),
+        _ => Ok(Dependency::Pending),
     }
 }
 
-/// Shared state for trailing orders
-pub type SharedTrailingMonitor = Arc<TrailingMonitor>;
+/// Track `order`, keeping the active-order gauge in step
+fn insert_order(orders: &mut HashMap<Uuid, TrailingOrder>, order: TrailingOrder) -> Uuid {
+    let id = order.id;
+    orders.insert(id, order);
+    METRICS.set_active_trailing_orders(orders.len());
+    tracing::info!("Added trailing order {}", id);
+    id
+}
 
-impl TrailingMonitor {
-    /// Create from order creation request
-    pub async fn add_from_request(
-        &self,
-        order_id: i64,
-        side: &str,
-        price: f64,
-        quantity: f64,
-        trailing_percent: f64,
-        use_production: bool,
-    ) -> Uuid {
-        let order_side = if side.to_uppercase() == "BUY" {
-            OrderSide::Buy
-        } else {
-            OrderSide::Sell
-        };
+/// Active orders created by `device_id`
+fn count_for_device(orders: &HashMap<Uuid, TrailingOrder>, device_id: &str) -> usize {
+    orders
+        .values()
+        .filter(|o| o.device_id.as_deref() == Some(device_id))
+        .count()
+}
+
+/// History persisted at `path`, or empty if missing or unreadable
+fn load_history(path: &Path) -> VecDeque<CompletedTrailingOrder> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return VecDeque::new();
+    };
+    match serde_json::from_str(&contents) {
+        Ok(history) => history,
+        Err(e) => {
+            tracing::warn!("Ignoring corrupt trailing history {}: {}", path.display(), e);
+            VecDeque::new()
+        }
+    }
+}
+
+/// Write the history to `path` via a temp file so a crash can't truncate it
+fn save_history(path: &Path, history: &VecDeque<CompletedTrailingOrder>) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec(history)?)?;
+    std::fs::rename(tmp, path)
+}
+
+/// Outcome of moving a trailing order
+#[derive(Debug, PartialEq)]
+enum Adjustment {
+    /// Replaced by `new_order_id` for the remainder after `executed` filled
+    Moved { new_order_id: i64, executed: f64 },
+    /// Nothing left to move
+    Filled,
+}
+
+/// Move an order to a new price, carrying over only its unfilled remainder
+///
+/// The order may have partially filled since it was placed, so its executed
+/// quantity is checked first rather than resubmitting the tracked size. It can
+/// keep filling until the cancel lands, so the cancel's own executedQty is the
+/// final word: a replacement sized from the earlier read is shrunk to match.
+/// A remainder too small to place counts as filled.
+async fn move_order(
+    client: &BinanceClient,
+    order: &TrailingOrder,
+    new_price: f64,
+) -> Result<Adjustment, BinanceApiError> {
+    let current = client.get_order(&order.symbol, order.order_id).await?;
+    // A garbled executedQty must not read as "nothing filled" and resubmit
+    // the whole order
+    let executed = current
+        .try_executed_qty_f64()
+        .map_err(|e| BinanceApiError::Parse(format!("order {}: {}", order.order_id, e)))?;
+
+    let remainder = unfilled(order, executed);
+    if current.status == "FILLED" || is_done(client, &remainder, new_price).await? {
+        return Ok(Adjustment::Filled);
+    }
+
+    // Replace at the new price
+    let replaced = client
+        .modify_order(
+            &order.symbol,
+            order.order_id,
+            order.side.as_str(),
+            order.resting_price(new_price),
+            remainder.quantity,
+            order.client_tag.as_deref(),
+        )
+        .await?;
+    let mut new_order_id = replaced.new_order.order_id;
+
+    // Fills between the status read and the cancel: the read is a lower bound
+    let cancelled_executed = replaced.cancelled.try_executed_qty_f64().unwrap_or(executed);
+    if cancelled_executed <= executed {
+        return Ok(Adjustment::Moved { new_order_id, executed });
+    }
+
+    tracing::info!(
+        "Order {} filled {} more before it was cancelled, resizing replacement {}",
+        order.order_id,
+        cancelled_executed - executed,
+        new_order_id
+    );
+    let remainder = unfilled(order, cancelled_executed);
+    if is_done(client, &remainder, new_price).await? {
+        client.cancel_order(&order.symbol, new_order_id).await?;
+        return Ok(Adjustment::Filled);
+    }
+    new_order_id = client
+        .modify_order(
+            &order.symbol,
+            new_order_id,
+            order.side.as_str(),
+            order.resting_price(new_price),
+            remainder.quantity,
+            order.client_tag.as_deref(),
+        )
+        .await?
+        .new_order
+        .order_id;
+
+    Ok(Adjustment::Moved {
+        new_order_id,
+        executed: cancelled_executed,
+    })
+}
+
+/// `order` with `executed` of it filled
+fn unfilled(order: &TrailingOrder, executed: f64) -> TrailingOrder {
+    let mut remainder = order.clone();
+    remainder.record_partial_fill(executed, order.current_order_price);
+    remainder
+}
+
+/// Nothing left worth placing: fully filled, or only dust Binance would reject
+async fn is_done(
+    client: &BinanceClient,
+    remainder: &TrailingOrder,
+    price: f64,
+) -> Result<bool, BinanceApiError> {
+    if remainder.is_fully_filled() {
+        return Ok(true);
+    }
+    client.is_dust(&remainder.symbol, remainder.quantity, price).await
+}
 
-        let order = TrailingOrder::new(
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binance::test_support::{mock_client, mock_config};
+    use crate::trailing::OrderSide;
+    use axum::{
+        extract::RawQuery,
+        routing::get,
+        Json, Router,
+    };
+    use std::sync::Mutex;
+
+    fn trailing_order(order_id: i64) -> TrailingOrder {
+        TrailingOrder::new(
             order_id,
-            order_side,
-            trailing_percent,
-            price,
-            quantity,
-            use_production,
+            "BTCUSDT".to_string(),
+            OrderSide::Sell,
+            1.0,
+            50000.0,
+            0.001,
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_lookup_by_binance_order_id() {
+        let monitor = TrailingMonitor::new(Config::for_tests());
+        let id = monitor.add_order(trailing_order(42)).await;
+        monitor.add_order(trailing_order(43)).await;
+
+        let found = monitor.get_by_order_id(42).await.unwrap();
+        assert_eq!(found.id, id.to_string());
+        assert_eq!(found.order_id, 42);
+        assert!(monitor.get_by_order_id(44).await.is_none());
+
+        assert!(monitor.remove_by_order_id(42, TrailingOutcome::Stopped).await.is_some());
+        assert!(monitor.get_by_order_id(42).await.is_none());
+        assert!(monitor.get_by_order_id(43).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_adds_cannot_pass_the_device_cap() {
+        let monitor = Arc::new(TrailingMonitor::new(Config::for_tests()));
+        monitor.add_order(trailing_order(1).with_device("phone")).await;
+
+        let adds = (2..12).map(|order_id| {
+            let monitor = monitor.clone();
+            tokio::spawn(async move {
+                let order = trailing_order(order_id).with_device("phone");
+                monitor.add_order_within_cap(order, 3).await
+            })
+        });
+        let added = futures::future::join_all(adds)
+            .await
+            .into_iter()
+            .filter(|result| result.as_ref().unwrap().is_ok())
+            .count();
+
+        assert_eq!(added, 2);
+        assert_eq!(monitor.count_for_device("phone").await, 3);
+        // Orders without a device aren't capped
+        assert!(monitor.add_order_within_cap(trailing_order(20), 3).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_filled_order_moves_to_history() {
+        let monitor = TrailingMonitor::new(Config::for_tests());
+        let mut order = trailing_order(42);
+        order.update_order(43, 50500.0);
+        let id = monitor.add_order(order).await;
+        monitor.add_order(trailing_order(44)).await;
+
+        monitor.remove_completed(id, TrailingOutcome::Filled).await;
+        monitor.remove_by_order_id(44, TrailingOutcome::Stopped).await;
+
+        assert!(monitor.get_all_orders().await.is_empty());
+        let history = monitor.history().await;
+        assert_eq!(history.len(), 2);
+        let filled = &history[1];
+        assert_eq!(filled.id, id.to_string());
+        assert_eq!(filled.outcome, TrailingOutcome::Filled);
+        assert_eq!(filled.order_id, 43);
+        assert_eq!(filled.final_price, 50500.0);
+        assert_eq!(filled.adjustments, 1);
+        assert_eq!(history[0].outcome, TrailingOutcome::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_history_survives_a_restart() {
+        let state_path =
+            std::env::temp_dir().join(format!("trailing_{}/state.json", std::process::id()));
+        std::fs::create_dir_all(state_path.parent().unwrap()).unwrap();
+        let config = Config {
+            monitor_state_path: state_path.to_string_lossy().into_owned(),
+            ..Config::for_tests()
+        };
+
+        let monitor = TrailingMonitor::new(config.clone());
+        let id = monitor.add_order(trailing_order(42)).await;
+        monitor.remove_completed(id, TrailingOutcome::Filled).await;
+
+        let history = TrailingMonitor::new(config).history().await;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].order_id, 42);
+        assert_eq!(history[0].outcome, TrailingOutcome::Filled);
+
+        std::fs::remove_dir_all(state_path.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn test_history_is_bounded() {
+        let monitor = TrailingMonitor::new(Config::for_tests());
+        for order_id in 0..HISTORY_LIMIT as i64 + 5 {
+            monitor
+                .record_completed(&trailing_order(order_id), TrailingOutcome::Cancelled)
+                .await;
+        }
+
+        let history = monitor.history().await;
+        assert_eq!(history.len(), HISTORY_LIMIT);
+        assert_eq!(history[0].order_id, HISTORY_LIMIT as i64 + 4);
+        assert_eq!(history[HISTORY_LIMIT - 1].order_id, 5);
+    }
+
+    #[tokio::test]
+    async fn test_start_returns_on_shutdown() {
+        let monitor = Arc::new(TrailingMonitor::new(Config::for_tests()));
+        let shutdown = CancellationToken::new();
+
+        let handle = tokio::spawn(monitor.start(shutdown.clone()));
+        shutdown.cancel();
+
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("monitor loop did not stop after shutdown")
+            .unwrap();
+    }
+
+    /// Mock Binance whose order query reports `status` with `executed_qty`
+    /// filled and whose cancels report `cancelled_qty` filled, returning the
+    /// client, the bodies of replacement orders and the cancelled order ids
+    async fn mock_binance_cancelling_at(
+        status: &'static str,
+        executed_qty: &'static str,
+        cancelled_qty: &'static str,
+    ) -> (BinanceClient, Arc<Mutex<Vec<String>>>, Arc<Mutex<Vec<String>>>) {
+        let placed = Arc::new(Mutex::new(Vec::new()));
+        let cancelled = Arc::new(Mutex::new(Vec::new()));
+        let (recorded, cancels) = (placed.clone(), cancelled.clone());
+        let router = Router::new()
+            .route(
+                "/api/v3/exchangeInfo",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "symbols": [{
+                            "symbol": "BTCUSDT",
+                            "filters": [
+                                { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                                {
+                                    "filterType": "LOT_SIZE",
+                                    "minQty": "0.00001000",
+                                    "stepSize": "0.00001000"
+                                },
+                                { "filterType": "NOTIONAL", "minNotional": "5.00000000" }
+                            ]
+                        }]
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/order",
+                get(move || async move {
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": 42,
+                        "clientOrderId": "abc",
+                        "price": "50000.00",
+                        "origQty": "0.00200",
+                        "executedQty": executed_qty,
+                        "status": status,
+                        "type": "LIMIT",
+                        "side": "SELL",
+                        "time": 1700000000000i64
+                    }))
+                })
+                .delete(move |RawQuery(query): RawQuery| async move {
+                    let query = query.unwrap_or_default();
+                    let order_id = query
+                        .split('&')
+                        .find_map(|pair| pair.strip_prefix("orderId="))
+                        .unwrap_or_default()
+                        .to_string();
+                    cancels.lock().unwrap().push(order_id.clone());
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": order_id.parse::<i64>().unwrap(),
+                        "status": "CANCELED",
+                        "executedQty": cancelled_qty
+                    }))
+                })
+                .post(move |body: String| async move {
+                    recorded.lock().unwrap().push(body);
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": 43,
+                        "clientOrderId": "def",
+                        "transactTime": 1700000000000i64,
+                        "price": "50500.00",
+                        "origQty": "0.00150",
+                        "executedQty": "0",
+                        "status": "NEW",
+                        "type": "LIMIT",
+                        "side": "SELL"
+                    }))
+                }),
+            );
+
+        let client = mock_client(router).await;
+        (client, placed, cancelled)
+    }
+
+    /// `mock_binance_cancelling_at` where nothing fills between the status read
+    /// and the cancel
+    async fn mock_binance(
+        status: &'static str,
+        executed_qty: &'static str,
+    ) -> (BinanceClient, Arc<Mutex<Vec<String>>>) {
+        let (client, placed, _) =
+            mock_binance_cancelling_at(status, executed_qty, executed_qty).await;
+        (client, placed)
+    }
+
+    #[tokio::test]
+    async fn test_partial_fill_moves_only_the_remainder() {
+        let (client, placed) = mock_binance("PARTIALLY_FILLED", "0.00050").await;
+        let mut order = trailing_order(42);
+        order.quantity = 0.002;
+
+        let adjustment = move_order(&client, &order, 50500.0).await.unwrap();
+        assert_eq!(
+            adjustment,
+            Adjustment::Moved {
+                new_order_id: 43,
+                executed: 0.0005,
+            }
         );
 
-        self.add_order(order).await
+        let placed = placed.lock().unwrap();
+        assert_eq!(placed.len(), 1);
+        assert!(placed[0].contains("quantity=0.00150&"), "{}", placed[0]);
+
+        order.record_partial_fill(0.0005, order.current_order_price);
+        assert_eq!(order.quantity, 0.0015);
+        assert_eq!(order.filled_quantity, 0.0005);
+    }
+
+    #[tokio::test]
+    async fn test_filled_order_is_not_replaced() {
+        let (client, placed) = mock_binance("FILLED", "0.00200").await;
+        let mut order = trailing_order(42);
+        order.quantity = 0.002;
+
+        let adjustment = move_order(&client, &order, 50500.0).await.unwrap();
+        assert_eq!(adjustment, Adjustment::Filled);
+        assert!(placed.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fills_before_the_cancel_shrink_the_replacement() {
+        let (client, placed, cancelled) =
+            mock_binance_cancelling_at("PARTIALLY_FILLED", "0.00050", "0.00100").await;
+        let mut order = trailing_order(42);
+        order.quantity = 0.002;
+
+        let adjustment = move_order(&client, &order, 50500.0).await.unwrap();
+        assert_eq!(
+            adjustment,
+            Adjustment::Moved {
+                new_order_id: 43,
+                executed: 0.001,
+            }
+        );
+
+        // Placed for the remainder as read, then resized to what the cancel left
+        let placed = placed.lock().unwrap();
+        assert_eq!(placed.len(), 2);
+        assert!(placed[0].contains("quantity=0.00150&"), "{}", placed[0]);
+        assert!(placed[1].contains("quantity=0.00100&"), "{}", placed[1]);
+        assert_eq!(*cancelled.lock().unwrap(), vec!["42", "43"]);
+    }
+
+    #[tokio::test]
+    async fn test_dust_remainder_counts_as_filled() {
+        // 0.00001 left is a whole step, but worth $0.51 against a $5 minimum
+        let (client, placed, cancelled) =
+            mock_binance_cancelling_at("PARTIALLY_FILLED", "0.00199", "0.00199").await;
+        let mut order = trailing_order(42);
+        order.quantity = 0.002;
+
+        assert_eq!(move_order(&client, &order, 50500.0).await.unwrap(), Adjustment::Filled);
+        assert!(placed.lock().unwrap().is_empty());
+        assert!(cancelled.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_executed_qty_is_not_read_as_unfilled() {
+        let (client, placed) = mock_binance("PARTIALLY_FILLED", "garbage").await;
+        let mut order = trailing_order(42);
+        order.quantity = 0.002;
+
+        let error = move_order(&client, &order, 50500.0).await.unwrap_err();
+        assert!(matches!(error, BinanceApiError::Parse(_)), "{:?}", error);
+        assert!(placed.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_adjustment_in_no_trade_window_waits_for_it_to_end() {
+        use chrono::TimeZone;
+
+        let config = Config {
+            no_trade_schedule: "13:25-13:45".parse().unwrap(),
+            ..Config::for_tests()
+        };
+        let monitor = TrailingMonitor::new(config);
+        let id = monitor.add_order(trailing_order(1)).await;
+        // A trail-up BUY's stop 53000 is due down to 52000 + 1%
+        let stop = TrailingOrder {
+            side: OrderSide::Buy,
+            current_order_price: 53000.0,
+            reference_price: 53000.0,
+            ..trailing_order(2)
+        };
+        let stop = stop.with_direction(crate::trailing::TrailDirection::Up);
+        let stop = monitor.add_order(stop).await;
+        let dependent = monitor.add_order(trailing_order(3).with_dependency(9)).await;
+        let prices = HashMap::from([("BTCUSDT".to_string(), 52000.0)]);
+        let at = |minute| Utc.with_ymd_and_hms(2024, 5, 3, 13, minute, 0).unwrap();
+
+        // Due (52000 - 1% is well above the 50000 order) but held back
+        assert!(monitor.due_adjustments(&prices, at(30)).await.is_empty());
+        let order = monitor.get_order(id).await.unwrap();
+        assert_eq!(order.reference_price, 52000.0);
+        assert_eq!(order.current_order_price, 50000.0);
+
+        // Arming inside the window doesn't let the new trail move either
+        let armed_at = at(35).timestamp_millis();
+        monitor.orders.write().await.get_mut(&dependent).unwrap().arm(52000.0, armed_at);
+        assert!(monitor.due_adjustments(&prices, at(40)).await.is_empty());
+
+        let mut adjustments = monitor.due_adjustments(&prices, at(45)).await;
+        adjustments.sort_by_key(|(_, _, order)| order.order_id);
+        let due: Vec<(Uuid, f64)> =
+            adjustments.iter().map(|(id, price, _)| (*id, *price)).collect();
+        assert_eq!(due, [(id, 51480.0), (stop, 52520.0), (dependent, 51480.0)]);
+    }
+
+    #[tokio::test]
+    async fn test_aged_reference_is_reset_on_next_check() {
+        use chrono::TimeZone;
+
+        let monitor = TrailingMonitor::new(Config::for_tests());
+        let now = Utc.with_ymd_and_hms(2024, 5, 3, 12, 0, 0).unwrap();
+        let updated_ago = |order: TrailingOrder, secs: i64| TrailingOrder {
+            reference_price: 52000.0,
+            reference_updated_at: now.timestamp_millis() - secs * 1000,
+            ..order.with_reference_max_age(3600)
+        };
+        let aged = monitor.add_order(updated_ago(trailing_order(1), 7200)).await;
+        let fresh = monitor.add_order(updated_ago(trailing_order(2), 600)).await;
+        let prices = HashMap::from([("BTCUSDT".to_string(), 49000.0)]);
+
+        // The stale high no longer pulls its order up; the fresh one still does
+        let adjustments = monitor.due_adjustments(&prices, now).await;
+        assert_eq!(adjustments.len(), 1);
+        assert_eq!(adjustments[0].0, fresh);
+
+        let order = monitor.get_order(aged).await.unwrap();
+        assert_eq!(order.reference_price, 49000.0);
+        assert_eq!(order.reference_updated_at, now.timestamp_millis());
+
+        // Still within its max age: the high keeps holding
+        let order = monitor.get_order(fresh).await.unwrap();
+        assert_eq!(order.reference_price, 52000.0);
+    }
+
+    #[tokio::test]
+    async fn test_order_missing_from_open_orders_is_pruned() {
+        let order = |order_id: i64, status: &str| {
+            serde_json::json!({
+                "symbol": "BTCUSDT",
+                "orderId": order_id,
+                "clientOrderId": "abc",
+                "price": "50000.00",
+                "origQty": "0.00100",
+                "executedQty": "0",
+                "status": status,
+                "type": "LIMIT",
+                "side": "SELL",
+                "time": 1700000000000i64
+            })
+        };
+        let router = Router::new()
+            .route(
+                "/api/v3/openOrders",
+                get(move || async move { Json(serde_json::json!([order(1, "NEW")])) }),
+            )
+            .route(
+                "/api/v3/order",
+                get(move |RawQuery(query): RawQuery| async move {
+                    let query = query.unwrap_or_default();
+                    let status = match () {
+                        _ if query.contains("orderId=2&") => "CANCELED",
+                        _ if query.contains("orderId=6&") => "FILLED",
+                        // Opened after the snapshot was taken
+                        _ if query.contains("orderId=8&") => "NEW",
+                        _ => {
+                            let unknown = serde_json::json!({
+                                "code": -2013, "msg": "Order does not exist."
+                            });
+                            return (axum::http::StatusCode::BAD_REQUEST, Json(unknown));
+                        }
+                    };
+                    (axum::http::StatusCode::OK, Json(order(6, status)))
+                }),
+            );
+        let client = mock_client(router).await;
+
+        let placed_long_ago = |mut order: TrailingOrder| {
+            order.order_placed_at -= 2 * RECONCILE_GRACE_MS;
+            order
+        };
+        let monitor = TrailingMonitor::new(Config::for_tests());
+        for order_id in [1, 2, 6, 7, 8] {
+            monitor.add_order(placed_long_ago(trailing_order(order_id))).await;
+        }
+        // Just placed: the snapshot may predate it
+        monitor.add_order(trailing_order(3)).await;
+        // Other markets are reconciled against their own open orders
+        let mut eth = placed_long_ago(trailing_order(4));
+        eth.symbol = "ETHUSDT".to_string();
+        monitor.add_order(eth).await;
+        let mut production = placed_long_ago(trailing_order(5));
+        production.use_production = true;
+        monitor.add_order(production).await;
+
+        let pruned = monitor.prune_missing(&client, "BTCUSDT", false).await.unwrap();
+        assert_eq!(pruned, 3);
+
+        for order_id in [1, 3, 4, 5, 8] {
+            assert!(monitor.get_by_order_id(order_id).await.is_some(), "{}", order_id);
+        }
+        let mut outcomes: Vec<(i64, TrailingOutcome)> =
+            monitor.history().await.iter().map(|o| (o.order_id, o.outcome)).collect();
+        outcomes.sort_by_key(|(order_id, _)| *order_id);
+        assert_eq!(
+            outcomes,
+            vec![
+                (2, TrailingOutcome::Cancelled),
+                (6, TrailingOutcome::Filled),
+                (7, TrailingOutcome::Missing)
+            ]
+        );
+    }
+
+    /// Mock Binance whose order query reports `status` (changeable through the
+    /// returned handle) for a BUY filled across two trades, and a monitor
+    /// pointed at it
+    async fn monitor_with_dependency_mock(
+        status: &'static str,
+    ) -> (TrailingMonitor, Arc<Mutex<&'static str>>) {
+        let status = Arc::new(Mutex::new(status));
+        let current = status.clone();
+        let router = Router::new()
+            .route(
+                "/api/v3/order",
+                get(move || async move {
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": 7,
+                        "clientOrderId": "abc",
+                        "price": "48100.00",
+                        "origQty": "0.00200",
+                        "executedQty": "0.00200",
+                        "status": *current.lock().unwrap(),
+                        "type": "LIMIT",
+                        "side": "BUY",
+                        "time": 1700000000000i64
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/myTrades",
+                get(|| async {
+                    let fill = |id: i64, price: &str| {
+                        serde_json::json!({
+                            "id": id,
+                            "orderId": 7,
+                            "symbol": "BTCUSDT",
+                            "price": price,
+                            "qty": "0.00100",
+                            "quoteQty": "48",
+                            "commission": "0",
+                            "commissionAsset": "USDT",
+                            "time": 1700000000000i64,
+                            "isBuyer": true,
+                            "isMaker": true
+                        })
+                    };
+                    Json(serde_json::json!([fill(1, "48000.00"), fill(2, "48100.00")]))
+                }),
+            );
+        (TrailingMonitor::new(mock_config(router).await), status)
+    }
+
+    #[tokio::test]
+    async fn test_dependent_trail_stays_dormant_until_dependency_fills() {
+        let (monitor, status) = monitor_with_dependency_mock("NEW").await;
+        let id = monitor.add_order(trailing_order(1).with_dependency(7)).await;
+        let prices = HashMap::from([("BTCUSDT".to_string(), 52000.0)]);
+
+        // A rally that would move an armed SELL leaves the dormant one untouched
+        monitor.arm_dependents().await;
+        assert!(monitor.due_adjustments(&prices, Utc::now()).await.is_empty());
+        let order = monitor.get_order(id).await.unwrap();
+        assert_eq!(order.depends_on_order_id, Some(7));
+        assert_eq!(order.reference_price, 50000.0);
+
+        *status.lock().unwrap() = "PARTIALLY_FILLED";
+        monitor.arm_dependents().await;
+        assert_eq!(monitor.get_order(id).await.unwrap().depends_on_order_id, Some(7));
+
+        // Armed from the BUY's average fill, then trails as usual
+        *status.lock().unwrap() = "FILLED";
+        monitor.arm_dependents().await;
+        let order = monitor.get_order(id).await.unwrap();
+        assert_eq!(order.depends_on_order_id, None);
+        assert!((order.reference_price - 48050.0).abs() < 1e-6, "{}", order.reference_price);
+
+        let adjustments = monitor.due_adjustments(&prices, Utc::now()).await;
+        assert_eq!(adjustments.len(), 1);
+        assert_eq!(adjustments[0].0, id);
+        assert_eq!(adjustments[0].1, 51480.0);
+    }
+
+    #[tokio::test]
+    async fn test_dependency_cancelled_stops_the_trail() {
+        let (monitor, _) = monitor_with_dependency_mock("CANCELED").await;
+        let id = monitor.add_order(trailing_order(1).with_dependency(7)).await;
+
+        monitor.arm_dependents().await;
+
+        assert!(monitor.get_order(id).await.is_none());
+        let history = monitor.history().await;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].outcome, TrailingOutcome::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_order_without_replacement_stops_the_trail() {
+        let router = Router::new()
+            .route(
+                "/api/v3/ticker/price",
+                get(|| async {
+                    Json(serde_json::json!({ "symbol": "BTCUSDT", "price": "52000.00" }))
+                }),
+            )
+            .route(
+                "/api/v3/exchangeInfo",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "symbols": [{
+                            "symbol": "BTCUSDT",
+                            "filters": [
+                                { "filterType": "PRICE_FILTER", "tickSize": "0.01000000" },
+                                { "filterType": "LOT_SIZE", "stepSize": "0.00001000" }
+                            ]
+                        }]
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/order",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "symbol": "BTCUSDT",
+                        "orderId": 42,
+                        "clientOrderId": "abc",
+                        "price": "50000.00",
+                        "origQty": "0.00100",
+                        "executedQty": "0",
+                        "status": "NEW",
+                        "type": "LIMIT",
+                        "side": "SELL",
+                        "time": 1700000000000i64
+                    }))
+                }),
+            )
+            .route(
+                "/api/v3/order/cancelReplace",
+                axum::routing::post(|| async {
+                    (
+                        axum::http::StatusCode::BAD_REQUEST,
+                        r#"{"code":-2021,"msg":"Order cancel-replace partially failed.","data":{
+                            "cancelResult":"SUCCESS","newOrderResult":"FAILURE",
+                            "cancelResponse":{"symbol":"BTCUSDT","orderId":42,"status":"CANCELED"},
+                            "newOrderResponse":{"code":-2010,"msg":"Account has insufficient balance."}}}"#,
+                    )
+                }),
+            );
+        let monitor = TrailingMonitor::new(mock_config(router).await);
+        let id = monitor.add_order(trailing_order(42)).await;
+
+        monitor.check_and_adjust().await.unwrap();
+
+        // Order 42 is gone: keeping the trail would later read it as filled
+        assert!(monitor.get_order(id).await.is_none());
+        let history = monitor.history().await;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].order_id, 42);
+        assert_eq!(history[0].outcome, TrailingOutcome::Cancelled);
     }
 }